@@ -0,0 +1,97 @@
+//! This module defines the clock the time based queues read the current time from.
+//! Time is measured in milliseconds. `SystemClock` reads the system time, `ManualClock` only moves when it is told to,
+//! which lets tests run deterministically.
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+///A source of the current time in milliseconds
+pub trait Clock {
+    ///Returns the current time in milliseconds
+    fn now(&self) -> u64;
+}
+
+///Clock reading the milliseconds since the UNIX epoch from the system time
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+///Clock which only moves when it is set or advanced
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<u64>,
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0)
+    }
+}
+
+impl ManualClock {
+    ///Returns a new clock standing at `now`
+    /// # Attributes
+    /// * `now`- the initial time in milliseconds
+    pub fn new(now: u64) -> ManualClock {
+        ManualClock {
+            now: Cell::new(now),
+        }
+    }
+
+    ///Sets the clock to `now`
+    /// # Attributes
+    /// * `now`- the new time in milliseconds
+    pub fn set(&self, now: u64) {
+        self.now.set(now);
+    }
+
+    ///Moves the clock `millis` milliseconds forward
+    /// # Attributes
+    /// * `millis`- the milliseconds to advance
+    pub fn advance(&self, millis: u64) {
+        self.now.set(self.now.get() + millis);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> u64 {
+        self.now.get()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+impl<C: Clock + ?Sized> Clock for Rc<C> {
+    fn now(&self) -> u64 {
+        (**self).now()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_manual_clock() {
+        let clock = ManualClock::new(10);
+        assert_eq!(10, clock.now());
+        clock.advance(5);
+        assert_eq!(15, clock.now());
+        clock.set(3);
+        assert_eq!(3, Clock::now(&&clock));
+        assert_eq!(7, Rc::new(ManualClock::new(7)).now());
+    }
+
+    #[test]
+    fn test_system_clock_moves_forward() {
+        let clock = SystemClock;
+        let first = clock.now();
+        assert!(first > 0);
+        assert!(clock.now() >= first);
+    }
+}
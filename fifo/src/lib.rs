@@ -1,6 +1,10 @@
+pub mod clock;
+pub mod ttl;
+
 pub mod fifo {
     //! This module implements a FIFO queue with enqueue and dequeue functionality.
-    //! For long queues we should implement the Drop function to ensure successful disposal of the queue.   
+    //! The queue implements Drop by dequeuing its items one by one, so long queues are disposed without
+    //! recursing through the nodes.
 
     use std::cell::RefCell;
     use std::rc::Rc;
@@ -27,12 +31,24 @@ pub mod fifo {
         /// * `value`- the value to assign to the node
         pub fn new(value: T) -> Node<T> {
             Node {
-                value: value,
+                value,
                 next: None,
             }
         }
     }
 
+    impl<T> Default for Fifo<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T> Drop for Fifo<T> {
+        fn drop(&mut self) {
+            while self.dequeue().is_some() {}
+        }
+    }
+
     impl<T> Fifo<T> {
         ///Returns a new empty FIFO queue
         pub fn new() -> Fifo<T> {
@@ -130,4 +146,13 @@ mod tests {
         // assert!(l.head.is_none());
         assert_eq!(l.get_length(), i - 2);
     }
+
+    #[test]
+    fn test_drop_long_fifo() {
+        let mut l = Fifo::new();
+        for i in 0..1_000_000 {
+            l.enqueue(i);
+        }
+        drop(l);
+    }
 }
@@ -0,0 +1,140 @@
+//! This module implements a FIFO queue whose items expire. Every item is enqueued with an expiry time,
+//! `dequeue` skips and drops the expired items and `purge_expired` removes them in bulk.
+//! The current time is read from an injectable `Clock`.
+use crate::clock::{Clock, SystemClock};
+use crate::fifo::Fifo;
+
+///An item of the queue together with the time it expires at
+struct Expiring<T> {
+    value: T,
+    expires_at: u64,
+}
+
+///The FIFO queue of expiring items
+pub struct TtlFifo<T, C = SystemClock> {
+    queue: Fifo<Expiring<T>>,
+    clock: C,
+}
+
+impl<T> Default for TtlFifo<T, SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TtlFifo<T, SystemClock> {
+    ///Returns a new empty queue reading the time from the system clock
+    pub fn new() -> TtlFifo<T, SystemClock> {
+        TtlFifo::with_clock(SystemClock)
+    }
+}
+
+impl<T, C: Clock> TtlFifo<T, C> {
+    ///Returns a new empty queue reading the time from `clock`
+    /// # Attributes
+    /// * `clock`- the clock to read the current time from
+    pub fn with_clock(clock: C) -> TtlFifo<T, C> {
+        TtlFifo {
+            queue: Fifo::new(),
+            clock,
+        }
+    }
+
+    ///Returns the length of the queue, including the expired items which were not removed yet
+    pub fn get_length(&self) -> i32 {
+        self.queue.get_length()
+    }
+
+    ///Returns the reference to the clock of the queue
+    pub fn get_clock(&self) -> &C {
+        &self.clock
+    }
+
+    ///Attaches `value` to the end of the queue, expiring `ttl` milliseconds from now, and returns the new length
+    /// # Attributes
+    /// * `value`- the value to append
+    /// * `ttl`- the time to live in milliseconds
+    pub fn enqueue(&mut self, value: T, ttl: u64) -> i32 {
+        let expires_at = self.clock.now().saturating_add(ttl);
+        self.enqueue_until(value, expires_at)
+    }
+
+    ///Attaches `value` to the end of the queue, expiring at the time `expires_at`, and returns the new length
+    /// # Attributes
+    /// * `value`- the value to append
+    /// * `expires_at`- the time in milliseconds the value expires at
+    pub fn enqueue_until(&mut self, value: T, expires_at: u64) -> i32 {
+        self.queue.enqueue(Expiring { value, expires_at })
+    }
+
+    ///Removes and returns the first item which is not expired. The expired items in front of it are dropped.
+    pub fn dequeue(&mut self) -> Option<T> {
+        let now = self.clock.now();
+        while let Some(item) = self.queue.dequeue() {
+            if item.expires_at > now {
+                return Some(item.value);
+            }
+        }
+        None
+    }
+
+    ///Removes all items which are expired at the time `now` and returns them in queue order.
+    ///The order of the remaining items is kept.
+    /// # Attributes
+    /// * `now`- the time in milliseconds to check the expiry against
+    pub fn purge_expired(&mut self, now: u64) -> Vec<T> {
+        let mut expired = Vec::new();
+        for _ in 0..self.queue.get_length() {
+            let item = self.queue.dequeue().unwrap();
+            if item.expires_at <= now {
+                expired.push(item.value);
+            } else {
+                self.queue.enqueue(item);
+            }
+        }
+        expired
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::ManualClock;
+
+    #[test]
+    fn test_new_ttl_fifo() {
+        let mut q: TtlFifo<i32> = TtlFifo::new();
+        assert_eq!(0, q.get_length());
+        assert!(q.dequeue().is_none());
+    }
+
+    #[test]
+    fn test_dequeue_skips_expired() {
+        let clock = ManualClock::new(0);
+        let mut q = TtlFifo::with_clock(&clock);
+        assert_eq!(1, q.enqueue(String::from("short"), 10));
+        assert_eq!(2, q.enqueue(String::from("long"), 100));
+        assert_eq!(3, q.enqueue(String::from("short2"), 10));
+        clock.advance(10);
+        assert_eq!(Some(String::from("long")), q.dequeue());
+        assert_eq!(1, q.get_length());
+        assert!(q.dequeue().is_none());
+        assert_eq!(0, q.get_length());
+    }
+
+    #[test]
+    fn test_purge_expired_keeps_order() {
+        let clock = ManualClock::new(1000);
+        let mut q = TtlFifo::with_clock(&clock);
+        for i in 0..10 {
+            q.enqueue(i, if i % 2 == 0 { 50 } else { 500 });
+        }
+        assert!(q.purge_expired(1049).is_empty());
+        assert_eq!(vec![0, 2, 4, 6, 8], q.purge_expired(1050));
+        assert_eq!(5, q.get_length());
+        q.enqueue_until(10, 2000);
+        assert_eq!(Some(1), q.dequeue());
+        assert_eq!(vec![3, 5, 7, 9], q.purge_expired(1500));
+        assert_eq!(Some(10), q.dequeue());
+    }
+}
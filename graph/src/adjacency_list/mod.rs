@@ -0,0 +1,90 @@
+//! This module implements a graph as adjacency lists: every node keeps the vector of its outgoing edges.
+//! Parallel edges are kept, which suits sparse graphs and multigraphs.
+use crate::graph::{Graph, Weight};
+
+///The graph with the outgoing edges of every node
+pub struct AdjacencyList {
+    neighbors: Vec<Vec<(usize, Weight)>>,
+    directed: bool,
+    edge_count: usize,
+}
+
+impl Graph for AdjacencyList {
+    fn new(node_count: usize, directed: bool) -> AdjacencyList {
+        AdjacencyList {
+            neighbors: vec![Vec::new(); node_count],
+            directed,
+            edge_count: 0,
+        }
+    }
+
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    fn get_node_count(&self) -> usize {
+        self.neighbors.len()
+    }
+
+    fn get_edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn add_node(&mut self) -> usize {
+        self.neighbors.push(Vec::new());
+        self.neighbors.len() - 1
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, weight: Weight) {
+        assert!(to < self.neighbors.len(), "node {} does not exist", to);
+        self.neighbors[from].push((to, weight));
+        if !self.directed && from != to {
+            self.neighbors[to].push((from, weight));
+        }
+        self.edge_count += 1;
+    }
+
+    fn get_neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Weight)> + '_ {
+        self.neighbors[node].iter().copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::Edge;
+
+    #[test]
+    fn test_directed_list() {
+        let mut g = AdjacencyList::from_edges(3, true, &[(0, 1, 5), (1, 2, 7)]);
+        assert!(g.is_directed());
+        assert_eq!(3, g.get_node_count());
+        assert_eq!(vec![(1, 5)], g.get_neighbors(0).collect::<Vec<_>>());
+        assert!(g.get_neighbors(2).next().is_none());
+        assert_eq!(Some(7), g.get_weight(1, 2));
+        assert!(g.get_weight(2, 1).is_none());
+        assert_eq!(3, g.add_node());
+        g.add_edge(3, 3, 1);
+        assert_eq!(3, g.get_edge_count());
+        assert_eq!(Edge::new(3, 3, 1), g.get_edges()[2]);
+    }
+
+    #[test]
+    fn test_undirected_list() {
+        let g = AdjacencyList::from_edges(3, false, &[(0, 1, 5), (2, 1, 7), (2, 2, 1)]);
+        assert_eq!(vec![(0, 5), (2, 7)], g.get_neighbors(1).collect::<Vec<_>>());
+        assert_eq!(vec![(1, 7), (2, 1)], g.get_neighbors(2).collect::<Vec<_>>());
+        assert_eq!(3, g.get_edge_count());
+        assert_eq!(
+            vec![Edge::new(0, 1, 5), Edge::new(1, 2, 7), Edge::new(2, 2, 1)],
+            g.get_edges()
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_edge_to_missing_node() {
+        let mut g = AdjacencyList::new(2, true);
+        g.add_edge(0, 2, 1);
+    }
+}
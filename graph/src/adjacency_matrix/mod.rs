@@ -0,0 +1,119 @@
+//! This module implements a graph as an adjacency matrix: the weight of the edge from node `i` to node `j` is
+//! stored at row `i` and column `j`. Adding an edge which already exists replaces its weight, which suits dense
+//! graphs with O(1) edge lookups.
+use crate::graph::{Graph, Weight};
+
+///The graph with the row major matrix of the edge weights
+pub struct AdjacencyMatrix {
+    weights: Vec<Option<Weight>>,
+    node_count: usize,
+    directed: bool,
+    edge_count: usize,
+}
+
+impl AdjacencyMatrix {
+    fn index(&self, from: usize, to: usize) -> usize {
+        assert!(
+            from < self.node_count && to < self.node_count,
+            "node {} or {} does not exist",
+            from,
+            to
+        );
+        from * self.node_count + to
+    }
+}
+
+impl Graph for AdjacencyMatrix {
+    fn new(node_count: usize, directed: bool) -> AdjacencyMatrix {
+        AdjacencyMatrix {
+            weights: vec![None; node_count * node_count],
+            node_count,
+            directed,
+            edge_count: 0,
+        }
+    }
+
+    fn is_directed(&self) -> bool {
+        self.directed
+    }
+
+    fn get_node_count(&self) -> usize {
+        self.node_count
+    }
+
+    fn get_edge_count(&self) -> usize {
+        self.edge_count
+    }
+
+    fn add_node(&mut self) -> usize {
+        let old = self.node_count;
+        let mut weights = vec![None; (old + 1) * (old + 1)];
+        for row in 0..old {
+            weights[row * (old + 1)..row * (old + 1) + old]
+                .copy_from_slice(&self.weights[row * old..(row + 1) * old]);
+        }
+        self.weights = weights;
+        self.node_count += 1;
+        old
+    }
+
+    fn add_edge(&mut self, from: usize, to: usize, weight: Weight) {
+        let index = self.index(from, to);
+        if self.weights[index].replace(weight).is_none() {
+            self.edge_count += 1;
+        }
+        if !self.directed {
+            let index = self.index(to, from);
+            self.weights[index] = Some(weight);
+        }
+    }
+
+    fn get_neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Weight)> + '_ {
+        let start = self.index(node, 0);
+        self.weights[start..start + self.node_count]
+            .iter()
+            .enumerate()
+            .filter_map(|(to, weight)| weight.map(|weight| (to, weight)))
+    }
+
+    fn get_weight(&self, from: usize, to: usize) -> Option<Weight> {
+        self.weights[self.index(from, to)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+
+    #[test]
+    fn test_directed_matrix() {
+        let mut g = AdjacencyMatrix::from_edges(3, true, &[(0, 1, 5), (1, 2, 7)]);
+        assert_eq!(Some(5), g.get_weight(0, 1));
+        assert!(g.get_weight(1, 0).is_none());
+        g.add_edge(0, 1, 6);
+        assert_eq!(Some(6), g.get_weight(0, 1));
+        assert_eq!(2, g.get_edge_count());
+        assert_eq!(3, g.add_node());
+        assert_eq!(Some(7), g.get_weight(1, 2));
+        g.add_edge(3, 0, 1);
+        assert_eq!(vec![(0, 1)], g.get_neighbors(3).collect::<Vec<_>>());
+        assert_eq!(vec![(1, 6)], g.get_neighbors(0).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_matches_adjacency_list() {
+        let edges = [(0, 3, 1), (3, 1, -2), (2, 2, 4), (1, 2, 9)];
+        let m = AdjacencyMatrix::from_edges(4, false, &edges);
+        let l = AdjacencyList::from_edges(4, false, &edges);
+        let mut list_edges = l.get_edges();
+        list_edges.sort_by_key(|edge| (edge.from, edge.to));
+        assert_eq!(list_edges, m.get_edges());
+        assert_eq!(l.get_edge_count(), m.get_edge_count());
+        for node in 0..4 {
+            let mut neighbors: Vec<_> = l.get_neighbors(node).collect();
+            neighbors.sort_unstable();
+            assert_eq!(neighbors, m.get_neighbors(node).collect::<Vec<_>>());
+        }
+    }
+}
@@ -0,0 +1,394 @@
+//! This module implements the topological sort of Kahn on a `fifo::Fifo`, the detection of cycles and the strongly
+//! connected components of Tarjan and Kosaraju. The depth first searches keep their frames on a `lifo::Lifo`
+//! instead of recursing, so they work on graphs with millions of nodes.
+//! All functions follow the edges in their direction; an undirected edge counts as a cycle of two nodes.
+use crate::adjacency_list::AdjacencyList;
+use crate::graph::Graph;
+use crate::traversal::Frame;
+use fifo::fifo::Fifo;
+use lifo::lifo::Lifo;
+use std::fmt;
+
+///The error of a topological sort of a graph which has a cycle
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CycleError {
+    ///The nodes of a cycle in order. The last node has an edge back to the first one.
+    pub cycle: Vec<usize>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "graph has the cycle {:?}", self.cycle)
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+///Returns the nodes of `graph` in an order where every edge goes from an earlier to a later node, found by Kahn's
+///algorithm. Fails with one of the cycles of the graph if there is no such order.
+/// # Attributes
+/// * `graph`- the graph to sort
+pub fn topological_sort<G: Graph + ?Sized>(graph: &G) -> Result<Vec<usize>, CycleError> {
+    let node_count = graph.get_node_count();
+    let mut in_degrees = vec![0usize; node_count];
+    for node in 0..node_count {
+        for (neighbor, _) in graph.get_neighbors(node) {
+            in_degrees[neighbor] += 1;
+        }
+    }
+    let mut queue = Fifo::new();
+    for (node, in_degree) in in_degrees.iter().enumerate() {
+        if *in_degree == 0 {
+            queue.enqueue(node);
+        }
+    }
+    let mut order = Vec::with_capacity(node_count);
+    while let Some(node) = queue.dequeue() {
+        order.push(node);
+        for (neighbor, _) in graph.get_neighbors(node) {
+            in_degrees[neighbor] -= 1;
+            if in_degrees[neighbor] == 0 {
+                queue.enqueue(neighbor);
+            }
+        }
+    }
+    if order.len() == node_count {
+        Ok(order)
+    } else {
+        let cycle = find_cycle(graph).expect("Something is wrong. We shouldn't arrive here!!!");
+        Err(CycleError { cycle })
+    }
+}
+
+///The state of a node in the depth first search for a cycle
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Color {
+    Unvisited,
+    //the node is on the current path of the search
+    OnPath,
+    Finished,
+}
+
+///Returns a cycle of `graph` as its nodes in order, or None if the graph is acyclic.
+///The last node of the cycle has an edge back to the first one.
+/// # Attributes
+/// * `graph`- the graph to search
+pub fn find_cycle<G: Graph + ?Sized>(graph: &G) -> Option<Vec<usize>> {
+    let node_count = graph.get_node_count();
+    let mut colors = vec![Color::Unvisited; node_count];
+    let mut parents = vec![None; node_count];
+    for start in 0..node_count {
+        if colors[start] != Color::Unvisited {
+            continue;
+        }
+        let mut stack: Lifo<Frame<_>> = Lifo::new();
+        colors[start] = Color::OnPath;
+        stack.enqueue((start, graph.get_neighbors(start)));
+        while let Some((node, mut neighbors)) = stack.dequeue() {
+            let neighbor = match neighbors.next() {
+                Some((neighbor, _)) => neighbor,
+                None => {
+                    colors[node] = Color::Finished;
+                    continue;
+                }
+            };
+            stack.enqueue((node, neighbors));
+            match colors[neighbor] {
+                Color::Unvisited => {
+                    colors[neighbor] = Color::OnPath;
+                    parents[neighbor] = Some(node);
+                    stack.enqueue((neighbor, graph.get_neighbors(neighbor)));
+                }
+                Color::OnPath => {
+                    //the edge closes a cycle along the current path from the neighbor to the node
+                    let mut cycle = vec![node];
+                    let mut current = node;
+                    while current != neighbor {
+                        current = parents[current].unwrap();
+                        cycle.push(current);
+                    }
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                Color::Finished => {}
+            }
+        }
+    }
+    None
+}
+
+///The bookkeeping of Tarjan's algorithm
+struct Tarjan {
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    on_stack: Vec<bool>,
+    component_stack: Lifo<usize>,
+    next_index: usize,
+}
+
+impl Tarjan {
+    ///Numbers the newly reached `node` and pushes it on the stack of the open components
+    fn discover(&mut self, node: usize) {
+        self.indices[node] = Some(self.next_index);
+        self.low_links[node] = self.next_index;
+        self.next_index += 1;
+        self.on_stack[node] = true;
+        self.component_stack.enqueue(node);
+    }
+
+    ///Pops the component whose root is `root` from the stack of the open components
+    fn pop_component(&mut self, root: usize) -> Vec<usize> {
+        let mut component = Vec::new();
+        while let Some(member) = self.component_stack.dequeue() {
+            self.on_stack[member] = false;
+            component.push(member);
+            if member == root {
+                break;
+            }
+        }
+        component
+    }
+}
+
+///Returns the strongly connected components of `graph` found by Tarjan's algorithm. The components come in reverse
+///topological order: no edge leads from a component to an earlier one.
+/// # Attributes
+/// * `graph`- the graph to split
+pub fn tarjan_scc<G: Graph + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    let node_count = graph.get_node_count();
+    let mut state = Tarjan {
+        indices: vec![None; node_count],
+        low_links: vec![0; node_count],
+        on_stack: vec![false; node_count],
+        component_stack: Lifo::new(),
+        next_index: 0,
+    };
+    let mut parents = vec![None; node_count];
+    let mut components = Vec::new();
+    for start in 0..node_count {
+        if state.indices[start].is_some() {
+            continue;
+        }
+        let mut stack: Lifo<Frame<_>> = Lifo::new();
+        state.discover(start);
+        stack.enqueue((start, graph.get_neighbors(start)));
+        while let Some((node, mut neighbors)) = stack.dequeue() {
+            if let Some((neighbor, _)) = neighbors.next() {
+                stack.enqueue((node, neighbors));
+                match state.indices[neighbor] {
+                    None => {
+                        parents[neighbor] = Some(node);
+                        state.discover(neighbor);
+                        stack.enqueue((neighbor, graph.get_neighbors(neighbor)));
+                    }
+                    Some(index) if state.on_stack[neighbor] => {
+                        state.low_links[node] = state.low_links[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+                continue;
+            }
+            //all neighbors were looked at, the node is the root of a component if it can't reach an earlier node
+            if Some(state.low_links[node]) == state.indices[node] {
+                components.push(state.pop_component(node));
+            }
+            if let Some(parent) = parents[node] {
+                state.low_links[parent] = state.low_links[parent].min(state.low_links[node]);
+            }
+        }
+    }
+    components
+}
+
+///Returns the nodes of `graph` reachable from `start` which are not yet marked in `visited`, in the order their
+///depth first search finished, and marks them
+fn finish_order<G: Graph + ?Sized>(graph: &G, start: usize, visited: &mut [bool]) -> Vec<usize> {
+    let mut order = Vec::new();
+    let mut stack: Lifo<Frame<_>> = Lifo::new();
+    visited[start] = true;
+    stack.enqueue((start, graph.get_neighbors(start)));
+    while let Some((node, mut neighbors)) = stack.dequeue() {
+        let neighbor = match neighbors.next() {
+            Some((neighbor, _)) => neighbor,
+            None => {
+                order.push(node);
+                continue;
+            }
+        };
+        stack.enqueue((node, neighbors));
+        if !visited[neighbor] {
+            visited[neighbor] = true;
+            stack.enqueue((neighbor, graph.get_neighbors(neighbor)));
+        }
+    }
+    order
+}
+
+///Returns the strongly connected components of `graph` found by Kosaraju's algorithm. The components come in
+///topological order: no edge leads from a component to an earlier one.
+/// # Attributes
+/// * `graph`- the graph to split
+pub fn kosaraju_scc<G: Graph + ?Sized>(graph: &G) -> Vec<Vec<usize>> {
+    let node_count = graph.get_node_count();
+    let mut visited = vec![false; node_count];
+    let mut order = Vec::with_capacity(node_count);
+    for start in 0..node_count {
+        if !visited[start] {
+            order.extend(finish_order(graph, start, &mut visited));
+        }
+    }
+    let mut transposed = AdjacencyList::new(node_count, true);
+    for node in 0..node_count {
+        for (neighbor, weight) in graph.get_neighbors(node) {
+            transposed.add_edge(neighbor, node, weight);
+        }
+    }
+    //the node which finished last is in a source component, in the transposed graph it reaches only its component
+    let mut visited = vec![false; node_count];
+    let mut components = Vec::new();
+    for start in order.into_iter().rev() {
+        if !visited[start] {
+            components.push(finish_order(&transposed, start, &mut visited));
+        }
+    }
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_matrix::AdjacencyMatrix;
+
+    fn assert_cycle<G: Graph>(graph: &G, cycle: &[usize]) {
+        assert!(!cycle.is_empty());
+        for (i, from) in cycle.iter().enumerate() {
+            let to = cycle[(i + 1) % cycle.len()];
+            assert!(
+                graph.get_weight(*from, to).is_some(),
+                "no edge {} -> {}",
+                from,
+                to
+            );
+        }
+    }
+
+    fn normalized(mut components: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+        for component in components.iter_mut() {
+            component.sort_unstable();
+        }
+        components.sort();
+        components
+    }
+
+    ///Three components {0, 1, 2} -> {3, 4} -> {5} and the single node {6} -> {3, 4}
+    fn components_sample<G: Graph>() -> G {
+        G::from_edges(
+            7,
+            true,
+            &[
+                (0, 1, 1),
+                (1, 2, 1),
+                (2, 0, 1),
+                (2, 3, 1),
+                (3, 4, 1),
+                (4, 3, 1),
+                (4, 5, 1),
+                (6, 4, 1),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_topological_sort() {
+        let g = AdjacencyList::from_edges(
+            6,
+            true,
+            &[
+                (5, 2, 1),
+                (5, 0, 1),
+                (4, 0, 1),
+                (4, 1, 1),
+                (2, 3, 1),
+                (3, 1, 1),
+            ],
+        );
+        let order = topological_sort(&g).unwrap();
+        assert_eq!(vec![4, 5, 2, 0, 3, 1], order);
+        let mut position = [0; 6];
+        for (i, node) in order.iter().enumerate() {
+            position[*node] = i;
+        }
+        for edge in g.get_edges() {
+            assert!(position[edge.from] < position[edge.to]);
+        }
+        assert!(find_cycle(&g).is_none());
+    }
+
+    #[test]
+    fn test_topological_sort_reports_cycle() {
+        let g = AdjacencyList::from_edges(
+            5,
+            true,
+            &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 1, 1), (3, 4, 1)],
+        );
+        let error = topological_sort(&g).unwrap_err();
+        assert_eq!(vec![1, 2, 3], error.cycle);
+        assert_cycle(&g, &error.cycle);
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let g = AdjacencyMatrix::from_edges(3, true, &[(0, 1, 1), (1, 1, 1)]);
+        assert_eq!(Some(vec![1]), find_cycle(&g));
+        let g = AdjacencyList::from_edges(2, false, &[(0, 1, 1)]);
+        assert_cycle(&g, &find_cycle(&g).unwrap());
+        //a diamond has no cycle even though 3 is reached twice
+        let g = AdjacencyList::from_edges(4, true, &[(0, 1, 1), (0, 2, 1), (1, 3, 1), (2, 3, 1)]);
+        assert!(find_cycle(&g).is_none());
+    }
+
+    #[test]
+    fn test_strongly_connected_components() {
+        let g: AdjacencyList = components_sample();
+        let expected = vec![vec![0, 1, 2], vec![3, 4], vec![5], vec![6]];
+        let tarjan = tarjan_scc(&g);
+        let kosaraju = kosaraju_scc(&g);
+        assert_eq!(expected, normalized(tarjan.clone()));
+        assert_eq!(expected, normalized(kosaraju.clone()));
+        assert_eq!(vec![5], tarjan[0]);
+        assert_eq!(vec![5], *kosaraju.last().unwrap());
+        assert_eq!(
+            normalized(tarjan),
+            normalized(tarjan_scc(&components_sample::<AdjacencyMatrix>()))
+        );
+    }
+
+    #[test]
+    fn test_scc_order_is_topological() {
+        let g: AdjacencyList = components_sample();
+        let components = kosaraju_scc(&g);
+        let mut component_of = [0; 7];
+        for (i, component) in components.iter().enumerate() {
+            for node in component {
+                component_of[*node] = i;
+            }
+        }
+        for edge in g.get_edges() {
+            assert!(component_of[edge.from] <= component_of[edge.to]);
+        }
+    }
+
+    #[test]
+    fn test_long_chain_does_not_overflow() {
+        let n = 300_000;
+        let mut edges: Vec<_> = (0..n - 1).map(|i| (i, i + 1, 1)).collect();
+        let g = AdjacencyList::from_edges(n, true, &edges);
+        assert_eq!(n, topological_sort(&g).unwrap().len());
+        assert_eq!(n, tarjan_scc(&g).len());
+        edges.push((n - 1, 0, 1));
+        let g = AdjacencyList::from_edges(n, true, &edges);
+        assert_eq!(n, find_cycle(&g).unwrap().len());
+        assert_eq!(1, tarjan_scc(&g).len());
+        assert_eq!(1, kosaraju_scc(&g).len());
+    }
+}
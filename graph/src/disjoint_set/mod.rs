@@ -0,0 +1,150 @@
+//! This module implements a disjoint set (union-find) over the elements 0 to n - 1 with union by rank and path
+//! compression. Both are iterative, so long chains of parents never recurse.
+
+///The disjoint set with the parent, the rank and the set size of every element
+pub struct DisjointSet {
+    parents: Vec<usize>,
+    ranks: Vec<u8>,
+    sizes: Vec<usize>,
+    set_count: usize,
+}
+
+impl DisjointSet {
+    ///Returns a new disjoint set of the elements 0 to `length` - 1, each in its own set
+    /// # Attributes
+    /// * `length`- the number of elements
+    pub fn new(length: usize) -> DisjointSet {
+        DisjointSet {
+            parents: (0..length).collect(),
+            ranks: vec![0; length],
+            sizes: vec![1; length],
+            set_count: length,
+        }
+    }
+
+    ///Returns the number of elements
+    pub fn get_length(&self) -> usize {
+        self.parents.len()
+    }
+
+    ///Returns true if there are no elements
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    ///Returns the number of disjoint sets
+    pub fn get_set_count(&self) -> usize {
+        self.set_count
+    }
+
+    ///Adds a new element in its own set and returns it
+    pub fn add(&mut self) -> usize {
+        let element = self.parents.len();
+        self.parents.push(element);
+        self.ranks.push(0);
+        self.sizes.push(1);
+        self.set_count += 1;
+        element
+    }
+
+    ///Returns the representative of the set of `element` and points all elements on the way directly to it
+    /// # Attributes
+    /// * `element`- the element to look up
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parents[root] != root {
+            root = self.parents[root];
+        }
+        let mut current = element;
+        while self.parents[current] != root {
+            let next = self.parents[current];
+            self.parents[current] = root;
+            current = next;
+        }
+        root
+    }
+
+    ///Merges the sets of `a` and `b`. Returns false if they were already in the same set.
+    /// # Attributes
+    /// * `a`- an element of the first set
+    /// * `b`- an element of the second set
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+        //the root of lower rank goes below the other one, so the trees stay flat
+        if self.ranks[a] < self.ranks[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+        self.parents[b] = a;
+        self.sizes[a] += self.sizes[b];
+        if self.ranks[a] == self.ranks[b] {
+            self.ranks[a] += 1;
+        }
+        self.set_count -= 1;
+        true
+    }
+
+    ///Returns true if `a` and `b` are in the same set
+    /// # Attributes
+    /// * `a`- the first element
+    /// * `b`- the second element
+    pub fn same_set(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    ///Returns the number of elements in the set of `element`
+    /// # Attributes
+    /// * `element`- an element of the set
+    pub fn get_set_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.sizes[root]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_disjoint_set() {
+        let mut d = DisjointSet::new(3);
+        assert_eq!(3, d.get_length());
+        assert_eq!(3, d.get_set_count());
+        assert!(!d.same_set(0, 1));
+        assert_eq!(1, d.get_set_size(2));
+        assert!(DisjointSet::new(0).is_empty());
+    }
+
+    #[test]
+    fn test_union() {
+        let mut d = DisjointSet::new(6);
+        assert!(d.union(0, 1));
+        assert!(d.union(2, 3));
+        assert!(d.union(1, 3));
+        assert!(!d.union(0, 2));
+        assert!(d.same_set(0, 3));
+        assert!(!d.same_set(0, 4));
+        assert_eq!(4, d.get_set_size(2));
+        assert_eq!(3, d.get_set_count());
+        let e = d.add();
+        assert_eq!(6, e);
+        assert!(d.union(e, 5));
+        assert_eq!(2, d.get_set_size(5));
+        assert_eq!(3, d.get_set_count());
+    }
+
+    #[test]
+    fn test_path_compression_flattens_chain() {
+        let n = 1_000_000;
+        let mut d = DisjointSet::new(n);
+        //a chain built without ranks would be n long, find must not recurse through it
+        for i in 1..n {
+            d.parents[i] = i - 1;
+        }
+        d.set_count = 1;
+        assert_eq!(0, d.find(n - 1));
+        assert!((1..n).all(|i| d.parents[i] == 0));
+    }
+}
@@ -0,0 +1,432 @@
+//! This module implements maximum flows on a `FlowNetwork` with the algorithms of Edmonds-Karp, whose shortest
+//! augmenting paths are found by a breadth first search on a `fifo::Fifo`, and Dinic, which saturates a whole
+//! level graph per phase. Both return the per edge flows and the minimum cut. A maximum bipartite matching is
+//! built on top of Dinic's algorithm.
+//! Every edge is stored together with its reverse residual edge at the index with the last bit flipped.
+use crate::graph::{Edge, Graph, Weight};
+use fifo::fifo::Fifo;
+
+///A directed edge of the residual network
+#[derive(Clone, Copy, Debug)]
+struct Arc {
+    to: usize,
+    capacity: Weight,
+    flow: Weight,
+}
+
+impl Arc {
+    fn get_residual(&self) -> Weight {
+        self.capacity - self.flow
+    }
+}
+
+///A directed network of edges with capacities
+#[derive(Clone, Debug)]
+pub struct FlowNetwork {
+    arcs: Vec<Arc>,
+    outgoing: Vec<Vec<usize>>,
+}
+
+///The result of a maximum flow computation
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MaxFlow {
+    ///The value of the maximum flow
+    pub value: Weight,
+    ///The flow over every edge, in the order the edges were added to the network
+    pub flows: Vec<Weight>,
+    ///True for the nodes on the source side of a minimum cut
+    pub source_side: Vec<bool>,
+    ///The saturated edges from the source side to the sink side, their capacities add up to the flow value
+    pub cut_edges: Vec<Edge>,
+}
+
+impl FlowNetwork {
+    ///Returns a new network with `node_count` nodes and no edges
+    /// # Attributes
+    /// * `node_count`- the number of nodes
+    pub fn new(node_count: usize) -> FlowNetwork {
+        FlowNetwork {
+            arcs: Vec::new(),
+            outgoing: vec![Vec::new(); node_count],
+        }
+    }
+
+    ///Returns a new network with the nodes of `graph` and its edges, whose weights are the capacities.
+    ///An undirected edge can carry flow in both directions.
+    /// # Attributes
+    /// * `graph`- the graph to convert
+    pub fn from_graph<G: Graph + ?Sized>(graph: &G) -> FlowNetwork {
+        let mut network = FlowNetwork::new(graph.get_node_count());
+        for edge in graph.get_edges() {
+            network.add_edge(edge.from, edge.to, edge.weight);
+            if !graph.is_directed() && edge.from != edge.to {
+                network.add_edge(edge.to, edge.from, edge.weight);
+            }
+        }
+        network
+    }
+
+    ///Returns the number of nodes
+    pub fn get_node_count(&self) -> usize {
+        self.outgoing.len()
+    }
+
+    ///Returns the number of edges
+    pub fn get_edge_count(&self) -> usize {
+        self.arcs.len() / 2
+    }
+
+    ///Returns the edge with index `edge` and its capacity as weight
+    /// # Attributes
+    /// * `edge`- the index returned when the edge was added
+    pub fn get_edge(&self, edge: usize) -> Edge {
+        let arc = self.arcs[2 * edge];
+        Edge::new(self.arcs[2 * edge + 1].to, arc.to, arc.capacity)
+    }
+
+    ///Adds the edge from `from` to `to` with the capacity `capacity` and returns its index.
+    ///Panics if the capacity is negative or a node does not exist.
+    /// # Attributes
+    /// * `from`- the node the edge starts at
+    /// * `to`- the node the edge ends at
+    /// * `capacity`- the maximum flow over the edge
+    pub fn add_edge(&mut self, from: usize, to: usize, capacity: Weight) -> usize {
+        assert!(capacity >= 0, "capacity {} is negative", capacity);
+        assert!(to < self.outgoing.len(), "node {} does not exist", to);
+        let index = self.arcs.len();
+        self.outgoing[from].push(index);
+        self.outgoing[to].push(index + 1);
+        self.arcs.push(Arc {
+            to,
+            capacity,
+            flow: 0,
+        });
+        self.arcs.push(Arc {
+            to: from,
+            capacity: 0,
+            flow: 0,
+        });
+        index / 2
+    }
+
+    ///Pushes `amount` more flow over the residual arc `arc`
+    fn push(&mut self, arc: usize, amount: Weight) {
+        self.arcs[arc].flow += amount;
+        self.arcs[arc ^ 1].flow -= amount;
+    }
+
+    ///Returns the distance in residual arcs from `source` to every node, None for the unreachable nodes
+    fn levels(&self, source: usize) -> Vec<Option<usize>> {
+        let mut levels = vec![None; self.get_node_count()];
+        let mut queue = Fifo::new();
+        levels[source] = Some(0);
+        queue.enqueue(source);
+        while let Some(node) = queue.dequeue() {
+            for &arc in &self.outgoing[node] {
+                let Arc { to, .. } = self.arcs[arc];
+                if levels[to].is_none() && self.arcs[arc].get_residual() > 0 {
+                    levels[to] = Some(levels[node].unwrap() + 1);
+                    queue.enqueue(to);
+                }
+            }
+        }
+        levels
+    }
+
+    ///Collects the result of the flow which is currently in the network
+    fn into_result(self, source: usize) -> MaxFlow {
+        let source_side: Vec<bool> = self
+            .levels(source)
+            .into_iter()
+            .map(|level| level.is_some())
+            .collect();
+        let value = self.outgoing[source]
+            .iter()
+            .map(|&arc| self.arcs[arc].flow)
+            .sum();
+        let mut cut_edges = Vec::new();
+        for edge in 0..self.get_edge_count() {
+            let edge = self.get_edge(edge);
+            if source_side[edge.from] && !source_side[edge.to] {
+                cut_edges.push(edge);
+            }
+        }
+        MaxFlow {
+            value,
+            flows: self.arcs.iter().step_by(2).map(|arc| arc.flow).collect(),
+            source_side,
+            cut_edges,
+        }
+    }
+}
+
+///Returns the maximum flow from `source` to `sink` in `network` found by the Edmonds-Karp algorithm
+/// # Attributes
+/// * `network`- the network to send the flow through
+/// * `source`- the node the flow starts at
+/// * `sink`- the node the flow ends at
+pub fn edmonds_karp(network: &FlowNetwork, source: usize, sink: usize) -> MaxFlow {
+    assert_ne!(source, sink, "source and sink must differ");
+    let mut residual = network.clone();
+    let node_count = residual.get_node_count();
+    loop {
+        //the arc every node was reached over on a shortest augmenting path
+        let mut reached_by: Vec<Option<usize>> = vec![None; node_count];
+        let mut queue = Fifo::new();
+        queue.enqueue(source);
+        while let Some(node) = queue.dequeue() {
+            for &arc in &residual.outgoing[node] {
+                let to = residual.arcs[arc].to;
+                if to != source && reached_by[to].is_none() && residual.arcs[arc].get_residual() > 0
+                {
+                    reached_by[to] = Some(arc);
+                    queue.enqueue(to);
+                }
+            }
+        }
+        if reached_by[sink].is_none() {
+            break;
+        }
+        let mut path = Vec::new();
+        let mut node = sink;
+        while let Some(arc) = reached_by[node] {
+            path.push(arc);
+            node = residual.arcs[arc ^ 1].to;
+        }
+        let amount = path
+            .iter()
+            .map(|&arc| residual.arcs[arc].get_residual())
+            .min()
+            .unwrap();
+        for arc in path {
+            residual.push(arc, amount);
+        }
+    }
+    residual.into_result(source)
+}
+
+///Returns the maximum flow from `source` to `sink` in `network` found by Dinic's algorithm
+/// # Attributes
+/// * `network`- the network to send the flow through
+/// * `source`- the node the flow starts at
+/// * `sink`- the node the flow ends at
+pub fn dinic(network: &FlowNetwork, source: usize, sink: usize) -> MaxFlow {
+    assert_ne!(source, sink, "source and sink must differ");
+    let mut residual = network.clone();
+    loop {
+        let levels = residual.levels(source);
+        if levels[sink].is_none() {
+            break;
+        }
+        //the index of the next outgoing arc to try for every node, arcs which led to a dead end are skipped
+        let mut next_arc = vec![0; residual.get_node_count()];
+        let mut path: Vec<usize> = Vec::new();
+        let mut node = source;
+        loop {
+            if node == sink {
+                let amount = path
+                    .iter()
+                    .map(|&arc| residual.arcs[arc].get_residual())
+                    .min()
+                    .unwrap();
+                for &arc in &path {
+                    residual.push(arc, amount);
+                }
+                //continue from the tail of the first saturated arc
+                let saturated = path
+                    .iter()
+                    .position(|&arc| residual.arcs[arc].get_residual() == 0)
+                    .unwrap();
+                path.truncate(saturated);
+                node = path.last().map_or(source, |&arc| residual.arcs[arc].to);
+                continue;
+            }
+            let candidate = residual.outgoing[node].get(next_arc[node]).copied();
+            match candidate {
+                Some(arc) => {
+                    let Arc { to, .. } = residual.arcs[arc];
+                    let forward = levels[to] == levels[node].map(|level| level + 1);
+                    if forward && residual.arcs[arc].get_residual() > 0 {
+                        path.push(arc);
+                        node = to;
+                    } else {
+                        next_arc[node] += 1;
+                    }
+                }
+                None => {
+                    //the node is a dead end of this phase
+                    match path.pop() {
+                        Some(arc) => {
+                            node = residual.arcs[arc ^ 1].to;
+                            next_arc[node] += 1;
+                        }
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+    residual.into_result(source)
+}
+
+///Returns a maximum matching of the bipartite graph with `left_count` nodes on the left, `right_count` nodes on the
+///right and the possible pairs `pairs` of a left and a right node. The matched pairs are sorted by their left node.
+/// # Attributes
+/// * `left_count`- the number of nodes on the left
+/// * `right_count`- the number of nodes on the right
+/// * `pairs`- the pairs (left, right) which may be matched
+pub fn bipartite_matching(
+    left_count: usize,
+    right_count: usize,
+    pairs: &[(usize, usize)],
+) -> Vec<(usize, usize)> {
+    //the source is left_count + right_count and the sink the node after it
+    let source = left_count + right_count;
+    let sink = source + 1;
+    let mut network = FlowNetwork::new(sink + 1);
+    for left in 0..left_count {
+        network.add_edge(source, left, 1);
+    }
+    for right in 0..right_count {
+        network.add_edge(left_count + right, sink, 1);
+    }
+    let pair_edges: Vec<usize> = pairs
+        .iter()
+        .map(|&(left, right)| {
+            assert!(
+                left < left_count && right < right_count,
+                "pair ({}, {}) is out of range",
+                left,
+                right
+            );
+            network.add_edge(left, left_count + right, 1)
+        })
+        .collect();
+    let flow = dinic(&network, source, sink);
+    let mut matching: Vec<(usize, usize)> = pair_edges
+        .into_iter()
+        .zip(pairs)
+        .filter(|(edge, _)| flow.flows[*edge] > 0)
+        .map(|(_, pair)| *pair)
+        .collect();
+    matching.sort_unstable();
+    matching
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+
+    ///The network of CLRS figure 26.1 with a maximum flow of 23
+    fn sample() -> FlowNetwork {
+        let mut n = FlowNetwork::new(6);
+        for &(from, to, capacity) in &[
+            (0, 1, 16),
+            (0, 2, 13),
+            (1, 3, 12),
+            (2, 1, 4),
+            (2, 4, 14),
+            (3, 2, 9),
+            (3, 5, 20),
+            (4, 3, 7),
+            (4, 5, 4),
+        ] {
+            n.add_edge(from, to, capacity);
+        }
+        n
+    }
+
+    ///Checks the capacity constraints, the flow conservation and the cut of `flow`
+    fn assert_valid(network: &FlowNetwork, flow: &MaxFlow, source: usize, sink: usize) {
+        let mut balance = vec![0; network.get_node_count()];
+        for (index, amount) in flow.flows.iter().enumerate() {
+            let edge = network.get_edge(index);
+            assert!(*amount >= 0 && *amount <= edge.weight);
+            balance[edge.from] -= amount;
+            balance[edge.to] += amount;
+        }
+        for (node, value) in balance.iter().enumerate() {
+            if node != source && node != sink {
+                assert_eq!(0, *value);
+            }
+        }
+        assert_eq!(flow.value, balance[sink]);
+        assert!(flow.source_side[source] && !flow.source_side[sink]);
+        assert_eq!(
+            flow.value,
+            flow.cut_edges.iter().map(|edge| edge.weight).sum()
+        );
+    }
+
+    #[test]
+    fn test_edmonds_karp() {
+        let n = sample();
+        let flow = edmonds_karp(&n, 0, 5);
+        assert_eq!(23, flow.value);
+        assert_valid(&n, &flow, 0, 5);
+        assert_eq!(vec![true, true, true, false, true, false], flow.source_side);
+    }
+
+    #[test]
+    fn test_dinic() {
+        let n = sample();
+        let flow = dinic(&n, 0, 5);
+        assert_eq!(23, flow.value);
+        assert_valid(&n, &flow, 0, 5);
+        assert_eq!(edmonds_karp(&n, 0, 5).source_side, flow.source_side);
+    }
+
+    #[test]
+    fn test_unreachable_sink_and_undirected_graph() {
+        let n = FlowNetwork::new(3);
+        let flow = dinic(&n, 0, 2);
+        assert_eq!(0, flow.value);
+        assert!(flow.cut_edges.is_empty());
+        let g = AdjacencyList::from_edges(3, false, &[(0, 1, 5), (2, 1, 3)]);
+        let n = FlowNetwork::from_graph(&g);
+        assert_eq!(4, n.get_edge_count());
+        assert_eq!(3, edmonds_karp(&n, 0, 2).value);
+        assert_eq!(3, dinic(&n, 2, 0).value);
+    }
+
+    #[test]
+    fn test_random_networks_agree() {
+        let mut state = 5u64;
+        for round in 0..20 {
+            let node_count = 12;
+            let mut n = FlowNetwork::new(node_count);
+            for _ in 0..40 {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                let from = (state >> 33) as usize % node_count;
+                let to = (state >> 45) as usize % node_count;
+                n.add_edge(from, to, (state >> 20) as Weight % 10);
+            }
+            let (a, b) = (
+                edmonds_karp(&n, 0, node_count - 1),
+                dinic(&n, 0, node_count - 1),
+            );
+            assert_eq!(a.value, b.value, "round {}", round);
+            assert_valid(&n, &a, 0, node_count - 1);
+            assert_valid(&n, &b, 0, node_count - 1);
+        }
+    }
+
+    #[test]
+    fn test_bipartite_matching() {
+        let pairs = [(0, 0), (0, 1), (1, 0), (2, 1), (2, 2), (3, 2)];
+        let matching = bipartite_matching(4, 3, &pairs);
+        assert_eq!(3, matching.len());
+        let mut lefts: Vec<usize> = matching.iter().map(|pair| pair.0).collect();
+        let mut rights: Vec<usize> = matching.iter().map(|pair| pair.1).collect();
+        lefts.dedup();
+        rights.sort_unstable();
+        rights.dedup();
+        assert_eq!(3, lefts.len());
+        assert_eq!(3, rights.len());
+        assert!(matching.iter().all(|pair| pairs.contains(pair)));
+        assert!(bipartite_matching(2, 2, &[]).is_empty());
+    }
+}
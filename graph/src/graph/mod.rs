@@ -0,0 +1,104 @@
+//! This module defines the common interface of the graph types of the crate. The nodes of a graph are numbered
+//! from 0 to `get_node_count() - 1` and every edge has a weight. An undirected edge is stored in both directions.
+
+///The type of the edge weights
+pub type Weight = i64;
+
+///Returns `sum`, a sum of weights added up in 128 bits, clamped to the range of `Weight`
+pub(crate) fn saturate(sum: i128) -> Weight {
+    sum.clamp(Weight::MIN as i128, Weight::MAX as i128) as Weight
+}
+
+///A weighted edge from the node `from` to the node `to`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Edge {
+    pub from: usize,
+    pub to: usize,
+    pub weight: Weight,
+}
+
+impl Edge {
+    ///Returns a new edge
+    /// # Attributes
+    /// * `from`- the node the edge starts at
+    /// * `to`- the node the edge ends at
+    /// * `weight`- the weight of the edge
+    pub fn new(from: usize, to: usize, weight: Weight) -> Edge {
+        Edge { from, to, weight }
+    }
+}
+
+///The common interface of the graph types
+pub trait Graph {
+    ///Returns a new graph with `node_count` nodes and no edges
+    /// # Attributes
+    /// * `node_count`- the number of nodes
+    /// * `directed`- true if the edges are directed
+    fn new(node_count: usize, directed: bool) -> Self
+    where
+        Self: Sized;
+
+    ///Returns true if the edges of the graph are directed
+    fn is_directed(&self) -> bool;
+
+    ///Returns the number of nodes
+    fn get_node_count(&self) -> usize;
+
+    ///Returns the number of edges. An undirected edge counts once.
+    fn get_edge_count(&self) -> usize;
+
+    ///Adds a new node without edges and returns its index
+    fn add_node(&mut self) -> usize;
+
+    ///Adds the edge from `from` to `to` with the weight `weight`, in both directions if the graph is undirected.
+    ///Panics if one of the nodes does not exist.
+    /// # Attributes
+    /// * `from`- the node the edge starts at
+    /// * `to`- the node the edge ends at
+    /// * `weight`- the weight of the edge
+    fn add_edge(&mut self, from: usize, to: usize, weight: Weight);
+
+    ///Returns an iterator over the nodes reachable over one edge from `node` together with the weights of the edges
+    /// # Attributes
+    /// * `node`- the node whose neighbors to return
+    fn get_neighbors(&self, node: usize) -> impl Iterator<Item = (usize, Weight)> + '_;
+
+    ///Returns the weight of the edge from `from` to `to`, or None if there is no such edge
+    /// # Attributes
+    /// * `from`- the node the edge starts at
+    /// * `to`- the node the edge ends at
+    fn get_weight(&self, from: usize, to: usize) -> Option<Weight> {
+        self.get_neighbors(from)
+            .find(|(neighbor, _)| *neighbor == to)
+            .map(|(_, weight)| weight)
+    }
+
+    ///Returns all edges. An undirected edge is returned once, from its smaller to its larger node.
+    fn get_edges(&self) -> Vec<Edge> {
+        let mut edges = Vec::new();
+        for from in 0..self.get_node_count() {
+            for (to, weight) in self.get_neighbors(from) {
+                if self.is_directed() || from <= to {
+                    edges.push(Edge::new(from, to, weight));
+                }
+            }
+        }
+        edges
+    }
+
+    ///Returns a new graph with `node_count` nodes and the edges `edges` given as (from, to, weight)
+    /// # Attributes
+    /// * `node_count`- the number of nodes
+    /// * `directed`- true if the edges are directed
+    /// * `edges`- the edges to add
+    fn from_edges(node_count: usize, directed: bool, edges: &[(usize, usize, Weight)]) -> Self
+    where
+        Self: Sized,
+    {
+        let mut graph = Self::new(node_count, directed);
+        for &(from, to, weight) in edges {
+            graph.add_edge(from, to, weight);
+        }
+        graph
+    }
+}
@@ -0,0 +1,9 @@
+pub mod adjacency_list;
+pub mod adjacency_matrix;
+pub mod dag;
+pub mod disjoint_set;
+pub mod flow;
+pub mod graph;
+pub mod shortest_path;
+pub mod spanning_tree;
+pub mod traversal;
@@ -0,0 +1,567 @@
+//! This module implements the single source shortest paths of Dijkstra (on a `BinaryHeap`) and Bellman-Ford,
+//! the single pair search A* with a pluggable heuristic and the all pairs shortest paths of Floyd-Warshall.
+//! Unreachable nodes have no distance and no path. Inputs the algorithms can't handle, negative weights for
+//! Dijkstra and A* and negative cycles for Bellman-Ford and Floyd-Warshall, are reported as a `PathError`.
+//! All algorithms add up the weights in 128 bits, so long paths and negative cycles can't overflow, and distances
+//! beyond the range of `Weight` are reported saturated.
+use crate::graph::{saturate, Edge, Graph, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fmt;
+
+///The reasons a shortest path search fails
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PathError {
+    ///The algorithm requires non negative weights but the graph has this edge
+    NegativeWeight(Edge),
+    ///The graph has a cycle with a negative total weight, given as its nodes in order. The last node has an
+    ///edge back to the first one.
+    NegativeCycle(Vec<usize>),
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathError::NegativeWeight(edge) => write!(
+                f,
+                "edge from {} to {} has the negative weight {}",
+                edge.from, edge.to, edge.weight
+            ),
+            PathError::NegativeCycle(cycle) => write!(f, "negative cycle through {:?}", cycle),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+///The shortest paths from a source node to all nodes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShortestPaths {
+    ///The node the paths start at
+    pub source: usize,
+    ///The total weight of the shortest path to every node, None for the unreachable nodes
+    pub distances: Vec<Option<Weight>>,
+    ///The node before every node on its shortest path, None for the source and the unreachable nodes
+    pub parents: Vec<Option<usize>>,
+}
+
+impl ShortestPaths {
+    ///Returns the total weight of the shortest path to `node`, or None if `node` is unreachable
+    /// # Attributes
+    /// * `node`- the node the path ends at
+    pub fn get_distance(&self, node: usize) -> Option<Weight> {
+        self.distances[node]
+    }
+
+    ///Returns the nodes of the shortest path from the source to `node`, or None if `node` is unreachable
+    /// # Attributes
+    /// * `node`- the node the path ends at
+    pub fn get_path(&self, node: usize) -> Option<Vec<usize>> {
+        self.distances[node]?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(parent) = self.parents[current] {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+///A path between two nodes with its total weight
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path {
+    pub nodes: Vec<usize>,
+    pub cost: Weight,
+}
+
+///The shortest paths between all pairs of nodes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AllPairsPaths {
+    distances: Vec<Vec<Option<Weight>>>,
+    //the node after `from` on the shortest path from `from` to `to`
+    next: Vec<Vec<Option<usize>>>,
+}
+
+impl AllPairsPaths {
+    ///Returns the total weight of the shortest path from `from` to `to`, or None if `to` is unreachable
+    /// # Attributes
+    /// * `from`- the node the path starts at
+    /// * `to`- the node the path ends at
+    pub fn get_distance(&self, from: usize, to: usize) -> Option<Weight> {
+        self.distances[from][to]
+    }
+
+    ///Returns the nodes of the shortest path from `from` to `to`, or None if `to` is unreachable
+    /// # Attributes
+    /// * `from`- the node the path starts at
+    /// * `to`- the node the path ends at
+    pub fn get_path(&self, from: usize, to: usize) -> Option<Vec<usize>> {
+        self.distances[from][to]?;
+        let mut path = vec![from];
+        let mut current = from;
+        while current != to {
+            current = self.next[current][to]?;
+            path.push(current);
+        }
+        Some(path)
+    }
+}
+
+///Returns the first edge of `graph` with a negative weight
+fn find_negative_weight<G: Graph + ?Sized>(graph: &G) -> Option<Edge> {
+    graph.get_edges().into_iter().find(|edge| edge.weight < 0)
+}
+
+///Returns the shortest paths from `source` to all nodes of `graph` found by Dijkstra's algorithm.
+///Fails if the graph has a negative weight.
+/// # Attributes
+/// * `graph`- the graph to search
+/// * `source`- the node the paths start at
+pub fn dijkstra<G: Graph + ?Sized>(graph: &G, source: usize) -> Result<ShortestPaths, PathError> {
+    if let Some(edge) = find_negative_weight(graph) {
+        return Err(PathError::NegativeWeight(edge));
+    }
+    let node_count = graph.get_node_count();
+    let mut paths = ShortestPaths {
+        source,
+        distances: vec![None; node_count],
+        parents: vec![None; node_count],
+    };
+    let mut done = vec![false; node_count];
+    let mut heap = BinaryHeap::new();
+    paths.distances[source] = Some(0);
+    heap.push(Reverse((0, source)));
+    while let Some(Reverse((distance, node))) = heap.pop() {
+        if done[node] {
+            //a shorter path to the node was already taken from the heap
+            continue;
+        }
+        done[node] = true;
+        for (neighbor, weight) in graph.get_neighbors(node) {
+            let candidate = saturate(distance as i128 + weight as i128);
+            if paths.distances[neighbor].is_none_or(|current| candidate < current) {
+                paths.distances[neighbor] = Some(candidate);
+                paths.parents[neighbor] = Some(node);
+                heap.push(Reverse((candidate, neighbor)));
+            }
+        }
+    }
+    Ok(paths)
+}
+
+///Relaxes all edges of `graph` starting from `distances` until no distance changes. Returns the distances and
+///the parents, or the negative cycle which keeps changing them.
+fn relax<G: Graph + ?Sized>(
+    graph: &G,
+    distances: Vec<Option<Weight>>,
+) -> Result<ShortestPaths, Vec<usize>> {
+    let node_count = graph.get_node_count();
+    let mut distances: Vec<Option<i128>> = distances
+        .into_iter()
+        .map(|distance| distance.map(i128::from))
+        .collect();
+    let mut arcs = Vec::new();
+    for from in 0..node_count {
+        for (to, weight) in graph.get_neighbors(from) {
+            arcs.push((from, to, weight));
+        }
+    }
+    let mut parents = vec![None; node_count];
+    //after node_count - 1 rounds all shortest paths are found, a change in the last round means a negative cycle
+    let mut last_changed = None;
+    for _ in 0..node_count {
+        last_changed = None;
+        for &(from, to, weight) in &arcs {
+            if let Some(distance) = distances[from] {
+                let candidate = distance + i128::from(weight);
+                if distances[to].is_none_or(|current| candidate < current) {
+                    distances[to] = Some(candidate);
+                    parents[to] = Some(from);
+                    last_changed = Some(to);
+                }
+            }
+        }
+        if last_changed.is_none() {
+            return Ok(ShortestPaths {
+                source: 0,
+                distances: distances
+                    .into_iter()
+                    .map(|distance| distance.map(saturate))
+                    .collect(),
+                parents,
+            });
+        }
+    }
+    //walking back node_count parents from the last changed node surely ends on the cycle
+    let mut node = last_changed.unwrap();
+    for _ in 0..node_count {
+        node = parents[node].expect("Something is wrong. We shouldn't arrive here!!!");
+    }
+    let mut cycle = vec![node];
+    let mut current = parents[node].unwrap();
+    while current != node {
+        cycle.push(current);
+        current = parents[current].unwrap();
+    }
+    cycle.reverse();
+    Err(cycle)
+}
+
+///Returns the shortest paths from `source` to all nodes of `graph` found by the Bellman-Ford algorithm.
+///Negative weights are allowed, a negative cycle reachable from `source` fails the search.
+/// # Attributes
+/// * `graph`- the graph to search
+/// * `source`- the node the paths start at
+pub fn bellman_ford<G: Graph + ?Sized>(
+    graph: &G,
+    source: usize,
+) -> Result<ShortestPaths, PathError> {
+    let mut distances = vec![None; graph.get_node_count()];
+    distances[source] = Some(0);
+    let mut paths = relax(graph, distances).map_err(PathError::NegativeCycle)?;
+    paths.source = source;
+    Ok(paths)
+}
+
+///Returns a cycle of `graph` with a negative total weight, or None if there is none
+/// # Attributes
+/// * `graph`- the graph to search
+pub fn find_negative_cycle<G: Graph + ?Sized>(graph: &G) -> Option<Vec<usize>> {
+    //starting every node at 0 is the same as starting at a virtual source with an edge to every node
+    relax(graph, vec![Some(0); graph.get_node_count()]).err()
+}
+
+///Returns the shortest path from `source` to `target` found by A*, or None if `target` is unreachable.
+///`heuristic` estimates the remaining weight from a node to `target`; the path is the shortest one as long as the
+///estimate never exceeds the real remaining weight. Fails if the graph has a negative weight.
+/// # Attributes
+/// * `graph`- the graph to search
+/// * `source`- the node the path starts at
+/// * `target`- the node the path ends at
+/// * `heuristic`- the estimate of the remaining weight from a node to `target`
+pub fn a_star<G, H>(
+    graph: &G,
+    source: usize,
+    target: usize,
+    heuristic: H,
+) -> Result<Option<Path>, PathError>
+where
+    G: Graph + ?Sized,
+    H: Fn(usize) -> Weight,
+{
+    if let Some(edge) = find_negative_weight(graph) {
+        return Err(PathError::NegativeWeight(edge));
+    }
+    let node_count = graph.get_node_count();
+    let mut costs: Vec<Option<Weight>> = vec![None; node_count];
+    let mut parents = vec![None; node_count];
+    let mut heap = BinaryHeap::new();
+    costs[source] = Some(0);
+    heap.push(Reverse((heuristic(source), 0, source)));
+    while let Some(Reverse((_, cost, node))) = heap.pop() {
+        if costs[node].is_some_and(|best| cost > best) {
+            //a cheaper path to the node was found after this entry was pushed
+            continue;
+        }
+        if node == target {
+            let mut nodes = vec![node];
+            let mut current = node;
+            while let Some(parent) = parents[current] {
+                nodes.push(parent);
+                current = parent;
+            }
+            nodes.reverse();
+            return Ok(Some(Path { nodes, cost }));
+        }
+        for (neighbor, weight) in graph.get_neighbors(node) {
+            let candidate = saturate(cost as i128 + weight as i128);
+            if costs[neighbor].is_none_or(|current| candidate < current) {
+                costs[neighbor] = Some(candidate);
+                parents[neighbor] = Some(node);
+                heap.push(Reverse((
+                    saturate(candidate as i128 + heuristic(neighbor) as i128),
+                    candidate,
+                    neighbor,
+                )));
+            }
+        }
+    }
+    Ok(None)
+}
+
+///Returns the shortest paths between all pairs of nodes of `graph` found by the Floyd-Warshall algorithm.
+///Negative weights are allowed, a negative cycle fails the search.
+/// # Attributes
+/// * `graph`- the graph to search
+pub fn floyd_warshall<G: Graph + ?Sized>(graph: &G) -> Result<AllPairsPaths, PathError> {
+    let node_count = graph.get_node_count();
+    let mut distances: Vec<Vec<Option<i128>>> = vec![vec![None; node_count]; node_count];
+    let mut next = vec![vec![None; node_count]; node_count];
+    for from in 0..node_count {
+        distances[from][from] = Some(0);
+        next[from][from] = Some(from);
+        for (to, weight) in graph.get_neighbors(from) {
+            let weight = i128::from(weight);
+            if distances[from][to].is_none_or(|current| weight < current) {
+                distances[from][to] = Some(weight);
+                next[from][to] = Some(to);
+            }
+        }
+    }
+    for via in 0..node_count {
+        for from in 0..node_count {
+            let first = match distances[from][via] {
+                Some(first) => first,
+                None => continue,
+            };
+            for to in 0..node_count {
+                if let Some(second) = distances[via][to] {
+                    if distances[from][to].is_none_or(|current| first + second < current) {
+                        //a node only gets a negative distance to itself on a negative cycle, stop before the
+                        //cycle drags further distances down
+                        if from == to && first + second < 0 {
+                            let cycle = find_negative_cycle(graph)
+                                .expect("Something is wrong. We shouldn't arrive here!!!");
+                            return Err(PathError::NegativeCycle(cycle));
+                        }
+                        distances[from][to] = Some(first + second);
+                        next[from][to] = next[from][via];
+                    }
+                }
+            }
+        }
+    }
+    let distances = distances
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|distance| distance.map(saturate))
+                .collect()
+        })
+        .collect();
+    Ok(AllPairsPaths { distances, next })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+    use crate::adjacency_matrix::AdjacencyMatrix;
+
+    fn sample() -> AdjacencyList {
+        AdjacencyList::from_edges(
+            6,
+            true,
+            &[
+                (0, 1, 7),
+                (0, 2, 9),
+                (0, 5, 14),
+                (1, 2, 10),
+                (1, 3, 15),
+                (2, 3, 11),
+                (2, 5, 2),
+                (5, 4, 9),
+                (3, 4, 6),
+            ],
+        )
+    }
+
+    ///A pseudo random graph with some negative weights but without negative cycles
+    fn dense_graph(node_count: usize) -> AdjacencyMatrix {
+        let mut g = AdjacencyMatrix::new(node_count, true);
+        let mut state = 17u64;
+        for from in 0..node_count {
+            for to in 0..node_count {
+                state = state
+                    .wrapping_mul(6364136223846793005)
+                    .wrapping_add(1442695040888963407);
+                if from != to && (state >> 60) < 5 {
+                    //a weight of at least to - from makes every cycle non negative
+                    let weight = (state >> 40) as Weight % 20 + to as Weight - from as Weight;
+                    g.add_edge(from, to, weight);
+                }
+            }
+        }
+        g
+    }
+
+    fn assert_negative_cycle<G: Graph>(graph: &G, cycle: &[usize]) {
+        assert!(!cycle.is_empty());
+        let mut total: i128 = 0;
+        for (i, from) in cycle.iter().enumerate() {
+            let to = cycle[(i + 1) % cycle.len()];
+            total += i128::from(
+                graph
+                    .get_weight(*from, to)
+                    .expect("cycle uses a missing edge"),
+            );
+        }
+        assert!(total < 0);
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let paths = dijkstra(&sample(), 0).unwrap();
+        assert_eq!(
+            vec![Some(0), Some(7), Some(9), Some(20), Some(20), Some(11)],
+            paths.distances
+        );
+        assert_eq!(Some(vec![0, 2, 5, 4]), paths.get_path(4));
+        let paths = dijkstra(&sample(), 4).unwrap();
+        assert_eq!(Some(0), paths.get_distance(4));
+        assert!(paths.get_distance(0).is_none());
+        assert!(paths.get_path(0).is_none());
+    }
+
+    #[test]
+    fn test_dijkstra_rejects_negative_weight() {
+        let g = AdjacencyList::from_edges(2, true, &[(0, 1, -1)]);
+        assert_eq!(
+            Err(PathError::NegativeWeight(Edge::new(0, 1, -1))),
+            dijkstra(&g, 0)
+        );
+    }
+
+    #[test]
+    fn test_bellman_ford() {
+        assert_eq!(dijkstra(&sample(), 0), bellman_ford(&sample(), 0));
+        let g = AdjacencyList::from_edges(4, true, &[(0, 1, 4), (0, 2, 5), (2, 1, -3), (1, 3, 1)]);
+        let paths = bellman_ford(&g, 0).unwrap();
+        assert_eq!(Some(3), paths.get_distance(3));
+        assert_eq!(Some(vec![0, 2, 1, 3]), paths.get_path(3));
+    }
+
+    #[test]
+    fn test_bellman_ford_reports_negative_cycle() {
+        let g = AdjacencyList::from_edges(
+            5,
+            true,
+            &[(0, 1, 1), (1, 2, 1), (2, 3, -4), (3, 1, 1), (3, 4, 1)],
+        );
+        match bellman_ford(&g, 0) {
+            Err(PathError::NegativeCycle(cycle)) => {
+                assert_negative_cycle(&g, &cycle);
+                assert_eq!(3, cycle.len());
+            }
+            other => panic!("expected a negative cycle, got {:?}", other),
+        }
+        //the cycle is not reachable from 4
+        assert!(bellman_ford(&g, 4).is_ok());
+        assert!(find_negative_cycle(&g).is_some());
+        assert!(find_negative_cycle(&sample()).is_none());
+    }
+
+    #[test]
+    fn test_undirected_negative_edge_is_a_cycle() {
+        let g = AdjacencyList::from_edges(2, false, &[(0, 1, -1)]);
+        let cycle = find_negative_cycle(&g).unwrap();
+        assert_eq!(2, cycle.len());
+        assert_negative_cycle(&g, &cycle);
+    }
+
+    #[test]
+    fn test_a_star_on_grid() {
+        //a 10x10 grid with a wall in column 5 except for the last row
+        let side = 10;
+        let mut g = AdjacencyList::new(side * side, false);
+        for y in 0..side {
+            for x in 0..side {
+                let wall = |x: usize, y: usize| x == 5 && y < side - 1;
+                if wall(x, y) {
+                    continue;
+                }
+                if x + 1 < side && !wall(x + 1, y) {
+                    g.add_edge(y * side + x, y * side + x + 1, 1);
+                }
+                if y + 1 < side && !wall(x, y + 1) {
+                    g.add_edge(y * side + x, (y + 1) * side + x, 1);
+                }
+            }
+        }
+        let target = side - 1;
+        let manhattan = |node: usize| {
+            let (x, y) = ((node % side) as Weight, (node / side) as Weight);
+            (target as Weight - x).abs() + y
+        };
+        let path = a_star(&g, 0, target, manhattan).unwrap().unwrap();
+        assert_eq!(
+            dijkstra(&g, 0).unwrap().get_distance(target),
+            Some(path.cost)
+        );
+        assert_eq!(path.cost as usize + 1, path.nodes.len());
+        assert_eq!(Some(&0), path.nodes.first());
+        assert_eq!(Some(&target), path.nodes.last());
+        let without_heuristic = a_star(&g, 0, target, |_| 0).unwrap().unwrap();
+        assert_eq!(path.cost, without_heuristic.cost);
+        //the wall cells are unreachable
+        assert_eq!(Ok(None), a_star(&g, 0, 5, manhattan));
+    }
+
+    #[test]
+    fn test_floyd_warshall() {
+        let g = dense_graph(30);
+        let all = floyd_warshall(&g).unwrap();
+        for source in 0..30 {
+            let single = bellman_ford(&g, source).unwrap();
+            for target in 0..30 {
+                assert_eq!(
+                    single.get_distance(target),
+                    all.get_distance(source, target)
+                );
+                if let Some(path) = all.get_path(source, target) {
+                    let cost: Weight = path
+                        .windows(2)
+                        .map(|pair| g.get_weight(pair[0], pair[1]).unwrap())
+                        .sum();
+                    assert_eq!(all.get_distance(source, target), Some(cost));
+                }
+            }
+        }
+        let g = AdjacencyList::from_edges(3, true, &[(0, 1, 1)]);
+        let all = floyd_warshall(&g).unwrap();
+        assert_eq!(Some(vec![0, 1]), all.get_path(0, 1));
+        assert_eq!(Some(vec![2]), all.get_path(2, 2));
+        assert!(all.get_path(1, 0).is_none());
+    }
+
+    #[test]
+    fn test_huge_weights_do_not_overflow() {
+        let huge = Weight::MAX / 2;
+        //a negative cycle whose total weight does not fit into a weight
+        let g = AdjacencyList::from_edges(
+            4,
+            true,
+            &[(0, 1, -huge), (1, 2, -huge), (2, 3, -huge), (3, 0, -huge)],
+        );
+        match floyd_warshall(&g) {
+            Err(PathError::NegativeCycle(cycle)) => assert_negative_cycle(&g, &cycle),
+            other => panic!("expected a negative cycle, got {:?}", other),
+        }
+        assert!(bellman_ford(&g, 0).is_err());
+        //a path longer than the largest weight saturates
+        let g = AdjacencyList::from_edges(3, true, &[(0, 1, Weight::MAX), (1, 2, Weight::MAX)]);
+        assert_eq!(
+            Some(Weight::MAX),
+            floyd_warshall(&g).unwrap().get_distance(0, 2)
+        );
+        assert_eq!(
+            Some(Weight::MAX),
+            bellman_ford(&g, 0).unwrap().get_distance(2)
+        );
+        assert_eq!(Some(Weight::MAX), dijkstra(&g, 0).unwrap().get_distance(2));
+        let path = a_star(&g, 0, 2, |_| Weight::MAX).unwrap().unwrap();
+        assert_eq!(vec![0, 1, 2], path.nodes);
+        assert_eq!(Weight::MAX, path.cost);
+    }
+
+    #[test]
+    fn test_floyd_warshall_reports_negative_cycle() {
+        let g = AdjacencyMatrix::from_edges(3, true, &[(0, 1, 2), (1, 2, -2), (2, 1, 1)]);
+        match floyd_warshall(&g) {
+            Err(PathError::NegativeCycle(cycle)) => assert_negative_cycle(&g, &cycle),
+            other => panic!("expected a negative cycle, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! This module implements the minimum spanning trees of Kruskal, on a `DisjointSet`, and Prim, on a
+//! `BinaryHeap`. The directions of the edges are ignored. A disconnected graph gets a spanning forest with one tree
+//! per connected component. The total weight is added up in 128 bits and saturates at the bounds of `Weight`.
+use crate::adjacency_list::AdjacencyList;
+use crate::disjoint_set::DisjointSet;
+use crate::graph::{saturate, Edge, Graph, Weight};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+///A minimum spanning forest with its edges and their total weight
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SpanningForest {
+    ///The chosen edges in the order they were chosen
+    pub edges: Vec<Edge>,
+    ///The sum of the weights of the chosen edges, clamped to the range of `Weight`
+    pub total_weight: Weight,
+    ///The number of trees, one per connected component
+    pub tree_count: usize,
+}
+
+impl SpanningForest {
+    ///Returns true if the forest is a single tree which spans all nodes
+    pub fn is_spanning_tree(&self) -> bool {
+        self.tree_count <= 1
+    }
+}
+
+///Returns the minimum spanning forest of `graph` found by Kruskal's algorithm
+/// # Attributes
+/// * `graph`- the graph to span
+pub fn kruskal<G: Graph + ?Sized>(graph: &G) -> SpanningForest {
+    let mut edges = graph.get_edges();
+    edges.sort_by_key(|edge| edge.weight);
+    let mut sets = DisjointSet::new(graph.get_node_count());
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        total_weight: 0,
+        tree_count: 0,
+    };
+    let mut total_weight = 0i128;
+    for edge in edges {
+        if sets.union(edge.from, edge.to) {
+            total_weight += edge.weight as i128;
+            forest.edges.push(edge);
+        }
+    }
+    forest.total_weight = saturate(total_weight);
+    forest.tree_count = sets.get_set_count();
+    forest
+}
+
+///Returns the minimum spanning forest of `graph` found by Prim's algorithm, growing one tree from the smallest
+///node of every connected component
+/// # Attributes
+/// * `graph`- the graph to span
+pub fn prim<G: Graph + ?Sized>(graph: &G) -> SpanningForest {
+    if !graph.is_directed() {
+        return prim_undirected(graph);
+    }
+    //Prim follows the neighbors of the nodes, so a directed edge has to be reachable from both of its nodes
+    let mut undirected = AdjacencyList::new(graph.get_node_count(), false);
+    for edge in graph.get_edges() {
+        undirected.add_edge(edge.from, edge.to, edge.weight);
+    }
+    prim_undirected(&undirected)
+}
+
+fn prim_undirected<G: Graph + ?Sized>(graph: &G) -> SpanningForest {
+    let node_count = graph.get_node_count();
+    let mut in_tree = vec![false; node_count];
+    let mut forest = SpanningForest {
+        edges: Vec::new(),
+        total_weight: 0,
+        tree_count: 0,
+    };
+    let mut total_weight = 0i128;
+    let mut heap = BinaryHeap::new();
+    for root in 0..node_count {
+        if in_tree[root] {
+            continue;
+        }
+        forest.tree_count += 1;
+        in_tree[root] = true;
+        for (to, weight) in graph.get_neighbors(root) {
+            heap.push(Reverse((weight, root, to)));
+        }
+        while let Some(Reverse((weight, from, to))) = heap.pop() {
+            if in_tree[to] {
+                continue;
+            }
+            in_tree[to] = true;
+            total_weight += weight as i128;
+            forest.edges.push(Edge::new(from, to, weight));
+            for (next, weight) in graph.get_neighbors(to) {
+                if !in_tree[next] {
+                    heap.push(Reverse((weight, to, next)));
+                }
+            }
+        }
+    }
+    forest.total_weight = saturate(total_weight);
+    forest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_matrix::AdjacencyMatrix;
+
+    fn sample<G: Graph>() -> G {
+        G::from_edges(
+            7,
+            false,
+            &[
+                (0, 1, 7),
+                (0, 3, 5),
+                (1, 2, 8),
+                (1, 3, 9),
+                (1, 4, 7),
+                (2, 4, 5),
+                (3, 4, 15),
+                (3, 5, 6),
+                (4, 5, 8),
+                (4, 6, 9),
+                (5, 6, 11),
+            ],
+        )
+    }
+
+    ///Returns true if `edges` connect all nodes of every component of `graph` without a cycle
+    fn is_forest_of<G: Graph>(graph: &G, forest: &SpanningForest) -> bool {
+        let mut sets = DisjointSet::new(graph.get_node_count());
+        let acyclic = forest
+            .edges
+            .iter()
+            .all(|edge| sets.union(edge.from, edge.to));
+        let mut components = DisjointSet::new(graph.get_node_count());
+        for edge in graph.get_edges() {
+            components.union(edge.from, edge.to);
+        }
+        acyclic && sets.get_set_count() == components.get_set_count()
+    }
+
+    #[test]
+    fn test_kruskal() {
+        let g: AdjacencyList = sample();
+        let forest = kruskal(&g);
+        assert_eq!(39, forest.total_weight);
+        assert_eq!(6, forest.edges.len());
+        assert_eq!(Edge::new(0, 3, 5), forest.edges[0]);
+        assert!(forest.is_spanning_tree());
+        assert!(is_forest_of(&g, &forest));
+    }
+
+    #[test]
+    fn test_prim() {
+        let g: AdjacencyMatrix = sample();
+        let forest = prim(&g);
+        assert_eq!(39, forest.total_weight);
+        assert_eq!(Edge::new(0, 3, 5), forest.edges[0]);
+        assert!(is_forest_of(&g, &forest));
+        assert_eq!(
+            kruskal(&g).total_weight,
+            prim(&sample::<AdjacencyList>()).total_weight
+        );
+    }
+
+    #[test]
+    fn test_disconnected_graph_gives_forest() {
+        let g = AdjacencyList::from_edges(6, false, &[(0, 1, 3), (1, 2, 1), (0, 2, 2), (3, 4, -1)]);
+        for forest in [kruskal(&g), prim(&g)] {
+            assert_eq!(3, forest.tree_count);
+            assert!(!forest.is_spanning_tree());
+            assert_eq!(2, forest.total_weight);
+            assert_eq!(3, forest.edges.len());
+            assert!(is_forest_of(&g, &forest));
+        }
+    }
+
+    #[test]
+    fn test_directions_are_ignored() {
+        let g = AdjacencyList::from_edges(3, true, &[(1, 0, 4), (2, 0, 1), (1, 2, 2)]);
+        assert_eq!(3, prim(&g).total_weight);
+        assert_eq!(3, kruskal(&g).total_weight);
+        assert_eq!(1, prim(&g).tree_count);
+    }
+
+    #[test]
+    fn test_huge_weights_do_not_overflow() {
+        let huge = Weight::MAX / 2;
+        let g = AdjacencyList::from_edges(4, false, &[(0, 1, huge), (1, 2, huge), (2, 3, huge)]);
+        assert_eq!(Weight::MAX, kruskal(&g).total_weight);
+        assert_eq!(Weight::MAX, prim(&g).total_weight);
+        let g = AdjacencyList::from_edges(4, false, &[(0, 1, huge), (1, 2, huge), (2, 3, -huge)]);
+        assert_eq!(huge, kruskal(&g).total_weight);
+        assert_eq!(huge, prim(&g).total_weight);
+        let g = AdjacencyList::from_edges(4, false, &[(0, 1, -huge), (1, 2, -huge), (2, 3, -huge)]);
+        assert_eq!(Weight::MIN, kruskal(&g).total_weight);
+        assert_eq!(Weight::MIN, prim(&g).total_weight);
+    }
+
+    #[test]
+    fn test_random_graph_prim_matches_kruskal() {
+        let mut state = 99u64;
+        let mut g = AdjacencyList::new(80, false);
+        for _ in 0..400 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            let from = (state >> 33) as usize % 80;
+            let to = (state >> 45) as usize % 80;
+            g.add_edge(from, to, (state >> 20) as Weight % 100 - 30);
+        }
+        let (k, p) = (kruskal(&g), prim(&g));
+        assert_eq!(k.total_weight, p.total_weight);
+        assert_eq!(k.tree_count, p.tree_count);
+        assert!(is_forest_of(&g, &k));
+        assert!(is_forest_of(&g, &p));
+    }
+}
@@ -0,0 +1,192 @@
+//! This module implements the breadth first search on a `fifo::Fifo` and the depth first search on a
+//! `lifo::Lifo`. The depth first search keeps its own stack of frames instead of recursing, so it works on
+//! graphs of any depth, and visits the nodes in the same order as the recursive version would.
+//! Both searches ignore the edge weights and return a `Traversal`.
+use crate::graph::Graph;
+use fifo::fifo::Fifo;
+use lifo::lifo::Lifo;
+
+///The result of a traversal from a start node
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Traversal {
+    ///The reached nodes in the order they were visited
+    pub order: Vec<usize>,
+    ///The node every reached node was discovered from, None for the start node and the unreached nodes
+    pub parents: Vec<Option<usize>>,
+    ///The number of edges from the start node in the traversal tree, None for the unreached nodes
+    pub distances: Vec<Option<usize>>,
+}
+
+impl Traversal {
+    fn new(node_count: usize) -> Traversal {
+        Traversal {
+            order: Vec::new(),
+            parents: vec![None; node_count],
+            distances: vec![None; node_count],
+        }
+    }
+
+    fn visit(&mut self, node: usize, parent: Option<usize>) {
+        self.order.push(node);
+        self.parents[node] = parent;
+        self.distances[node] = Some(parent.map_or(0, |parent| self.distances[parent].unwrap() + 1));
+    }
+
+    ///Returns true if `node` was reached
+    /// # Attributes
+    /// * `node`- the node to check
+    pub fn is_reached(&self, node: usize) -> bool {
+        self.distances[node].is_some()
+    }
+
+    ///Returns the nodes on the path of the traversal tree from the start node to `node`, or None if `node` was
+    ///not reached
+    /// # Attributes
+    /// * `node`- the node the path ends at
+    pub fn get_path(&self, node: usize) -> Option<Vec<usize>> {
+        self.distances[node]?;
+        let mut path = vec![node];
+        let mut current = node;
+        while let Some(parent) = self.parents[current] {
+            path.push(parent);
+            current = parent;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+///Returns the breadth first traversal of `graph` from `start`. The distances are the fewest edges to every node.
+/// # Attributes
+/// * `graph`- the graph to traverse
+/// * `start`- the node to start at
+pub fn bfs<G: Graph + ?Sized>(graph: &G, start: usize) -> Traversal {
+    let mut traversal = Traversal::new(graph.get_node_count());
+    let mut queue = Fifo::new();
+    traversal.visit(start, None);
+    queue.enqueue(start);
+    while let Some(node) = queue.dequeue() {
+        for (neighbor, _) in graph.get_neighbors(node) {
+            if !traversal.is_reached(neighbor) {
+                traversal.visit(neighbor, Some(node));
+                queue.enqueue(neighbor);
+            }
+        }
+    }
+    traversal
+}
+
+///A node on the stack of the depth first search with the iterator over the neighbors it has not looked at yet
+pub(crate) type Frame<I> = (usize, I);
+
+///Returns the depth first traversal of `graph` from `start`. The distances are the depths in the depth first tree.
+/// # Attributes
+/// * `graph`- the graph to traverse
+/// * `start`- the node to start at
+pub fn dfs<G: Graph + ?Sized>(graph: &G, start: usize) -> Traversal {
+    let mut traversal = Traversal::new(graph.get_node_count());
+    let mut stack: Lifo<Frame<_>> = Lifo::new();
+    traversal.visit(start, None);
+    stack.enqueue((start, graph.get_neighbors(start)));
+    while let Some((node, mut neighbors)) = stack.dequeue() {
+        let neighbor = match neighbors.next() {
+            Some((neighbor, _)) => neighbor,
+            None => continue,
+        };
+        //the node stays on the stack until all its neighbors were looked at
+        stack.enqueue((node, neighbors));
+        if !traversal.is_reached(neighbor) {
+            traversal.visit(neighbor, Some(node));
+            stack.enqueue((neighbor, graph.get_neighbors(neighbor)));
+        }
+    }
+    traversal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adjacency_list::AdjacencyList;
+    use crate::adjacency_matrix::AdjacencyMatrix;
+
+    ///  0 - 1 - 3
+    ///  |   |
+    ///  2 - 4   5
+    fn sample<G: Graph>() -> G {
+        G::from_edges(
+            6,
+            false,
+            &[(0, 1, 1), (0, 2, 1), (1, 3, 1), (1, 4, 1), (2, 4, 1)],
+        )
+    }
+
+    fn recursive_dfs(
+        graph: &AdjacencyList,
+        node: usize,
+        visited: &mut Vec<bool>,
+        order: &mut Vec<usize>,
+    ) {
+        visited[node] = true;
+        order.push(node);
+        for (neighbor, _) in graph.get_neighbors(node) {
+            if !visited[neighbor] {
+                recursive_dfs(graph, neighbor, visited, order);
+            }
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let t = bfs(&sample::<AdjacencyList>(), 0);
+        assert_eq!(vec![0, 1, 2, 3, 4], t.order);
+        assert_eq!(
+            vec![Some(0), Some(1), Some(1), Some(2), Some(2), None],
+            t.distances
+        );
+        assert_eq!(Some(vec![0, 1, 4]), t.get_path(4));
+        assert!(!t.is_reached(5));
+        assert!(t.get_path(5).is_none());
+        assert_eq!(t, bfs(&sample::<AdjacencyMatrix>(), 0));
+    }
+
+    #[test]
+    fn test_dfs() {
+        let t = dfs(&sample::<AdjacencyList>(), 0);
+        assert_eq!(vec![0, 1, 3, 4, 2], t.order);
+        assert_eq!(Some(vec![0, 1, 4, 2]), t.get_path(2));
+        assert_eq!(Some(3), t.distances[2]);
+        assert!(t.parents[0].is_none());
+        assert_eq!(t, dfs(&sample::<AdjacencyMatrix>(), 0));
+    }
+
+    #[test]
+    fn test_directed_traversal() {
+        let g = AdjacencyList::from_edges(3, true, &[(0, 1, 1), (2, 1, 1)]);
+        assert_eq!(vec![0, 1], bfs(&g, 0).order);
+        assert_eq!(vec![1], dfs(&g, 1).order);
+    }
+
+    #[test]
+    fn test_dfs_matches_recursive_order() {
+        let mut edges = Vec::new();
+        for i in 0..200usize {
+            edges.push((i, (i * 7 + 3) % 200, 1));
+            edges.push((i, (i * 13 + 5) % 200, 1));
+        }
+        let g = AdjacencyList::from_edges(200, true, &edges);
+        let mut visited = vec![false; 200];
+        let mut order = Vec::new();
+        recursive_dfs(&g, 0, &mut visited, &mut order);
+        assert_eq!(order, dfs(&g, 0).order);
+    }
+
+    #[test]
+    fn test_deep_path_does_not_overflow() {
+        let n = 200_000;
+        let edges: Vec<_> = (0..n - 1).map(|i| (i, i + 1, 1)).collect();
+        let g = AdjacencyList::from_edges(n, true, &edges);
+        let t = dfs(&g, 0);
+        assert_eq!(Some(n - 1), t.distances[n - 1]);
+        assert_eq!(Some(n - 1), bfs(&g, 0).distances[n - 1]);
+    }
+}
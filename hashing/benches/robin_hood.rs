@@ -0,0 +1,120 @@
+//! Compares the Robin Hood hash map with `std::collections::HashMap` for inserting, looking up present and missing
+//! keys and removing, both with the default hasher of the standard library.
+//! Run with `cargo bench -p hashing --bench robin_hood`.
+use hashing::robin_hood::HashMap;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITEMS: u64 = 500_000;
+const ROUNDS: u32 = 5;
+
+///Runs `f` `ROUNDS` times and returns the fastest duration
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, duration: Duration) {
+    println!("{:<30} {:>10.3} ms", name, duration.as_secs_f64() * 1000.0);
+}
+
+///Spreads the keys so that they are not inserted in the order of their hashes
+fn key(i: u64) -> u64 {
+    i.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+}
+
+fn main() {
+    report(
+        "insert std",
+        measure(|| {
+            let mut m = std::collections::HashMap::new();
+            for i in 0..ITEMS {
+                m.insert(key(i), i);
+            }
+            black_box(m);
+        }),
+    );
+    report(
+        "insert RobinHood",
+        measure(|| {
+            let mut m = HashMap::new();
+            for i in 0..ITEMS {
+                m.insert(key(i), i);
+            }
+            black_box(m);
+        }),
+    );
+
+    let std_map: std::collections::HashMap<u64, u64> = (0..ITEMS).map(|i| (key(i), i)).collect();
+    let robin_hood: HashMap<u64, u64> = (0..ITEMS).map(|i| (key(i), i)).collect();
+    report(
+        "get present std",
+        measure(|| {
+            black_box((0..ITEMS).filter_map(|i| std_map.get(&key(i))).sum::<u64>());
+        }),
+    );
+    report(
+        "get present RobinHood",
+        measure(|| {
+            black_box(
+                (0..ITEMS)
+                    .filter_map(|i| robin_hood.get(&key(i)))
+                    .sum::<u64>(),
+            );
+        }),
+    );
+    report(
+        "get missing std",
+        measure(|| {
+            black_box(
+                (ITEMS..2 * ITEMS)
+                    .filter(|i| std_map.contains_key(&key(*i)))
+                    .count(),
+            );
+        }),
+    );
+    report(
+        "get missing RobinHood",
+        measure(|| {
+            black_box(
+                (ITEMS..2 * ITEMS)
+                    .filter(|i| robin_hood.contains_key(&key(*i)))
+                    .count(),
+            );
+        }),
+    );
+    report(
+        "remove std",
+        measure(|| {
+            let mut m = std_map.clone();
+            for i in 0..ITEMS {
+                m.remove(&key(i));
+            }
+            black_box(m);
+        }),
+    );
+    report(
+        "remove RobinHood",
+        measure(|| {
+            let mut m = robin_hood.clone();
+            for i in 0..ITEMS {
+                m.remove(&key(i));
+            }
+            black_box(m);
+        }),
+    );
+
+    let statistics = robin_hood.probe_statistics();
+    println!(
+        "RobinHood load {:.3}, longest probe {}, average probe {:.3}",
+        robin_hood.get_load_factor(),
+        statistics.longest,
+        statistics.average
+    );
+}
@@ -0,0 +1,656 @@
+//! This module implements a hash map with separate chaining. The entries are kept in a vector of slots, a removed
+//! entry frees its slot for the next insertion. Every slot of the table is a bucket, a `lists::SList` of the hashes
+//! and slots of the entries whose hashes point to it. A `lists::DlList` of the slots keeps the order of insertion,
+//! and every entry holds the `NodeHandle` of its slot, so removing an entry unlinks it in O(1) and iterating reads
+//! the entries without looking their keys up again.
+//! The table doubles incrementally: when it grows, the old table is kept and every following insertion or removal
+//! moves a few of its buckets into the new one, so no single operation pays for rehashing all entries. The number
+//! of buckets moved per operation is chosen so the old table is empty before the table has to grow again. An entry
+//! lives in the old table as long as its old bucket has not moved yet and in the new table afterwards.
+use lists::dll::{self, DlList, NodeHandle};
+use lists::slist::SList;
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+///The maximum load factor of a map which is not given one
+pub const DEFAULT_MAX_LOAD_FACTOR: f64 = 1.0;
+
+///The number of buckets of the first table
+const MIN_CAPACITY: usize = 8;
+
+///The least number of old buckets moved by an insertion or removal while the table grows
+const MIGRATION_STEP: usize = 4;
+
+///An entry of the map with the handle of its slot in the insertion order
+struct Entry<K, V> {
+    key: K,
+    value: V,
+    handle: NodeHandle<usize>,
+}
+
+///A hash and the slot of its entry, the element of a bucket
+type Link = (u64, usize);
+
+///A hash map with separate chaining over the hashers built by `S`
+pub struct ChainedHashMap<K, V, S = RandomState> {
+    slots: Vec<Option<Entry<K, V>>>,
+    //the empty slots
+    free: Vec<usize>,
+    buckets: Vec<SList<Link>>,
+    //the table being emptied into `buckets`, empty if the map is not growing
+    old_buckets: Vec<SList<Link>>,
+    //the number of old buckets already moved
+    migrated: usize,
+    //the number of old buckets moved by an insertion or removal
+    migration_step: usize,
+    //the slots of the entries in the order of their insertion
+    order: DlList<usize>,
+    length: usize,
+    max_load_factor: f64,
+    hasher: S,
+}
+
+///The distribution of the entries over the buckets of both tables
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChainStatistics {
+    ///The number of buckets
+    pub buckets: usize,
+    ///The number of buckets without entries
+    pub empty_buckets: usize,
+    ///The largest number of entries in one bucket
+    pub longest_chain: usize,
+    ///The average number of entries of the buckets which have entries, 0 for an empty map
+    pub average_chain: f64,
+    ///The number of entries which share their bucket with an entry before them
+    pub collisions: usize,
+}
+
+///Iterator over the entries of a `ChainedHashMap` in the order of their insertion
+pub struct Iter<'a, K, V> {
+    order: dll::Iter<'a, usize>,
+    slots: &'a [Option<Entry<K, V>>],
+}
+
+impl<K: Hash + Eq, V> Default for ChainedHashMap<K, V, RandomState> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> ChainedHashMap<K, V, RandomState> {
+    ///Returns a new empty map with the default maximum load factor and randomly seeded hashers
+    pub fn new() -> ChainedHashMap<K, V, RandomState> {
+        Self::with_hasher(RandomState::new())
+    }
+
+    ///Returns a new empty map with randomly seeded hashers which grows its table once there would be more than
+    ///`max_load_factor` entries per bucket. Panics if `max_load_factor` is not positive.
+    /// # Attributes
+    /// * `max_load_factor`- the largest ratio of entries to buckets
+    pub fn with_max_load_factor(max_load_factor: f64) -> ChainedHashMap<K, V, RandomState> {
+        Self::with_hasher_and_max_load_factor(RandomState::new(), max_load_factor)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> ChainedHashMap<K, V, S> {
+    ///Returns a new empty map with the default maximum load factor which hashes the keys with `hasher`
+    /// # Attributes
+    /// * `hasher`- builds the hashers of the keys
+    pub fn with_hasher(hasher: S) -> ChainedHashMap<K, V, S> {
+        Self::with_hasher_and_max_load_factor(hasher, DEFAULT_MAX_LOAD_FACTOR)
+    }
+
+    ///Returns a new empty map which hashes the keys with `hasher` and grows its table once there would be more
+    ///than `max_load_factor` entries per bucket. Panics if `max_load_factor` is not positive.
+    /// # Attributes
+    /// * `hasher`- builds the hashers of the keys
+    /// * `max_load_factor`- the largest ratio of entries to buckets
+    pub fn with_hasher_and_max_load_factor(
+        hasher: S,
+        max_load_factor: f64,
+    ) -> ChainedHashMap<K, V, S> {
+        assert!(
+            max_load_factor > 0.0,
+            "the maximum load factor {} is not positive",
+            max_load_factor
+        );
+        ChainedHashMap {
+            slots: Vec::new(),
+            free: Vec::new(),
+            buckets: Vec::new(),
+            old_buckets: Vec::new(),
+            migrated: 0,
+            migration_step: MIGRATION_STEP,
+            order: DlList::new(),
+            length: 0,
+            max_load_factor,
+            hasher,
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of buckets of the current table
+    pub fn get_capacity(&self) -> usize {
+        self.buckets.len()
+    }
+
+    ///Returns true if buckets of an old table still have to be moved into the current one
+    pub fn is_resizing(&self) -> bool {
+        !self.old_buckets.is_empty()
+    }
+
+    ///Returns the ratio of entries to buckets above which the table grows
+    pub fn get_max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`. Moves a few buckets if the table is growing.
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.migrate(self.migration_step);
+        let hash = self.hasher.hash_one(&key);
+        if let Some(slot) = self.find(hash, &key) {
+            let entry = self.slots[slot].as_mut().unwrap();
+            return Some(std::mem::replace(&mut entry.value, value));
+        }
+        if (self.length + 1) as f64 > self.buckets.len() as f64 * self.max_load_factor {
+            self.grow();
+        }
+        let slot = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(None);
+            self.slots.len() - 1
+        });
+        let handle = self.order.enqueue_with_handle(slot);
+        self.slots[slot] = Some(Entry { key, value, handle });
+        self.bucket_mut(hash).unwrap().push_front((hash, slot));
+        self.length += 1;
+        None
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let slot = self.find(self.hasher.hash_one(key), key)?;
+        self.slots[slot].as_ref().map(|entry| &entry.value)
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let slot = self.find(self.hasher.hash_one(key), key)?;
+        self.slots[slot].as_mut().map(|entry| &mut entry.value)
+    }
+
+    ///Returns true if the map has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.get(key).is_some()
+    }
+
+    ///Removes the entry of `key` and returns its value. Moves a few buckets if the table is growing.
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        self.migrate(self.migration_step);
+        let hash = self.hasher.hash_one(key);
+        let slot = self.find(hash, key)?;
+        self.bucket_mut(hash)?.remove_first(|link| link.1 == slot);
+        let entry = self.slots[slot].take()?;
+        self.free.push(slot);
+        self.order.remove(&entry.handle);
+        self.length -= 1;
+        Some(entry.value)
+    }
+
+    ///Removes all entries and the tables
+    pub fn clear(&mut self) {
+        self.slots = Vec::new();
+        self.free = Vec::new();
+        self.buckets = Vec::new();
+        self.old_buckets = Vec::new();
+        self.migrated = 0;
+        self.migration_step = MIGRATION_STEP;
+        self.order = DlList::new();
+        self.length = 0;
+    }
+
+    ///Returns an iterator over the entries in the order of their insertion
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            order: self.order.iter(),
+            slots: &self.slots,
+        }
+    }
+
+    ///Returns the distribution of the entries over the buckets of both tables
+    pub fn chain_statistics(&self) -> ChainStatistics {
+        let mut statistics = ChainStatistics {
+            buckets: 0,
+            empty_buckets: 0,
+            longest_chain: 0,
+            average_chain: 0.0,
+            collisions: 0,
+        };
+        for bucket in self
+            .buckets
+            .iter()
+            .chain(self.old_buckets[self.migrated..].iter())
+        {
+            let length = bucket.get_length() as usize;
+            statistics.buckets += 1;
+            if length == 0 {
+                statistics.empty_buckets += 1;
+            }
+            statistics.longest_chain = statistics.longest_chain.max(length);
+            statistics.collisions += length.saturating_sub(1);
+        }
+        let used = statistics.buckets - statistics.empty_buckets;
+        if used > 0 {
+            statistics.average_chain = self.length as f64 / used as f64;
+        }
+        statistics
+    }
+
+    ///Checks that every entry is linked once with the hash of its key from the bucket of its hash, that the moved
+    ///old buckets are empty, that the free slots are empty and that the insertion order holds every entry once.
+    ///Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut linked = vec![false; self.slots.len()];
+        for (index, bucket) in self.old_buckets.iter().enumerate() {
+            if index < self.migrated && !bucket.is_empty() {
+                return Err(format!("moved old bucket {} has entries", index));
+            }
+        }
+        let tables = [(&self.buckets, 0), (&self.old_buckets, self.migrated)];
+        for (table, first) in tables.iter() {
+            for (index, bucket) in table.iter().enumerate().skip(*first) {
+                for &(hash, slot) in bucket.iter() {
+                    let entry = match self.slots.get(slot) {
+                        Some(Some(entry)) if !linked[slot] => entry,
+                        _ => {
+                            return Err(format!(
+                                "bucket {} links slot {} which is empty or linked twice",
+                                index, slot
+                            ))
+                        }
+                    };
+                    linked[slot] = true;
+                    if hash != self.hasher.hash_one(&entry.key) {
+                        return Err(format!("an entry of bucket {} stores a wrong hash", index));
+                    }
+                    let home = self.bucket(hash).map(|home| home as *const _);
+                    if home != Some(bucket as *const _) {
+                        return Err(format!("bucket {} holds an entry of another bucket", index));
+                    }
+                }
+            }
+        }
+        let count = linked.iter().filter(|&&linked| linked).count();
+        if count != self.length {
+            return Err(format!("length {} but {} entries", self.length, count));
+        }
+        if count + self.free.len() != self.slots.len()
+            || self.free.iter().any(|&slot| self.slots[slot].is_some())
+        {
+            return Err(String::from("the free slots do not match the entries"));
+        }
+        let mut ordered = vec![false; self.slots.len()];
+        for slot in self.order.iter() {
+            if !linked.get(slot).copied().unwrap_or(false) || ordered[slot] {
+                return Err(String::from(
+                    "the insertion order does not match the entries",
+                ));
+            }
+            ordered[slot] = true;
+        }
+        if self.order.get_length() as usize != count {
+            return Err(String::from(
+                "the insertion order does not match the entries",
+            ));
+        }
+        Ok(())
+    }
+
+    ///Returns the slot of the entry of `key`, whose hash is `hash`
+    fn find<Q: Eq + ?Sized>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        self.bucket(hash)?
+            .iter()
+            .find(|link| {
+                link.0 == hash
+                    && self.slots[link.1]
+                        .as_ref()
+                        .is_some_and(|entry| entry.key.borrow() == key)
+            })
+            .map(|link| link.1)
+    }
+
+    ///Returns the bucket which holds the entries of `hash`, None if there is no table
+    fn bucket(&self, hash: u64) -> Option<&SList<Link>> {
+        if !self.old_buckets.is_empty() {
+            let index = hash as usize & (self.old_buckets.len() - 1);
+            if index >= self.migrated {
+                return Some(&self.old_buckets[index]);
+            }
+        }
+        if self.buckets.is_empty() {
+            return None;
+        }
+        Some(&self.buckets[hash as usize & (self.buckets.len() - 1)])
+    }
+
+    ///Returns the mutable bucket which holds the entries of `hash`, None if there is no table
+    fn bucket_mut(&mut self, hash: u64) -> Option<&mut SList<Link>> {
+        if !self.old_buckets.is_empty() {
+            let index = hash as usize & (self.old_buckets.len() - 1);
+            if index >= self.migrated {
+                return Some(&mut self.old_buckets[index]);
+            }
+        }
+        if self.buckets.is_empty() {
+            return None;
+        }
+        let index = hash as usize & (self.buckets.len() - 1);
+        Some(&mut self.buckets[index])
+    }
+
+    ///Starts moving the entries into a table of twice the buckets and spreads the moves over the insertions left
+    ///until the new table is full. The old table is empty by then, the migration here only guards against that.
+    fn grow(&mut self) {
+        self.migrate(self.old_buckets.len());
+        let capacity = (self.buckets.len() * 2).max(MIN_CAPACITY);
+        let mut buckets = Vec::with_capacity(capacity);
+        buckets.resize_with(capacity, SList::new);
+        self.old_buckets = std::mem::replace(&mut self.buckets, buckets);
+        self.migrated = 0;
+        //every insertion up to and including the one which grows the table again moves buckets
+        let limit = (capacity as f64 * self.max_load_factor) as usize;
+        let insertions = limit.saturating_sub(self.length).max(1);
+        self.migration_step = self
+            .old_buckets
+            .len()
+            .div_ceil(insertions)
+            .max(MIGRATION_STEP);
+    }
+
+    ///Moves up to `count` old buckets into the current table and drops the old table once it is empty
+    fn migrate(&mut self, count: usize) {
+        let end = (self.migrated + count).min(self.old_buckets.len());
+        let mask = self.buckets.len().wrapping_sub(1);
+        for index in self.migrated..end {
+            while let Some(link) = self.old_buckets[index].pop_front() {
+                self.buckets[link.0 as usize & mask].push_front(link);
+            }
+        }
+        self.migrated = end;
+        if self.migrated == self.old_buckets.len() {
+            self.old_buckets = Vec::new();
+            self.migrated = 0;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let slot = self.order.next()?;
+        self.slots[slot]
+            .as_ref()
+            .map(|entry| (&entry.key, &entry.value))
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a ChainedHashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for ChainedHashMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = ChainedHashMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for ChainedHashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    ///A hasher which maps every key to the same hash, so all keys collide
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            3
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[test]
+    fn test_new_chained_map() {
+        let m: ChainedHashMap<i32, i32> = ChainedHashMap::new();
+        assert!(m.is_empty());
+        assert!(m.get(&1).is_none());
+        assert_eq!(0, m.chain_statistics().buckets);
+        assert_eq!(0, m.iter().count());
+        m.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut m = ChainedHashMap::new();
+        assert!(m.insert(String::from("one"), 1).is_none());
+        assert!(m.insert(String::from("two"), 2).is_none());
+        assert_eq!(Some(1), m.insert(String::from("one"), 10));
+        assert_eq!(Some(&10), m.get("one"));
+        *m.get_mut("two").unwrap() += 5;
+        assert_eq!(Some(7), m.remove("two"));
+        assert!(m.remove("two").is_none());
+        assert!(m.contains_key("one") && !m.contains_key("two"));
+        m.check_invariants().unwrap();
+        m.clear();
+        assert!(m.is_empty());
+        m.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        let mut m = ChainedHashMap::new();
+        for i in (0..100).rev() {
+            m.insert(i, i * 2);
+        }
+        for i in (0..100).step_by(3) {
+            m.remove(&i);
+        }
+        m.insert(0, 7);
+        m.insert(50, 1);
+        let mut expected: Vec<(i32, i32)> = (0..100)
+            .rev()
+            .filter(|i| i % 3 != 0)
+            .map(|i| (i, i * 2))
+            .collect();
+        expected.push((0, 7));
+        for entry in expected.iter_mut() {
+            if entry.0 == 50 {
+                entry.1 = 1;
+            }
+        }
+        assert_eq!(
+            expected,
+            m.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_incremental_growth() {
+        let mut m = ChainedHashMap::new();
+        for i in 0..8 {
+            m.insert(i, i);
+        }
+        assert_eq!(8, m.get_capacity());
+        assert!(!m.is_resizing());
+        m.insert(8, 8);
+        assert_eq!(16, m.get_capacity());
+        assert!(m.is_resizing());
+        m.check_invariants().unwrap();
+        assert!((0..9).all(|i| m.get(&i) == Some(&i)));
+        //every insertion moves four of the eight old buckets
+        m.insert(9, 9);
+        assert!(m.is_resizing());
+        m.insert(10, 10);
+        assert!(!m.is_resizing());
+        m.check_invariants().unwrap();
+        for i in 11..10_000 {
+            m.insert(i, i);
+            if m.is_resizing() {
+                m.check_invariants().unwrap();
+                assert!(m.get_length() as f64 <= m.get_capacity() as f64);
+            }
+        }
+        assert_eq!(16_384, m.get_capacity());
+    }
+
+    #[test]
+    fn test_low_load_factor_grows_incrementally() {
+        let mut m = ChainedHashMap::with_max_load_factor(0.1);
+        for i in 0..10_000 {
+            let capacity = m.get_capacity();
+            let left = m.old_buckets.len() - m.migrated;
+            let step = m.migration_step;
+            m.insert(i, i);
+            if m.get_capacity() != capacity {
+                //the migration of the insertion emptied the old table before it grew again
+                assert!(
+                    left <= step,
+                    "{} old buckets left for a step of {}",
+                    left,
+                    step
+                );
+            }
+            assert!(m.migration_step < 64);
+        }
+        m.check_invariants().unwrap();
+        assert!(m.get_length() as f64 <= m.get_capacity() as f64 * 0.1);
+        assert!((0..10_000).all(|i| m.get(&i) == Some(&i)));
+        assert!(m.iter().map(|(k, _)| *k).eq(0..10_000));
+    }
+
+    #[test]
+    fn test_free_slots_are_reused() {
+        let mut m = ChainedHashMap::new();
+        for i in 0..100 {
+            m.insert(i, i);
+        }
+        for i in 0..50 {
+            m.remove(&i);
+        }
+        for i in 100..150 {
+            m.insert(i, i);
+        }
+        assert_eq!(100, m.slots.len());
+        m.check_invariants().unwrap();
+        assert!(m.iter().map(|(k, _)| *k).eq(50..150));
+    }
+
+    #[test]
+    fn test_keys_without_clone() {
+        #[derive(Hash, PartialEq, Eq, Debug)]
+        struct Key(u32);
+
+        let mut m: ChainedHashMap<Key, u32> = (0..20).map(|i| (Key(i), i)).collect();
+        assert_eq!(Some(&7), m.get(&Key(7)));
+        assert_eq!(Some(3), m.remove(&Key(3)));
+        assert_eq!(Some(&Key(0)), m.iter().next().map(|(key, _)| key));
+        m.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_chain_statistics() {
+        let mut m: ChainedHashMap<u32, u32, BuildHasherDefault<ConstantHasher>> =
+            ChainedHashMap::with_hasher_and_max_load_factor(BuildHasherDefault::default(), 2.0);
+        for i in 0..16 {
+            m.insert(i, i);
+        }
+        m.check_invariants().unwrap();
+        let statistics = m.chain_statistics();
+        assert_eq!(8, statistics.buckets);
+        assert_eq!(7, statistics.empty_buckets);
+        assert_eq!(16, statistics.longest_chain);
+        assert_eq!(15, statistics.collisions);
+        assert_eq!(16.0, statistics.average_chain);
+        assert!((0..16).all(|i| m.remove(&i) == Some(i)));
+        assert_eq!(0, m.chain_statistics().collisions);
+    }
+
+    #[test]
+    fn test_random_operations_against_std() {
+        let mut rng = XorShift64::new(48);
+        let mut m = ChainedHashMap::with_max_load_factor(0.75);
+        let mut reference = std::collections::HashMap::new();
+        for i in 0..20_000 {
+            let key = rng.next_below(3_000);
+            if rng.next_below(3) == 0 {
+                assert_eq!(reference.remove(&key), m.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, i), m.insert(key, i));
+            }
+            if i % 500 == 0 {
+                m.check_invariants().unwrap();
+            }
+        }
+        m.check_invariants().unwrap();
+        assert_eq!(reference.len(), m.get_length());
+        assert!(m
+            .iter()
+            .all(|(key, value)| reference.get(key) == Some(value)));
+        assert_eq!(reference.len(), m.iter().count());
+    }
+}
@@ -0,0 +1,646 @@
+//! This module implements cuckoo hashing, which bounds a lookup to two slots. `CuckooSet` keeps two tables with a
+//! hash function each, and every value lives in the slot of one of its two hashes. An insertion takes the slot of
+//! its value in the first table and evicts the value there into its slot in the other table, which may evict
+//! again. An eviction chain longer than a bound which grows with the logarithm of the size is taken as a loop: the
+//! set then rehashes all values with two new hash functions, and doubles its tables if that keeps failing.
+//! `CuckooFilter` is an approximate set of fingerprints in buckets of four. The second bucket of a fingerprint is
+//! derived from the first bucket and the fingerprint alone, so fingerprints can be moved and deleted without the
+//! values. A lookup can return a false positive but never a false negative for an inserted value.
+use lists::rng::{RandomSource, XorShift64};
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+///The largest ratio of values to slots of a `CuckooSet`, above which its tables double
+pub const MAX_LOAD_FACTOR: f64 = 0.45;
+
+///The number of slots of each first table of a `CuckooSet`
+const MIN_CAPACITY: usize = 8;
+
+///The number of failed rehashes of a `CuckooSet` after which its tables double
+const REHASHES_PER_SIZE: usize = 4;
+
+///The number of failed rehashes after which a `CuckooSet` gives up, as its hash functions can't separate the values
+const MAX_REHASHES: usize = 64;
+
+///The number of fingerprints in a bucket of a `CuckooFilter`
+const BUCKET_SIZE: usize = 4;
+
+///The ratio of fingerprints to entries a `CuckooFilter` is sized for
+const FILTER_LOAD_FACTOR: f64 = 0.95;
+
+///The number of fingerprints a `CuckooFilter` evicts before it stores the last one aside and reports being full
+const MAX_KICKS: usize = 500;
+
+///A set with cuckoo hashing over two hash functions derived from the hashers built by `S`
+pub struct CuckooSet<T, S = RandomState> {
+    tables: [Vec<Option<T>>; 2],
+    //the seeds which make the two hash functions out of the hashers of `hasher`
+    seeds: [u64; 2],
+    length: usize,
+    rehashes: usize,
+    rng: XorShift64,
+    hasher: S,
+}
+
+///An approximate set of the fingerprints of values hashed by the hashers built by `S`
+pub struct CuckooFilter<S = RandomState> {
+    //0 marks an empty entry
+    buckets: Vec<[u16; BUCKET_SIZE]>,
+    fingerprint_bits: u32,
+    length: usize,
+    //a fingerprint and its bucket which found no place, the filter is full as long as it is there
+    victim: Option<(usize, u16)>,
+    rng: XorShift64,
+    hasher: S,
+}
+
+impl<T: Hash + Eq> Default for CuckooSet<T, RandomState> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Hash + Eq> CuckooSet<T, RandomState> {
+    ///Returns a new empty set with randomly seeded hashers
+    pub fn new() -> CuckooSet<T, RandomState> {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher> CuckooSet<T, S> {
+    ///Returns a new empty set which derives its two hash functions from `hasher`
+    /// # Attributes
+    /// * `hasher`- builds the hashers of the values
+    pub fn with_hasher(hasher: S) -> CuckooSet<T, S> {
+        CuckooSet {
+            tables: [Vec::new(), Vec::new()],
+            seeds: [0, 0],
+            length: 0,
+            rehashes: 0,
+            rng: XorShift64::from_time(),
+            hasher,
+        }
+    }
+
+    ///Returns the number of values
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the set has no values
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of slots of both tables
+    pub fn get_capacity(&self) -> usize {
+        self.tables[0].len() * 2
+    }
+
+    ///Returns the number of times the values were rehashed because an insertion ran into a loop
+    pub fn get_rehash_count(&self) -> usize {
+        self.rehashes
+    }
+
+    ///Inserts `value` and returns true if it was not in the set yet. Panics if the values can't be placed even
+    ///after many rehashes, which happens only if the hasher maps many values to the same hashes.
+    /// # Attributes
+    /// * `value`- the value to insert
+    pub fn insert(&mut self, value: T) -> bool {
+        if self.contains(&value) {
+            return false;
+        }
+        let capacity = self.tables[0].len();
+        if (self.length + 1) as f64 > self.get_capacity() as f64 * MAX_LOAD_FACTOR {
+            self.rebuild(Some(value), (capacity * 2).max(MIN_CAPACITY));
+        } else if let Err(left) = self.place(value) {
+            self.rehashes += 1;
+            self.rebuild(Some(left), capacity);
+        }
+        self.length += 1;
+        true
+    }
+
+    ///Returns true if `value` is in the set, looking at no more than two slots
+    /// # Attributes
+    /// * `value`- the value to look up
+    pub fn contains<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        self.find(value).is_some()
+    }
+
+    ///Removes `value` and returns true if it was in the set
+    /// # Attributes
+    /// * `value`- the value to remove
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+    {
+        match self.find(value) {
+            Some((table, index)) => {
+                self.tables[table][index] = None;
+                self.length -= 1;
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///Returns an iterator over the values in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.tables.iter().flat_map(|table| table.iter().flatten())
+    }
+
+    ///Checks that every value is in the slot of its hash in its table and the length is right. Returns the first
+    ///violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut count = 0;
+        for (table, slots) in self.tables.iter().enumerate() {
+            for (index, slot) in slots.iter().enumerate() {
+                if let Some(value) = slot {
+                    count += 1;
+                    if self.index(table, value) != index {
+                        return Err(format!(
+                            "slot {} of table {} holds a value of another slot",
+                            index, table
+                        ));
+                    }
+                }
+            }
+        }
+        if count != self.length {
+            return Err(format!("length {} but {} values", self.length, count));
+        }
+        Ok(())
+    }
+
+    ///Returns the slot of `value` in `table`
+    fn index<Q: Hash + ?Sized>(&self, table: usize, value: &Q) -> usize {
+        self.hasher.hash_one((self.seeds[table], value)) as usize & (self.tables[table].len() - 1)
+    }
+
+    ///Returns the table and the slot of `value`
+    fn find<Q: Hash + Eq + ?Sized>(&self, value: &Q) -> Option<(usize, usize)>
+    where
+        T: Borrow<Q>,
+    {
+        if self.tables[0].is_empty() {
+            return None;
+        }
+        (0..2)
+            .map(|table| (table, self.index(table, value)))
+            .find(|(table, index)| {
+                self.tables[*table][*index]
+                    .as_ref()
+                    .is_some_and(|other| other.borrow() == value)
+            })
+    }
+
+    ///Places `value` by evicting values between the tables. Returns the value left without a slot if the eviction
+    ///chain gets too long.
+    fn place(&mut self, mut value: T) -> Result<(), T> {
+        let max_evictions = 4 * (self.tables[0].len().trailing_zeros() as usize + 1);
+        let mut table = 0;
+        for _ in 0..max_evictions {
+            let index = self.index(table, &value);
+            match self.tables[table][index].replace(value) {
+                None => return Ok(()),
+                Some(evicted) => value = evicted,
+            }
+            table = 1 - table;
+        }
+        Err(value)
+    }
+
+    ///Places all values and `pending` into new tables of `capacity` slots each with new hash functions, retrying with
+    ///other hash functions and doubling the tables until all values find a slot
+    fn rebuild(&mut self, pending: Option<T>, mut capacity: usize) {
+        let mut values: Vec<T> = self
+            .tables
+            .iter_mut()
+            .flat_map(|table| table.drain(..).flatten())
+            .collect();
+        values.extend(pending);
+        let mut failures = 0;
+        'attempt: loop {
+            self.seeds = [self.rng.next_u64(), self.rng.next_u64()];
+            for table in self.tables.iter_mut() {
+                table.clear();
+                table.resize_with(capacity, || None);
+            }
+            while let Some(value) = values.pop() {
+                if let Err(left) = self.place(value) {
+                    values.push(left);
+                    values.extend(
+                        self.tables
+                            .iter_mut()
+                            .flat_map(|table| table.drain(..).flatten()),
+                    );
+                    self.rehashes += 1;
+                    failures += 1;
+                    assert!(
+                        failures < MAX_REHASHES,
+                        "the hash functions can't separate the values"
+                    );
+                    if failures % REHASHES_PER_SIZE == 0 {
+                        capacity *= 2;
+                    }
+                    continue 'attempt;
+                }
+            }
+            return;
+        }
+    }
+}
+
+impl<T: Hash + Eq, S: BuildHasher + Default> FromIterator<T> for CuckooSet<T, S> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut set = CuckooSet::with_hasher(S::default());
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+impl CuckooFilter<RandomState> {
+    ///Returns a new empty filter sized for about `capacity` values with fingerprints of 16 bits and randomly
+    ///seeded hashers
+    /// # Attributes
+    /// * `capacity`- the number of values to make room for
+    pub fn new(capacity: usize) -> CuckooFilter<RandomState> {
+        Self::with_hasher(capacity, 16, RandomState::new())
+    }
+
+    ///Returns a new empty filter sized for about `capacity` values with fingerprints of `fingerprint_bits` bits and
+    ///randomly seeded hashers. Panics if `fingerprint_bits` is not between 1 and 16.
+    /// # Attributes
+    /// * `capacity`- the number of values to make room for
+    /// * `fingerprint_bits`- the size of a fingerprint, every bit halves the false positive rate
+    pub fn with_fingerprint_bits(
+        capacity: usize,
+        fingerprint_bits: u32,
+    ) -> CuckooFilter<RandomState> {
+        Self::with_hasher(capacity, fingerprint_bits, RandomState::new())
+    }
+}
+
+impl<S: BuildHasher> CuckooFilter<S> {
+    ///Returns a new empty filter sized for about `capacity` values with fingerprints of `fingerprint_bits` bits
+    ///which hashes the values with `hasher`. Panics if `fingerprint_bits` is not between 1 and 16.
+    /// # Attributes
+    /// * `capacity`- the number of values to make room for
+    /// * `fingerprint_bits`- the size of a fingerprint, every bit halves the false positive rate
+    /// * `hasher`- builds the hashers of the values
+    pub fn with_hasher(capacity: usize, fingerprint_bits: u32, hasher: S) -> CuckooFilter<S> {
+        assert!(
+            (1..=16).contains(&fingerprint_bits),
+            "fingerprints of {} bits are not supported",
+            fingerprint_bits
+        );
+        let entries = (capacity as f64 / FILTER_LOAD_FACTOR).ceil() as usize;
+        let buckets = entries.div_ceil(BUCKET_SIZE).next_power_of_two();
+        CuckooFilter {
+            buckets: vec![[0; BUCKET_SIZE]; buckets],
+            fingerprint_bits,
+            length: 0,
+            victim: None,
+            rng: XorShift64::from_time(),
+            hasher,
+        }
+    }
+
+    ///Returns the number of fingerprints
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the filter has no fingerprints
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of entries for fingerprints
+    pub fn get_capacity(&self) -> usize {
+        self.buckets.len() * BUCKET_SIZE
+    }
+
+    ///Returns the ratio of fingerprints to entries
+    pub fn get_load_factor(&self) -> f64 {
+        self.length as f64 / self.get_capacity() as f64
+    }
+
+    ///Returns true if an insertion failed to find an entry, so further insertions fail until a value is removed
+    pub fn is_full(&self) -> bool {
+        self.victim.is_some()
+    }
+
+    ///Returns the probability that a value which was not inserted is reported as contained by a full filter,
+    ///the chance that one of the fingerprints of its two buckets equals its own
+    pub fn expected_false_positive_rate(&self) -> f64 {
+        let miss = 1.0 - 1.0 / ((1u32 << self.fingerprint_bits) - 1) as f64;
+        1.0 - miss.powi(2 * BUCKET_SIZE as i32)
+    }
+
+    ///Inserts the fingerprint of `value` and returns true, or returns false if the filter is full. A value can be
+    ///inserted more than once and is then contained until it is removed as often.
+    /// # Attributes
+    /// * `value`- the value to insert
+    pub fn insert<T: Hash + ?Sized>(&mut self, value: &T) -> bool {
+        if self.victim.is_some() {
+            return false;
+        }
+        let (mut fingerprint, first) = self.fingerprint_and_index(value);
+        let second = self.alternate(first, fingerprint);
+        if self.put(first, fingerprint) || self.put(second, fingerprint) {
+            self.length += 1;
+            return true;
+        }
+        let mut index = if self.rng.next_bool() { first } else { second };
+        for _ in 0..MAX_KICKS {
+            //the fingerprint takes a random entry and the evicted one moves to its other bucket
+            let entry = self.rng.next_below(BUCKET_SIZE as u64) as usize;
+            std::mem::swap(&mut fingerprint, &mut self.buckets[index][entry]);
+            index = self.alternate(index, fingerprint);
+            if self.put(index, fingerprint) {
+                self.length += 1;
+                return true;
+            }
+        }
+        self.victim = Some((index, fingerprint));
+        self.length += 1;
+        true
+    }
+
+    ///Returns true if the fingerprint of `value` is in the filter, which is always the case for inserted values and
+    ///by chance for others
+    /// # Attributes
+    /// * `value`- the value to look up
+    pub fn contains<T: Hash + ?Sized>(&self, value: &T) -> bool {
+        let (fingerprint, first) = self.fingerprint_and_index(value);
+        let second = self.alternate(first, fingerprint);
+        self.buckets[first].contains(&fingerprint)
+            || self.buckets[second].contains(&fingerprint)
+            || self.victim.is_some_and(|(index, other)| {
+                other == fingerprint && (index == first || index == second)
+            })
+    }
+
+    ///Removes one fingerprint of `value` and returns true if there was one. Only inserted values may be removed,
+    ///removing another value with the same fingerprint would remove the fingerprint of an inserted one.
+    /// # Attributes
+    /// * `value`- the value to remove
+    pub fn remove<T: Hash + ?Sized>(&mut self, value: &T) -> bool {
+        let (fingerprint, first) = self.fingerprint_and_index(value);
+        let second = self.alternate(first, fingerprint);
+        let mut removed = false;
+        for index in [first, second].iter() {
+            if let Some(entry) = self.buckets[*index]
+                .iter_mut()
+                .find(|entry| **entry == fingerprint)
+            {
+                *entry = 0;
+                removed = true;
+                break;
+            }
+        }
+        if !removed
+            && self.victim.is_some_and(|(index, other)| {
+                other == fingerprint && (index == first || index == second)
+            })
+        {
+            self.victim = None;
+            removed = true;
+        }
+        if !removed {
+            return false;
+        }
+        self.length -= 1;
+        //the freed entry may make room for the fingerprint stored aside
+        if let Some((index, fingerprint)) = self.victim {
+            let other = self.alternate(index, fingerprint);
+            if self.put(index, fingerprint) || self.put(other, fingerprint) {
+                self.victim = None;
+            }
+        }
+        true
+    }
+
+    ///Checks that the number of fingerprints matches the length. Returns the violation if there is one.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = self
+            .buckets
+            .iter()
+            .flat_map(|bucket| bucket.iter())
+            .filter(|entry| **entry != 0)
+            .count()
+            + self.victim.iter().count();
+        if count != self.length {
+            return Err(format!("length {} but {} fingerprints", self.length, count));
+        }
+        Ok(())
+    }
+
+    ///Returns the fingerprint of `value`, which is never 0, and its first bucket
+    fn fingerprint_and_index<T: Hash + ?Sized>(&self, value: &T) -> (u16, usize) {
+        let hash = self.hasher.hash_one(value);
+        let mask = (1u64 << self.fingerprint_bits) - 1;
+        //the fingerprint comes from the upper half of the hash and the bucket from the lower half, 0 marks a free
+        //entry so the fingerprints are spread evenly over the other values
+        let fingerprint = ((hash >> 32) % mask + 1) as u16;
+        (fingerprint, hash as usize & (self.buckets.len() - 1))
+    }
+
+    ///Returns the other bucket of `fingerprint` in `index`, the function is its own inverse
+    fn alternate(&self, index: usize, fingerprint: u16) -> usize {
+        let spread = (fingerprint as u64).wrapping_mul(0x5bd1_e995_9e37_79b9) >> 16;
+        (index ^ spread as usize) & (self.buckets.len() - 1)
+    }
+
+    ///Puts `fingerprint` into a free entry of the bucket `index` and returns true if there was one
+    fn put(&mut self, index: usize, fingerprint: u16) -> bool {
+        match self.buckets[index].iter_mut().find(|entry| **entry == 0) {
+            Some(entry) => {
+                *entry = fingerprint;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    ///A hasher which maps every value to the same hash, so the hash functions can't separate any values
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            5
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    ///Returns the ratio of the values of `start..end`, none of which was inserted, which `filter` contains
+    fn false_positive_rate(filter: &CuckooFilter, start: u64, end: u64) -> f64 {
+        (start..end).filter(|value| filter.contains(value)).count() as f64 / (end - start) as f64
+    }
+
+    #[test]
+    fn test_new_cuckoo_set() {
+        let s: CuckooSet<i32> = CuckooSet::new();
+        assert!(s.is_empty());
+        assert!(!s.contains(&1));
+        assert_eq!(0, s.get_capacity());
+        s.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_insert_contains_remove() {
+        let mut s = CuckooSet::new();
+        assert!(s.insert(String::from("a")));
+        assert!(s.insert(String::from("b")));
+        assert!(!s.insert(String::from("a")));
+        assert!(s.contains("a") && s.contains("b") && !s.contains("c"));
+        assert!(s.remove("a"));
+        assert!(!s.remove("a"));
+        assert_eq!(1, s.get_length());
+        assert_eq!(vec!["b"], s.iter().collect::<Vec<_>>());
+        s.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_growth_keeps_load_bounded() {
+        let mut s = CuckooSet::new();
+        for i in 0..10_000 {
+            assert!(s.insert(i));
+            assert!(s.get_length() as f64 <= s.get_capacity() as f64 * MAX_LOAD_FACTOR);
+        }
+        s.check_invariants().unwrap();
+        assert!((0..10_000).all(|i| s.contains(&i)));
+        assert!(!s.contains(&10_000));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_inseparable_values() {
+        let mut s: CuckooSet<u32, BuildHasherDefault<ConstantHasher>> =
+            CuckooSet::with_hasher(BuildHasherDefault::default());
+        for i in 0..3 {
+            s.insert(i);
+        }
+    }
+
+    #[test]
+    fn test_random_operations_against_std() {
+        let mut rng = XorShift64::new(49);
+        let mut s = CuckooSet::new();
+        let mut reference = HashSet::new();
+        for i in 0..20_000 {
+            let value = rng.next_below(4_000);
+            if rng.next_below(3) == 0 {
+                assert_eq!(reference.remove(&value), s.remove(&value));
+            } else {
+                assert_eq!(reference.insert(value), s.insert(value));
+            }
+            if i % 1000 == 0 {
+                s.check_invariants().unwrap();
+            }
+        }
+        s.check_invariants().unwrap();
+        assert_eq!(reference.len(), s.get_length());
+        assert!(s.iter().all(|value| reference.contains(value)));
+    }
+
+    #[test]
+    fn test_filter_has_no_false_negatives() {
+        let mut f = CuckooFilter::new(10_000);
+        for i in 0..10_000u64 {
+            assert!(f.insert(&i));
+        }
+        f.check_invariants().unwrap();
+        assert!((0..10_000u64).all(|i| f.contains(&i)));
+        assert!(false_positive_rate(&f, 10_000, 110_000) < 0.001);
+    }
+
+    #[test]
+    fn test_filter_false_positive_rate() {
+        for bits in [6, 8, 12].iter() {
+            let mut f = CuckooFilter::with_fingerprint_bits(4_000, *bits);
+            for i in 0..4_000u64 {
+                f.insert(&i);
+            }
+            let expected = f.expected_false_positive_rate();
+            let measured = false_positive_rate(&f, 1_000_000, 1_200_000);
+            //the filter is not full, so fewer entries than in the expected rate can match
+            assert!(
+                measured < expected * 1.2,
+                "{} bits: measured {} expected {}",
+                bits,
+                measured,
+                expected
+            );
+            assert!(measured > expected * f.get_load_factor() * 0.5);
+        }
+    }
+
+    #[test]
+    fn test_fingerprints_are_uniform() {
+        let f = CuckooFilter::with_fingerprint_bits(1_000, 2);
+        let mut counts = [0usize; 4];
+        for i in 0..30_000u64 {
+            counts[f.fingerprint_and_index(&i).0 as usize] += 1;
+        }
+        assert_eq!(0, counts[0]);
+        for count in counts[1..].iter() {
+            assert!((9_500..10_500).contains(count), "{:?}", counts);
+        }
+    }
+
+    #[test]
+    fn test_filter_remove() {
+        let mut f = CuckooFilter::with_fingerprint_bits(1_000, 8);
+        for i in 0..1_000u64 {
+            f.insert(&i);
+        }
+        f.insert(&7u64);
+        for i in (0..1_000u64).step_by(2) {
+            assert!(f.remove(&i));
+        }
+        assert!((1..1_000u64).step_by(2).all(|i| f.contains(&i)));
+        f.check_invariants().unwrap();
+        for i in (1..1_000u64).step_by(2) {
+            assert!(f.remove(&i));
+        }
+        //7 was inserted twice
+        assert!(f.contains(&7u64));
+        assert!(f.remove(&7u64));
+        assert!(f.is_empty());
+        assert_eq!(0.0, false_positive_rate(&f, 0, 10_000));
+    }
+
+    #[test]
+    fn test_filter_fills_up() {
+        let mut f = CuckooFilter::with_fingerprint_bits(1_000, 16);
+        let mut inserted = 0u64;
+        while f.insert(&inserted) {
+            inserted += 1;
+        }
+        assert!(f.is_full());
+        assert!(f.get_load_factor() > 0.9);
+        assert!((0..inserted).all(|i| f.contains(&i)));
+        assert!(f.remove(&0u64));
+        assert!((1..inserted).all(|i| f.contains(&i)));
+        f.check_invariants().unwrap();
+    }
+}
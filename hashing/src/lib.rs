@@ -0,0 +1,4 @@
+pub mod chained;
+pub mod cuckoo;
+pub mod robin_hood;
+pub mod sketch;
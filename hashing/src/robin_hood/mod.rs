@@ -0,0 +1,720 @@
+//! This module implements a hash map with open addressing and Robin Hood hashing. The entries live directly in a
+//! table of a power of two slots and a key is searched by linear probing from its home slot, the slot its hash
+//! points to. On insertion an entry which is further from its home than the entry in its way takes that slot, and
+//! the entry it displaced continues the probing, which keeps the probe lengths short and even. A search stops as
+//! soon as it meets an entry closer to its home than the key would be. On deletion the following entries of the
+//! probe sequence are shifted back by one slot instead of leaving a tombstone.
+//! The table doubles once the number of entries would exceed the maximum load factor of the capacity.
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+///The maximum load factor of a map which is not given one
+pub const DEFAULT_MAX_LOAD_FACTOR: f64 = 0.875;
+
+///The number of slots of the first table
+const MIN_CAPACITY: usize = 8;
+
+///An entry of the table with the hash of its key and its distance from its home slot
+#[derive(Clone)]
+struct Bucket<K, V> {
+    hash: u64,
+    distance: usize,
+    key: K,
+    value: V,
+}
+
+///A hash map with Robin Hood hashing over the hashers built by `S`
+#[derive(Clone)]
+pub struct HashMap<K, V, S = RandomState> {
+    slots: Vec<Option<Bucket<K, V>>>,
+    length: usize,
+    max_load_factor: f64,
+    hasher: S,
+}
+
+///The distribution of the distances of the entries from their home slots
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProbeStatistics {
+    ///The largest distance of an entry
+    pub longest: usize,
+    ///The average distance of the entries, 0 for an empty map
+    pub average: f64,
+    ///The number of entries at each distance, the entry `d` counts the entries `d` slots after their home
+    pub histogram: Vec<usize>,
+}
+
+///Iterator over the entries of a `HashMap` in the order of the table
+pub struct Iter<'a, K, V> {
+    slots: std::slice::Iter<'a, Option<Bucket<K, V>>>,
+}
+
+///Iterator over the entries of a `HashMap` with mutable references to the values
+pub struct IterMut<'a, K, V> {
+    slots: std::slice::IterMut<'a, Option<Bucket<K, V>>>,
+}
+
+///A view into a single entry of a `HashMap`, which is either occupied or vacant
+pub enum Entry<'a, K, V, S> {
+    Occupied(OccupiedEntry<'a, K, V, S>),
+    Vacant(VacantEntry<'a, K, V, S>),
+}
+
+///A view into an entry of a `HashMap` which has a value
+pub struct OccupiedEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    index: usize,
+}
+
+///A view into an entry of a `HashMap` which has no value
+pub struct VacantEntry<'a, K, V, S> {
+    map: &'a mut HashMap<K, V, S>,
+    hash: u64,
+    key: K,
+}
+
+impl<K: Hash + Eq, V> Default for HashMap<K, V, RandomState> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V> HashMap<K, V, RandomState> {
+    ///Returns a new empty map with the default maximum load factor and randomly seeded hashers
+    pub fn new() -> HashMap<K, V, RandomState> {
+        Self::with_hasher(RandomState::new())
+    }
+
+    ///Returns a new empty map with randomly seeded hashers which grows its table once the entries would fill more
+    ///than `max_load_factor` of it. Panics if `max_load_factor` is not in the interval (0, 1).
+    /// # Attributes
+    /// * `max_load_factor`- the largest ratio of entries to slots
+    pub fn with_max_load_factor(max_load_factor: f64) -> HashMap<K, V, RandomState> {
+        Self::with_hasher_and_max_load_factor(RandomState::new(), max_load_factor)
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> HashMap<K, V, S> {
+    ///Returns a new empty map with the default maximum load factor which hashes the keys with `hasher`
+    /// # Attributes
+    /// * `hasher`- builds the hashers of the keys
+    pub fn with_hasher(hasher: S) -> HashMap<K, V, S> {
+        Self::with_hasher_and_max_load_factor(hasher, DEFAULT_MAX_LOAD_FACTOR)
+    }
+
+    ///Returns a new empty map which hashes the keys with `hasher` and grows its table once the entries would fill
+    ///more than `max_load_factor` of it. Panics if `max_load_factor` is not in the interval (0, 1).
+    /// # Attributes
+    /// * `hasher`- builds the hashers of the keys
+    /// * `max_load_factor`- the largest ratio of entries to slots
+    pub fn with_hasher_and_max_load_factor(hasher: S, max_load_factor: f64) -> HashMap<K, V, S> {
+        assert!(
+            max_load_factor > 0.0 && max_load_factor < 1.0,
+            "the maximum load factor {} is not between 0 and 1",
+            max_load_factor
+        );
+        HashMap {
+            slots: Vec::new(),
+            length: 0,
+            max_load_factor,
+            hasher,
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of slots of the table
+    pub fn get_capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    ///Returns the ratio of entries to slots, 0 for a map without a table
+    pub fn get_load_factor(&self) -> f64 {
+        if self.slots.is_empty() {
+            0.0
+        } else {
+            self.length as f64 / self.slots.len() as f64
+        }
+    }
+
+    ///Returns the ratio of entries to slots above which the table grows
+    pub fn get_max_load_factor(&self) -> f64 {
+        self.max_load_factor
+    }
+
+    ///Returns the reference to the hasher builder
+    pub fn get_hasher(&self) -> &S {
+        &self.hasher
+    }
+
+    ///Grows the table so that `additional` more entries fit without growing it again
+    /// # Attributes
+    /// * `additional`- the number of entries to make room for
+    pub fn reserve(&mut self, additional: usize) {
+        let needed = self.length + additional;
+        if self.fits(needed) {
+            return;
+        }
+        let mut capacity = self.slots.len().max(MIN_CAPACITY);
+        while (capacity as f64 * self.max_load_factor) < needed as f64 {
+            capacity *= 2;
+        }
+        self.resize(capacity);
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(self.hasher.hash_one(key), key)?;
+        self.slots[index].as_ref().map(|bucket| &bucket.value)
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(self.hasher.hash_one(key), key)?;
+        self.slots[index].as_mut().map(|bucket| &mut bucket.value)
+    }
+
+    ///Returns true if the map has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(self.hasher.hash_one(key), key).is_some()
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove<Q: Hash + Eq + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let index = self.find(self.hasher.hash_one(key), key)?;
+        Some(self.remove_at(index).1)
+    }
+
+    ///Returns the entry of `key` for in-place manipulation
+    /// # Attributes
+    /// * `key`- the key of the entry
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V, S> {
+        let hash = self.hasher.hash_one(&key);
+        match self.find(hash, &key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { map: self, index }),
+            None => Entry::Vacant(VacantEntry {
+                map: self,
+                hash,
+                key,
+            }),
+        }
+    }
+
+    ///Removes all entries and keeps the table
+    pub fn clear(&mut self) {
+        for slot in self.slots.iter_mut() {
+            *slot = None;
+        }
+        self.length = 0;
+    }
+
+    ///Returns an iterator over the entries in the order of the table
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            slots: self.slots.iter(),
+        }
+    }
+
+    ///Returns an iterator over the entries with mutable references to the values
+    pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
+        IterMut {
+            slots: self.slots.iter_mut(),
+        }
+    }
+
+    ///Returns the distribution of the distances of the entries from their home slots
+    pub fn probe_statistics(&self) -> ProbeStatistics {
+        let mut histogram = Vec::new();
+        let mut total = 0;
+        for bucket in self.slots.iter().flatten() {
+            if histogram.len() <= bucket.distance {
+                histogram.resize(bucket.distance + 1, 0);
+            }
+            histogram[bucket.distance] += 1;
+            total += bucket.distance;
+        }
+        ProbeStatistics {
+            longest: histogram.len().saturating_sub(1),
+            average: if self.length == 0 {
+                0.0
+            } else {
+                total as f64 / self.length as f64
+            },
+            histogram,
+        }
+    }
+
+    ///Checks that every entry is stored with the hash of its key and its distance from its home slot, that no entry
+    ///is further from its home than the one before it plus one and that the load stays below the maximum. Returns
+    ///the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let capacity = self.slots.len();
+        let mut count = 0;
+        for (index, slot) in self.slots.iter().enumerate() {
+            let bucket = match slot {
+                Some(bucket) => bucket,
+                None => continue,
+            };
+            count += 1;
+            if bucket.hash != self.hasher.hash_one(&bucket.key) {
+                return Err(format!("slot {} stores a wrong hash", index));
+            }
+            let home = self.home(bucket.hash);
+            if (index + capacity - home) % capacity != bucket.distance {
+                return Err(format!("slot {} stores a wrong distance", index));
+            }
+            //the entry before may be empty only for an entry in its home slot
+            let previous = match &self.slots[(index + capacity - 1) % capacity] {
+                Some(previous) => previous.distance + 1,
+                None => 0,
+            };
+            if bucket.distance > previous {
+                return Err(format!(
+                    "slot {} is further from home than the slot before allows",
+                    index
+                ));
+            }
+        }
+        if count != self.length {
+            return Err(format!("length {} but {} entries", self.length, count));
+        }
+        if count > 0 && !self.fits(count) {
+            return Err(format!("{} entries overload {} slots", count, capacity));
+        }
+        Ok(())
+    }
+
+    ///Returns true if `count` entries fit into the table without exceeding the maximum load factor
+    fn fits(&self, count: usize) -> bool {
+        count as f64 <= self.slots.len() as f64 * self.max_load_factor
+    }
+
+    ///Returns the home slot of `hash`
+    fn home(&self, hash: u64) -> usize {
+        (hash as usize) & (self.slots.len() - 1)
+    }
+
+    ///Returns the slot of `key` with the hash `hash`
+    fn find<Q: Eq + ?Sized>(&self, hash: u64, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let mask = self.slots.len() - 1;
+        let mut index = self.home(hash);
+        let mut distance = 0;
+        while let Some(bucket) = &self.slots[index] {
+            if bucket.distance < distance {
+                //the key would have displaced this entry
+                return None;
+            }
+            if bucket.hash == hash && bucket.key.borrow() == key {
+                return Some(index);
+            }
+            index = (index + 1) & mask;
+            distance += 1;
+        }
+        None
+    }
+
+    ///Places `bucket`, whose key is not in the table, into the table which has a free slot and returns its slot
+    fn place(&mut self, mut bucket: Bucket<K, V>) -> usize {
+        let mask = self.slots.len() - 1;
+        let mut index = self.home(bucket.hash);
+        bucket.distance = 0;
+        let mut placed = None;
+        loop {
+            let slot = &mut self.slots[index];
+            if let Some(resident) = slot {
+                if resident.distance < bucket.distance {
+                    //the richer resident gives its slot away and continues the probing
+                    std::mem::swap(resident, &mut bucket);
+                    placed.get_or_insert(index);
+                }
+            } else {
+                *slot = Some(bucket);
+                return placed.unwrap_or(index);
+            }
+            index = (index + 1) & mask;
+            bucket.distance += 1;
+        }
+    }
+
+    ///Removes the entry at `index` and shifts the following entries of its probe sequence back by one slot
+    fn remove_at(&mut self, index: usize) -> (K, V) {
+        let mask = self.slots.len() - 1;
+        let removed = self.slots[index].take().unwrap();
+        let mut hole = index;
+        loop {
+            let next = (hole + 1) & mask;
+            match self.slots[next].take() {
+                Some(mut bucket) if bucket.distance > 0 => {
+                    bucket.distance -= 1;
+                    self.slots[hole] = Some(bucket);
+                    hole = next;
+                }
+                other => {
+                    self.slots[next] = other;
+                    break;
+                }
+            }
+        }
+        self.length -= 1;
+        (removed.key, removed.value)
+    }
+
+    ///Moves the entries into a new table of `capacity` slots
+    fn resize(&mut self, capacity: usize) {
+        let mut slots = Vec::with_capacity(capacity);
+        slots.resize_with(capacity, || None);
+        let old = std::mem::replace(&mut self.slots, slots);
+        for bucket in old.into_iter().flatten() {
+            self.place(bucket);
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .by_ref()
+            .flatten()
+            .next()
+            .map(|bucket| (&bucket.key, &bucket.value))
+    }
+}
+
+impl<'a, K, V> Iterator for IterMut<'a, K, V> {
+    type Item = (&'a K, &'a mut V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.slots
+            .by_ref()
+            .flatten()
+            .next()
+            .map(|bucket| (&bucket.key, &mut bucket.value))
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> IntoIterator for &'a HashMap<K, V, S> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher + Default> FromIterator<(K, V)> for HashMap<K, V, S> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = HashMap::with_hasher(S::default());
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Extend<(K, V)> for HashMap<K, V, S> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> Entry<'a, K, V, S> {
+    ///Returns the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    ///Inserts `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- the value to insert
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    ///Inserts the value returned by `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- returns the value to insert
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    ///Calls `f` with the value if the entry is occupied
+    /// # Attributes
+    /// * `f`- modifies the value
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, K: Hash + Eq, V: Default, S: BuildHasher> Entry<'a, K, V, S> {
+    ///Inserts the default value if the entry is vacant and returns the mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> OccupiedEntry<'a, K, V, S> {
+    fn bucket(&self) -> &Bucket<K, V> {
+        self.map.slots[self.index].as_ref().unwrap()
+    }
+
+    ///Returns the key of the entry
+    pub fn key(&self) -> &K {
+        &self.bucket().key
+    }
+
+    ///Returns the reference to the value
+    pub fn get(&self) -> &V {
+        &self.bucket().value
+    }
+
+    ///Returns the mutable reference to the value
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    ///Returns the mutable reference to the value which lives as long as the map is borrowed
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.map.slots[self.index].as_mut().unwrap().value
+    }
+
+    ///Replaces the value with `value` and returns the old one
+    /// # Attributes
+    /// * `value`- the new value
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+
+    ///Removes the entry from the map and returns its key and value
+    pub fn remove_entry(self) -> (K, V) {
+        self.map.remove_at(self.index)
+    }
+}
+
+impl<'a, K: Hash + Eq, V, S: BuildHasher> VacantEntry<'a, K, V, S> {
+    ///Returns the key of the entry
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    ///Inserts `value` under the key of the entry and returns the mutable reference to it
+    /// # Attributes
+    /// * `value`- the value to insert
+    pub fn insert(self, value: V) -> &'a mut V {
+        let map = self.map;
+        map.reserve(1);
+        let index = map.place(Bucket {
+            hash: self.hash,
+            distance: 0,
+            key: self.key,
+            value,
+        });
+        map.length += 1;
+        &mut map.slots[index].as_mut().unwrap().value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::hash::{BuildHasherDefault, Hasher};
+
+    ///A hasher which maps every key to the same hash, so all keys collide
+    #[derive(Default)]
+    struct ConstantHasher;
+
+    impl Hasher for ConstantHasher {
+        fn finish(&self) -> u64 {
+            7
+        }
+
+        fn write(&mut self, _bytes: &[u8]) {}
+    }
+
+    #[test]
+    fn test_new_map() {
+        let m: HashMap<i32, i32> = HashMap::new();
+        assert!(m.is_empty());
+        assert_eq!(0, m.get_capacity());
+        assert!(m.get(&1).is_none());
+        assert_eq!(0.0, m.probe_statistics().average);
+        m.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_load_factor() {
+        HashMap::<i32, i32>::with_max_load_factor(1.0);
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut m = HashMap::new();
+        assert!(m.insert(String::from("one"), 1).is_none());
+        assert!(m.insert(String::from("two"), 2).is_none());
+        assert_eq!(Some(1), m.insert(String::from("one"), 10));
+        assert_eq!(Some(&10), m.get("one"));
+        *m.get_mut("two").unwrap() += 5;
+        assert_eq!(Some(&7), m.get("two"));
+        assert!(m.contains_key("two") && !m.contains_key("three"));
+        assert_eq!(Some(7), m.remove("two"));
+        assert!(m.remove("two").is_none());
+        assert_eq!(1, m.get_length());
+        m.check_invariants().unwrap();
+        m.clear();
+        assert!(m.is_empty() && m.get_capacity() > 0);
+    }
+
+    #[test]
+    fn test_load_factor_and_growth() {
+        let mut m = HashMap::with_max_load_factor(0.5);
+        for i in 0..100 {
+            m.insert(i, i);
+            assert!(m.get_load_factor() <= 0.5);
+            m.check_invariants().unwrap();
+        }
+        assert_eq!(256, m.get_capacity());
+        let mut m: HashMap<i32, i32> = HashMap::new();
+        m.reserve(100);
+        let capacity = m.get_capacity();
+        m.extend((0..100).map(|i| (i, i)));
+        assert_eq!(capacity, m.get_capacity());
+    }
+
+    #[test]
+    fn test_colliding_hasher() {
+        let mut m: HashMap<u32, u32, BuildHasherDefault<ConstantHasher>> =
+            HashMap::with_hasher(BuildHasherDefault::default());
+        for i in 0..50 {
+            m.insert(i, i * i);
+        }
+        m.check_invariants().unwrap();
+        assert_eq!(49, m.probe_statistics().longest);
+        for i in (0..50).step_by(2) {
+            assert_eq!(Some(i * i), m.remove(&i));
+            m.check_invariants().unwrap();
+        }
+        assert!((1..50).step_by(2).all(|i| m.get(&i) == Some(&(i * i))));
+        assert_eq!(24, m.probe_statistics().longest);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut m: HashMap<&str, i32> = HashMap::new();
+        for word in "a b a c b a".split(' ') {
+            *m.entry(word).or_default() += 1;
+        }
+        assert_eq!(Some(&3), m.get("a"));
+        assert_eq!(Some(&2), m.get("b"));
+        m.entry("c").and_modify(|count| *count *= 10).or_insert(0);
+        m.entry("d").and_modify(|count| *count *= 10).or_insert(4);
+        assert_eq!(Some(&10), m.get("c"));
+        assert_eq!(Some(&4), m.get("d"));
+        assert_eq!(&"d", m.entry("d").key());
+        match m.entry("b") {
+            Entry::Occupied(entry) => assert_eq!(("b", 2), entry.remove_entry()),
+            Entry::Vacant(_) => panic!("b is in the map"),
+        }
+        assert!(!m.contains_key("b"));
+        m.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_probe_statistics() {
+        let m: HashMap<u64, ()> = (0..10_000).map(|i| (i, ())).collect();
+        let statistics = m.probe_statistics();
+        assert_eq!(10_000, statistics.histogram.iter().sum::<usize>());
+        assert_eq!(statistics.longest + 1, statistics.histogram.len());
+        assert!(statistics.average < 3.0);
+    }
+
+    #[test]
+    fn test_random_operations_against_std() {
+        let mut rng = XorShift64::new(47);
+        let mut m = HashMap::with_max_load_factor(0.9);
+        let mut reference = std::collections::HashMap::new();
+        for i in 0..20_000 {
+            let key = rng.next_below(2_000);
+            if rng.next_below(3) == 0 {
+                assert_eq!(reference.remove(&key), m.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, i), m.insert(key, i));
+            }
+            if i % 1000 == 0 {
+                m.check_invariants().unwrap();
+            }
+        }
+        m.check_invariants().unwrap();
+        assert_eq!(reference.len(), m.get_length());
+        for (key, value) in m.iter_mut() {
+            assert_eq!(reference.get(key), Some(&*value));
+            *value += 1;
+        }
+        assert!(reference
+            .iter()
+            .all(|(key, value)| m.get(key) == Some(&(value + 1))));
+    }
+}
@@ -0,0 +1,1146 @@
+//! This module implements probabilistic sketches which summarize huge streams in little memory.
+//! `BloomFilter` answers membership with false positives but no false negatives and is sized from the expected
+//! number of items and the acceptable false positive rate. `CountMinSketch` overestimates the count of an item by
+//! at most a fraction of the total count with high probability, and `HeavyHitters` follows the items with the
+//! largest estimates on top of it. `HyperLogLog` estimates the number of distinct items from the longest runs of
+//! leading zeros of their hashes.
+//! The sketches hash with a seeded hasher of their own, so two sketches with the same parameters and seed can be
+//! merged and a sketch read back with `from_bytes` keeps working, also in another process. `HeavyHitters` writes
+//! its followed items too, so its items have to implement `KeyBytes`. Every sketch can be fed by draining a
+//! `fifo::Fifo`.
+use crate::robin_hood::HashMap;
+use fifo::fifo::Fifo;
+use std::convert::TryInto;
+use std::f64::consts::{E, LN_2};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+///The seed of the sketches which are not given one
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+///The reasons bytes can't be read back into a sketch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    ///The bytes do not start with the tag of the sketch
+    WrongKind,
+    ///The bytes end before the sketch does
+    Truncated,
+    ///The bytes describe a sketch which can't exist, for this reason
+    Invalid(&'static str),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::WrongKind => write!(f, "the bytes hold another kind of sketch"),
+            DecodeError::Truncated => write!(f, "the bytes end before the sketch"),
+            DecodeError::Invalid(reason) => write!(f, "invalid sketch: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+///An item of `HeavyHitters` which can be written as bytes and read back
+pub trait KeyBytes: Sized {
+    ///Returns the item as bytes which `from_key_bytes` reads back
+    fn key_bytes(&self) -> Vec<u8>;
+
+    ///Returns the item written by `key_bytes`, None if `bytes` hold no item
+    /// # Attributes
+    /// * `bytes`- the bytes of the item
+    fn from_key_bytes(bytes: &[u8]) -> Option<Self>;
+}
+
+impl KeyBytes for u32 {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Option<u32> {
+        bytes.try_into().ok().map(u32::from_le_bytes)
+    }
+}
+
+impl KeyBytes for u64 {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Option<u64> {
+        bytes.try_into().ok().map(u64::from_le_bytes)
+    }
+}
+
+impl KeyBytes for i64 {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.to_le_bytes().to_vec()
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Option<i64> {
+        bytes.try_into().ok().map(i64::from_le_bytes)
+    }
+}
+
+impl KeyBytes for String {
+    fn key_bytes(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+
+    fn from_key_bytes(bytes: &[u8]) -> Option<String> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+///A set of items with false positives, stored as bits set by several hash functions per item
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BloomFilter {
+    words: Vec<u64>,
+    bit_count: usize,
+    hash_count: u32,
+    seed: u64,
+}
+
+///A table of counters with one row per hash function, which estimates the counts of items from above
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CountMinSketch {
+    //the rows one after the other
+    counters: Vec<u64>,
+    width: usize,
+    depth: usize,
+    total: u64,
+    seed: u64,
+}
+
+///The items with the largest counts of a stream, estimated by a `CountMinSketch`
+pub struct HeavyHitters<K> {
+    sketch: CountMinSketch,
+    //the followed items with their estimates when they were last added
+    candidates: HashMap<K, u64>,
+    capacity: usize,
+}
+
+///An estimator of the number of distinct items with one register per bucket of hashes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HyperLogLog {
+    registers: Vec<u8>,
+    precision: u32,
+    seed: u64,
+}
+
+///FNV-1a over the bytes, started from the seed and finished by the mixer of SplitMix64, so the hashes are the same
+///in every process
+struct SeededHasher {
+    state: u64,
+}
+
+impl Hasher for SeededHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.state ^= *byte as u64;
+            self.state = self.state.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+
+    fn finish(&self) -> u64 {
+        mix(self.state)
+    }
+}
+
+///Returns `value` with its bits spread over the whole word
+fn mix(mut value: u64) -> u64 {
+    value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    value ^ (value >> 31)
+}
+
+///Returns the hash of `item` under `seed`
+fn hash<T: Hash + ?Sized>(seed: u64, item: &T) -> u64 {
+    let mut hasher = SeededHasher {
+        state: 0xcbf2_9ce4_8422_2325 ^ mix(seed),
+    };
+    item.hash(&mut hasher);
+    hasher.finish()
+}
+
+///Returns the first `count` positions below `bound` of the item with the hash `hash`, derived by double hashing
+fn positions(hash: u64, count: usize, bound: usize) -> impl Iterator<Item = usize> {
+    let step = mix(hash ^ 0x9e37_79b9_7f4a_7c15) | 1;
+    (0..count as u64)
+        .map(move |i| (hash.wrapping_add(i.wrapping_mul(step)) % bound as u64) as usize)
+}
+
+///Reads the fields of a sketch from bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    ///Returns a reader of the fields after `tag`, the tag of a sketch
+    fn new(bytes: &'a [u8], tag: &[u8; 4]) -> Result<Reader<'a>, DecodeError> {
+        if bytes.len() < tag.len() {
+            return Err(DecodeError::Truncated);
+        }
+        if &bytes[..tag.len()] != tag {
+            return Err(DecodeError::WrongKind);
+        }
+        Ok(Reader {
+            bytes: &bytes[tag.len()..],
+        })
+    }
+
+    fn take(&mut self, count: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < count {
+            return Err(DecodeError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(count);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, DecodeError> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, DecodeError> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    ///Checks that all bytes were read
+    fn finish(self) -> Result<(), DecodeError> {
+        if self.bytes.is_empty() {
+            Ok(())
+        } else {
+            Err(DecodeError::Invalid("bytes after the end of the sketch"))
+        }
+    }
+}
+
+impl BloomFilter {
+    ///Returns the number of bits and of hash functions which give the false positive rate `false_positive_rate` with
+    ///the fewest bits once `expected_items` items are inserted. Panics if `expected_items` is 0 or the rate is not
+    ///in the interval (0, 1).
+    /// # Attributes
+    /// * `expected_items`- the number of items the filter will hold
+    /// * `false_positive_rate`- the acceptable ratio of false positives
+    pub fn optimal_parameters(expected_items: usize, false_positive_rate: f64) -> (usize, u32) {
+        assert!(expected_items > 0, "a filter must expect at least one item");
+        assert!(
+            false_positive_rate > 0.0 && false_positive_rate < 1.0,
+            "the false positive rate {} is not between 0 and 1",
+            false_positive_rate
+        );
+        let bits = (-(expected_items as f64) * false_positive_rate.ln() / (LN_2 * LN_2)).ceil();
+        let hashes = (bits / expected_items as f64 * LN_2).round().max(1.0);
+        (bits as usize, hashes as u32)
+    }
+
+    ///Returns a new empty filter sized by `optimal_parameters` with the default seed
+    /// # Attributes
+    /// * `expected_items`- the number of items the filter will hold
+    /// * `false_positive_rate`- the acceptable ratio of false positives
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> BloomFilter {
+        let (bit_count, hash_count) = Self::optimal_parameters(expected_items, false_positive_rate);
+        Self::with_parameters(bit_count, hash_count, DEFAULT_SEED)
+    }
+
+    ///Returns a new empty filter of `bit_count` bits which sets `hash_count` bits per item. Panics if a count is 0.
+    /// # Attributes
+    /// * `bit_count`- the number of bits
+    /// * `hash_count`- the number of hash functions
+    /// * `seed`- selects the hash functions, only filters with equal seeds can be merged
+    pub fn with_parameters(bit_count: usize, hash_count: u32, seed: u64) -> BloomFilter {
+        assert!(
+            bit_count > 0 && hash_count > 0,
+            "a filter needs at least one bit and one hash function"
+        );
+        BloomFilter {
+            words: vec![0; bit_count.div_ceil(64)],
+            bit_count,
+            hash_count,
+            seed,
+        }
+    }
+
+    ///Returns the number of bits
+    pub fn get_bit_count(&self) -> usize {
+        self.bit_count
+    }
+
+    ///Returns the number of bits set per item
+    pub fn get_hash_count(&self) -> u32 {
+        self.hash_count
+    }
+
+    ///Returns true if no item was inserted
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    ///Inserts `item` and returns true if it was surely not in the filter before
+    /// # Attributes
+    /// * `item`- the item to insert
+    pub fn insert<T: Hash + ?Sized>(&mut self, item: &T) -> bool {
+        let mut changed = false;
+        for position in positions(
+            hash(self.seed, item),
+            self.hash_count as usize,
+            self.bit_count,
+        ) {
+            let mask = 1 << (position % 64);
+            changed |= self.words[position / 64] & mask == 0;
+            self.words[position / 64] |= mask;
+        }
+        changed
+    }
+
+    ///Returns true if `item` may be in the filter, which is always the case for inserted items
+    /// # Attributes
+    /// * `item`- the item to look up
+    pub fn contains<T: Hash + ?Sized>(&self, item: &T) -> bool {
+        positions(
+            hash(self.seed, item),
+            self.hash_count as usize,
+            self.bit_count,
+        )
+        .all(|position| self.words[position / 64] & (1 << (position % 64)) != 0)
+    }
+
+    ///Dequeues all items of `queue` into the filter and returns their number
+    /// # Attributes
+    /// * `queue`- the stream of items
+    pub fn drain_from<T: Hash>(&mut self, queue: &mut Fifo<T>) -> usize {
+        let mut count = 0;
+        while let Some(item) = queue.dequeue() {
+            self.insert(&item);
+            count += 1;
+        }
+        count
+    }
+
+    ///Returns the estimated number of distinct items inserted, computed from the number of set bits
+    pub fn estimate_items(&self) -> f64 {
+        let m = self.bit_count as f64;
+        let ones = self.count_ones() as f64;
+        if ones >= m {
+            return f64::INFINITY;
+        }
+        -m / self.hash_count as f64 * (1.0 - ones / m).ln()
+    }
+
+    ///Returns the current false positive rate, the chance that all bits of an item which was not inserted are set
+    pub fn estimated_false_positive_rate(&self) -> f64 {
+        (self.count_ones() as f64 / self.bit_count as f64).powi(self.hash_count as i32)
+    }
+
+    ///Adds the items of `other` to the filter. Panics if the filters differ in size, hash functions or seed.
+    /// # Attributes
+    /// * `other`- the filter to merge
+    pub fn merge(&mut self, other: &BloomFilter) {
+        assert!(
+            self.bit_count == other.bit_count
+                && self.hash_count == other.hash_count
+                && self.seed == other.seed,
+            "the filters differ in their parameters"
+        );
+        for (word, other) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= other;
+        }
+    }
+
+    ///Returns the filter as bytes which `from_bytes` reads back
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(32 + self.words.len() * 8);
+        bytes.extend_from_slice(b"BLOM");
+        bytes.extend_from_slice(&(self.bit_count as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.hash_count.to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        for word in self.words.iter() {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///Returns the filter written by `to_bytes`
+    /// # Attributes
+    /// * `bytes`- the bytes of the filter
+    pub fn from_bytes(bytes: &[u8]) -> Result<BloomFilter, DecodeError> {
+        let mut reader = Reader::new(bytes, b"BLOM")?;
+        let bit_count = reader.read_u64()? as usize;
+        let hash_count = reader.read_u32()?;
+        let seed = reader.read_u64()?;
+        if bit_count == 0 || hash_count == 0 {
+            return Err(DecodeError::Invalid(
+                "a filter needs at least one bit and one hash function",
+            ));
+        }
+        let mut words = Vec::new();
+        for _ in 0..bit_count.div_ceil(64) {
+            words.push(reader.read_u64()?);
+        }
+        reader.finish()?;
+        if !bit_count.is_multiple_of(64)
+            && words
+                .last()
+                .is_some_and(|word| word >> (bit_count % 64) != 0)
+        {
+            return Err(DecodeError::Invalid(
+                "bits beyond the size of the filter are set",
+            ));
+        }
+        Ok(BloomFilter {
+            words,
+            bit_count,
+            hash_count,
+            seed,
+        })
+    }
+
+    fn count_ones(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+}
+
+impl CountMinSketch {
+    ///Returns a new empty sketch with the default seed whose estimates exceed the true counts by at most `epsilon`
+    ///times the total count with a probability of at least 1 - `delta`. Panics if `epsilon` is not positive or
+    ///`delta` is not in the interval (0, 1).
+    /// # Attributes
+    /// * `epsilon`- the error relative to the total count
+    /// * `delta`- the probability of a larger error
+    pub fn new(epsilon: f64, delta: f64) -> CountMinSketch {
+        assert!(epsilon > 0.0, "the error {} is not positive", epsilon);
+        assert!(
+            delta > 0.0 && delta < 1.0,
+            "the probability {} is not between 0 and 1",
+            delta
+        );
+        let width = (E / epsilon).ceil() as usize;
+        let depth = (1.0 / delta).ln().ceil().max(1.0) as usize;
+        Self::with_dimensions(width, depth, DEFAULT_SEED)
+    }
+
+    ///Returns a new empty sketch with `depth` rows of `width` counters. Panics if a dimension is 0.
+    /// # Attributes
+    /// * `width`- the number of counters per row
+    /// * `depth`- the number of rows, one per hash function
+    /// * `seed`- selects the hash functions, only sketches with equal seeds can be merged
+    pub fn with_dimensions(width: usize, depth: usize, seed: u64) -> CountMinSketch {
+        assert!(
+            width > 0 && depth > 0,
+            "a sketch needs at least one row and one column"
+        );
+        CountMinSketch {
+            counters: vec![0; width * depth],
+            width,
+            depth,
+            total: 0,
+            seed,
+        }
+    }
+
+    ///Returns the number of counters per row
+    pub fn get_width(&self) -> usize {
+        self.width
+    }
+
+    ///Returns the number of rows
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    ///Returns the sum of all counts added
+    pub fn get_total(&self) -> u64 {
+        self.total
+    }
+
+    ///Adds `count` occurrences of `item`
+    /// # Attributes
+    /// * `item`- the item to count
+    /// * `count`- the number of occurrences
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T, count: u64) {
+        for (row, column) in positions(hash(self.seed, item), self.depth, self.width).enumerate() {
+            let counter = &mut self.counters[row * self.width + column];
+            *counter = counter.saturating_add(count);
+        }
+        self.total = self.total.saturating_add(count);
+    }
+
+    ///Returns the estimated number of occurrences of `item`, which is never below the true number
+    /// # Attributes
+    /// * `item`- the item to look up
+    pub fn estimate<T: Hash + ?Sized>(&self, item: &T) -> u64 {
+        positions(hash(self.seed, item), self.depth, self.width)
+            .enumerate()
+            .map(|(row, column)| self.counters[row * self.width + column])
+            .min()
+            .unwrap()
+    }
+
+    ///Dequeues all items of `queue` into the sketch, counting each once, and returns their number
+    /// # Attributes
+    /// * `queue`- the stream of items
+    pub fn drain_from<T: Hash>(&mut self, queue: &mut Fifo<T>) -> usize {
+        let mut count = 0;
+        while let Some(item) = queue.dequeue() {
+            self.add(&item, 1);
+            count += 1;
+        }
+        count
+    }
+
+    ///Adds the counts of `other` to the sketch. Panics if the sketches differ in their dimensions or seed.
+    /// # Attributes
+    /// * `other`- the sketch to merge
+    pub fn merge(&mut self, other: &CountMinSketch) {
+        assert!(
+            self.width == other.width && self.depth == other.depth && self.seed == other.seed,
+            "the sketches differ in their parameters"
+        );
+        for (counter, other) in self.counters.iter_mut().zip(other.counters.iter()) {
+            *counter = counter.saturating_add(*other);
+        }
+        self.total = self.total.saturating_add(other.total);
+    }
+
+    ///Returns the sketch as bytes which `from_bytes` reads back
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(36 + self.counters.len() * 8);
+        bytes.extend_from_slice(b"CMSK");
+        bytes.extend_from_slice(&(self.width as u64).to_le_bytes());
+        bytes.extend_from_slice(&(self.depth as u64).to_le_bytes());
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.total.to_le_bytes());
+        for counter in self.counters.iter() {
+            bytes.extend_from_slice(&counter.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///Returns the sketch written by `to_bytes`
+    /// # Attributes
+    /// * `bytes`- the bytes of the sketch
+    pub fn from_bytes(bytes: &[u8]) -> Result<CountMinSketch, DecodeError> {
+        let mut reader = Reader::new(bytes, b"CMSK")?;
+        let width = reader.read_u64()? as usize;
+        let depth = reader.read_u64()? as usize;
+        let seed = reader.read_u64()?;
+        let total = reader.read_u64()?;
+        if width == 0 || depth == 0 {
+            return Err(DecodeError::Invalid(
+                "a sketch needs at least one row and one column",
+            ));
+        }
+        let mut counters = Vec::new();
+        let counter_count = width
+            .checked_mul(depth)
+            .ok_or(DecodeError::Invalid("dimensions overflow"))?;
+        for _ in 0..counter_count {
+            counters.push(reader.read_u64()?);
+        }
+        reader.finish()?;
+        //every addition adds to one counter of every row
+        if counters.chunks(width).any(|row| {
+            row.iter()
+                .fold(0u64, |sum, counter| sum.saturating_add(*counter))
+                != total
+        }) {
+            return Err(DecodeError::Invalid("a row does not sum up to the total"));
+        }
+        Ok(CountMinSketch {
+            counters,
+            width,
+            depth,
+            total,
+            seed,
+        })
+    }
+}
+
+impl<K: Hash + Eq + Clone> HeavyHitters<K> {
+    ///Returns a new tracker which follows up to `capacity` items over a `CountMinSketch::new(epsilon, delta)`
+    /// # Attributes
+    /// * `capacity`- the number of items to follow
+    /// * `epsilon`- the error of the sketch relative to the total count
+    /// * `delta`- the probability of a larger error
+    pub fn new(capacity: usize, epsilon: f64, delta: f64) -> HeavyHitters<K> {
+        Self::with_sketch(capacity, CountMinSketch::new(epsilon, delta))
+    }
+
+    ///Returns a new tracker which follows up to `capacity` items over `sketch`. Panics if `capacity` is 0.
+    /// # Attributes
+    /// * `capacity`- the number of items to follow
+    /// * `sketch`- the sketch estimating the counts
+    pub fn with_sketch(capacity: usize, sketch: CountMinSketch) -> HeavyHitters<K> {
+        assert!(capacity > 0, "a tracker must follow at least one item");
+        HeavyHitters {
+            sketch,
+            candidates: HashMap::new(),
+            capacity,
+        }
+    }
+
+    ///Returns the reference to the sketch
+    pub fn get_sketch(&self) -> &CountMinSketch {
+        &self.sketch
+    }
+
+    ///Adds `count` occurrences of `item` and follows it if its estimate is among the largest
+    /// # Attributes
+    /// * `item`- the item to count
+    /// * `count`- the number of occurrences
+    pub fn add(&mut self, item: K, count: u64) {
+        self.sketch.add(&item, count);
+        let estimate = self.sketch.estimate(&item);
+        if let Some(followed) = self.candidates.get_mut(&item) {
+            *followed = estimate;
+            return;
+        }
+        if self.candidates.get_length() >= self.capacity {
+            let (smallest, followed) = self
+                .candidates
+                .iter()
+                .min_by_key(|(_, estimate)| **estimate)
+                .map(|(key, estimate)| (key.clone(), *estimate))
+                .unwrap();
+            if followed >= estimate {
+                return;
+            }
+            self.candidates.remove(&smallest);
+        }
+        self.candidates.insert(item, estimate);
+    }
+
+    ///Dequeues all items of `queue`, counting each once, and returns their number
+    /// # Attributes
+    /// * `queue`- the stream of items
+    pub fn drain_from(&mut self, queue: &mut Fifo<K>) -> usize {
+        let mut count = 0;
+        while let Some(item) = queue.dequeue() {
+            self.add(item, 1);
+            count += 1;
+        }
+        count
+    }
+
+    ///Returns the followed items with their current estimates, the largest first
+    pub fn top(&self) -> Vec<(&K, u64)> {
+        let mut top: Vec<(&K, u64)> = self
+            .candidates
+            .iter()
+            .map(|(key, _)| (key, self.sketch.estimate(key)))
+            .collect();
+        top.sort_by_key(|(_, estimate)| std::cmp::Reverse(*estimate));
+        top
+    }
+
+    ///Returns the followed items whose estimates are at least `fraction` of the total count, the largest first
+    /// # Attributes
+    /// * `fraction`- the smallest share of the total count
+    pub fn heavy_hitters(&self, fraction: f64) -> Vec<(&K, u64)> {
+        let threshold = fraction * self.sketch.get_total() as f64;
+        self.top()
+            .into_iter()
+            .filter(|(_, estimate)| *estimate as f64 >= threshold)
+            .collect()
+    }
+
+    ///Adds the counts of `other` and follows the items with the largest estimates of both. Panics if the sketches
+    ///differ in their dimensions or seed.
+    /// # Attributes
+    /// * `other`- the tracker to merge
+    pub fn merge(&mut self, other: &HeavyHitters<K>) {
+        self.sketch.merge(&other.sketch);
+        let mut candidates: Vec<(K, u64)> = self
+            .candidates
+            .iter()
+            .chain(other.candidates.iter())
+            .map(|(key, _)| (key.clone(), self.sketch.estimate(key)))
+            .collect();
+        candidates.sort_by_key(|(_, estimate)| std::cmp::Reverse(*estimate));
+        self.candidates = HashMap::new();
+        for (key, estimate) in candidates {
+            if self.candidates.get_length() < self.capacity {
+                self.candidates.insert(key, estimate);
+            }
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone + KeyBytes> HeavyHitters<K> {
+    ///Returns the tracker as bytes which `from_bytes` reads back: the capacity, the bytes of the sketch and the
+    ///followed items with their estimates
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let sketch = self.sketch.to_bytes();
+        let mut bytes = Vec::with_capacity(28 + sketch.len());
+        bytes.extend_from_slice(b"HHIT");
+        bytes.extend_from_slice(&(self.capacity as u64).to_le_bytes());
+        bytes.extend_from_slice(&(sketch.len() as u64).to_le_bytes());
+        bytes.extend_from_slice(&sketch);
+        bytes.extend_from_slice(&(self.candidates.get_length() as u64).to_le_bytes());
+        for (key, estimate) in self.candidates.iter() {
+            let key = key.key_bytes();
+            bytes.extend_from_slice(&(key.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(&key);
+            bytes.extend_from_slice(&estimate.to_le_bytes());
+        }
+        bytes
+    }
+
+    ///Returns the tracker written by `to_bytes`
+    /// # Attributes
+    /// * `bytes`- the bytes of the tracker
+    pub fn from_bytes(bytes: &[u8]) -> Result<HeavyHitters<K>, DecodeError> {
+        let mut reader = Reader::new(bytes, b"HHIT")?;
+        let capacity = reader.read_u64()? as usize;
+        if capacity == 0 {
+            return Err(DecodeError::Invalid(
+                "a tracker must follow at least one item",
+            ));
+        }
+        let sketch_length = reader.read_u64()? as usize;
+        let sketch = CountMinSketch::from_bytes(reader.take(sketch_length)?)?;
+        let count = reader.read_u64()? as usize;
+        if count > capacity {
+            return Err(DecodeError::Invalid(
+                "more followed items than the capacity",
+            ));
+        }
+        let mut candidates = HashMap::new();
+        for _ in 0..count {
+            let key_length = reader.read_u64()? as usize;
+            let key = K::from_key_bytes(reader.take(key_length)?)
+                .ok_or(DecodeError::Invalid("an item can't be read"))?;
+            let estimate = reader.read_u64()?;
+            if estimate > sketch.get_total() {
+                return Err(DecodeError::Invalid("an estimate exceeds the total count"));
+            }
+            if candidates.insert(key, estimate).is_some() {
+                return Err(DecodeError::Invalid("an item is followed twice"));
+            }
+        }
+        reader.finish()?;
+        Ok(HeavyHitters {
+            sketch,
+            candidates,
+            capacity,
+        })
+    }
+}
+
+impl HyperLogLog {
+    ///Returns a new empty estimator with 2 to the power of `precision` registers and the default seed. Panics if
+    ///`precision` is not between 4 and 16.
+    /// # Attributes
+    /// * `precision`- the number of hash bits which select the register
+    pub fn new(precision: u32) -> HyperLogLog {
+        Self::with_seed(precision, DEFAULT_SEED)
+    }
+
+    ///Returns a new empty estimator with 2 to the power of `precision` registers. Panics if `precision` is not
+    ///between 4 and 16.
+    /// # Attributes
+    /// * `precision`- the number of hash bits which select the register
+    /// * `seed`- selects the hash function, only estimators with equal seeds can be merged
+    pub fn with_seed(precision: u32, seed: u64) -> HyperLogLog {
+        assert!(
+            (4..=16).contains(&precision),
+            "the precision {} is not between 4 and 16",
+            precision
+        );
+        HyperLogLog {
+            registers: vec![0; 1 << precision],
+            precision,
+            seed,
+        }
+    }
+
+    ///Returns the number of hash bits which select the register
+    pub fn get_precision(&self) -> u32 {
+        self.precision
+    }
+
+    ///Returns the relative standard error of the estimates
+    pub fn standard_error(&self) -> f64 {
+        1.04 / (self.registers.len() as f64).sqrt()
+    }
+
+    ///Adds `item`
+    /// # Attributes
+    /// * `item`- the item to count
+    pub fn add<T: Hash + ?Sized>(&mut self, item: &T) {
+        let hash = hash(self.seed, item);
+        let index = (hash >> (64 - self.precision)) as usize;
+        //the rank is the position of the first set bit after the index bits, counting from 1
+        let rest = hash << self.precision;
+        let rank = (rest.leading_zeros() + 1).min(64 - self.precision + 1) as u8;
+        self.registers[index] = self.registers[index].max(rank);
+    }
+
+    ///Dequeues all items of `queue` into the estimator and returns their number
+    /// # Attributes
+    /// * `queue`- the stream of items
+    pub fn drain_from<T: Hash>(&mut self, queue: &mut Fifo<T>) -> usize {
+        let mut count = 0;
+        while let Some(item) = queue.dequeue() {
+            self.add(&item);
+            count += 1;
+        }
+        count
+    }
+
+    ///Returns the estimated number of distinct items added
+    pub fn estimate(&self) -> f64 {
+        let m = self.registers.len() as f64;
+        let alpha = match self.registers.len() {
+            16 => 0.673,
+            32 => 0.697,
+            64 => 0.709,
+            _ => 0.7213 / (1.0 + 1.079 / m),
+        };
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|register| 2f64.powi(-(*register as i32)))
+            .sum();
+        let raw = alpha * m * m / sum;
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|register| **register == 0)
+            .count();
+        if raw <= 2.5 * m && zeros > 0 {
+            //few items leave empty registers, whose share is the better estimate
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        }
+    }
+
+    ///Adds the items of `other` to the estimator. Panics if the estimators differ in precision or seed.
+    /// # Attributes
+    /// * `other`- the estimator to merge
+    pub fn merge(&mut self, other: &HyperLogLog) {
+        assert!(
+            self.precision == other.precision && self.seed == other.seed,
+            "the estimators differ in their parameters"
+        );
+        for (register, other) in self.registers.iter_mut().zip(other.registers.iter()) {
+            *register = (*register).max(*other);
+        }
+    }
+
+    ///Returns the estimator as bytes which `from_bytes` reads back
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(13 + self.registers.len());
+        bytes.extend_from_slice(b"HLOG");
+        bytes.push(self.precision as u8);
+        bytes.extend_from_slice(&self.seed.to_le_bytes());
+        bytes.extend_from_slice(&self.registers);
+        bytes
+    }
+
+    ///Returns the estimator written by `to_bytes`
+    /// # Attributes
+    /// * `bytes`- the bytes of the estimator
+    pub fn from_bytes(bytes: &[u8]) -> Result<HyperLogLog, DecodeError> {
+        let mut reader = Reader::new(bytes, b"HLOG")?;
+        let precision = reader.read_u8()? as u32;
+        let seed = reader.read_u64()?;
+        if !(4..=16).contains(&precision) {
+            return Err(DecodeError::Invalid(
+                "the precision is not between 4 and 16",
+            ));
+        }
+        let registers = reader.take(1 << precision)?.to_vec();
+        reader.finish()?;
+        if registers
+            .iter()
+            .any(|register| *register as u32 > 64 - precision + 1)
+        {
+            return Err(DecodeError::Invalid("a register exceeds the largest rank"));
+        }
+        Ok(HyperLogLog {
+            registers,
+            precision,
+            seed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    #[test]
+    fn test_seeded_hash_is_stable() {
+        assert_eq!(hash(1, "telemetry"), hash(1, "telemetry"));
+        assert_ne!(hash(1, "telemetry"), hash(2, "telemetry"));
+        assert!(positions(hash(3, &7u64), 50, 13).all(|position| position < 13));
+    }
+
+    #[test]
+    fn test_bloom_optimal_parameters() {
+        assert_eq!((9586, 7), BloomFilter::optimal_parameters(1000, 0.01));
+        assert_eq!((4793, 3), BloomFilter::optimal_parameters(1000, 0.1));
+        let f = BloomFilter::new(1000, 0.01);
+        assert_eq!(9586, f.get_bit_count());
+        assert!(f.is_empty());
+    }
+
+    #[test]
+    fn test_bloom_false_positive_rate() {
+        let mut f = BloomFilter::new(10_000, 0.01);
+        //an item inserted late can already be a false positive
+        let surely_new = (0..10_000u64).filter(|i| f.insert(i)).count();
+        assert!(surely_new > 9_900);
+        assert!(!f.insert(&5u64));
+        assert!((0..10_000u64).all(|i| f.contains(&i)));
+        let false_positives = (10_000..110_000u64).filter(|i| f.contains(i)).count();
+        let rate = false_positives as f64 / 100_000.0;
+        assert!(rate < 0.015, "false positive rate {}", rate);
+        assert!((f.estimated_false_positive_rate() - 0.01).abs() < 0.003);
+        assert!((f.estimate_items() - 10_000.0).abs() < 300.0);
+    }
+
+    #[test]
+    fn test_bloom_merge_and_bytes() {
+        let mut a = BloomFilter::new(1_000, 0.01);
+        let mut b = BloomFilter::new(1_000, 0.01);
+        let mut queue = Fifo::new();
+        for i in 0..500u64 {
+            a.insert(&i);
+            queue.enqueue(i + 500);
+        }
+        assert_eq!(500, b.drain_from(&mut queue));
+        a.merge(&b);
+        assert!((0..1_000u64).all(|i| a.contains(&i)));
+        let bytes = a.to_bytes();
+        assert_eq!(Ok(a.clone()), BloomFilter::from_bytes(&bytes));
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            BloomFilter::from_bytes(&bytes[..bytes.len() - 1])
+        );
+        assert_eq!(
+            Err(DecodeError::WrongKind),
+            BloomFilter::from_bytes(b"CMSK")
+        );
+        let mut longer = bytes;
+        longer.push(0);
+        assert!(matches!(
+            BloomFilter::from_bytes(&longer),
+            Err(DecodeError::Invalid(_))
+        ));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_bloom_merge_different_seeds() {
+        let mut a = BloomFilter::with_parameters(100, 3, 1);
+        a.merge(&BloomFilter::with_parameters(100, 3, 2));
+    }
+
+    #[test]
+    fn test_count_min_error_bound() {
+        let (epsilon, delta) = (0.001, 0.01);
+        let mut s = CountMinSketch::new(epsilon, delta);
+        assert_eq!((2719, 5), (s.get_width(), s.get_depth()));
+        let mut rng = XorShift64::new(50);
+        let mut counts = vec![0u64; 5_000];
+        for _ in 0..100_000 {
+            //smaller items are drawn more often
+            let bound = rng.next_below(5_000) + 1;
+            let item = rng.next_below(bound) as usize;
+            counts[item] += 1;
+            s.add(&item, 1);
+        }
+        assert_eq!(100_000, s.get_total());
+        let bound = (epsilon * s.get_total() as f64) as u64;
+        let mut exceeding = 0;
+        for (item, count) in counts.iter().enumerate() {
+            let estimate = s.estimate(&item);
+            assert!(estimate >= *count);
+            if estimate > count + bound {
+                exceeding += 1;
+            }
+        }
+        assert!((exceeding as f64) < delta * counts.len() as f64);
+    }
+
+    #[test]
+    fn test_count_min_merge_and_bytes() {
+        let mut a = CountMinSketch::with_dimensions(64, 4, 9);
+        let mut b = CountMinSketch::with_dimensions(64, 4, 9);
+        a.add("x", 5);
+        b.add("x", 7);
+        let mut queue = Fifo::new();
+        queue.enqueue("y");
+        queue.enqueue("y");
+        assert_eq!(2, b.drain_from(&mut queue));
+        a.merge(&b);
+        assert_eq!(14, a.get_total());
+        assert!(a.estimate("x") >= 12 && a.estimate("y") >= 2);
+        let bytes = a.to_bytes();
+        assert_eq!(Ok(a.clone()), CountMinSketch::from_bytes(&bytes));
+        let mut corrupt = bytes.clone();
+        corrupt[28] ^= 1;
+        assert!(matches!(
+            CountMinSketch::from_bytes(&corrupt),
+            Err(DecodeError::Invalid(_))
+        ));
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            CountMinSketch::from_bytes(&bytes[..40])
+        );
+        let mut huge = bytes[..4].to_vec();
+        huge.extend_from_slice(&u64::MAX.to_le_bytes());
+        huge.extend_from_slice(&bytes[12..]);
+        assert_eq!(
+            Err(DecodeError::Invalid("dimensions overflow")),
+            CountMinSketch::from_bytes(&huge)
+        );
+    }
+
+    #[test]
+    fn test_heavy_hitters() {
+        let mut rng = XorShift64::new(51);
+        let mut h = HeavyHitters::new(5, 0.001, 0.01);
+        let mut queue = Fifo::new();
+        for _ in 0..50_000 {
+            //the items 0, 1 and 2 make up about a tenth of the stream each
+            let item = match rng.next_below(10) {
+                0 => 0,
+                1 => 1,
+                2 => 2,
+                _ => 3 + rng.next_below(10_000),
+            };
+            queue.enqueue(item);
+        }
+        assert_eq!(50_000, h.drain_from(&mut queue));
+        let mut hitters: Vec<u64> = h
+            .heavy_hitters(0.05)
+            .into_iter()
+            .map(|(item, _)| *item)
+            .collect();
+        hitters.sort_unstable();
+        assert_eq!(vec![0, 1, 2], hitters);
+        assert_eq!(5, h.top().len());
+        let mut other = HeavyHitters::new(5, 0.001, 0.01);
+        for _ in 0..20_000 {
+            other.add(7, 1);
+        }
+        h.merge(&other);
+        assert_eq!(Some(&7), h.top().first().map(|(item, _)| *item));
+        assert_eq!(70_000, h.get_sketch().get_total());
+    }
+
+    #[test]
+    fn test_heavy_hitters_bytes() {
+        let mut h = HeavyHitters::new(3, 0.01, 0.01);
+        for (item, count) in ["a", "b", "c", "d"].iter().zip([7, 1, 5, 3].iter()) {
+            h.add(String::from(*item), *count);
+        }
+        let bytes = h.to_bytes();
+        let read: HeavyHitters<String> = HeavyHitters::from_bytes(&bytes).unwrap();
+        assert_eq!(h.get_sketch(), read.get_sketch());
+        assert_eq!(h.top(), read.top());
+        assert_eq!(
+            Err(DecodeError::Truncated),
+            HeavyHitters::<String>::from_bytes(&bytes[..bytes.len() - 1]).map(|_| ())
+        );
+        let mut corrupt = bytes.clone();
+        corrupt[4..12].copy_from_slice(&1u64.to_le_bytes());
+        assert_eq!(
+            Err(DecodeError::Invalid(
+                "more followed items than the capacity"
+            )),
+            HeavyHitters::<String>::from_bytes(&corrupt).map(|_| ())
+        );
+        //every item takes its length, one byte and its estimate, the byte of the first is not UTF-8
+        let first = bytes.len() - 3 * 17 + 8;
+        let mut corrupt = bytes.clone();
+        corrupt[first] = 0xff;
+        assert_eq!(
+            Err(DecodeError::Invalid("an item can't be read")),
+            HeavyHitters::<String>::from_bytes(&corrupt).map(|_| ())
+        );
+        assert!(HeavyHitters::<u64>::from_bytes(&bytes).is_err());
+        let mut numbers = HeavyHitters::new(2, 0.01, 0.01);
+        numbers.add(9u64, 4);
+        numbers.add(u64::MAX, 2);
+        let read = HeavyHitters::<u64>::from_bytes(&numbers.to_bytes()).unwrap();
+        assert_eq!(numbers.top(), read.top());
+    }
+
+    #[test]
+    fn test_hyperloglog_accuracy() {
+        for precision in [10, 14].iter() {
+            let mut h = HyperLogLog::new(*precision);
+            for i in 0..200_000u64 {
+                h.add(&i);
+                h.add(&i);
+            }
+            let error = (h.estimate() - 200_000.0).abs() / 200_000.0;
+            assert!(
+                error < 3.0 * h.standard_error(),
+                "precision {} error {}",
+                precision,
+                error
+            );
+        }
+        let mut h = HyperLogLog::new(12);
+        assert_eq!(0.0, h.estimate());
+        for i in 0..100 {
+            h.add(&i);
+        }
+        assert!((h.estimate() - 100.0).abs() < 5.0);
+    }
+
+    #[test]
+    fn test_hyperloglog_merge_and_bytes() {
+        let mut a = HyperLogLog::new(12);
+        let mut b = HyperLogLog::new(12);
+        let mut all = HyperLogLog::new(12);
+        let mut queue = Fifo::new();
+        for i in 0..50_000u64 {
+            a.add(&i);
+            queue.enqueue(i + 25_000);
+            all.add(&i);
+            all.add(&(i + 25_000));
+        }
+        assert_eq!(50_000, b.drain_from(&mut queue));
+        a.merge(&b);
+        assert_eq!(all, a);
+        let bytes = a.to_bytes();
+        assert_eq!(Ok(a), HyperLogLog::from_bytes(&bytes));
+        let mut corrupt = bytes.clone();
+        corrupt[4] = 20;
+        assert!(matches!(
+            HyperLogLog::from_bytes(&corrupt),
+            Err(DecodeError::Invalid(_))
+        ));
+        assert_eq!(
+            Err(DecodeError::WrongKind),
+            HyperLogLog::from_bytes(b"BLOM")
+        );
+        assert_eq!(Err(DecodeError::Truncated), HyperLogLog::from_bytes(b"HL"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_hyperloglog_invalid_precision() {
+        HyperLogLog::new(3);
+    }
+}
@@ -1,6 +1,7 @@
 pub mod lifo {
     //! This module implements a LIFO queue with enqueue and dequeue functionality.
-    //! For long queues we should implement the Drop function to ensure successful disposal of the queue.   
+    //! The queue implements Drop by dequeuing its items one by one, so long queues are disposed without
+    //! recursing through the nodes.
 
     use std::cell::RefCell;
     use std::rc::Rc;
@@ -27,12 +28,24 @@ pub mod lifo {
         /// * `value`- the value to assign to the node
         pub fn new(value: T) -> Node<T> {
             Node {
-                value: value,
+                value,
                 previous: None,
             }
         }
     }
 
+    impl<T> Default for Lifo<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T> Drop for Lifo<T> {
+        fn drop(&mut self) {
+            while self.dequeue().is_some() {}
+        }
+    }
+
     impl<T> Lifo<T> {
         ///Returns a new empty FIFO queue
         pub fn new() -> Lifo<T> {
@@ -130,4 +143,13 @@ mod tests {
         // assert!(l.head.is_none());
         assert_eq!(l.get_length(), i - 2);
     }
+
+    #[test]
+    fn test_drop_long_lifo() {
+        let mut l = Lifo::new();
+        for i in 0..1_000_000 {
+            l.enqueue(i);
+        }
+        drop(l);
+    }
 }
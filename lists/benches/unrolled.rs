@@ -0,0 +1,128 @@
+//! Compares the unrolled list with `DlList` and `Vec` for appending and scanning, and with `Vec` for inserting and
+//! removing at random positions, which `DlList` has no indexed operations for.
+//! Run with `cargo bench -p lists --bench unrolled`.
+use lists::dll::DlList;
+use lists::rng::{RandomSource, XorShift64};
+use lists::unrolled::UnrolledList;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ITEMS: i64 = 1_000_000;
+const ROUNDS: u32 = 5;
+const INDEXED_ITEMS: usize = 100_000;
+const INDEXED_OPERATIONS: usize = 5_000;
+
+///Runs `f` `ROUNDS` times and returns the fastest duration
+fn measure<F: FnMut()>(mut f: F) -> Duration {
+    (0..ROUNDS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, duration: Duration) {
+    println!("{:<30} {:>10.3} ms", name, duration.as_secs_f64() * 1000.0);
+}
+
+fn main() {
+    report(
+        "push_back Vec",
+        measure(|| {
+            let mut v = Vec::new();
+            for i in 0..ITEMS {
+                v.push(i);
+            }
+            black_box(v);
+        }),
+    );
+    report(
+        "push_back UnrolledList",
+        measure(|| {
+            let mut l = UnrolledList::new();
+            for i in 0..ITEMS {
+                l.push_back(i);
+            }
+            black_box(l);
+        }),
+    );
+    report(
+        "enqueue DlList",
+        measure(|| {
+            let mut l = DlList::new();
+            for i in 0..ITEMS {
+                l.enqueue(i);
+            }
+            while l.get_head().is_some() {}
+        }),
+    );
+
+    let v: Vec<i64> = (0..ITEMS).collect();
+    let unrolled: UnrolledList<i64> = (0..ITEMS).collect();
+    let mut dll = DlList::new();
+    for i in 0..ITEMS {
+        dll.enqueue(i);
+    }
+    report(
+        "scan Vec",
+        measure(|| {
+            black_box(v.iter().sum::<i64>());
+        }),
+    );
+    report(
+        "scan UnrolledList",
+        measure(|| {
+            black_box(unrolled.iter().sum::<i64>());
+        }),
+    );
+    report(
+        "scan DlList",
+        measure(|| {
+            while dll.move_backward() {}
+            let mut sum = *dll.peek_current_position().unwrap();
+            while dll.move_forward() {
+                sum += *dll.peek_current_position().unwrap();
+            }
+            black_box(sum);
+        }),
+    );
+    while dll.get_head().is_some() {}
+
+    //the positions of the inserts into and then of the removals from a list of INDEXED_ITEMS items
+    let mut rng = XorShift64::new(28);
+    let inserts: Vec<usize> = (0..INDEXED_OPERATIONS)
+        .map(|i| rng.next_below((INDEXED_ITEMS + i + 1) as u64) as usize)
+        .collect();
+    let removals: Vec<usize> = (0..INDEXED_OPERATIONS)
+        .map(|i| rng.next_below((INDEXED_ITEMS + INDEXED_OPERATIONS - i) as u64) as usize)
+        .collect();
+    report(
+        "indexed insert/remove Vec",
+        measure(|| {
+            let mut v: Vec<usize> = (0..INDEXED_ITEMS).collect();
+            for index in inserts.iter() {
+                v.insert(*index, *index);
+            }
+            for index in removals.iter() {
+                black_box(v.remove(*index));
+            }
+            black_box(v);
+        }),
+    );
+    report(
+        "indexed insert/remove Unrolled",
+        measure(|| {
+            let mut l: UnrolledList<usize> = (0..INDEXED_ITEMS).collect();
+            for index in inserts.iter() {
+                l.insert(*index, *index);
+            }
+            for index in removals.iter() {
+                black_box(l.remove(*index));
+            }
+            black_box(l);
+        }),
+    );
+}
@@ -0,0 +1,364 @@
+//! This module implements an adaptive replacement cache (ARC) as described by Megiddo and Modha.
+//! The cache keeps four `DlList`s of keys in recency order: `t1` holds the keys seen once recently, `t2` the keys
+//! seen at least twice, and the ghost lists `b1` and `b2` remember the keys recently evicted from `t1` and `t2`
+//! without their values. A miss on a ghost key shifts the target size `p` of `t1`, so the cache adapts between
+//! recency and frequency. Every key is indexed with the `NodeHandle` of its list node, which makes all moves O(1).
+use crate::cache::{Cache, CacheStats};
+use crate::dll::{DlList, NodeHandle};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+///The list a key is currently in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Location {
+    T1,
+    T2,
+    B1,
+    B2,
+}
+
+///The index entry of a key. Ghost keys have no value.
+struct Entry<K, V> {
+    value: Option<V>,
+    location: Location,
+    handle: NodeHandle<K>,
+}
+
+///The ARC cache with its index, its two resident and its two ghost lists
+pub struct ArcCache<K, V> {
+    map: HashMap<K, Entry<K, V>>,
+    t1: DlList<K>,
+    t2: DlList<K>,
+    b1: DlList<K>,
+    b2: DlList<K>,
+    //target size of t1
+    p: usize,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> ArcCache<K, V> {
+    ///Returns a new empty cache which holds up to `capacity` entries and remembers up to `capacity` evicted keys
+    /// # Attributes
+    /// * `capacity`- the maximum number of entries
+    pub fn new(capacity: usize) -> ArcCache<K, V> {
+        ArcCache {
+            map: HashMap::with_capacity(2 * capacity),
+            t1: DlList::new(),
+            t2: DlList::new(),
+            b1: DlList::new(),
+            b2: DlList::new(),
+            p: 0,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    ///Returns the number of cached entries
+    pub fn get_length(&self) -> usize {
+        (self.t1.get_length() + self.t2.get_length()) as usize
+    }
+
+    ///Returns true if the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.get_length() == 0
+    }
+
+    ///Returns the maximum number of entries
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///Returns the current target size of the list of keys seen once
+    pub fn get_target(&self) -> usize {
+        self.p
+    }
+
+    ///Returns true if `key` is cached. Ghost keys are not cached.
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.get(key).is_some_and(|entry| entry.value.is_some())
+    }
+
+    fn list(&mut self, location: Location) -> &mut DlList<K> {
+        match location {
+            Location::T1 => &mut self.t1,
+            Location::T2 => &mut self.t2,
+            Location::B1 => &mut self.b1,
+            Location::B2 => &mut self.b2,
+        }
+    }
+
+    ///Moves the indexed `key` from its list to the most recently used end of the list `to`
+    fn relocate(&mut self, key: &K, to: Location) {
+        let (from, handle) = {
+            let entry = &self.map[key];
+            (entry.location, entry.handle.clone())
+        };
+        if from == to {
+            self.list(to).move_to_tail(&handle);
+            return;
+        }
+        self.list(from).remove(&handle);
+        let handle = self.list(to).enqueue_with_handle(key.clone());
+        let entry = self.map.get_mut(key).unwrap();
+        entry.location = to;
+        entry.handle = handle;
+    }
+
+    ///Moves the least recently used key of the resident list `from` to the ghost list `to` and returns its entry
+    fn demote(&mut self, from: Location, to: Location) -> Option<(K, V)> {
+        let key = self.list(from).get_head()?;
+        let handle = self.list(to).enqueue_with_handle(key.clone());
+        let entry = self.map.get_mut(&key).unwrap();
+        entry.location = to;
+        entry.handle = handle;
+        let value = entry.value.take().unwrap();
+        self.stats.evictions += 1;
+        Some((key, value))
+    }
+
+    ///Forgets the least recently used key of the ghost list `from`
+    fn forget(&mut self, from: Location) {
+        if let Some(key) = self.list(from).get_head() {
+            self.map.remove(&key);
+        }
+    }
+
+    ///Makes room for one entry by moving the least recently used key of `t1` or `t2` to its ghost list,
+    ///depending on the target size of `t1`
+    fn replace(&mut self, hit_in_b2: bool) -> Option<(K, V)> {
+        let t1_length = self.t1.get_length() as usize;
+        if t1_length > 0 && (t1_length > self.p || (hit_in_b2 && t1_length == self.p))
+            || self.t2.get_length() == 0
+        {
+            self.demote(Location::T1, Location::B1)
+        } else {
+            self.demote(Location::T2, Location::B2)
+        }
+    }
+
+    ///Returns the reference to the value of `key` and marks the key as seen at least twice
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.contains_key(key) {
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.relocate(key, Location::T2);
+        self.map[key].value.as_ref()
+    }
+
+    ///Returns the reference to the value of `key` without changing the lists
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map.get(key).and_then(|entry| entry.value.as_ref())
+    }
+
+    ///Caches `value` under `key`. Returns the entry which was pushed out: the old entry of `key` if the key was
+    ///already cached, otherwise the entry evicted to make room for the new one.
+    ///A key found in a ghost list adapts the target size of `t1` and goes directly to `t2`.
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if self.capacity == 0 {
+            return Some((key, value));
+        }
+        let location = self.map.get(&key).map(|entry| entry.location);
+        match location {
+            Some(Location::T1) | Some(Location::T2) => {
+                self.relocate(&key, Location::T2);
+                let entry = self.map.get_mut(&key).unwrap();
+                let old = entry.value.replace(value).unwrap();
+                Some((key, old))
+            }
+            Some(ghost) => {
+                let (b1, b2) = (self.b1.get_length() as usize, self.b2.get_length() as usize);
+                let hit_in_b2 = ghost == Location::B2;
+                if hit_in_b2 {
+                    self.p = self.p.saturating_sub((b1 / b2).max(1));
+                } else {
+                    self.p = (self.p + (b2 / b1).max(1)).min(self.capacity);
+                }
+                let evicted = if self.get_length() >= self.capacity {
+                    self.replace(hit_in_b2)
+                } else {
+                    None
+                };
+                self.relocate(&key, Location::T2);
+                self.map.get_mut(&key).unwrap().value = Some(value);
+                evicted
+            }
+            None => {
+                let t1 = self.t1.get_length() as usize;
+                let b1 = self.b1.get_length() as usize;
+                let total = self.get_length() + b1 + self.b2.get_length() as usize;
+                let mut evicted = None;
+                if t1 + b1 >= self.capacity {
+                    if t1 < self.capacity {
+                        self.forget(Location::B1);
+                        if self.get_length() >= self.capacity {
+                            evicted = self.replace(false);
+                        }
+                    } else {
+                        //t1 fills the whole cache, its least recently used key is dropped without a ghost
+                        let key = self.t1.get_head().unwrap();
+                        let entry = self.map.remove(&key).unwrap();
+                        self.stats.evictions += 1;
+                        evicted = entry.value.map(|value| (key, value));
+                    }
+                } else if total >= self.capacity {
+                    if total >= 2 * self.capacity {
+                        self.forget(Location::B2);
+                    }
+                    if self.get_length() >= self.capacity {
+                        evicted = self.replace(false);
+                    }
+                }
+                let handle = self.t1.enqueue_with_handle(key.clone());
+                self.map.insert(
+                    key,
+                    Entry {
+                        value: Some(value),
+                        location: Location::T1,
+                        handle,
+                    },
+                );
+                evicted
+            }
+        }
+    }
+
+    ///Removes the entry of `key` and returns its value. A ghost key is forgotten and returns None.
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.map.remove(key)?;
+        self.list(entry.location).remove(&entry.handle);
+        entry.value
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for ArcCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        ArcCache::get(self, key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        ArcCache::put(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        ArcCache::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        ArcCache::contains_key(self, key)
+    }
+
+    fn get_length(&self) -> usize {
+        ArcCache::get_length(self)
+    }
+
+    fn get_capacity(&self) -> usize {
+        ArcCache::get_capacity(self)
+    }
+
+    fn get_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{RandomSource, XorShift64};
+
+    #[test]
+    fn test_new_arc() {
+        let mut c: ArcCache<i32, i32> = ArcCache::new(2);
+        assert_eq!(0, c.get_length());
+        assert!(c.get(&1).is_none());
+        assert!(c.remove(&1).is_none());
+    }
+
+    #[test]
+    fn test_frequent_keys_survive_a_scan() {
+        let mut c = ArcCache::new(3);
+        c.put(1, 1);
+        c.put(2, 2);
+        c.get(&1);
+        c.get(&2);
+        for i in 10..20 {
+            c.put(i, i);
+        }
+        assert_eq!(Some(&1), c.get(&1));
+        assert_eq!(Some(&2), c.get(&2));
+        assert_eq!(3, c.get_length());
+    }
+
+    #[test]
+    fn test_ghost_hit_adapts_target() {
+        let mut c = ArcCache::new(2);
+        c.put(1, 1);
+        c.put(2, 2);
+        c.get(&1);
+        //2 goes to the ghost list of keys seen once
+        assert_eq!(Some((2, 2)), c.put(3, 3));
+        assert!(!c.contains_key(&2));
+        assert!(c.peek(&2).is_none());
+        assert_eq!(0, c.get_target());
+        //the ghost hit grows the target of t1 and evicts from t2
+        assert_eq!(Some((1, 1)), c.put(2, 20));
+        assert_eq!(1, c.get_target());
+        assert_eq!(Some(&20), c.peek(&2));
+        assert_eq!(2, c.get_length());
+    }
+
+    #[test]
+    fn test_t1_overflow_drops_without_ghost() {
+        let mut c = ArcCache::new(2);
+        c.put(1, 1);
+        c.put(2, 2);
+        assert_eq!(Some((1, 1)), c.put(3, 3));
+        assert!(c.remove(&1).is_none());
+        assert_eq!(2, c.map.len());
+    }
+
+    #[test]
+    fn test_random_operations_keep_invariants() {
+        let mut rng = XorShift64::new(8);
+        let capacity = 8;
+        let mut c = ArcCache::new(capacity);
+        for _ in 0..5000 {
+            let key = rng.next_below(30);
+            match rng.next_below(4) {
+                0 => {
+                    c.remove(&key);
+                }
+                1 => {
+                    c.get(&key);
+                }
+                _ => {
+                    c.put(key, key * 2);
+                    assert_eq!(Some(&(key * 2)), c.peek(&key));
+                }
+            }
+            let resident = c.get_length();
+            let ghosts = (c.b1.get_length() + c.b2.get_length()) as usize;
+            assert!(resident <= capacity);
+            assert!(resident + ghosts <= 2 * capacity);
+            assert!(c.get_target() <= capacity);
+            assert_eq!(resident + ghosts, c.map.len());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! This module defines the common interface of the cache policies of the crate (`LruCache`, `LfuCache` and
+//! `ArcCache`) together with their hit/miss statistics, so policies can be swapped and compared by replaying a
+//! recorded trace of keys.
+
+///Hit/miss statistics of a cache
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+impl CacheStats {
+    ///Returns the number of recorded lookups
+    pub fn get_lookups(&self) -> u64 {
+        self.hits + self.misses
+    }
+
+    ///Returns the share of lookups which were hits, 0 if there was no lookup
+    pub fn get_hit_ratio(&self) -> f64 {
+        if self.get_lookups() == 0 {
+            0.0
+        } else {
+            self.hits as f64 / self.get_lookups() as f64
+        }
+    }
+}
+
+///The common interface of the cache policies
+pub trait Cache<K, V> {
+    ///Returns the reference to the value of `key` and records a hit or a miss
+    fn get(&mut self, key: &K) -> Option<&V>;
+
+    ///Caches `value` under `key` and returns the entry which was pushed out, either the old entry of `key`
+    ///or the entry evicted by the policy
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)>;
+
+    ///Removes the entry of `key` and returns its value
+    fn remove(&mut self, key: &K) -> Option<V>;
+
+    ///Returns true if `key` is cached without recording a lookup or changing the policy state
+    fn contains_key(&self, key: &K) -> bool;
+
+    ///Returns the number of cached entries
+    fn get_length(&self) -> usize;
+
+    ///Returns the maximum number of entries
+    fn get_capacity(&self) -> usize;
+
+    ///Returns the statistics recorded since the creation of the cache or the last reset
+    fn get_stats(&self) -> CacheStats;
+
+    ///Sets all statistics to 0
+    fn reset_stats(&mut self);
+}
+
+///Replays the trace of keys `trace` against `cache`. Every key is looked up and, on a miss, cached with the value
+///returned by `load`. Returns the statistics of the replay.
+/// # Attributes
+/// * `cache`- the cache to replay the trace against
+/// * `trace`- the recorded keys in the order of their access
+/// * `load`- returns the value of a key which missed the cache
+pub fn replay<K, V, C, F>(cache: &mut C, trace: &[K], mut load: F) -> CacheStats
+where
+    K: Clone,
+    C: Cache<K, V> + ?Sized,
+    F: FnMut(&K) -> V,
+{
+    cache.reset_stats();
+    for key in trace {
+        if cache.get(key).is_none() {
+            cache.put(key.clone(), load(key));
+        }
+    }
+    cache.get_stats()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::arc::ArcCache;
+    use crate::lfu::LfuCache;
+    use crate::lru::LruCache;
+
+    ///Two hot keys which are accessed over and over, interleaved with a scan of keys which are used once
+    fn hot_keys_with_scan() -> Vec<u32> {
+        let mut trace = vec![1, 2, 1, 2];
+        for round in 0..50 {
+            trace.extend(&[1, 2, 100 + round * 2, 101 + round * 2]);
+        }
+        trace
+    }
+
+    type Policies = Vec<(&'static str, Box<dyn Cache<u32, u32>>)>;
+
+    fn all_policies(capacity: usize) -> Policies {
+        vec![
+            ("lru", Box::new(LruCache::new(capacity))),
+            ("lfu", Box::new(LfuCache::new(capacity))),
+            ("arc", Box::new(ArcCache::new(capacity))),
+        ]
+    }
+
+    #[test]
+    fn test_stats() {
+        let mut stats = CacheStats::default();
+        assert_eq!(0.0, stats.get_hit_ratio());
+        stats.hits = 3;
+        stats.misses = 1;
+        assert_eq!(4, stats.get_lookups());
+        assert_eq!(0.75, stats.get_hit_ratio());
+    }
+
+    #[test]
+    fn test_policies_share_the_interface() {
+        for (name, mut cache) in all_policies(2) {
+            assert!(cache.get(&1).is_none(), "{}", name);
+            assert!(cache.put(1, 10).is_none(), "{}", name);
+            assert_eq!(Some(&10), cache.get(&1), "{}", name);
+            assert_eq!(Some((1, 10)), cache.put(1, 11), "{}", name);
+            cache.put(2, 20);
+            assert!(cache.put(3, 30).is_some(), "{}", name);
+            assert_eq!(2, cache.get_length(), "{}", name);
+            assert!(cache.contains_key(&3), "{}", name);
+            assert_eq!(Some(30), cache.remove(&3), "{}", name);
+            let stats = cache.get_stats();
+            assert_eq!(1, stats.hits, "{}", name);
+            assert_eq!(1, stats.misses, "{}", name);
+            assert_eq!(1, stats.evictions, "{}", name);
+            cache.reset_stats();
+            assert_eq!(CacheStats::default(), cache.get_stats(), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_replay_compares_policies() {
+        let trace = hot_keys_with_scan();
+        let mut results = Vec::new();
+        for (name, mut cache) in all_policies(3) {
+            let stats = replay(cache.as_mut(), &trace, |key| key * 10);
+            assert_eq!(trace.len() as u64, stats.get_lookups(), "{}", name);
+            results.push(stats);
+        }
+        let (lru, lfu, arc) = (results[0], results[1], results[2]);
+        //the scan pushes the hot keys out of the LRU cache, LFU and ARC keep them
+        assert!(lfu.hits > lru.hits);
+        assert!(arc.hits > lru.hits);
+    }
+}
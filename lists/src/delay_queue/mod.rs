@@ -0,0 +1,132 @@
+//! This module implements a delay queue on top of the `TimingWheel`. Items are inserted with a delay and can be
+//! taken from the queue once their delay elapsed, in the order of their deadlines.
+//! The current time is read from an injectable `Clock` of the `fifo` crate, measured in milliseconds.
+use crate::timing_wheel::{TimerHandle, TimingWheel};
+use fifo::clock::{Clock, SystemClock};
+use fifo::fifo::Fifo;
+
+///The delay queue with the wheel of the waiting items and the queue of the expired ones
+pub struct DelayQueue<T, C = SystemClock> {
+    wheel: TimingWheel<T>,
+    ready: Fifo<T>,
+    clock: C,
+}
+
+impl<T> Default for DelayQueue<T, SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DelayQueue<T, SystemClock> {
+    ///Returns a new empty queue reading the time from the system clock
+    pub fn new() -> DelayQueue<T, SystemClock> {
+        DelayQueue::with_clock(SystemClock)
+    }
+}
+
+impl<T, C: Clock> DelayQueue<T, C> {
+    ///Returns a new empty queue reading the time from `clock`
+    /// # Attributes
+    /// * `clock`- the clock to read the current time from
+    pub fn with_clock(clock: C) -> DelayQueue<T, C> {
+        DelayQueue {
+            wheel: TimingWheel::new(clock.now()),
+            ready: Fifo::new(),
+            clock,
+        }
+    }
+
+    ///Returns the number of items in the queue, waiting or expired
+    pub fn get_length(&self) -> usize {
+        self.wheel.get_length() + self.ready.get_length() as usize
+    }
+
+    ///Returns true if the queue has no items
+    pub fn is_empty(&self) -> bool {
+        self.get_length() == 0
+    }
+
+    ///Inserts `item` to expire `delay` milliseconds from now and returns the handle to cancel it
+    /// # Attributes
+    /// * `item`- the item to insert
+    /// * `delay`- the delay in milliseconds
+    pub fn insert(&mut self, item: T, delay: u64) -> TimerHandle {
+        let at = self.clock.now().saturating_add(delay);
+        self.wheel.schedule(at, item)
+    }
+
+    ///Inserts `item` to expire at the time `at` and returns the handle to cancel it
+    /// # Attributes
+    /// * `item`- the item to insert
+    /// * `at`- the time in milliseconds the item expires at
+    pub fn insert_at(&mut self, item: T, at: u64) -> TimerHandle {
+        self.wheel.schedule(at, item)
+    }
+
+    ///Removes the waiting item of `handle` and returns it. Returns None if the item already expired.
+    /// # Attributes
+    /// * `handle`- the handle returned when the item was inserted
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        self.wheel.cancel(handle)
+    }
+
+    ///Removes and returns the next item whose delay elapsed
+    pub fn poll(&mut self) -> Option<T> {
+        for (_, item) in self.wheel.advance(self.clock.now()) {
+            self.ready.enqueue(item);
+        }
+        self.ready.dequeue()
+    }
+
+    ///Removes and returns all items whose delay elapsed, in the order of their deadlines
+    pub fn poll_all(&mut self) -> Vec<T> {
+        let mut items = Vec::new();
+        while let Some(item) = self.poll() {
+            items.push(item);
+        }
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fifo::clock::ManualClock;
+
+    #[test]
+    fn test_new_delay_queue() {
+        let mut q: DelayQueue<i32> = DelayQueue::new();
+        assert!(q.is_empty());
+        assert!(q.poll().is_none());
+    }
+
+    #[test]
+    fn test_poll_after_delay() {
+        let clock = ManualClock::new(500);
+        let mut q = DelayQueue::with_clock(&clock);
+        q.insert("b", 200);
+        q.insert("a", 100);
+        q.insert_at("c", 900);
+        assert_eq!(3, q.get_length());
+        assert!(q.poll().is_none());
+        clock.advance(100);
+        assert_eq!(Some("a"), q.poll());
+        assert!(q.poll().is_none());
+        clock.set(1000);
+        assert_eq!(vec!["b", "c"], q.poll_all());
+        assert!(q.is_empty());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let clock = ManualClock::new(0);
+        let mut q = DelayQueue::with_clock(&clock);
+        let a = q.insert(1, 10);
+        let b = q.insert(2, 20);
+        assert_eq!(Some(2), q.cancel(b));
+        clock.advance(30);
+        assert!(q.cancel(a).is_some());
+        assert!(q.poll().is_none());
+    }
+}
@@ -1,446 +1,680 @@
-//! This module implements a double linked list with enqueue, a consuming get_head, a consuming get_tail,
-//! and non consuming peek
-//! For long queues we should implement the Drop function to ensure successful disposal of the queue.   
-use std::cell::{Ref, RefCell};
-use std::rc::Rc;
-
-///Type to simplify variable declaration
-type Link<T> = Option<Rc<RefCell<Node<T>>>>;
-
-///A single node in the queue with a value and a pointer to another node.
-pub struct Node<T> {
-    value: T,
-    next: Link<T>,
-    previous: Link<T>,
-}
-
-///The FIFO queue with a pointer to its head and tail
-pub struct DlList<T> {
-    head: Link<T>,
-    tail: Link<T>,
-    current_position: Link<T>,
-    length: i32,
-}
-
-impl<T> Node<T> {
-    ///Returns a new Node with the value `value`
-    /// # Attributes
-    /// * `value`- the value to assign to the node
-    pub fn new(value: T) -> Node<T> {
-        Node {
-            value: value,
-            next: None,
-            previous: None,
-        }
-    }
-}
-
-impl<T> DlList<T> {
-    ///Returns a new empty FIFO queue
-    pub fn new() -> DlList<T> {
-        DlList {
-            head: None,
-            tail: None,
-            current_position: None,
-            length: 0,
-        }
-    }
-    ///Returns the length of its queue
-    pub fn get_length(&self) -> i32 {
-        self.length
-    }
-
-    ///attaches a new Node to the end of its queue, moves the references of the list accordingly and returns its new length
-    /// # Attributes
-    /// * `value`- the value to append
-    pub fn enqueue(&mut self, value: T) -> i32 {
-        // DlList::print_pointer_count(self.head.as_ref(), "in Enqueue at beginning", "head");
-        // DlList::print_pointer_count(self.tail.as_ref(), "in Enqueue at beginning", "tail");
-        let mut new_node = Node::new(value);
-        //lets take the tail and match it to see if we enter the first item
-        match self.tail.take() {
-            Some(old_tail) => {
-                new_node.previous = Some(Rc::clone(&old_tail));
-                let new_tail_ref = Rc::new(RefCell::new(new_node));
-                let next_node_ref = Rc::clone(&new_tail_ref);
-                old_tail.borrow_mut().next = Some(next_node_ref);
-                self.tail = Some(new_tail_ref);
-            }
-            None => {
-                //first item in the list
-                //lets add it to the head...
-                let ref_node_head = Rc::new(RefCell::new(new_node));
-                let ref_node_tail = Rc::clone(&ref_node_head);
-                let ref_node_current_pos = Rc::clone(&ref_node_head);
-                self.head = Some(ref_node_head);
-                //... and tail
-                self.tail = Some(ref_node_tail);
-                //...and current current_position
-                self.current_position = Some(ref_node_current_pos);
-            }
-        }
-        // DlList::print_pointer_count(self.head.as_ref(), "in Enqueue at after match", "head");
-        // DlList::print_pointer_count(self.tail.as_ref(), "in Enqueue at after match", "tail");
-        self.length += 1;
-        self.length
-    }
-
-    ///Removes and returns the head item in the list and moves the references of the list accordingly
-    pub fn get_head(&mut self) -> Option<T> {
-        match self.head.take() {
-            Some(head) => {
-                //what is the next element in the list
-                let next = head.borrow_mut().next.take();
-                match next {
-                    Some(next) => {
-                        //we need to set the prevoius point to None as it is the head now
-                        next.borrow_mut().previous = None;
-                        //We need to check if current_position is pointing to our taken head
-                        if Rc::ptr_eq(&self.current_position.as_ref().unwrap(), &head) {
-                            //if it is we move the curren_position to our new head
-                            self.current_position = Some(Rc::clone(&next));
-                        }
-                        //and we set the head
-                        self.head = Some(next);
-                    }
-                    None => {
-                        //no item in the list anymore
-                        self.tail = None;
-                        self.current_position = None;
-                    }
-                };
-                self.length -= 1;
-                match Rc::try_unwrap(head) {
-                    Ok(i) => Some(i.into_inner().value),
-                    Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
-                }
-            }
-            None => None,
-        }
-    }
-
-    ///Removes and returns the tail item in the list and moves the references of the list accordingly
-    pub fn get_tail(&mut self) -> Option<T> {
-        match self.tail.take() {
-            Some(tail) => {
-                //what is the previous element in the list
-                let previous = tail.borrow_mut().previous.take();
-                match previous {
-                    Some(previous) => {
-                        //we need to set the next point to None as it is the tail now
-                        previous.borrow_mut().next = None;
-                        //We need to check if current_position is pointing to our taken tail
-                        if Rc::ptr_eq(&self.current_position.as_ref().unwrap(), &tail) {
-                            //if it is we move the curren_position to our new tail
-                            self.current_position = Some(Rc::clone(&previous));
-                        }
-                        self.tail = Some(previous);
-                    }
-                    None => {
-                        //no item in the list anymore
-                        self.head = None;
-                        self.current_position = None;
-                    }
-                };
-                self.length -= 1;
-                match Rc::try_unwrap(tail) {
-                    Ok(i) => Some(i.into_inner().value),
-                    Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
-                }
-            }
-            None => None,
-        }
-    }
-
-    fn print_pointer_count(
-        pointer: std::option::Option<&std::rc::Rc<std::cell::RefCell<Node<T>>>>,
-        location: &str,
-        pointer_name: &str,
-    ) {
-        match pointer {
-            Some(p) => println!(
-                "Pointer {} Count at {}: {}",
-                pointer_name,
-                location,
-                Rc::strong_count(&p)
-            ),
-            None => println!("Pointer {} is NONE at {}", pointer_name, location),
-        }
-    }
-
-    ///Removes and returns the item at the current position and advances the current Position in the list and moves the references of the list accordingly
-    pub fn get_current_position(&mut self) -> Option<T> {
-        DlList::print_pointer_count(
-            self.current_position.as_ref(),
-            "Beginning of get_current_position()",
-            "current_position",
-        );
-        //get the current_position
-        match self.current_position.take() {
-            Some(cp) => {
-                let previous_item = cp.borrow_mut().previous.take();
-                let next_item = cp.borrow_mut().next.take();
-                match previous_item {
-                    Some(previous_item) => {
-                        //we are not at the head
-                        match next_item {
-                            Some(next_item) => {
-                                //current position was not at the head
-                                //first we set the previous item next element to the next_item
-                                previous_item.borrow_mut().next = Some(Rc::clone(&next_item));
-                                //then we should set the next item previous element to the previous element
-                                next_item.borrow_mut().previous = Some(Rc::clone(&previous_item));
-                                //and we advance the currenposition to the next element
-                                self.current_position = Some(Rc::clone(&next_item));
-                            }
-                            None => {
-                                //current position was not at the head
-                                //first we set the previous item next element to None as it is the new tail
-                                previous_item.borrow_mut().next = None;
-                                //current Position was at the tail
-                                //we need to set the tail to the previous item
-                                self.tail = Some(Rc::clone(&previous_item));
-                                //we need to set the curren_position to the previous item
-                                self.current_position = Some(Rc::clone(&previous_item));
-                            }
-                        }
-                    }
-                    None => {
-                        //current position was at the head
-                        match next_item {
-                            Some(next_item) => {
-                                //We set the next_item previous element to None as it is the new head
-                                next_item.borrow_mut().previous = None;
-                                //we need to set the curren_position to the next item
-                                self.current_position = Some(Rc::clone(&next_item));
-                                //and we set the head to the next item
-                                self.head = Some(next_item);
-                            }
-                            None => {
-                                //there is no item in the list after taking current-position
-                                self.head = None;
-                                self.tail = None;
-                                self.current_position = None;
-                            }
-                        }
-                    }
-                }
-                self.length -= 1;
-                DlList::print_pointer_count(
-                    self.current_position.as_ref(),
-                    "In get_current_position before Rc::try_unwrap",
-                    "current_position",
-                );
-                match Rc::try_unwrap(cp) {
-                    Ok(i) => Some(i.into_inner().value),
-                    Err(rc) => {
-                        DlList::print_pointer_count(
-                            self.current_position.as_ref(),
-                            "In get_current_position in Rc::try_unwrap Error case",
-                            "current_position",
-                        );
-                        panic!(
-                            "Something is wrong. We shouldn't arrive here!!! Pointer Count: {}",
-                            Rc::strong_count(&rc)
-                        )
-                    }
-                }
-            }
-            //if it is None we should assert, that the list is empty and return None
-            None => {
-                assert_eq!(0, self.length);
-                return None;
-            }
-        }
-    }
-
-    ///Moves the current position one item forward in the list and returns true if the move was successful, false otherwise.
-    pub fn move_forward(&mut self) -> bool {
-        DlList::print_pointer_count(self.head.as_ref(), "in move_forward at beginning", "head");
-        let cp: Link<T>;
-        if self.length == 0 {
-            return false;
-        } else {
-            let c = self.current_position.as_ref().unwrap().borrow();
-            if c.next.is_none() {
-                return false;
-            } else {
-                let mut next = Some(Ref::map(c, |node| node.next.as_ref().unwrap()));
-                cp = match next.take() {
-                    Some(x) => Some(Rc::clone(&*x)),
-                    None => return false,
-                };
-            }
-        }
-        self.current_position = cp;
-        DlList::print_pointer_count(self.head.as_ref(), "in move_forward at end", "head");
-        return true;
-    }
-
-    ///Moves the current position one item backwards in the list and returns true if the move was successful, false otherwise.
-    pub fn move_backward(&mut self) -> bool {
-        // DlList::print_pointer_count(self.head.as_ref(), "in move_backward at beginning", "head");
-        let cp: Link<T>;
-        if self.length == 0 {
-            return false;
-        } else {
-            let c = self.current_position.as_ref().unwrap().borrow();
-            if c.previous.is_none() {
-                return false;
-            } else {
-                let mut previous = Some(Ref::map(c, |node| node.previous.as_ref().unwrap()));
-                cp = match previous.take() {
-                    Some(x) => Some(Rc::clone(&*x)),
-                    None => return false,
-                };
-            }
-        }
-        self.current_position = cp;
-        // DlList::print_pointer_count(self.head.as_ref(), "in move_backward at end", "head");
-        return true;
-    }
-
-    ///Peeks the reference to the value of the current position without consuming it. The value is returned inside a `Ref`
-    pub fn peek_current_position(&self) -> Option<Ref<T>> {
-        self.current_position
-            .as_ref()
-            .map(|node| Ref::map(node.borrow(), |node| &node.value))
-    }
-
-    ///Peeks the reference to the value of the head position without consuming it. The value is returned inside a `Ref`
-    pub fn peek_head(&self) -> Option<Ref<T>> {
-        self.head
-            .as_ref()
-            .map(|node| Ref::map(node.borrow(), |node| &node.value))
-    }
-
-    ///Peeks the reference to the value of the tail position without consuming it. The value is returned inside a `Ref`
-    pub fn peek_tail(&self) -> Option<Ref<T>> {
-        self.tail
-            .as_ref()
-            .map(|node| Ref::map(node.borrow(), |node| &node.value))
-    }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    #[test]
-    fn test_new_dll() {
-        let l: DlList<i32> = DlList::new();
-        assert_eq!(0, l.get_length());
-    }
-    #[test]
-    fn test_empty_dll_head() {
-        let mut l: DlList<i32> = DlList::new();
-        assert!(l.get_head().is_none());
-    }
-
-    #[test]
-    fn test_empty_dll_tail() {
-        let mut l: DlList<i32> = DlList::new();
-        assert!(l.get_tail().is_none());
-    }
-    #[test]
-    fn test_one_item_dll_head() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test")));
-        assert_eq!(l.get_length(), 1);
-        assert_eq!(String::from("test"), l.get_head().unwrap());
-        assert_eq!(l.get_length(), 0);
-    }
-
-    #[test]
-    fn test_one_item_dll_tail() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test")));
-        assert_eq!(l.get_length(), 1);
-        assert_eq!(String::from("test"), l.get_tail().unwrap());
-        assert_eq!(l.get_length(), 0);
-    }
-
-    #[test]
-    fn test_multi_item_dll_head() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test1")));
-        assert_eq!(2, l.enqueue(String::from("test2")));
-        assert_eq!(2, l.get_length());
-        assert_eq!(String::from("test1"), l.get_head().unwrap());
-        assert_eq!(l.get_length(), 1);
-        assert_eq!(String::from("test2"), l.get_head().unwrap());
-        assert_eq!(l.get_length(), 0);
-        assert!(l.get_head().is_none());
-    }
-
-    #[test]
-    fn test_multi_item_dll_tail() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test1")));
-        assert_eq!(2, l.enqueue(String::from("test2")));
-        assert_eq!(2, l.get_length());
-        assert_eq!(String::from("test2"), l.get_tail().unwrap());
-        assert_eq!(l.get_length(), 1);
-        assert_eq!(String::from("test1"), l.get_tail().unwrap());
-        assert_eq!(l.get_length(), 0);
-        assert!(l.get_tail().is_none());
-    }
-    #[test]
-    fn test_multi_item_dll_mixed() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test1"))); //
-        assert_eq!(2, l.enqueue(String::from("test2"))); //
-        assert_eq!(3, l.enqueue(String::from("test3")));
-        assert_eq!(4, l.enqueue(String::from("test4")));
-        assert_eq!(5, l.enqueue(String::from("test5"))); //
-        assert_eq!(5, l.get_length());
-        assert_eq!(String::from("test5"), l.get_tail().unwrap());
-        assert_eq!(l.get_length(), 4);
-        assert_eq!(String::from("test1"), l.get_head().unwrap());
-        assert_eq!(l.get_length(), 3);
-        assert_eq!(4, l.enqueue(String::from("test6"))); //
-        assert_eq!(l.get_length(), 4);
-        assert_eq!(String::from("test2"), l.get_head().unwrap());
-        assert_eq!(String::from("test6"), l.get_tail().unwrap());
-        assert_eq!(String::from("test4"), l.get_tail().unwrap());
-        assert_eq!(String::from("test3"), l.get_head().unwrap());
-        assert!(l.get_head().is_none());
-        assert!(l.get_tail().is_none());
-        assert_eq!(0, l.get_length());
-    }
-
-    #[test]
-    fn test_peek() {
-        let mut l = DlList::new();
-        assert_eq!(1, l.enqueue(String::from("test1")));
-        assert_eq!(2, l.enqueue(String::from("test2")));
-        assert_eq!(String::from("test1"), *l.peek_head().unwrap());
-        assert_eq!(String::from("test2"), *l.peek_tail().unwrap());
-        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
-        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
-        assert!(l.move_forward());
-        assert_eq!(String::from("test2"), *l.peek_current_position().unwrap());
-        assert!(!l.move_forward());
-        assert!(l.move_backward());
-        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
-        assert!(!l.move_backward());
-        assert_eq!(String::from("test1"), l.get_head().unwrap());
-        assert_eq!(String::from("test2"), *l.peek_current_position().unwrap());
-        assert_eq!(String::from("test2"), l.get_head().unwrap());
-        assert!(l.peek_current_position().is_none());
-    }
-
-    #[test]
-    fn test_get_current_position() {
-        let mut l = DlList::new();
-        for i in 1..5 {
-            assert_eq!(i, l.enqueue(i));
-        }
-        //current position should be the head
-        assert_eq!(1, l.get_current_position().unwrap());
-        while l.move_forward() {}
-        //we should be at the tail now
-        //lets move one backwards
-        l.move_backward();
-        assert_eq!(3, l.get_current_position().unwrap());
-    }
-}
+//! This module implements a double linked list with enqueue, a consuming get_head, a consuming get_tail,
+//! and non consuming peek.
+//! Nodes can also be enqueued with a `NodeHandle`, which allows to remove or move them from anywhere in the list in O(1).
+use std::cell::{Ref, RefCell};
+use std::marker::PhantomData;
+use std::rc::{Rc, Weak};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+///The source of the ids which tell the lists apart, so handles of other lists are detected
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(0);
+
+///Type to simplify variable declaration
+type Link<T> = Option<Rc<RefCell<Node<T>>>>;
+
+///A single node in the queue with a value and a pointer to another node.
+pub struct Node<T> {
+    value: T,
+    next: Link<T>,
+    previous: Link<T>,
+}
+
+///The FIFO queue with a pointer to its head and tail
+pub struct DlList<T> {
+    head: Link<T>,
+    tail: Link<T>,
+    current_position: Link<T>,
+    length: i32,
+    id: usize,
+}
+
+///A handle to a node of a `DlList` which allows to remove or move the node in O(1).
+///The handle does not keep the node alive, it gets invalid when the node is removed from the list.
+pub struct NodeHandle<T> {
+    node: Weak<RefCell<Node<T>>>,
+    list_id: usize,
+}
+
+///Iterator over copies of the values of a `DlList`, which borrows the list so it can't change during the iteration
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    next_back: Link<T>,
+    remaining: i32,
+    list: PhantomData<&'a DlList<T>>,
+}
+
+///Consuming iterator over the values of a `DlList` from head to tail
+pub struct IntoIter<T>(DlList<T>);
+
+impl<T> Node<T> {
+    ///Returns a new Node with the value `value`
+    /// # Attributes
+    /// * `value`- the value to assign to the node
+    pub fn new(value: T) -> Node<T> {
+        Node {
+            value,
+            next: None,
+            previous: None,
+        }
+    }
+}
+
+impl<T> Default for DlList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> DlList<T> {
+    ///Returns a new empty FIFO queue
+    pub fn new() -> DlList<T> {
+        DlList {
+            head: None,
+            tail: None,
+            current_position: None,
+            length: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+    ///Returns the length of its queue
+    pub fn get_length(&self) -> i32 {
+        self.length
+    }
+
+    ///attaches a new Node to the end of its queue, moves the references of the list accordingly and returns its new length
+    /// # Attributes
+    /// * `value`- the value to append
+    pub fn enqueue(&mut self, value: T) -> i32 {
+        self.attach_tail(Rc::new(RefCell::new(Node::new(value))));
+        self.length
+    }
+
+    ///attaches a new Node to the end of its queue and returns a handle to it, which allows to remove or move the node later in O(1)
+    /// # Attributes
+    /// * `value`- the value to append
+    pub fn enqueue_with_handle(&mut self, value: T) -> NodeHandle<T> {
+        let node = Rc::new(RefCell::new(Node::new(value)));
+        let handle = NodeHandle {
+            node: Rc::downgrade(&node),
+            list_id: self.id,
+        };
+        self.attach_tail(node);
+        handle
+    }
+
+    ///attaches the unlinked node `node` to the end of its queue and moves the references of the list accordingly
+    fn attach_tail(&mut self, node: Rc<RefCell<Node<T>>>) {
+        //lets take the tail and match it to see if we enter the first item
+        match self.tail.take() {
+            Some(old_tail) => {
+                node.borrow_mut().previous = Some(Rc::clone(&old_tail));
+                old_tail.borrow_mut().next = Some(Rc::clone(&node));
+                self.tail = Some(node);
+            }
+            None => {
+                //first item in the list
+                //lets add it to the head, the current_position...
+                self.head = Some(Rc::clone(&node));
+                self.current_position = Some(Rc::clone(&node));
+                //... and tail
+                self.tail = Some(node);
+            }
+        }
+        self.length += 1;
+    }
+
+    ///unlinks the node `node` from anywhere in the list and moves the references of the list accordingly.
+    ///Like `get_current_position` a current_position on the node advances, or moves backwards if the node is the tail.
+    fn unlink(&mut self, node: &Rc<RefCell<Node<T>>>) {
+        let previous = node.borrow_mut().previous.take();
+        let next = node.borrow_mut().next.take();
+        if self
+            .current_position
+            .as_ref()
+            .is_some_and(|cp| Rc::ptr_eq(cp, node))
+        {
+            self.current_position = next.clone().or_else(|| previous.clone());
+        }
+        match previous.as_ref() {
+            Some(previous) => previous.borrow_mut().next = next.clone(),
+            None => self.head = next.clone(),
+        }
+        match next {
+            Some(next) => next.borrow_mut().previous = previous,
+            None => self.tail = previous,
+        }
+        self.length -= 1;
+    }
+
+    ///Removes and returns the head item in the list and moves the references of the list accordingly
+    pub fn get_head(&mut self) -> Option<T> {
+        match self.head.take() {
+            Some(head) => {
+                //what is the next element in the list
+                let next = head.borrow_mut().next.take();
+                match next {
+                    Some(next) => {
+                        //we need to set the prevoius point to None as it is the head now
+                        next.borrow_mut().previous = None;
+                        //We need to check if current_position is pointing to our taken head
+                        if Rc::ptr_eq(self.current_position.as_ref().unwrap(), &head) {
+                            //if it is we move the curren_position to our new head
+                            self.current_position = Some(Rc::clone(&next));
+                        }
+                        //and we set the head
+                        self.head = Some(next);
+                    }
+                    None => {
+                        //no item in the list anymore
+                        self.tail = None;
+                        self.current_position = None;
+                    }
+                };
+                self.length -= 1;
+                match Rc::try_unwrap(head) {
+                    Ok(i) => Some(i.into_inner().value),
+                    Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
+                }
+            }
+            None => None,
+        }
+    }
+
+    ///Removes and returns the tail item in the list and moves the references of the list accordingly
+    pub fn get_tail(&mut self) -> Option<T> {
+        match self.tail.take() {
+            Some(tail) => {
+                //what is the previous element in the list
+                let previous = tail.borrow_mut().previous.take();
+                match previous {
+                    Some(previous) => {
+                        //we need to set the next point to None as it is the tail now
+                        previous.borrow_mut().next = None;
+                        //We need to check if current_position is pointing to our taken tail
+                        if Rc::ptr_eq(self.current_position.as_ref().unwrap(), &tail) {
+                            //if it is we move the curren_position to our new tail
+                            self.current_position = Some(Rc::clone(&previous));
+                        }
+                        self.tail = Some(previous);
+                    }
+                    None => {
+                        //no item in the list anymore
+                        self.head = None;
+                        self.current_position = None;
+                    }
+                };
+                self.length -= 1;
+                match Rc::try_unwrap(tail) {
+                    Ok(i) => Some(i.into_inner().value),
+                    Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
+                }
+            }
+            None => None,
+        }
+    }
+
+    ///Removes and returns the item at the current position and advances the current Position in the list and moves the references of the list accordingly
+    pub fn get_current_position(&mut self) -> Option<T> {
+        //get the current_position
+        match self.current_position.take() {
+            Some(cp) => {
+                let previous_item = cp.borrow_mut().previous.take();
+                let next_item = cp.borrow_mut().next.take();
+                match previous_item {
+                    Some(previous_item) => {
+                        //we are not at the head
+                        match next_item {
+                            Some(next_item) => {
+                                //current position was not at the head
+                                //first we set the previous item next element to the next_item
+                                previous_item.borrow_mut().next = Some(Rc::clone(&next_item));
+                                //then we should set the next item previous element to the previous element
+                                next_item.borrow_mut().previous = Some(Rc::clone(&previous_item));
+                                //and we advance the currenposition to the next element
+                                self.current_position = Some(Rc::clone(&next_item));
+                            }
+                            None => {
+                                //current position was not at the head
+                                //first we set the previous item next element to None as it is the new tail
+                                previous_item.borrow_mut().next = None;
+                                //current Position was at the tail
+                                //we need to set the tail to the previous item
+                                self.tail = Some(Rc::clone(&previous_item));
+                                //we need to set the curren_position to the previous item
+                                self.current_position = Some(Rc::clone(&previous_item));
+                            }
+                        }
+                    }
+                    None => {
+                        //current position was at the head
+                        match next_item {
+                            Some(next_item) => {
+                                //We set the next_item previous element to None as it is the new head
+                                next_item.borrow_mut().previous = None;
+                                //we need to set the curren_position to the next item
+                                self.current_position = Some(Rc::clone(&next_item));
+                                //and we set the head to the next item
+                                self.head = Some(next_item);
+                            }
+                            None => {
+                                //there is no item in the list after taking current-position
+                                self.head = None;
+                                self.tail = None;
+                                self.current_position = None;
+                            }
+                        }
+                    }
+                }
+                self.length -= 1;
+                match Rc::try_unwrap(cp) {
+                    Ok(i) => Some(i.into_inner().value),
+                    Err(rc) => panic!(
+                        "Something is wrong. We shouldn't arrive here!!! Pointer Count: {}",
+                        Rc::strong_count(&rc)
+                    ),
+                }
+            }
+            //if it is None we should assert, that the list is empty and return None
+            None => {
+                assert_eq!(0, self.length);
+                None
+            }
+        }
+    }
+
+    ///Moves the current position one item forward in the list and returns true if the move was successful, false otherwise.
+    pub fn move_forward(&mut self) -> bool {
+        let cp: Link<T>;
+        if self.length == 0 {
+            return false;
+        } else {
+            let c = self.current_position.as_ref().unwrap().borrow();
+            if c.next.is_none() {
+                return false;
+            } else {
+                let mut next = Some(Ref::map(c, |node| node.next.as_ref().unwrap()));
+                cp = match next.take() {
+                    Some(x) => Some(Rc::clone(&*x)),
+                    None => return false,
+                };
+            }
+        }
+        self.current_position = cp;
+        true
+    }
+
+    ///Moves the current position one item backwards in the list and returns true if the move was successful, false otherwise.
+    pub fn move_backward(&mut self) -> bool {
+        let cp: Link<T>;
+        if self.length == 0 {
+            return false;
+        } else {
+            let c = self.current_position.as_ref().unwrap().borrow();
+            if c.previous.is_none() {
+                return false;
+            } else {
+                let mut previous = Some(Ref::map(c, |node| node.previous.as_ref().unwrap()));
+                cp = match previous.take() {
+                    Some(x) => Some(Rc::clone(&*x)),
+                    None => return false,
+                };
+            }
+        }
+        self.current_position = cp;
+        true
+    }
+
+    ///Peeks the reference to the value of the current position without consuming it. The value is returned inside a `Ref`
+    pub fn peek_current_position(&self) -> Option<Ref<'_, T>> {
+        self.current_position
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    ///Peeks the reference to the value of the head position without consuming it. The value is returned inside a `Ref`
+    pub fn peek_head(&self) -> Option<Ref<'_, T>> {
+        self.head
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    ///Peeks the reference to the value of the tail position without consuming it. The value is returned inside a `Ref`
+    pub fn peek_tail(&self) -> Option<Ref<'_, T>> {
+        self.tail
+            .as_ref()
+            .map(|node| Ref::map(node.borrow(), |node| &node.value))
+    }
+
+    ///Returns the node of `handle`, or None if it was removed. Panics if the handle was returned by another list.
+    fn upgrade(&self, handle: &NodeHandle<T>) -> Link<T> {
+        assert_eq!(
+            self.id, handle.list_id,
+            "the handle belongs to another list"
+        );
+        handle.node.upgrade()
+    }
+
+    ///Removes the node of `handle` from the list and returns its value, or None if the node was already removed.
+    ///Panics if the handle was returned by another list.
+    /// # Attributes
+    /// * `handle`- the handle of the node to remove
+    pub fn remove(&mut self, handle: &NodeHandle<T>) -> Option<T> {
+        let node = self.upgrade(handle)?;
+        self.unlink(&node);
+        match Rc::try_unwrap(node) {
+            Ok(i) => Some(i.into_inner().value),
+            Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
+        }
+    }
+
+    ///Moves the node of `handle` to the end of the list and returns true if the move was successful,
+    ///false if the node was already removed. A current_position on the node moves along with it.
+    ///Panics if the handle was returned by another list.
+    /// # Attributes
+    /// * `handle`- the handle of the node to move
+    pub fn move_to_tail(&mut self, handle: &NodeHandle<T>) -> bool {
+        let node = match self.upgrade(handle) {
+            Some(node) => node,
+            None => return false,
+        };
+        if self
+            .tail
+            .as_ref()
+            .is_some_and(|tail| Rc::ptr_eq(tail, &node))
+        {
+            return true;
+        }
+        let is_current = self
+            .current_position
+            .as_ref()
+            .is_some_and(|cp| Rc::ptr_eq(cp, &node));
+        self.unlink(&node);
+        self.attach_tail(Rc::clone(&node));
+        if is_current {
+            self.current_position = Some(node);
+        }
+        true
+    }
+
+    ///Returns an iterator over copies of the values from head to tail. It can be reversed to iterate from tail to head.
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.clone(),
+            next_back: self.tail.clone(),
+            remaining: self.length,
+            list: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for NodeHandle<T> {
+    fn clone(&self) -> Self {
+        NodeHandle {
+            node: Weak::clone(&self.node),
+            list_id: self.list_id,
+        }
+    }
+}
+
+impl<'a, T: Clone> Iterator for Iter<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.next.take()?;
+        let node = node.borrow();
+        self.next = node.next.clone();
+        self.remaining -= 1;
+        Some(node.value.clone())
+    }
+}
+
+impl<'a, T: Clone> DoubleEndedIterator for Iter<'a, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let node = self.next_back.take()?;
+        let node = node.borrow();
+        self.next_back = node.previous.clone();
+        self.remaining -= 1;
+        Some(node.value.clone())
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.get_head()
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.get_tail()
+    }
+}
+
+impl<T> IntoIterator for DlList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> Drop for DlList<T> {
+    ///Takes the items one by one from the head to break the reference cycles between the nodes
+    fn drop(&mut self) {
+        while self.get_head().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_new_dll() {
+        let l: DlList<i32> = DlList::new();
+        assert_eq!(0, l.get_length());
+    }
+    #[test]
+    fn test_empty_dll_head() {
+        let mut l: DlList<i32> = DlList::new();
+        assert!(l.get_head().is_none());
+    }
+
+    #[test]
+    fn test_empty_dll_tail() {
+        let mut l: DlList<i32> = DlList::new();
+        assert!(l.get_tail().is_none());
+    }
+    #[test]
+    fn test_one_item_dll_head() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test")));
+        assert_eq!(l.get_length(), 1);
+        assert_eq!(String::from("test"), l.get_head().unwrap());
+        assert_eq!(l.get_length(), 0);
+    }
+
+    #[test]
+    fn test_one_item_dll_tail() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test")));
+        assert_eq!(l.get_length(), 1);
+        assert_eq!(String::from("test"), l.get_tail().unwrap());
+        assert_eq!(l.get_length(), 0);
+    }
+
+    #[test]
+    fn test_multi_item_dll_head() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test1")));
+        assert_eq!(2, l.enqueue(String::from("test2")));
+        assert_eq!(2, l.get_length());
+        assert_eq!(String::from("test1"), l.get_head().unwrap());
+        assert_eq!(l.get_length(), 1);
+        assert_eq!(String::from("test2"), l.get_head().unwrap());
+        assert_eq!(l.get_length(), 0);
+        assert!(l.get_head().is_none());
+    }
+
+    #[test]
+    fn test_multi_item_dll_tail() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test1")));
+        assert_eq!(2, l.enqueue(String::from("test2")));
+        assert_eq!(2, l.get_length());
+        assert_eq!(String::from("test2"), l.get_tail().unwrap());
+        assert_eq!(l.get_length(), 1);
+        assert_eq!(String::from("test1"), l.get_tail().unwrap());
+        assert_eq!(l.get_length(), 0);
+        assert!(l.get_tail().is_none());
+    }
+    #[test]
+    fn test_multi_item_dll_mixed() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test1"))); //
+        assert_eq!(2, l.enqueue(String::from("test2"))); //
+        assert_eq!(3, l.enqueue(String::from("test3")));
+        assert_eq!(4, l.enqueue(String::from("test4")));
+        assert_eq!(5, l.enqueue(String::from("test5"))); //
+        assert_eq!(5, l.get_length());
+        assert_eq!(String::from("test5"), l.get_tail().unwrap());
+        assert_eq!(l.get_length(), 4);
+        assert_eq!(String::from("test1"), l.get_head().unwrap());
+        assert_eq!(l.get_length(), 3);
+        assert_eq!(4, l.enqueue(String::from("test6"))); //
+        assert_eq!(l.get_length(), 4);
+        assert_eq!(String::from("test2"), l.get_head().unwrap());
+        assert_eq!(String::from("test6"), l.get_tail().unwrap());
+        assert_eq!(String::from("test4"), l.get_tail().unwrap());
+        assert_eq!(String::from("test3"), l.get_head().unwrap());
+        assert!(l.get_head().is_none());
+        assert!(l.get_tail().is_none());
+        assert_eq!(0, l.get_length());
+    }
+
+    #[test]
+    fn test_peek() {
+        let mut l = DlList::new();
+        assert_eq!(1, l.enqueue(String::from("test1")));
+        assert_eq!(2, l.enqueue(String::from("test2")));
+        assert_eq!(String::from("test1"), *l.peek_head().unwrap());
+        assert_eq!(String::from("test2"), *l.peek_tail().unwrap());
+        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
+        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
+        assert!(l.move_forward());
+        assert_eq!(String::from("test2"), *l.peek_current_position().unwrap());
+        assert!(!l.move_forward());
+        assert!(l.move_backward());
+        assert_eq!(String::from("test1"), *l.peek_current_position().unwrap());
+        assert!(!l.move_backward());
+        assert_eq!(String::from("test1"), l.get_head().unwrap());
+        assert_eq!(String::from("test2"), *l.peek_current_position().unwrap());
+        assert_eq!(String::from("test2"), l.get_head().unwrap());
+        assert!(l.peek_current_position().is_none());
+    }
+
+    #[test]
+    fn test_get_current_position() {
+        let mut l = DlList::new();
+        for i in 1..5 {
+            assert_eq!(i, l.enqueue(i));
+        }
+        //current position should be the head
+        assert_eq!(1, l.get_current_position().unwrap());
+        while l.move_forward() {}
+        //we should be at the tail now
+        //lets move one backwards
+        l.move_backward();
+        assert_eq!(3, l.get_current_position().unwrap());
+    }
+
+    #[test]
+    fn test_handle_remove() {
+        let mut l = DlList::new();
+        let h1 = l.enqueue_with_handle(1);
+        let h2 = l.enqueue_with_handle(2);
+        let h3 = l.enqueue_with_handle(3);
+        assert_eq!(3, l.get_length());
+        assert_eq!(Some(2), l.remove(&h2));
+        assert!(l.remove(&h2).is_none());
+        assert_eq!(vec![1, 3], l.iter().collect::<Vec<_>>());
+        //the current position at the head advances
+        assert_eq!(Some(1), l.remove(&h1));
+        assert_eq!(3, *l.peek_current_position().unwrap());
+        assert_eq!(Some(3), l.remove(&h3));
+        assert_eq!(0, l.get_length());
+        assert!(l.peek_head().is_none());
+        assert!(l.peek_tail().is_none());
+        assert!(l.peek_current_position().is_none());
+    }
+
+    #[test]
+    fn test_handle_move_to_tail() {
+        let mut l = DlList::new();
+        let handles: Vec<NodeHandle<i32>> = (1..5).map(|i| l.enqueue_with_handle(i)).collect();
+        assert!(l.move_to_tail(&handles[0]));
+        assert_eq!(vec![2, 3, 4, 1], l.iter().collect::<Vec<_>>());
+        //the current position moves along with its node
+        assert_eq!(1, *l.peek_current_position().unwrap());
+        assert!(l.move_to_tail(&handles[2]));
+        assert!(l.move_to_tail(&handles[2]));
+        assert_eq!(vec![2, 4, 1, 3], l.iter().collect::<Vec<_>>());
+        assert_eq!(vec![3, 1, 4, 2], l.iter().rev().collect::<Vec<_>>());
+        assert_eq!(2, l.get_head().unwrap());
+        assert!(!l.move_to_tail(&handles[1]));
+        assert_eq!(3, l.get_length());
+        assert_eq!(vec![4, 1, 3], l.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_iter_borrows_list() {
+        let mut l = DlList::new();
+        for i in 1..5 {
+            l.enqueue(i);
+        }
+        //the iterator keeps the nodes borrowed, so the list changes only after it is gone
+        let mut iter = l.iter();
+        assert_eq!(Some(1), iter.next());
+        assert_eq!(Some(4), iter.next_back());
+        drop(iter);
+        assert_eq!(Some(1), l.get_head());
+        assert_eq!(vec![2, 3, 4], l.iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    #[should_panic(expected = "the handle belongs to another list")]
+    fn test_handle_of_other_list_remove() {
+        let mut l = DlList::new();
+        let mut other = DlList::new();
+        l.enqueue(1);
+        let handle = other.enqueue_with_handle(1);
+        l.remove(&handle);
+    }
+
+    #[test]
+    #[should_panic(expected = "the handle belongs to another list")]
+    fn test_handle_of_other_list_move_to_tail() {
+        let mut l = DlList::new();
+        let mut other = DlList::new();
+        l.enqueue(1);
+        let handle = other.enqueue_with_handle(2);
+        l.move_to_tail(&handle);
+    }
+
+    #[test]
+    fn test_long_list_drop() {
+        let mut l = DlList::new();
+        for i in 0..200_000 {
+            l.enqueue(i);
+        }
+        drop(l);
+    }
+}
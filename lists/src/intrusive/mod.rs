@@ -0,0 +1,408 @@
+//! This module implements an intrusive double linked list. The links live inside the user's struct in an
+//! `IntrusiveLink` field, which the list finds through the `Linked` adapter trait, so linking an item needs no
+//! allocation besides the item itself. The list owns its items as `Box<T>` and offers the same traversal and
+//! removal functions as `DlList`, plus an O(1) `remove` of an item given a reference to it.
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+///Type to simplify variable declaration
+type Link<T> = Option<NonNull<T>>;
+
+///Source of the ids which tie a linked item to its list
+static NEXT_LIST_ID: AtomicUsize = AtomicUsize::new(1);
+
+///The link field to embed into the items of an `IntrusiveList`
+pub struct IntrusiveLink<T> {
+    next: Cell<Link<T>>,
+    previous: Cell<Link<T>>,
+    //id of the list the item is linked in, 0 if it is not linked
+    owner: Cell<usize>,
+}
+
+///Adapter trait which gives the list access to the link field of an item
+/// # Safety
+/// `get_link` must always return the same `IntrusiveLink` field, which is owned by `self`.
+pub unsafe trait Linked: Sized {
+    ///Returns the reference to the link field of the item
+    fn get_link(&self) -> &IntrusiveLink<Self>;
+}
+
+///The intrusive list with pointers to its head, tail and current position
+pub struct IntrusiveList<T: Linked> {
+    head: Link<T>,
+    tail: Link<T>,
+    current_position: Link<T>,
+    length: i32,
+    id: usize,
+    _owns: PhantomData<Box<T>>,
+}
+
+///Iterator over the references of the items of an `IntrusiveList` from head to tail
+pub struct Iter<'a, T> {
+    next: Link<T>,
+    _list: PhantomData<&'a T>,
+}
+
+impl<T> Default for IntrusiveLink<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> IntrusiveLink<T> {
+    ///Returns a new unlinked link
+    pub fn new() -> IntrusiveLink<T> {
+        IntrusiveLink {
+            next: Cell::new(None),
+            previous: Cell::new(None),
+            owner: Cell::new(0),
+        }
+    }
+
+    ///Returns true if the item of the link is currently in a list
+    pub fn is_linked(&self) -> bool {
+        self.owner.get() != 0
+    }
+
+    fn reset(&self) {
+        self.next.set(None);
+        self.previous.set(None);
+        self.owner.set(0);
+    }
+}
+
+///Returns the link of the item behind `pointer`
+/// # Safety
+/// `pointer` must point to an item owned by a live list
+unsafe fn link<'a, T: Linked>(pointer: NonNull<T>) -> &'a IntrusiveLink<T> {
+    (*pointer.as_ptr()).get_link()
+}
+
+impl<T: Linked> Default for IntrusiveList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Linked> IntrusiveList<T> {
+    ///Returns a new empty list
+    pub fn new() -> IntrusiveList<T> {
+        IntrusiveList {
+            head: None,
+            tail: None,
+            current_position: None,
+            length: 0,
+            id: NEXT_LIST_ID.fetch_add(1, Ordering::Relaxed),
+            _owns: PhantomData,
+        }
+    }
+
+    ///Returns the length of the list
+    pub fn get_length(&self) -> i32 {
+        self.length
+    }
+
+    ///Returns true if `item` is linked in this list
+    /// # Attributes
+    /// * `item`- the item to check
+    pub fn contains(&self, item: &T) -> bool {
+        item.get_link().owner.get() == self.id
+    }
+
+    ///Attaches `item` to the end of the list and returns its new length
+    /// # Attributes
+    /// * `item`- the item to append
+    pub fn enqueue(&mut self, item: Box<T>) -> i32 {
+        assert!(
+            !item.get_link().is_linked(),
+            "the item is already linked in a list"
+        );
+        let pointer = NonNull::from(Box::leak(item));
+        // SAFETY: the item was just leaked from its box and is owned by this list from now on
+        let new_link = unsafe { link(pointer) };
+        new_link.owner.set(self.id);
+        new_link.previous.set(self.tail);
+        match self.tail {
+            // SAFETY: the tail is owned by this list
+            Some(tail) => unsafe { link(tail) }.next.set(Some(pointer)),
+            None => {
+                self.head = Some(pointer);
+                self.current_position = Some(pointer);
+            }
+        }
+        self.tail = Some(pointer);
+        self.length += 1;
+        self.length
+    }
+
+    ///Unlinks the item behind `pointer`, moves the current position away from it and returns its box
+    /// # Safety
+    /// `pointer` must point to an item linked in this list
+    unsafe fn unlink(&mut self, pointer: NonNull<T>) -> Box<T> {
+        let item_link = link(pointer);
+        let next = item_link.next.get();
+        let previous = item_link.previous.get();
+        match previous {
+            Some(previous) => link(previous).next.set(next),
+            None => self.head = next,
+        }
+        match next {
+            Some(next) => link(next).previous.set(previous),
+            None => self.tail = previous,
+        }
+        if self.current_position == Some(pointer) {
+            //like DlList the current position advances, or moves back if it was at the tail
+            self.current_position = next.or(previous);
+        }
+        item_link.reset();
+        self.length -= 1;
+        Box::from_raw(pointer.as_ptr())
+    }
+
+    ///Removes and returns the head item in the list
+    pub fn get_head(&mut self) -> Option<Box<T>> {
+        // SAFETY: the head is linked in this list
+        self.head.map(|head| unsafe { self.unlink(head) })
+    }
+
+    ///Removes and returns the tail item in the list
+    pub fn get_tail(&mut self) -> Option<Box<T>> {
+        // SAFETY: the tail is linked in this list
+        self.tail.map(|tail| unsafe { self.unlink(tail) })
+    }
+
+    ///Removes and returns the item at the current position and advances the current position in the list
+    pub fn get_current_position(&mut self) -> Option<Box<T>> {
+        // SAFETY: the current position is linked in this list
+        self.current_position
+            .map(|current| unsafe { self.unlink(current) })
+    }
+
+    ///Removes `item` from the list in O(1) and returns its box, or `None` if the item is not linked in this list
+    /// # Attributes
+    /// * `item`- the item to remove
+    pub fn remove(&mut self, item: &T) -> Option<Box<T>> {
+        if !self.contains(item) {
+            return None;
+        }
+        //take the owning pointer from the neighbour instead of deriving it from the shared reference
+        let pointer = match item.get_link().previous.get() {
+            // SAFETY: the owner id proves that the item and its neighbours are linked in this list
+            Some(previous) => unsafe { link(previous) }.next.get(),
+            None => self.head,
+        }?;
+        // SAFETY: the owner id proves that the item is linked in this list
+        Some(unsafe { self.unlink(pointer) })
+    }
+
+    ///Moves the current position one item forward in the list and returns true if the move was successful, false otherwise.
+    pub fn move_forward(&mut self) -> bool {
+        // SAFETY: the current position is linked in this list
+        match self
+            .current_position
+            .and_then(|current| unsafe { link(current) }.next.get())
+        {
+            Some(next) => {
+                self.current_position = Some(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///Moves the current position one item backwards in the list and returns true if the move was successful, false otherwise.
+    pub fn move_backward(&mut self) -> bool {
+        // SAFETY: the current position is linked in this list
+        match self
+            .current_position
+            .and_then(|current| unsafe { link(current) }.previous.get())
+        {
+            Some(previous) => {
+                self.current_position = Some(previous);
+                true
+            }
+            None => false,
+        }
+    }
+
+    ///Peeks the reference to the item at the current position without consuming it
+    pub fn peek_current_position(&self) -> Option<&T> {
+        // SAFETY: the item is owned by the list and lives as long as the borrow of the list
+        self.current_position
+            .map(|current| unsafe { &*current.as_ptr() })
+    }
+
+    ///Peeks the reference to the head item without consuming it
+    pub fn peek_head(&self) -> Option<&T> {
+        // SAFETY: the item is owned by the list and lives as long as the borrow of the list
+        self.head.map(|head| unsafe { &*head.as_ptr() })
+    }
+
+    ///Peeks the reference to the tail item without consuming it
+    pub fn peek_tail(&self) -> Option<&T> {
+        // SAFETY: the item is owned by the list and lives as long as the borrow of the list
+        self.tail.map(|tail| unsafe { &*tail.as_ptr() })
+    }
+
+    ///Returns an iterator over the references of the items from head to tail
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head,
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<'a, T: Linked> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|current| {
+            // SAFETY: the iterator borrows the list, so its items stay alive and linked
+            let item = unsafe { &*current.as_ptr() };
+            self.next = item.get_link().next.get();
+            item
+        })
+    }
+}
+
+impl<T: Linked> Drop for IntrusiveList<T> {
+    fn drop(&mut self) {
+        while self.get_head().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memcheck::{Ledger, Tracked};
+
+    struct Item {
+        value: Tracked,
+        link: IntrusiveLink<Item>,
+    }
+
+    unsafe impl Linked for Item {
+        fn get_link(&self) -> &IntrusiveLink<Item> {
+            &self.link
+        }
+    }
+
+    fn item(ledger: &Ledger, value: i32) -> Box<Item> {
+        Box::new(Item {
+            value: ledger.track(value),
+            link: IntrusiveLink::new(),
+        })
+    }
+
+    fn values(l: &IntrusiveList<Item>) -> Vec<i32> {
+        l.iter().map(|i| i.value.get_value()).collect()
+    }
+
+    #[test]
+    fn test_new_intrusive() {
+        let mut l: IntrusiveList<Item> = IntrusiveList::new();
+        assert_eq!(0, l.get_length());
+        assert!(l.get_head().is_none());
+        assert!(l.get_tail().is_none());
+        assert!(l.get_current_position().is_none());
+        assert!(!l.move_forward());
+    }
+
+    #[test]
+    fn test_head_and_tail() {
+        let ledger = Ledger::new();
+        let mut l = IntrusiveList::new();
+        for i in 1..6 {
+            assert_eq!(i, l.enqueue(item(&ledger, i)));
+        }
+        assert!(l.peek_head().unwrap().value == 1);
+        assert!(l.peek_tail().unwrap().value == 5);
+        let head = l.get_head().unwrap();
+        assert!(head.value == 1);
+        assert!(!head.link.is_linked());
+        assert!(l.get_tail().unwrap().value == 5);
+        assert_eq!(vec![2, 3, 4], values(&l));
+        drop(head);
+        drop(l);
+        ledger.assert_all_dropped();
+    }
+
+    #[test]
+    fn test_current_position() {
+        let ledger = Ledger::new();
+        let mut l = IntrusiveList::new();
+        for i in 1..5 {
+            l.enqueue(item(&ledger, i));
+        }
+        //current position should be the head
+        assert!(l.get_current_position().unwrap().value == 1);
+        while l.move_forward() {}
+        assert!(l.peek_current_position().unwrap().value == 4);
+        l.move_backward();
+        assert!(l.get_current_position().unwrap().value == 3);
+        assert!(l.peek_current_position().unwrap().value == 4);
+        //removing the tail at the current position moves it backwards
+        assert!(l.get_current_position().unwrap().value == 4);
+        assert!(l.peek_current_position().unwrap().value == 2);
+        assert!(!l.move_forward());
+        assert_eq!(1, l.get_length());
+        assert!(l.get_current_position().unwrap().value == 2);
+        assert!(l.peek_current_position().is_none());
+        ledger.assert_all_dropped();
+    }
+
+    #[test]
+    fn test_remove_by_reference() {
+        let ledger = Ledger::new();
+        let mut l = IntrusiveList::new();
+        let mut other = IntrusiveList::new();
+        for i in 1..5 {
+            l.enqueue(item(&ledger, i));
+        }
+        other.enqueue(item(&ledger, 10));
+        let third: *const Item = l.iter().nth(2).unwrap();
+        // an item of another list is rejected
+        assert!(l.remove(other.peek_head().unwrap()).is_none());
+        let removed = l.remove(unsafe { &*third }).unwrap();
+        assert!(removed.value == 3);
+        assert_eq!(vec![1, 2, 4], values(&l));
+        //the removed item can be linked again
+        other.enqueue(removed);
+        assert_eq!(vec![10, 3], values(&other));
+        assert_eq!(5, ledger.get_live_count());
+        drop(l);
+        drop(other);
+        ledger.assert_all_dropped();
+    }
+
+    #[test]
+    fn test_random_operations_memory() {
+        use crate::rng::{RandomSource, XorShift64};
+        let ledger = Ledger::new();
+        let mut rng = XorShift64::new(11);
+        let mut l = IntrusiveList::new();
+        let mut reference = std::collections::VecDeque::new();
+        for i in 0..2000 {
+            match rng.next_below(4) {
+                0 | 1 => {
+                    l.enqueue(item(&ledger, i));
+                    reference.push_back(i);
+                }
+                2 => assert_eq!(
+                    reference.pop_front(),
+                    l.get_head().map(|i| i.value.get_value())
+                ),
+                _ => assert_eq!(
+                    reference.pop_back(),
+                    l.get_tail().map(|i| i.value.get_value())
+                ),
+            }
+        }
+        assert_eq!(reference.len(), ledger.get_live_count());
+        drop(l);
+        ledger.assert_all_dropped();
+    }
+}
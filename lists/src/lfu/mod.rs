@@ -0,0 +1,343 @@
+//! This module implements a least frequently used (LFU) cache with O(1) operations. Every access frequency has a
+//! bucket, a `DlList` of the keys with that frequency in recency order. An access moves the key with its
+//! `NodeHandle` from its bucket to the bucket of the next frequency, an eviction takes the least recently used key
+//! of the bucket with the lowest frequency. The buckets are also linked in the order of their frequencies, so the
+//! lowest frequency is known in O(1) even after a removal emptied its bucket.
+use crate::cache::{Cache, CacheStats};
+use crate::dll::{DlList, NodeHandle};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+///A cached value together with its access frequency and the handle of its key in the frequency bucket
+struct Entry<K, V> {
+    value: V,
+    frequency: u64,
+    handle: NodeHandle<K>,
+}
+
+///The keys of one access frequency in recency order with the next lower and higher frequencies which have a bucket
+struct Bucket<K> {
+    keys: DlList<K>,
+    lower: Option<u64>,
+    higher: Option<u64>,
+}
+
+///The LFU cache with its index and its frequency buckets
+pub struct LfuCache<K, V> {
+    map: HashMap<K, Entry<K, V>>,
+    buckets: HashMap<u64, Bucket<K>>,
+    //the lowest frequency which has a bucket
+    min_frequency: Option<u64>,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+impl<K: Hash + Eq + Clone, V> LfuCache<K, V> {
+    ///Returns a new empty cache which holds up to `capacity` entries
+    /// # Attributes
+    /// * `capacity`- the maximum number of entries
+    pub fn new(capacity: usize) -> LfuCache<K, V> {
+        LfuCache {
+            map: HashMap::with_capacity(capacity),
+            buckets: HashMap::new(),
+            min_frequency: None,
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    ///Returns the number of cached entries
+    pub fn get_length(&self) -> usize {
+        self.map.len()
+    }
+
+    ///Returns true if the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    ///Returns the maximum number of entries
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///Returns true if `key` is cached, without counting an access
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    ///Returns the access frequency of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_frequency(&self, key: &K) -> Option<u64> {
+        self.map.get(key).map(|entry| entry.frequency)
+    }
+
+    ///Removes the key of `handle` from the bucket of `frequency` and drops the bucket if it gets empty
+    fn detach(&mut self, frequency: u64, handle: &NodeHandle<K>) {
+        let bucket = self
+            .buckets
+            .get_mut(&frequency)
+            .expect("frequency bucket is missing");
+        bucket.keys.remove(handle);
+        if bucket.keys.get_length() == 0 {
+            self.drop_bucket(frequency);
+        }
+    }
+
+    ///Removes the empty bucket of `frequency` and links its lower and higher buckets with each other
+    fn drop_bucket(&mut self, frequency: u64) {
+        let bucket = self.buckets.remove(&frequency).unwrap();
+        match bucket.lower {
+            Some(lower) => self.buckets.get_mut(&lower).unwrap().higher = bucket.higher,
+            None => self.min_frequency = bucket.higher,
+        }
+        if let Some(higher) = bucket.higher {
+            self.buckets.get_mut(&higher).unwrap().lower = bucket.lower;
+        }
+    }
+
+    ///Attaches `key` to the bucket of `frequency` and returns its handle. A missing bucket is linked in right
+    ///after the bucket of `lower`, the next lower frequency with a bucket, or as the lowest bucket if it is None.
+    fn attach(&mut self, frequency: u64, lower: Option<u64>, key: K) -> NodeHandle<K> {
+        if !self.buckets.contains_key(&frequency) {
+            let higher = match lower {
+                Some(lower) => self.buckets[&lower].higher,
+                None => self.min_frequency,
+            };
+            match lower {
+                Some(lower) => self.buckets.get_mut(&lower).unwrap().higher = Some(frequency),
+                None => self.min_frequency = Some(frequency),
+            }
+            if let Some(higher) = higher {
+                self.buckets.get_mut(&higher).unwrap().lower = Some(frequency);
+            }
+            let bucket = Bucket {
+                keys: DlList::new(),
+                lower,
+                higher,
+            };
+            self.buckets.insert(frequency, bucket);
+        }
+        self.buckets
+            .get_mut(&frequency)
+            .unwrap()
+            .keys
+            .enqueue_with_handle(key)
+    }
+
+    ///Counts an access of the cached `key` by moving it to the bucket of the next frequency
+    fn touch(&mut self, key: &K) {
+        let (frequency, handle) = {
+            let entry = &self.map[key];
+            (entry.frequency, entry.handle.clone())
+        };
+        //attach first, so the bucket of the next frequency can be linked in after the current one
+        let next_handle = self.attach(frequency + 1, Some(frequency), key.clone());
+        self.detach(frequency, &handle);
+        let entry = self.map.get_mut(key).unwrap();
+        entry.frequency = frequency + 1;
+        entry.handle = next_handle;
+    }
+
+    ///Returns the reference to the value of `key` and counts an access of the key
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if !self.map.contains_key(key) {
+            self.stats.misses += 1;
+            return None;
+        }
+        self.stats.hits += 1;
+        self.touch(key);
+        self.map.get(key).map(|entry| &entry.value)
+    }
+
+    ///Returns the reference to the value of `key` without counting an access
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map.get(key).map(|entry| &entry.value)
+    }
+
+    ///Caches `value` under `key`. Returns the entry which was pushed out: the old entry of `key` if the key was
+    ///already cached, which also counts as an access, otherwise the least recently used entry of the lowest
+    ///frequency if the cache was full.
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if self.map.contains_key(&key) {
+            self.touch(&key);
+            let entry = self.map.get_mut(&key).unwrap();
+            let old = std::mem::replace(&mut entry.value, value);
+            return Some((key, old));
+        }
+        if self.capacity == 0 {
+            return Some((key, value));
+        }
+        let evicted = if self.map.len() >= self.capacity {
+            self.stats.evictions += 1;
+            self.pop_lfu()
+        } else {
+            None
+        };
+        let handle = self.attach(1, None, key.clone());
+        self.map.insert(
+            key,
+            Entry {
+                value,
+                frequency: 1,
+                handle,
+            },
+        );
+        evicted
+    }
+
+    ///Removes and returns the least recently used entry of the lowest frequency
+    pub fn pop_lfu(&mut self) -> Option<(K, V)> {
+        let frequency = self.min_frequency?;
+        let bucket = self.buckets.get_mut(&frequency).unwrap();
+        let key = bucket.keys.get_head().unwrap();
+        if bucket.keys.get_length() == 0 {
+            self.drop_bucket(frequency);
+        }
+        let entry = self
+            .map
+            .remove(&key)
+            .expect("frequency buckets and index are out of sync");
+        Some((key, entry.value))
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.map.remove(key)?;
+        self.detach(entry.frequency, &entry.handle);
+        Some(entry.value)
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LfuCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LfuCache::get(self, key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        LfuCache::put(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        LfuCache::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        LfuCache::contains_key(self, key)
+    }
+
+    fn get_length(&self) -> usize {
+        LfuCache::get_length(self)
+    }
+
+    fn get_capacity(&self) -> usize {
+        LfuCache::get_capacity(self)
+    }
+
+    fn get_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new_lfu() {
+        let mut c: LfuCache<i32, i32> = LfuCache::new(2);
+        assert_eq!(0, c.get_length());
+        assert!(c.get(&1).is_none());
+        assert!(c.pop_lfu().is_none());
+    }
+
+    #[test]
+    fn test_evicts_lowest_frequency() {
+        let mut c = LfuCache::new(2);
+        c.put(1, "one");
+        c.put(2, "two");
+        c.get(&1);
+        c.get(&1);
+        assert_eq!(Some(3), c.get_frequency(&1));
+        assert_eq!(Some(1), c.get_frequency(&2));
+        assert_eq!(Some((2, "two")), c.put(3, "three"));
+        //3 was used least recently among the keys with the lowest frequency
+        c.get(&3);
+        assert_eq!(Some((3, "three")), c.put(4, "four"));
+        assert!(c.contains_key(&1));
+        assert_eq!(Some(&"one"), c.peek(&1));
+    }
+
+    #[test]
+    fn test_ties_evict_least_recently_used() {
+        let mut c = LfuCache::new(3);
+        for i in 1..4 {
+            c.put(i, i);
+        }
+        c.get(&1);
+        c.get(&2);
+        c.get(&3);
+        assert_eq!(Some((1, 1)), c.put(4, 4));
+    }
+
+    #[test]
+    fn test_remove_keeps_buckets_consistent() {
+        let mut c = LfuCache::new(3);
+        c.put(1, 1);
+        c.put(2, 2);
+        c.get(&2);
+        c.put(3, 3);
+        c.get(&3);
+        assert_eq!(Some(1), c.remove(&1));
+        assert!(c.remove(&1).is_none());
+        c.put(4, 4);
+        c.get(&4);
+        c.get(&4);
+        //2 and 3 have frequency 2 and 4 has frequency 3
+        assert_eq!(Some((2, 2)), c.pop_lfu());
+        assert_eq!(Some((3, 3)), c.pop_lfu());
+        assert_eq!(Some((4, 4)), c.pop_lfu());
+        assert!(c.is_empty());
+    }
+
+    #[test]
+    fn test_remove_of_lowest_bucket() {
+        let mut c = LfuCache::new(4);
+        for i in 1..5 {
+            c.put(i, i);
+        }
+        for _ in 0..5 {
+            c.get(&4);
+        }
+        for _ in 0..3 {
+            c.get(&3);
+        }
+        c.get(&2);
+        //the frequencies are 1, 2, 4 and 6, removing 1 leaves 2 as the lowest
+        assert_eq!(Some(1), c.remove(&1));
+        assert_eq!(Some(3), c.remove(&3));
+        assert_eq!(Some((2, 2)), c.pop_lfu());
+        c.put(5, 5);
+        assert_eq!(Some((5, 5)), c.pop_lfu());
+        assert_eq!(Some((4, 4)), c.pop_lfu());
+        assert!(c.pop_lfu().is_none());
+        assert!(c.buckets.is_empty());
+        assert!(c.min_frequency.is_none());
+    }
+}
@@ -1,3 +1,18 @@
+pub mod arc;
+pub mod cache;
+pub mod delay_queue;
 pub mod dll;
+pub mod intrusive;
+pub mod lfu;
+pub mod lru;
+#[cfg(test)]
+mod memcheck;
+pub mod plist;
+pub mod rng;
+pub mod skiplist;
+pub mod slist;
+pub mod timing_wheel;
+pub mod unrolled;
+pub mod xor;
 
 pub mod lists {}
@@ -0,0 +1,320 @@
+//! This module implements a least recently used (LRU) cache. A hash map indexes the cached values by key and a
+//! `DlList` of keys keeps the recency order: the head is the least recently used key and the tail the most recently
+//! used one. The map keeps the `NodeHandle` of every key, so promoting and evicting a key is O(1).
+use crate::cache::{Cache, CacheStats};
+use crate::dll::{self, DlList, NodeHandle};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::iter::Rev;
+
+///A cached value together with the handle of its key in the recency list
+struct Entry<K, V> {
+    value: V,
+    handle: NodeHandle<K>,
+}
+
+///The LRU cache with its index and its recency list
+pub struct LruCache<K, V> {
+    map: HashMap<K, Entry<K, V>>,
+    order: DlList<K>,
+    capacity: usize,
+    stats: CacheStats,
+}
+
+///Iterator over the entries of a `LruCache` from the most recently to the least recently used
+pub struct Iter<'a, K, V> {
+    keys: Rev<dll::Iter<'a, K>>,
+    map: &'a HashMap<K, Entry<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V> LruCache<K, V> {
+    ///Returns a new empty cache which holds up to `capacity` entries
+    /// # Attributes
+    /// * `capacity`- the maximum number of entries
+    pub fn new(capacity: usize) -> LruCache<K, V> {
+        LruCache {
+            map: HashMap::with_capacity(capacity),
+            order: DlList::new(),
+            capacity,
+            stats: CacheStats::default(),
+        }
+    }
+
+    ///Returns the number of cached entries
+    pub fn get_length(&self) -> usize {
+        self.map.len()
+    }
+
+    ///Returns true if the cache has no entries
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    ///Returns the maximum number of entries
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///Returns true if `key` is cached, without promoting it
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.map.contains_key(key)
+    }
+
+    ///Returns the reference to the value of `key` and promotes the key to the most recently used one
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        match self.map.get(key) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                self.order.move_to_tail(&entry.handle);
+                Some(&entry.value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    ///Returns the mutable reference to the value of `key` and promotes the key to the most recently used one
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        match self.map.get_mut(key) {
+            Some(entry) => {
+                self.stats.hits += 1;
+                self.order.move_to_tail(&entry.handle);
+                Some(&mut entry.value)
+            }
+            None => {
+                self.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    ///Returns the reference to the value of `key` without changing the recency order
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn peek(&self, key: &K) -> Option<&V> {
+        self.map.get(key).map(|entry| &entry.value)
+    }
+
+    ///Returns the least recently used entry without changing the recency order
+    pub fn peek_lru(&self) -> Option<(K, &V)> {
+        let key = self.order.peek_head()?.clone();
+        let value = &self.map[&key].value;
+        Some((key, value))
+    }
+
+    ///Caches `value` under `key` as the most recently used entry. Returns the entry which was pushed out:
+    ///the old entry of `key` if the key was already cached, otherwise the least recently used entry if the cache was full.
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        if let Some(entry) = self.map.get_mut(&key) {
+            self.order.move_to_tail(&entry.handle);
+            let old = std::mem::replace(&mut entry.value, value);
+            return Some((key, old));
+        }
+        if self.capacity == 0 {
+            return Some((key, value));
+        }
+        let evicted = if self.map.len() >= self.capacity {
+            self.stats.evictions += 1;
+            self.pop_lru()
+        } else {
+            None
+        };
+        let handle = self.order.enqueue_with_handle(key.clone());
+        self.map.insert(key, Entry { value, handle });
+        evicted
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let entry = self.map.remove(key)?;
+        self.order.remove(&entry.handle);
+        Some(entry.value)
+    }
+
+    ///Removes and returns the least recently used entry
+    pub fn pop_lru(&mut self) -> Option<(K, V)> {
+        let key = self.order.get_head()?;
+        let entry = self
+            .map
+            .remove(&key)
+            .expect("recency list and index are out of sync");
+        Some((key, entry.value))
+    }
+
+    ///Changes the capacity to `capacity` and returns the entries evicted to fit into it, least recently used first
+    /// # Attributes
+    /// * `capacity`- the new maximum number of entries
+    pub fn resize(&mut self, capacity: usize) -> Vec<(K, V)> {
+        self.capacity = capacity;
+        let mut evicted = Vec::new();
+        while self.map.len() > capacity {
+            self.stats.evictions += 1;
+            evicted.extend(self.pop_lru());
+        }
+        evicted
+    }
+
+    ///Removes all entries
+    pub fn clear(&mut self) {
+        self.map.clear();
+        self.order = DlList::new();
+    }
+
+    ///Returns an iterator over the entries from the most recently to the least recently used
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            keys: self.order.iter().rev(),
+            map: &self.map,
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Cache<K, V> for LruCache<K, V> {
+    fn get(&mut self, key: &K) -> Option<&V> {
+        LruCache::get(self, key)
+    }
+
+    fn put(&mut self, key: K, value: V) -> Option<(K, V)> {
+        LruCache::put(self, key, value)
+    }
+
+    fn remove(&mut self, key: &K) -> Option<V> {
+        LruCache::remove(self, key)
+    }
+
+    fn contains_key(&self, key: &K) -> bool {
+        LruCache::contains_key(self, key)
+    }
+
+    fn get_length(&self) -> usize {
+        LruCache::get_length(self)
+    }
+
+    fn get_capacity(&self) -> usize {
+        LruCache::get_capacity(self)
+    }
+
+    fn get_stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    fn reset_stats(&mut self) {
+        self.stats = CacheStats::default();
+    }
+}
+
+impl<'a, K: Hash + Eq + Clone, V> Iterator for Iter<'a, K, V> {
+    type Item = (K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let key = self.keys.next()?;
+        let value = &self.map[&key].value;
+        Some((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(c: &LruCache<i32, String>) -> Vec<i32> {
+        c.iter().map(|(k, _)| k).collect()
+    }
+
+    #[test]
+    fn test_new_lru() {
+        let mut c: LruCache<i32, String> = LruCache::new(2);
+        assert_eq!(0, c.get_length());
+        assert_eq!(2, c.get_capacity());
+        assert!(c.get(&1).is_none());
+        assert!(c.pop_lru().is_none());
+    }
+
+    #[test]
+    fn test_put_evicts_lru() {
+        let mut c = LruCache::new(2);
+        assert!(c.put(1, String::from("one")).is_none());
+        assert!(c.put(2, String::from("two")).is_none());
+        assert_eq!(
+            Some((1, String::from("one"))),
+            c.put(3, String::from("three"))
+        );
+        assert!(!c.contains_key(&1));
+        assert_eq!(vec![3, 2], keys(&c));
+        //updating a key returns its old entry and promotes it
+        assert_eq!(
+            Some((2, String::from("two"))),
+            c.put(2, String::from("zwei"))
+        );
+        assert_eq!(vec![2, 3], keys(&c));
+        assert_eq!(2, c.get_length());
+    }
+
+    #[test]
+    fn test_get_promotes_peek_does_not() {
+        let mut c = LruCache::new(3);
+        for i in 1..4 {
+            c.put(i, i.to_string());
+        }
+        assert_eq!(Some(&String::from("1")), c.peek(&1));
+        assert_eq!(vec![3, 2, 1], keys(&c));
+        assert_eq!(Some(&String::from("1")), c.get(&1));
+        assert_eq!(vec![1, 3, 2], keys(&c));
+        c.get_mut(&2).unwrap().push('!');
+        assert_eq!(Some((3, &String::from("3"))), c.peek_lru());
+        assert_eq!(Some((3, String::from("3"))), c.put(4, String::from("4")));
+        assert_eq!(Some(&String::from("2!")), c.peek(&2));
+    }
+
+    #[test]
+    fn test_get_mut_records_stats() {
+        let mut c = LruCache::new(2);
+        c.put(1, 1);
+        *c.get_mut(&1).unwrap() += 1;
+        assert!(c.get_mut(&2).is_none());
+        assert!(c.get(&1).is_some());
+        let stats = Cache::get_stats(&c);
+        assert_eq!((2, 1), (stats.hits, stats.misses));
+    }
+
+    #[test]
+    fn test_remove_and_resize() {
+        let mut c = LruCache::new(5);
+        for i in 1..6 {
+            c.put(i, i.to_string());
+        }
+        assert_eq!(Some(String::from("3")), c.remove(&3));
+        assert!(c.remove(&3).is_none());
+        assert_eq!(vec![5, 4, 2, 1], keys(&c));
+        let evicted = c.resize(2);
+        assert_eq!(
+            vec![(1, String::from("1")), (2, String::from("2"))],
+            evicted
+        );
+        assert_eq!(vec![5, 4], keys(&c));
+        assert_eq!(2, c.get_capacity());
+        c.clear();
+        assert!(c.is_empty());
+        assert!(c.put(1, String::new()).is_none());
+    }
+
+    #[test]
+    fn test_zero_capacity() {
+        let mut c = LruCache::new(0);
+        assert_eq!(Some((1, 1)), c.put(1, 1));
+        assert!(c.is_empty());
+    }
+}
@@ -0,0 +1,79 @@
+//! This module implements a small memory checker for the tests of the pointer based lists.
+//! Every `Tracked` value registers itself in a `Ledger` when it is created and checks out when it is dropped,
+//! so a test can detect leaked values and catch double drops the moment they happen.
+use std::cell::RefCell;
+use std::rc::Rc;
+
+///The register of all values created by a ledger. The flag of a value is true as long as it is alive.
+pub struct Ledger {
+    alive: Rc<RefCell<Vec<bool>>>,
+}
+
+///A value registered in a `Ledger`
+#[derive(Debug)]
+pub struct Tracked {
+    id: usize,
+    value: i32,
+    alive: Rc<RefCell<Vec<bool>>>,
+}
+
+impl Ledger {
+    ///Returns a new empty ledger
+    pub fn new() -> Ledger {
+        Ledger {
+            alive: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    ///Returns a new value registered in this ledger
+    /// # Attributes
+    /// * `value`- the payload of the tracked value
+    pub fn track(&self, value: i32) -> Tracked {
+        let mut alive = self.alive.borrow_mut();
+        alive.push(true);
+        Tracked {
+            id: alive.len() - 1,
+            value,
+            alive: Rc::clone(&self.alive),
+        }
+    }
+
+    ///Returns the number of registered values which are not dropped yet
+    pub fn get_live_count(&self) -> usize {
+        self.alive.borrow().iter().filter(|alive| **alive).count()
+    }
+
+    ///Panics if a registered value is not dropped yet
+    pub fn assert_all_dropped(&self) {
+        let leaked: Vec<usize> = self
+            .alive
+            .borrow()
+            .iter()
+            .enumerate()
+            .filter(|(_, alive)| **alive)
+            .map(|(id, _)| id)
+            .collect();
+        assert!(leaked.is_empty(), "leaked tracked values: {:?}", leaked);
+    }
+}
+
+impl Tracked {
+    ///Returns the payload of the value
+    pub fn get_value(&self) -> i32 {
+        self.value
+    }
+}
+
+impl PartialEq<i32> for Tracked {
+    fn eq(&self, other: &i32) -> bool {
+        self.value == *other
+    }
+}
+
+impl Drop for Tracked {
+    fn drop(&mut self) {
+        let mut alive = self.alive.borrow_mut();
+        assert!(alive[self.id], "double drop of tracked value {}", self.id);
+        alive[self.id] = false;
+    }
+}
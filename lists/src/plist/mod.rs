@@ -0,0 +1,312 @@
+//! This module implements a persistent (immutable) singly linked list with structural sharing.
+//! Every operation returns a new version of the list and leaves the old one untouched. Versions share
+//! their common tail through `Rc` pointers, so `prepend` and `tail` are O(1) and cloning a list is O(1).
+use crate::dll::DlList;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+///Type to simplify variable declaration
+type Link<T> = Option<Rc<Node<T>>>;
+
+///A single immutable node in the list with a value and a shared pointer to the next node.
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+///The persistent list with a shared pointer to its head
+pub struct PList<T> {
+    head: Link<T>,
+    length: i32,
+}
+
+///Iterator over the references of the values of a `PList` from head to end
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+impl<T> Default for PList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> PList<T> {
+    ///Returns a new empty persistent list
+    pub fn new() -> PList<T> {
+        PList {
+            head: None,
+            length: 0,
+        }
+    }
+
+    ///Returns the length of the list
+    pub fn get_length(&self) -> i32 {
+        self.length
+    }
+
+    ///Returns true if the list has no items
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    ///Returns a new list with `value` in front of the items of this list. The items of this list are
+    ///shared, not copied.
+    /// # Attributes
+    /// * `value`- the value to put in front of the list
+    pub fn prepend(&self, value: T) -> PList<T> {
+        PList {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.head.clone(),
+            })),
+            length: self.length + 1,
+        }
+    }
+
+    ///Returns a new list containing all items but the first one. The returned list shares its items with this list.
+    ///The tail of an empty list is the empty list.
+    pub fn tail(&self) -> PList<T> {
+        match self.head.as_ref() {
+            Some(head) => PList {
+                head: head.next.clone(),
+                length: self.length - 1,
+            },
+            None => PList::new(),
+        }
+    }
+
+    ///Peeks the reference to the value of the head without consuming it
+    pub fn peek_head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    ///Returns true if both lists are the same version, i.e. they share the same head node
+    /// # Attributes
+    /// * `other`- the list to compare with
+    pub fn ptr_eq(&self, other: &PList<T>) -> bool {
+        match (self.head.as_ref(), other.head.as_ref()) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    ///Returns an iterator over the references of the values from head to end
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+}
+
+impl<T: Clone> PList<T> {
+    ///Returns a new list with `value` attached to the end of the items of this list.
+    ///As the nodes are immutable all items are copied, which makes this operation O(n).
+    /// # Attributes
+    /// * `value`- the value to append
+    pub fn append(&self, value: T) -> PList<T> {
+        let mut values: Vec<T> = self.iter().cloned().collect();
+        values.push(value);
+        values.into_iter().collect()
+    }
+
+    ///Returns a new list with the items of this list followed by the items of `other`.
+    ///Only the items of this list are copied, the items of `other` are shared.
+    /// # Attributes
+    /// * `other`- the list to attach at the end
+    pub fn concat(&self, other: &PList<T>) -> PList<T> {
+        let mut result = other.clone();
+        let values: Vec<&T> = self.iter().collect();
+        for value in values.into_iter().rev() {
+            result = result.prepend(value.clone());
+        }
+        result
+    }
+
+    ///Returns a new list with the items in reversed order
+    pub fn reverse(&self) -> PList<T> {
+        let mut result = PList::new();
+        for value in self.iter() {
+            result = result.prepend(value.clone());
+        }
+        result
+    }
+
+    ///Returns a new `DlList` containing copies of the items of this list in the same order
+    pub fn to_dll(&self) -> DlList<T> {
+        let mut list = DlList::new();
+        for value in self.iter() {
+            list.enqueue(value.clone());
+        }
+        list
+    }
+}
+
+impl<T> Clone for PList<T> {
+    ///Returns a new version sharing all items with this list in O(1)
+    fn clone(&self) -> Self {
+        PList {
+            head: self.head.clone(),
+            length: self.length,
+        }
+    }
+}
+
+impl<T> Drop for PList<T> {
+    ///Drops the nodes iteratively as long as they are not shared with another version,
+    ///so long lists do not overflow the stack with recursive drops.
+    fn drop(&mut self) {
+        let mut next = self.head.take();
+        while let Some(node) = next {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => next = node.next.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next.map(|node| {
+            self.next = node.next.as_deref();
+            &node.value
+        })
+    }
+}
+
+impl<'a, T> IntoIterator for &'a PList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for PList<T> {
+    ///Builds a list with the items in the order of the iterator
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut list = PList::new();
+        for value in values.into_iter().rev() {
+            list = list.prepend(value);
+        }
+        list
+    }
+}
+
+impl<T> From<DlList<T>> for PList<T> {
+    ///Consumes the `DlList` from its tail and builds a list with the items in the same order
+    fn from(mut dll: DlList<T>) -> Self {
+        let mut list = PList::new();
+        while let Some(value) = dll.get_tail() {
+            list = list.prepend(value);
+        }
+        list
+    }
+}
+
+impl<T: PartialEq> PartialEq for PList<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.length == other.length && self.iter().eq(other.iter())
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for PList<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_new_plist() {
+        let l: PList<i32> = PList::new();
+        assert_eq!(0, l.get_length());
+        assert!(l.is_empty());
+        assert!(l.peek_head().is_none());
+        assert!(l.tail().is_empty());
+    }
+
+    #[test]
+    fn test_prepend_keeps_old_version() {
+        let l1 = PList::new().prepend(1);
+        let l2 = l1.prepend(2);
+        let l3 = l1.prepend(3);
+        assert_eq!(vec![1], l1.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(vec![2, 1], l2.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(vec![3, 1], l3.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(2, l2.get_length());
+        //both versions share the tail
+        assert!(l2.tail().ptr_eq(&l1));
+        assert!(l3.tail().ptr_eq(&l1));
+    }
+
+    #[test]
+    fn test_tail() {
+        let l: PList<i32> = (1..4).collect();
+        let t = l.tail();
+        assert_eq!(Some(&1), l.peek_head());
+        assert_eq!(Some(&2), t.peek_head());
+        assert_eq!(2, t.get_length());
+        assert_eq!(3, l.get_length());
+    }
+
+    #[test]
+    fn test_append_and_concat() {
+        let l: PList<i32> = (1..4).collect();
+        let a = l.append(4);
+        assert_eq!(vec![1, 2, 3], l.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(vec![1, 2, 3, 4], a.iter().cloned().collect::<Vec<_>>());
+        let other: PList<i32> = (7..9).collect();
+        let c = l.concat(&other);
+        assert_eq!(vec![1, 2, 3, 7, 8], c.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(5, c.get_length());
+        //the items of other are shared
+        let mut shared = c.clone();
+        for _ in 0..3 {
+            shared = shared.tail();
+        }
+        assert!(shared.ptr_eq(&other));
+    }
+
+    #[test]
+    fn test_reverse() {
+        let l: PList<i32> = (1..5).collect();
+        assert_eq!(l.reverse(), (1..5).rev().collect());
+        assert_eq!(format!("{:?}", l), "[1, 2, 3, 4]");
+    }
+
+    #[test]
+    fn test_dll_conversion() {
+        let mut dll = DlList::new();
+        for i in 1..5 {
+            dll.enqueue(i);
+        }
+        let l = PList::from(dll);
+        assert_eq!(vec![1, 2, 3, 4], l.iter().cloned().collect::<Vec<_>>());
+        let mut back = l.to_dll();
+        assert_eq!(4, back.get_length());
+        assert_eq!(1, back.get_head().unwrap());
+        assert_eq!(4, back.get_tail().unwrap());
+        //the persistent list is untouched
+        assert_eq!(4, l.get_length());
+    }
+
+    #[test]
+    fn test_long_list_drop() {
+        let mut l = PList::new();
+        for i in 0..200_000 {
+            l = l.prepend(i);
+        }
+        let shared = l.tail();
+        drop(l);
+        assert_eq!(199_999, shared.get_length());
+    }
+}
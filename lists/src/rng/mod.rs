@@ -0,0 +1,120 @@
+//! This module implements a small pseudo random number source for the randomized structures of the crate.
+//! The structures take the source as a type parameter, so tests can plug in a seeded source and get
+//! reproducible shapes.
+use std::time::{SystemTime, UNIX_EPOCH};
+
+///A source of pseudo random numbers
+pub trait RandomSource {
+    ///Returns the next pseudo random 64 bit number
+    fn next_u64(&mut self) -> u64;
+
+    ///Returns a pseudo random number in the range `0..bound`. Returns 0 if `bound` is 0.
+    /// # Attributes
+    /// * `bound`- the exclusive upper bound
+    fn next_below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            0
+        } else {
+            self.next_u64() % bound
+        }
+    }
+
+    ///Returns true with a probability of 1/2
+    fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+}
+
+///A random source which can be created from a seed, so an independent source can be derived from another one
+pub trait SeedableSource: RandomSource + Sized {
+    ///Returns a new source for the seed `seed`
+    /// # Attributes
+    /// * `seed`- the seed of the source
+    fn from_seed(seed: u64) -> Self;
+
+    ///Returns a new source seeded with a number drawn from this one
+    fn reseed(&mut self) -> Self {
+        Self::from_seed(self.next_u64())
+    }
+}
+
+///A xorshift64* generator. It is fast and deterministic for a given seed but not cryptographically secure.
+#[derive(Clone, Debug)]
+pub struct XorShift64 {
+    state: u64,
+}
+
+impl XorShift64 {
+    ///Returns a new generator for the seed `seed`. A seed of 0 is replaced by a fixed non zero value
+    ///as the generator would only return zeros otherwise.
+    /// # Attributes
+    /// * `seed`- the seed of the generator
+    pub fn new(seed: u64) -> XorShift64 {
+        XorShift64 {
+            state: if seed == 0 {
+                0x9E37_79B9_7F4A_7C15
+            } else {
+                seed
+            },
+        }
+    }
+
+    ///Returns a new generator seeded with the current system time
+    pub fn from_time() -> XorShift64 {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0);
+        XorShift64::new(nanos)
+    }
+}
+
+impl RandomSource for XorShift64 {
+    fn next_u64(&mut self) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+}
+
+impl SeedableSource for XorShift64 {
+    fn from_seed(seed: u64) -> XorShift64 {
+        XorShift64::new(seed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = XorShift64::new(42);
+        let mut b = XorShift64::new(42);
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn test_reseed() {
+        let mut a = XorShift64::new(42);
+        let mut b = a.reseed();
+        assert!((0..100).any(|_| a.next_u64() != b.next_u64()));
+    }
+
+    #[test]
+    fn test_zero_seed() {
+        let mut r = XorShift64::new(0);
+        assert_ne!(0, r.next_u64());
+    }
+
+    #[test]
+    fn test_next_below() {
+        let mut r = XorShift64::new(7);
+        assert_eq!(0, r.next_below(0));
+        for _ in 0..1000 {
+            assert!(r.next_below(10) < 10);
+        }
+    }
+}
@@ -0,0 +1,519 @@
+//! This module implements an ordered key/value map as a skip list with probabilistic O(log n) search.
+//! Every link of a node also stores its span, the number of items it skips, so the position (rank) of a key
+//! can be computed during the search. The random levels of the nodes are drawn from a pluggable
+//! `RandomSource`, which allows reproducible lists in tests.
+//! Lookups and iterators hand out references into the nodes, which borrow the list so it can't change meanwhile.
+use crate::rng::{RandomSource, XorShift64};
+use std::cell::RefCell;
+use std::ops::{Bound, RangeBounds};
+use std::rc::Rc;
+
+///The maximum number of levels of a skip list
+const MAX_LEVEL: usize = 32;
+
+///Types to simplify variable declaration
+type NodeRef<K, V> = Rc<RefCell<Node<K, V>>>;
+type Link<K, V> = Option<NodeRef<K, V>>;
+
+///A single node in the skip list with a key/value entry and a pointer and span for each level of the node.
+///The head of the list is a node without entry.
+struct Node<K, V> {
+    entry: Option<(K, V)>,
+    next: Vec<Link<K, V>>,
+    span: Vec<usize>,
+}
+
+///The skip list with a pointer to its head node
+pub struct SkipList<K, V, R = XorShift64> {
+    head: NodeRef<K, V>,
+    level: usize,
+    length: usize,
+    rng: R,
+}
+
+///Iterator over the entries of a `SkipList` in ascending key order
+pub struct Range<'a, K, V> {
+    next: Option<&'a Node<K, V>>,
+    //the first node after the range
+    end: Option<&'a Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    ///Returns a new Node with the entry `entry` and `level` levels
+    /// # Attributes
+    /// * `entry`- the entry of the node, `None` for the head
+    /// * `level`- the number of levels of the node
+    fn new(entry: Option<(K, V)>, level: usize) -> Node<K, V> {
+        Node {
+            entry,
+            next: vec![None; level],
+            span: vec![0; level],
+        }
+    }
+
+    ///Returns the reference to the key of the node. Must not be called on the head.
+    fn key(&self) -> &K {
+        &self.entry.as_ref().expect("the head has no key").0
+    }
+
+    ///Returns the references to the key and the value of the node. Must not be called on the head.
+    fn entry(&self) -> (&K, &V) {
+        let (key, value) = self.entry.as_ref().expect("the head has no entry");
+        (key, value)
+    }
+
+    ///Returns the next node on the level `level`, borrowed for as long as this node is
+    fn next_node(&self, level: usize) -> Option<&Node<K, V>> {
+        self.next[level].as_deref().map(borrow_node)
+    }
+}
+
+///Returns the node inside `cell` without a borrow guard, so references into it can live as long as the list is borrowed
+fn borrow_node<K, V>(cell: &RefCell<Node<K, V>>) -> &Node<K, V> {
+    // SAFETY: nodes are only borrowed mutably by the methods taking `&mut SkipList` and by its drop, which can't run
+    // while a shared reference to the list, and through it to the node, is alive
+    unsafe { cell.try_borrow_unguarded() }.expect("the node is borrowed mutably")
+}
+
+impl<K: Ord, V> Default for SkipList<K, V, XorShift64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> SkipList<K, V, XorShift64> {
+    ///Returns a new empty skip list whose levels are drawn from a time seeded `XorShift64`
+    pub fn new() -> SkipList<K, V, XorShift64> {
+        SkipList::with_rng(XorShift64::from_time())
+    }
+}
+
+impl<K: Ord, V, R: RandomSource> SkipList<K, V, R> {
+    ///Returns a new empty skip list whose levels are drawn from `rng`
+    /// # Attributes
+    /// * `rng`- the random source used to draw the level of new nodes
+    pub fn with_rng(rng: R) -> SkipList<K, V, R> {
+        SkipList {
+            head: Rc::new(RefCell::new(Node::new(None, MAX_LEVEL))),
+            level: 1,
+            length: 0,
+            rng,
+        }
+    }
+
+    ///Returns the number of entries in the list
+    pub fn get_length(&self) -> i32 {
+        self.length as i32
+    }
+
+    ///Returns true if the list has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of levels currently in use
+    pub fn get_level(&self) -> usize {
+        self.level
+    }
+
+    ///Draws the level of a new node. Every additional level has a probability of 1/2.
+    fn random_level(&mut self) -> usize {
+        let mut level = 1;
+        while level < MAX_LEVEL && self.rng.next_bool() {
+            level += 1;
+        }
+        level
+    }
+
+    ///Returns for every level the last node with a key smaller than `key` together with its rank
+    /// # Attributes
+    /// * `key`- the key to search for
+    fn find_predecessors(&self, key: &K) -> (Vec<NodeRef<K, V>>, Vec<usize>) {
+        let mut update = vec![Rc::clone(&self.head); MAX_LEVEL];
+        let mut rank = vec![0; MAX_LEVEL];
+        let mut x = Rc::clone(&self.head);
+        for i in (0..self.level).rev() {
+            rank[i] = if i + 1 == self.level { 0 } else { rank[i + 1] };
+            loop {
+                let next = x.borrow().next[i].clone();
+                match next {
+                    Some(n) if n.borrow().key() < key => {
+                        rank[i] += x.borrow().span[i];
+                        x = n;
+                    }
+                    _ => break,
+                }
+            }
+            update[i] = Rc::clone(&x);
+        }
+        (update, rank)
+    }
+
+    ///Returns the first node whose key is greater than (or equal to if `inclusive`) `key`
+    fn find_first(&self, key: &K, inclusive: bool) -> Option<&Node<K, V>> {
+        let mut x = borrow_node(&self.head);
+        for i in (0..self.level).rev() {
+            while let Some(n) = x.next_node(i) {
+                if n.key() < key || (!inclusive && n.key() == key) {
+                    x = n;
+                } else {
+                    break;
+                }
+            }
+        }
+        x.next_node(0)
+    }
+
+    ///Returns the node with the key `key`
+    fn find_node(&self, key: &K) -> Option<&Node<K, V>> {
+        self.find_first(key, true).filter(|node| node.key() == key)
+    }
+
+    ///Inserts `value` under `key` and returns the value previously stored under `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (mut update, mut rank) = self.find_predecessors(&key);
+        let candidate = update[0].borrow().next[0].clone();
+        if let Some(candidate) = candidate {
+            let mut candidate = candidate.borrow_mut();
+            let entry = candidate.entry.as_mut().unwrap();
+            if entry.0 == key {
+                return Some(std::mem::replace(&mut entry.1, value));
+            }
+        }
+        let level = self.random_level();
+        if level > self.level {
+            //the new levels start at the head which spans the whole list
+            for i in self.level..level {
+                rank[i] = 0;
+                update[i] = Rc::clone(&self.head);
+                self.head.borrow_mut().span[i] = self.length;
+            }
+            self.level = level;
+        }
+        let node = Rc::new(RefCell::new(Node::new(Some((key, value)), level)));
+        for i in 0..level {
+            let mut previous = update[i].borrow_mut();
+            let mut new_node = node.borrow_mut();
+            new_node.next[i] = previous.next[i].take();
+            new_node.span[i] = previous.span[i] - (rank[0] - rank[i]);
+            previous.span[i] = rank[0] - rank[i] + 1;
+            previous.next[i] = Some(Rc::clone(&node));
+        }
+        //the levels above the new node now skip one more item
+        for (i, previous) in update.iter().enumerate().take(self.level).skip(level) {
+            previous.borrow_mut().span[i] += 1;
+        }
+        self.length += 1;
+        None
+    }
+
+    ///Removes the entry with the key `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let (update, _) = self.find_predecessors(key);
+        let target = update[0].borrow().next[0].clone();
+        let target = match target {
+            Some(node) if node.borrow().key() == key => node,
+            _ => return None,
+        };
+        for (i, previous) in update.iter().enumerate().take(self.level) {
+            let mut previous = previous.borrow_mut();
+            let points_to_target = previous.next[i]
+                .as_ref()
+                .is_some_and(|node| Rc::ptr_eq(node, &target));
+            if points_to_target {
+                let mut target = target.borrow_mut();
+                previous.span[i] += target.span[i];
+                previous.span[i] -= 1;
+                previous.next[i] = target.next[i].take();
+            } else {
+                previous.span[i] -= 1;
+            }
+        }
+        while self.level > 1 && self.head.borrow().next[self.level - 1].is_none() {
+            self.level -= 1;
+        }
+        self.length -= 1;
+        match Rc::try_unwrap(target) {
+            Ok(node) => node.into_inner().entry.map(|(_, value)| value),
+            Err(_) => panic!("Something is wrong. We shouldn't arrive here!!!"),
+        }
+    }
+
+    ///Returns true if the list contains an entry with the key `key`
+    /// # Attributes
+    /// * `key`- the key to search for
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find_node(key).is_some()
+    }
+
+    ///Returns the zero based position of `key` in the ordered list, or `None` if the key is not in the list
+    /// # Attributes
+    /// * `key`- the key to search for
+    pub fn rank(&self, key: &K) -> Option<usize> {
+        let mut x = Rc::clone(&self.head);
+        let mut rank = 0;
+        for i in (0..self.level).rev() {
+            loop {
+                let next = x.borrow().next[i].clone();
+                match next {
+                    Some(n) if n.borrow().key() <= key => {
+                        rank += x.borrow().span[i];
+                        x = n;
+                    }
+                    _ => break,
+                }
+            }
+            if rank > 0 && x.borrow().key() == key {
+                return Some(rank - 1);
+            }
+        }
+        None
+    }
+
+    ///Returns the first node of the list
+    fn first_node(&self) -> Option<&Node<K, V>> {
+        borrow_node(&self.head).next_node(0)
+    }
+
+    ///Returns the node at the zero based position `index`
+    fn node_at(&self, index: usize) -> Option<&Node<K, V>> {
+        if index >= self.length {
+            return None;
+        }
+        let target = index + 1;
+        let mut traversed = 0;
+        let mut x = borrow_node(&self.head);
+        for i in (0..self.level).rev() {
+            while let Some(n) = x.next_node(i) {
+                if traversed + x.span[i] > target {
+                    break;
+                }
+                traversed += x.span[i];
+                x = n;
+            }
+            if traversed == target {
+                return Some(x);
+            }
+        }
+        None
+    }
+
+    ///Returns the reference to the value stored under `key`
+    /// # Attributes
+    /// * `key`- the key to search for
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find_node(key).map(|node| node.entry().1)
+    }
+
+    ///Returns the references to the key and the value of the entry at the zero based position `index` in the ordered list
+    /// # Attributes
+    /// * `index`- the position of the entry
+    pub fn get_by_rank(&self, index: usize) -> Option<(&K, &V)> {
+        self.node_at(index).map(Node::entry)
+    }
+
+    ///Returns an iterator over the entries whose keys are inside `range`
+    /// # Attributes
+    /// * `range`- the range of keys to iterate over
+    pub fn range<B: RangeBounds<K>>(&self, range: B) -> Range<'_, K, V> {
+        let next = match range.start_bound() {
+            Bound::Included(key) => self.find_first(key, true),
+            Bound::Excluded(key) => self.find_first(key, false),
+            Bound::Unbounded => self.first_node(),
+        };
+        let end = match range.end_bound() {
+            Bound::Included(key) => self.find_first(key, false),
+            Bound::Excluded(key) => self.find_first(key, true),
+            Bound::Unbounded => None,
+        };
+        //a range which ends before it starts is empty
+        let next = match (next, end) {
+            (Some(first), Some(end)) if end.key() < first.key() => None,
+            _ => next,
+        };
+        Range { next, end }
+    }
+
+    ///Returns an iterator over all entries in ascending key order
+    pub fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+}
+
+impl<K, V, R> Drop for SkipList<K, V, R> {
+    ///Unlinks the nodes one by one so long lists do not overflow the stack with recursive drops
+    fn drop(&mut self) {
+        let mut next = {
+            let mut head = self.head.borrow_mut();
+            let first = head.next[0].take();
+            head.next.clear();
+            first
+        };
+        while let Some(node) = next {
+            let mut node = node.borrow_mut();
+            next = node.next[0].take();
+            node.next.clear();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        if self.end.is_some_and(|end| std::ptr::eq(end, node)) {
+            return None;
+        }
+        self.next = node.next_node(0);
+        Some(node.entry())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn seeded() -> SkipList<i32, String, XorShift64> {
+        SkipList::with_rng(XorShift64::new(1234))
+    }
+
+    #[test]
+    fn test_new_skiplist() {
+        let l = seeded();
+        assert_eq!(0, l.get_length());
+        assert!(l.is_empty());
+        assert!(l.get(&1).is_none());
+        assert!(l.iter().next().is_none());
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut l = seeded();
+        assert!(l.insert(5, String::from("five")).is_none());
+        assert!(l.insert(1, String::from("one")).is_none());
+        assert!(l.insert(3, String::from("three")).is_none());
+        assert_eq!(3, l.get_length());
+        assert_eq!(Some(&String::from("three")), l.get(&3));
+        assert_eq!(
+            Some(String::from("three")),
+            l.insert(3, String::from("drei"))
+        );
+        assert_eq!(3, l.get_length());
+        assert_eq!(Some(String::from("drei")), l.remove(&3));
+        assert!(l.remove(&3).is_none());
+        assert!(!l.contains_key(&3));
+        assert_eq!(2, l.get_length());
+    }
+
+    #[test]
+    fn test_range() {
+        let mut l = seeded();
+        for i in (0..20).rev() {
+            l.insert(i, i.to_string());
+        }
+        let keys: Vec<i32> = l.range(5..9).map(|(k, _)| *k).collect();
+        assert_eq!(vec![5, 6, 7, 8], keys);
+        let keys: Vec<i32> = l.range(17..).map(|(k, _)| *k).collect();
+        assert_eq!(vec![17, 18, 19], keys);
+        let keys: Vec<i32> = l
+            .range((Bound::Excluded(2), Bound::Included(4)))
+            .map(|(k, _)| *k)
+            .collect();
+        assert_eq!(vec![3, 4], keys);
+        assert_eq!(20, l.iter().count());
+        assert!(l
+            .range((Bound::Included(9), Bound::Excluded(5)))
+            .next()
+            .is_none());
+        assert!(l.range(30..).next().is_none());
+    }
+
+    #[test]
+    fn test_range_borrows_list() {
+        //the values need not be cloneable as the range hands out references
+        struct Value(i32);
+        let mut l = SkipList::with_rng(XorShift64::new(7));
+        for i in 0..10 {
+            l.insert(i, Value(i * 10));
+        }
+        let mut range = l.range(3..=5);
+        assert_eq!(Some(30), range.next().map(|(_, v)| v.0));
+        assert_eq!(Some(40), l.get(&4).map(|v| v.0));
+        assert_eq!(vec![4, 5], range.map(|(k, _)| *k).collect::<Vec<_>>());
+        //the list changes only after the range is gone
+        assert_eq!(Some(40), l.remove(&4).map(|v| v.0));
+        assert_eq!(
+            vec![3, 5],
+            l.range(3..=5).map(|(k, _)| *k).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut l = seeded();
+        for i in 0..50 {
+            l.insert(i * 2, i.to_string());
+        }
+        for i in 0..50 {
+            assert_eq!(Some(i as usize), l.rank(&(i * 2)));
+            assert_eq!(Some((&(i * 2), &i.to_string())), l.get_by_rank(i as usize));
+        }
+        assert!(l.rank(&3).is_none());
+        assert!(l.get_by_rank(50).is_none());
+        l.remove(&0);
+        assert_eq!(Some(0), l.rank(&2));
+        assert_eq!(Some((&98, &String::from("49"))), l.get_by_rank(48));
+    }
+
+    #[test]
+    fn test_reproducible_levels() {
+        let mut a = seeded();
+        let mut b = seeded();
+        for i in 0..1000 {
+            a.insert(i, String::new());
+            b.insert(i, String::new());
+        }
+        assert_eq!(a.get_level(), b.get_level());
+        assert!(a.get_level() > 1);
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(99);
+        let mut l = SkipList::with_rng(XorShift64::new(5));
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(300) as i32;
+            if rng.next_below(3) == 0 {
+                assert_eq!(reference.remove(&key), l.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, key * 10), l.insert(key, key * 10));
+            }
+        }
+        assert_eq!(reference.len() as i32, l.get_length());
+        let expected: Vec<(i32, i32)> = reference.iter().map(|(k, v)| (*k, *v)).collect();
+        assert_eq!(
+            expected,
+            l.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>()
+        );
+        for (i, (key, _)) in expected.iter().enumerate() {
+            assert_eq!(Some(i), l.rank(key));
+        }
+    }
+
+    #[test]
+    fn test_long_list_drop() {
+        let mut l = SkipList::with_rng(XorShift64::new(3));
+        for i in 0..200_000 {
+            l.insert(i, i);
+        }
+        drop(l);
+    }
+}
@@ -0,0 +1,234 @@
+//! This module implements a singly linked list of owned boxes with push and pop at its head.
+//! Its values can be visited and changed in place through borrowing iterators, and the first value matching a
+//! predicate can be removed from anywhere in the list, which makes it fit for the buckets of a chained hash table.
+//! The list implements Drop by popping its nodes one by one, so long lists are disposed without recursing.
+use std::iter::FromIterator;
+
+///Type to simplify variable declaration
+type Link<T> = Option<Box<Node<T>>>;
+
+///A single node in the list with a value and the link to the next node
+struct Node<T> {
+    value: T,
+    next: Link<T>,
+}
+
+///The singly linked list with its head and its length
+pub struct SList<T> {
+    head: Link<T>,
+    length: i32,
+}
+
+///Iterator over the references of the values of a `SList` from head to tail
+pub struct Iter<'a, T> {
+    next: Option<&'a Node<T>>,
+}
+
+///Iterator over the mutable references of the values of a `SList` from head to tail
+pub struct IterMut<'a, T> {
+    next: Option<&'a mut Node<T>>,
+}
+
+///Iterator which pops the values of a `SList` from head to tail
+pub struct IntoIter<T>(SList<T>);
+
+impl<T> Default for SList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SList<T> {
+    ///Returns a new empty list
+    pub fn new() -> SList<T> {
+        SList {
+            head: None,
+            length: 0,
+        }
+    }
+
+    ///Returns the length of the list
+    pub fn get_length(&self) -> i32 {
+        self.length
+    }
+
+    ///Returns true if the list has no values
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    ///Attaches `value` in front of the head and returns the new length
+    /// # Attributes
+    /// * `value`- the value to prepend
+    pub fn push_front(&mut self, value: T) -> i32 {
+        let next = self.head.take();
+        self.head = Some(Box::new(Node { value, next }));
+        self.length += 1;
+        self.length
+    }
+
+    ///Removes and returns the value at the head
+    pub fn pop_front(&mut self) -> Option<T> {
+        let node = self.head.take()?;
+        self.head = node.next;
+        self.length -= 1;
+        Some(node.value)
+    }
+
+    ///Returns the reference to the value at the head
+    pub fn peek_head(&self) -> Option<&T> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    ///Removes and returns the first value for which `predicate` returns true
+    /// # Attributes
+    /// * `predicate`- returns true for the value to remove
+    pub fn remove_first<F: FnMut(&T) -> bool>(&mut self, mut predicate: F) -> Option<T> {
+        let mut link = &mut self.head;
+        //advance the link until it holds the matching node or the end
+        while link.as_ref().is_some_and(|node| !predicate(&node.value)) {
+            link = &mut link.as_mut().unwrap().next;
+        }
+        let node = link.take()?;
+        *link = node.next;
+        self.length -= 1;
+        Some(node.value)
+    }
+
+    ///Returns an iterator over the references of the values from head to tail
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            next: self.head.as_deref(),
+        }
+    }
+
+    ///Returns an iterator over the mutable references of the values from head to tail
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut {
+            next: self.head.as_deref_mut(),
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next?;
+        self.next = node.next.as_deref();
+        Some(&node.value)
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.next.take()?;
+        self.next = node.next.as_deref_mut();
+        Some(&mut node.value)
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> IntoIterator for SList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> FromIterator<T> for SList<T> {
+    ///Builds a list with the values in the order of `iter`
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let values: Vec<T> = iter.into_iter().collect();
+        let mut list = SList::new();
+        for value in values.into_iter().rev() {
+            list.push_front(value);
+        }
+        list
+    }
+}
+
+impl<T> Drop for SList<T> {
+    fn drop(&mut self) {
+        let mut link = self.head.take();
+        while let Some(mut node) = link {
+            link = node.next.take();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memcheck::Ledger;
+
+    #[test]
+    fn test_new_slist() {
+        let mut l: SList<i32> = SList::new();
+        assert_eq!(0, l.get_length());
+        assert!(l.is_empty());
+        assert!(l.pop_front().is_none());
+        assert!(l.peek_head().is_none());
+        assert!(l.remove_first(|_| true).is_none());
+    }
+
+    #[test]
+    fn test_push_pop_and_iterate() {
+        let mut l = SList::new();
+        for i in 1..6 {
+            assert_eq!(i, l.push_front(i));
+        }
+        assert_eq!(Some(&5), l.peek_head());
+        assert_eq!(vec![5, 4, 3, 2, 1], l.iter().cloned().collect::<Vec<_>>());
+        for value in l.iter_mut() {
+            *value *= 10;
+        }
+        assert_eq!(Some(50), l.pop_front());
+        assert_eq!(vec![40, 30, 20, 10], l.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_remove_first() {
+        let mut l: SList<i32> = (1..8).collect();
+        assert_eq!(Some(1), l.remove_first(|v| *v == 1));
+        assert_eq!(Some(4), l.remove_first(|v| v % 2 == 0 && *v > 3));
+        assert_eq!(Some(7), l.remove_first(|v| *v == 7));
+        assert!(l.remove_first(|v| *v == 7).is_none());
+        assert_eq!(4, l.get_length());
+        assert_eq!(vec![2, 3, 5, 6], l.iter().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_memory_is_released() {
+        let ledger = Ledger::new();
+        let mut l = SList::new();
+        for i in 0..100_000 {
+            l.push_front(ledger.track(i));
+        }
+        drop(l.remove_first(|v| *v == 500));
+        drop(l.pop_front());
+        assert_eq!(l.get_length() as usize, ledger.get_live_count());
+        drop(l);
+        ledger.assert_all_dropped();
+    }
+}
@@ -0,0 +1,319 @@
+//! This module implements a hierarchical timing wheel. The wheel has `LEVELS` levels of `SLOTS` slots each, a slot
+//! of level `l` covers `SLOTS^l` ticks. A timer is stored in the level of the highest digit (base `SLOTS`) in which
+//! its deadline differs from the current time, so the slots only hold timers of the current range of their level.
+//! When the time reaches a slot of a higher level, its timers cascade down to the lower levels until they expire
+//! in level 0. Timers beyond the range of the top level wait in an overflow list.
+//! Every slot is a `DlList` of timer ids and every timer keeps the `NodeHandle` of its id, so cancelling is O(1).
+use crate::dll::{DlList, NodeHandle};
+use std::collections::HashMap;
+
+///The number of slots per level
+const SLOTS: usize = 64;
+///The number of bits of a digit of a deadline, log2 of `SLOTS`
+const SLOT_BITS: u32 = 6;
+///The number of levels
+const LEVELS: usize = 6;
+
+///A handle to a scheduled timer which allows to cancel it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerHandle(u64);
+
+///The list a timer is currently stored in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Bucket {
+    //deadline was already reached when the timer was scheduled
+    Due,
+    Slot(usize, usize),
+    Overflow,
+}
+
+///A scheduled timer with its item and the position of its id in the wheel
+struct Timer<T> {
+    deadline: u64,
+    item: T,
+    bucket: Bucket,
+    handle: NodeHandle<u64>,
+}
+
+///The timing wheel with its slots and its timers
+pub struct TimingWheel<T> {
+    slots: Vec<Vec<DlList<u64>>>,
+    due: DlList<u64>,
+    overflow: DlList<u64>,
+    timers: HashMap<u64, Timer<T>>,
+    next_id: u64,
+    current: u64,
+}
+
+///Returns the first tick of the range of level `level` which contains `time`
+fn range_start(time: u64, level: usize) -> u64 {
+    let bits = SLOT_BITS * (level as u32 + 1);
+    if bits >= 64 {
+        0
+    } else {
+        (time >> bits) << bits
+    }
+}
+
+///Returns the digit of `time` for the level `level`
+fn digit(time: u64, level: usize) -> usize {
+    ((time >> (SLOT_BITS * level as u32)) as usize) & (SLOTS - 1)
+}
+
+impl<T> TimingWheel<T> {
+    ///Returns a new empty wheel whose current time is `now`
+    /// # Attributes
+    /// * `now`- the start time of the wheel in ticks
+    pub fn new(now: u64) -> TimingWheel<T> {
+        TimingWheel {
+            slots: (0..LEVELS)
+                .map(|_| (0..SLOTS).map(|_| DlList::new()).collect())
+                .collect(),
+            due: DlList::new(),
+            overflow: DlList::new(),
+            timers: HashMap::new(),
+            next_id: 0,
+            current: now,
+        }
+    }
+
+    ///Returns the number of scheduled timers
+    pub fn get_length(&self) -> usize {
+        self.timers.len()
+    }
+
+    ///Returns true if no timer is scheduled
+    pub fn is_empty(&self) -> bool {
+        self.timers.is_empty()
+    }
+
+    ///Returns the current time of the wheel in ticks
+    pub fn get_current_time(&self) -> u64 {
+        self.current
+    }
+
+    ///Returns the deadline of the timer of `handle`, or None if it fired or was cancelled
+    /// # Attributes
+    /// * `handle`- the handle of the timer
+    pub fn get_deadline(&self, handle: TimerHandle) -> Option<u64> {
+        self.timers.get(&handle.0).map(|timer| timer.deadline)
+    }
+
+    ///Returns the bucket a timer with the deadline `deadline` belongs to at the current time
+    fn bucket_for(&self, deadline: u64) -> Bucket {
+        if deadline < self.current {
+            return Bucket::Due;
+        }
+        let differing = deadline ^ self.current;
+        let level = if differing == 0 {
+            0
+        } else {
+            ((63 - differing.leading_zeros()) / SLOT_BITS) as usize
+        };
+        if level >= LEVELS {
+            Bucket::Overflow
+        } else {
+            Bucket::Slot(level, digit(deadline, level))
+        }
+    }
+
+    fn list(&mut self, bucket: Bucket) -> &mut DlList<u64> {
+        match bucket {
+            Bucket::Due => &mut self.due,
+            Bucket::Slot(level, slot) => &mut self.slots[level][slot],
+            Bucket::Overflow => &mut self.overflow,
+        }
+    }
+
+    ///Stores the id of the scheduled timer `id` in the bucket of its deadline
+    fn place(&mut self, id: u64) {
+        let bucket = self.bucket_for(self.timers[&id].deadline);
+        let handle = self.list(bucket).enqueue_with_handle(id);
+        let timer = self.timers.get_mut(&id).unwrap();
+        timer.bucket = bucket;
+        timer.handle = handle;
+    }
+
+    ///Schedules `item` to expire at the tick `at` and returns the handle of the timer.
+    ///A deadline before the current time expires with the next `advance`.
+    /// # Attributes
+    /// * `at`- the deadline in ticks
+    /// * `item`- the item to return when the timer expires
+    pub fn schedule(&mut self, at: u64, item: T) -> TimerHandle {
+        let id = self.next_id;
+        self.next_id += 1;
+        let bucket = self.bucket_for(at);
+        let handle = self.list(bucket).enqueue_with_handle(id);
+        self.timers.insert(
+            id,
+            Timer {
+                deadline: at,
+                item,
+                bucket,
+                handle,
+            },
+        );
+        TimerHandle(id)
+    }
+
+    ///Cancels the timer of `handle` in O(1) and returns its item, or None if the timer fired or was cancelled before
+    /// # Attributes
+    /// * `handle`- the handle of the timer to cancel
+    pub fn cancel(&mut self, handle: TimerHandle) -> Option<T> {
+        let timer = self.timers.remove(&handle.0)?;
+        self.list(timer.bucket).remove(&timer.handle);
+        Some(timer.item)
+    }
+
+    ///Returns the earliest non empty bucket together with the tick it starts at
+    fn next_bucket(&self) -> Option<(Bucket, u64)> {
+        for level in 0..LEVELS {
+            let first = digit(self.current, level);
+            if let Some(slot) =
+                (first..SLOTS).find(|slot| self.slots[level][*slot].get_length() > 0)
+            {
+                let start = range_start(self.current, level)
+                    + ((slot as u64) << (SLOT_BITS * level as u32));
+                return Some((Bucket::Slot(level, slot), start.max(self.current)));
+            }
+        }
+        if self.overflow.get_length() > 0 {
+            //the overflow is checked again when the time enters the next range of the top level
+            let start = range_start(self.current, LEVELS - 1)
+                .checked_add(1 << (SLOT_BITS * LEVELS as u32))?;
+            return Some((Bucket::Overflow, start));
+        }
+        None
+    }
+
+    ///Moves the current time to `to` and returns the items of all timers with a deadline up to `to`,
+    ///ordered by deadline and, for equal deadlines, by the order they were scheduled in.
+    ///A `to` before the current time keeps the current time.
+    /// # Attributes
+    /// * `to`- the new current time in ticks
+    pub fn advance(&mut self, to: u64) -> Vec<(u64, T)> {
+        let mut expired_ids: Vec<u64> = self.due.iter().collect();
+        self.due = DlList::new();
+        while let Some((bucket, start)) = self.next_bucket() {
+            if start > to {
+                break;
+            }
+            self.current = start;
+            let mut ids = std::mem::take(self.list(bucket));
+            while let Some(id) = ids.get_head() {
+                match bucket {
+                    Bucket::Slot(0, _) => expired_ids.push(id),
+                    _ => self.place(id),
+                }
+            }
+        }
+        self.current = self.current.max(to);
+        let mut expired: Vec<(u64, u64, T)> = expired_ids
+            .into_iter()
+            .map(|id| {
+                let timer = self.timers.remove(&id).unwrap();
+                (timer.deadline, id, timer.item)
+            })
+            .collect();
+        expired.sort_by_key(|(deadline, id, _)| (*deadline, *id));
+        expired
+            .into_iter()
+            .map(|(deadline, _, item)| (deadline, item))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{RandomSource, XorShift64};
+
+    #[test]
+    fn test_new_wheel() {
+        let mut w: TimingWheel<i32> = TimingWheel::new(100);
+        assert!(w.is_empty());
+        assert_eq!(100, w.get_current_time());
+        assert!(w.advance(1_000_000).is_empty());
+        assert_eq!(1_000_000, w.get_current_time());
+    }
+
+    #[test]
+    fn test_advance_returns_expired_in_order() {
+        let mut w = TimingWheel::new(0);
+        w.schedule(100, "c");
+        w.schedule(5, "a");
+        w.schedule(70, "b");
+        w.schedule(100, "d");
+        w.schedule(5_000_000, "e");
+        assert_eq!(5, w.get_length());
+        assert_eq!(vec![(5, "a")], w.advance(69));
+        assert_eq!(vec![(70, "b"), (100, "c"), (100, "d")], w.advance(100));
+        assert!(w.advance(4_999_999).is_empty());
+        assert_eq!(vec![(5_000_000, "e")], w.advance(5_000_000));
+        assert!(w.is_empty());
+    }
+
+    #[test]
+    fn test_cancel() {
+        let mut w = TimingWheel::new(0);
+        let a = w.schedule(10, 'a');
+        let b = w.schedule(10_000, 'b');
+        let c = w.schedule(20, 'c');
+        assert_eq!(Some(10_000), w.get_deadline(b));
+        assert_eq!(Some('b'), w.cancel(b));
+        assert!(w.cancel(b).is_none());
+        assert_eq!(vec![(10, 'a'), (20, 'c')], w.advance(20_000));
+        assert!(w.cancel(a).is_none());
+        assert!(w.cancel(c).is_none());
+    }
+
+    #[test]
+    fn test_past_deadline_and_overflow() {
+        let mut w = TimingWheel::new(1000);
+        w.schedule(10, "past");
+        let far = 1u64 << 40;
+        w.schedule(far, "far");
+        assert_eq!(vec![(10, "past")], w.advance(1000));
+        assert!(w.advance(far - 1).is_empty());
+        assert_eq!(vec![(far, "far")], w.advance(far));
+    }
+
+    #[test]
+    fn test_random_timers_against_sorted_reference() {
+        let mut rng = XorShift64::new(33);
+        let mut w = TimingWheel::new(0);
+        let mut reference: Vec<(u64, u64)> = Vec::new();
+        let mut handles = Vec::new();
+        let mut now = 0;
+        for i in 0..3000u64 {
+            match rng.next_below(10) {
+                0 if !handles.is_empty() => {
+                    let (handle, item) =
+                        handles.swap_remove(rng.next_below(handles.len() as u64) as usize);
+                    if let Some(cancelled) = w.cancel(handle) {
+                        assert_eq!(item, cancelled);
+                        reference.retain(|(_, id)| *id != item);
+                    }
+                }
+                1 => {
+                    now += rng.next_below(5000);
+                    let mut expected: Vec<(u64, u64)> = reference
+                        .iter()
+                        .filter(|(d, _)| *d <= now)
+                        .cloned()
+                        .collect();
+                    expected.sort();
+                    reference.retain(|(d, _)| *d > now);
+                    assert_eq!(expected, w.advance(now));
+                }
+                _ => {
+                    let bits = rng.next_below(24);
+                    let deadline = now + rng.next_below(1 << bits);
+                    handles.push((w.schedule(deadline, i), i));
+                    reference.push((deadline, i));
+                }
+            }
+        }
+        assert_eq!(reference.len(), w.get_length());
+    }
+}
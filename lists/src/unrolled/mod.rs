@@ -0,0 +1,501 @@
+//! This module implements an unrolled linked list. Every node stores up to `node_capacity` items in a small array,
+//! so a scan touches one allocation per block of items instead of one per item as in `DlList`.
+//! The nodes live in an arena and are linked by their index, which keeps them free of `Rc`/`RefCell` overhead
+//! and allows iteration over references.
+//! Inserting into a full node splits it in two halves, removing from a node which got less than half full
+//! merges it with its successor if both fit into one node.
+use std::iter::FromIterator;
+
+///The node capacity used by `UnrolledList::new`
+const DEFAULT_NODE_CAPACITY: usize = 64;
+
+///A single node in the list with its block of items and the indexes of its neighbours in the arena.
+struct Node<T> {
+    values: Vec<T>,
+    next: Option<usize>,
+    previous: Option<usize>,
+}
+
+///The unrolled list with the indexes of its head and tail node
+pub struct UnrolledList<T> {
+    nodes: Vec<Option<Node<T>>>,
+    free: Vec<usize>,
+    head: Option<usize>,
+    tail: Option<usize>,
+    node_capacity: usize,
+    length: usize,
+}
+
+///Iterator over the references of the items of an `UnrolledList` from head to tail
+pub struct Iter<'a, T> {
+    list: &'a UnrolledList<T>,
+    node: Option<usize>,
+    offset: usize,
+}
+
+///Consuming iterator over the items of an `UnrolledList` from head to tail
+pub struct IntoIter<T>(UnrolledList<T>);
+
+impl<T> Node<T> {
+    ///Returns a new empty Node which can hold `capacity` items
+    /// # Attributes
+    /// * `capacity`- the number of items the node can hold
+    fn new(capacity: usize) -> Node<T> {
+        Node {
+            values: Vec::with_capacity(capacity),
+            next: None,
+            previous: None,
+        }
+    }
+}
+
+impl<T> Default for UnrolledList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> UnrolledList<T> {
+    ///Returns a new empty list with the default node capacity
+    pub fn new() -> UnrolledList<T> {
+        UnrolledList::with_node_capacity(DEFAULT_NODE_CAPACITY)
+    }
+
+    ///Returns a new empty list whose nodes hold up to `node_capacity` items. Capacities below 2 are raised to 2.
+    /// # Attributes
+    /// * `node_capacity`- the number of items per node
+    pub fn with_node_capacity(node_capacity: usize) -> UnrolledList<T> {
+        UnrolledList {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            head: None,
+            tail: None,
+            node_capacity: node_capacity.max(2),
+            length: 0,
+        }
+    }
+
+    ///Returns the length of the list
+    pub fn get_length(&self) -> i32 {
+        self.length as i32
+    }
+
+    ///Returns true if the list has no items
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of nodes currently linked in the list
+    pub fn get_node_count(&self) -> usize {
+        self.nodes.len() - self.free.len()
+    }
+
+    fn node(&self, index: usize) -> &Node<T> {
+        self.nodes[index].as_ref().expect("link to a freed node")
+    }
+
+    fn node_mut(&mut self, index: usize) -> &mut Node<T> {
+        self.nodes[index].as_mut().expect("link to a freed node")
+    }
+
+    ///Stores a new empty node in the arena and returns its index
+    fn allocate(&mut self) -> usize {
+        let node = Node::new(self.node_capacity);
+        match self.free.pop() {
+            Some(index) => {
+                self.nodes[index] = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Some(node));
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    ///Links the node `new` after the node `after`, or as the head if `after` is None
+    fn link_after(&mut self, after: Option<usize>, new: usize) {
+        let next = match after {
+            Some(after) => self.node(after).next,
+            None => self.head,
+        };
+        {
+            let node = self.node_mut(new);
+            node.previous = after;
+            node.next = next;
+        }
+        match after {
+            Some(after) => self.node_mut(after).next = Some(new),
+            None => self.head = Some(new),
+        }
+        match next {
+            Some(next) => self.node_mut(next).previous = Some(new),
+            None => self.tail = Some(new),
+        }
+    }
+
+    ///Unlinks the node `index` from the list, frees it in the arena and returns its items
+    fn unlink(&mut self, index: usize) -> Vec<T> {
+        let node = self.nodes[index].take().expect("link to a freed node");
+        match node.previous {
+            Some(previous) => self.node_mut(previous).next = node.next,
+            None => self.head = node.next,
+        }
+        match node.next {
+            Some(next) => self.node_mut(next).previous = node.previous,
+            None => self.tail = node.previous,
+        }
+        self.free.push(index);
+        node.values
+    }
+
+    ///Returns the node holding the item at `index` and the offset of the item inside the node.
+    ///Walks from the end which is closer to `index`.
+    fn locate(&self, index: usize) -> Option<(usize, usize)> {
+        if index >= self.length {
+            return None;
+        }
+        if index < self.length / 2 {
+            let mut remaining = index;
+            let mut current = self.head;
+            while let Some(node_index) = current {
+                let node = self.node(node_index);
+                if remaining < node.values.len() {
+                    return Some((node_index, remaining));
+                }
+                remaining -= node.values.len();
+                current = node.next;
+            }
+        } else {
+            let mut remaining = self.length - index;
+            let mut current = self.tail;
+            while let Some(node_index) = current {
+                let node = self.node(node_index);
+                if remaining <= node.values.len() {
+                    return Some((node_index, node.values.len() - remaining));
+                }
+                remaining -= node.values.len();
+                current = node.previous;
+            }
+        }
+        None
+    }
+
+    ///Attaches `value` at the end of the list and returns its new length
+    /// # Attributes
+    /// * `value`- the value to append
+    pub fn push_back(&mut self, value: T) -> i32 {
+        let tail = match self.tail {
+            Some(tail) if self.node(tail).values.len() < self.node_capacity => tail,
+            tail => {
+                let new = self.allocate();
+                self.link_after(tail, new);
+                new
+            }
+        };
+        self.node_mut(tail).values.push(value);
+        self.length += 1;
+        self.length as i32
+    }
+
+    ///Puts `value` in front of the list and returns its new length
+    /// # Attributes
+    /// * `value`- the value to put in front
+    pub fn push_front(&mut self, value: T) -> i32 {
+        let head = match self.head {
+            Some(head) if self.node(head).values.len() < self.node_capacity => head,
+            _ => {
+                let new = self.allocate();
+                self.link_after(None, new);
+                new
+            }
+        };
+        self.node_mut(head).values.insert(0, value);
+        self.length += 1;
+        self.length as i32
+    }
+
+    ///Removes and returns the last item of the list
+    pub fn pop_back(&mut self) -> Option<T> {
+        let tail = self.tail?;
+        let value = self.node_mut(tail).values.pop();
+        if self.node(tail).values.is_empty() {
+            self.unlink(tail);
+        }
+        self.length -= 1;
+        value
+    }
+
+    ///Removes and returns the first item of the list
+    pub fn pop_front(&mut self) -> Option<T> {
+        let head = self.head?;
+        let value = self.node_mut(head).values.remove(0);
+        if self.node(head).values.is_empty() {
+            self.unlink(head);
+        }
+        self.length -= 1;
+        Some(value)
+    }
+
+    ///Returns the reference to the item at `index`
+    /// # Attributes
+    /// * `index`- the position of the item
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.locate(index)
+            .map(|(node, offset)| &self.node(node).values[offset])
+    }
+
+    ///Returns the mutable reference to the item at `index`
+    /// # Attributes
+    /// * `index`- the position of the item
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        let (node, offset) = self.locate(index)?;
+        Some(&mut self.node_mut(node).values[offset])
+    }
+
+    ///Inserts `value` at the position `index`, shifting the following items, and returns the new length.
+    ///A full node is split in two halves before the insert.
+    /// # Attributes
+    /// * `index`- the position of the new item
+    /// * `value`- the value to insert
+    /// # Panics
+    /// Panics if `index` is greater than the length of the list
+    pub fn insert(&mut self, index: usize, value: T) -> i32 {
+        assert!(
+            index <= self.length,
+            "insertion index {} is out of bounds for length {}",
+            index,
+            self.length
+        );
+        if index == self.length {
+            return self.push_back(value);
+        }
+        let (mut node_index, mut offset) = self.locate(index).unwrap();
+        if self.node(node_index).values.len() == self.node_capacity {
+            let half = self.node_capacity / 2;
+            let moved = self.node_mut(node_index).values.split_off(half);
+            let new = self.allocate();
+            self.node_mut(new).values.extend(moved);
+            self.link_after(Some(node_index), new);
+            if offset >= half {
+                node_index = new;
+                offset -= half;
+            }
+        }
+        self.node_mut(node_index).values.insert(offset, value);
+        self.length += 1;
+        self.length as i32
+    }
+
+    ///Removes and returns the item at the position `index`. A node which gets less than half full
+    ///is merged with its successor if both fit into one node.
+    /// # Attributes
+    /// * `index`- the position of the item to remove
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let (node_index, offset) = self.locate(index)?;
+        let value = self.node_mut(node_index).values.remove(offset);
+        self.length -= 1;
+        let length = self.node(node_index).values.len();
+        if length == 0 {
+            self.unlink(node_index);
+        } else if length < self.node_capacity / 2 {
+            if let Some(next) = self.node(node_index).next {
+                if length + self.node(next).values.len() <= self.node_capacity {
+                    let moved = self.unlink(next);
+                    self.node_mut(node_index).values.extend(moved);
+                }
+            }
+        }
+        Some(value)
+    }
+
+    ///Returns the reference to the first item
+    pub fn peek_front(&self) -> Option<&T> {
+        self.head.and_then(|head| self.node(head).values.first())
+    }
+
+    ///Returns the reference to the last item
+    pub fn peek_back(&self) -> Option<&T> {
+        self.tail.and_then(|tail| self.node(tail).values.last())
+    }
+
+    ///Returns an iterator over the references of the items from head to tail
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            list: self,
+            node: self.head,
+            offset: 0,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let list = self.list;
+        let node = list.node(self.node?);
+        let value = &node.values[self.offset];
+        self.offset += 1;
+        if self.offset == node.values.len() {
+            self.node = node.next;
+            self.offset = 0;
+        }
+        Some(value)
+    }
+}
+
+impl<'a, T> IntoIterator for &'a UnrolledList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.pop_front()
+    }
+}
+
+impl<T> IntoIterator for UnrolledList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self)
+    }
+}
+
+impl<T> FromIterator<T> for UnrolledList<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut list = UnrolledList::new();
+        for value in iter {
+            list.push_back(value);
+        }
+        list
+    }
+}
+
+impl<T> Extend<T> for UnrolledList<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push_back(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::{RandomSource, XorShift64};
+
+    #[test]
+    fn test_new_unrolled() {
+        let l: UnrolledList<i32> = UnrolledList::new();
+        assert_eq!(0, l.get_length());
+        assert!(l.is_empty());
+        assert!(l.get(0).is_none());
+        assert_eq!(0, l.get_node_count());
+    }
+
+    #[test]
+    fn test_push_pop_both_ends() {
+        let mut l = UnrolledList::with_node_capacity(4);
+        for i in 0..10 {
+            assert_eq!(i + 1, l.push_back(i));
+        }
+        for i in 1..4 {
+            l.push_front(-i);
+        }
+        assert_eq!(13, l.get_length());
+        assert_eq!(Some(&-3), l.peek_front());
+        assert_eq!(Some(&9), l.peek_back());
+        assert_eq!(Some(-3), l.pop_front());
+        assert_eq!(Some(9), l.pop_back());
+        let values: Vec<i32> = l.iter().cloned().collect();
+        assert_eq!(vec![-2, -1, 0, 1, 2, 3, 4, 5, 6, 7, 8], values);
+        while l.pop_back().is_some() {}
+        assert_eq!(0, l.get_node_count());
+        assert!(l.pop_front().is_none());
+    }
+
+    #[test]
+    fn test_insert_splits_node() {
+        let mut l: UnrolledList<i32> = UnrolledList::with_node_capacity(4);
+        l.extend(0..4);
+        assert_eq!(1, l.get_node_count());
+        l.insert(1, 10);
+        assert_eq!(2, l.get_node_count());
+        assert_eq!(vec![0, 10, 1, 2, 3], l.iter().cloned().collect::<Vec<_>>());
+        l.insert(5, 20);
+        l.insert(0, 30);
+        assert_eq!(
+            vec![30, 0, 10, 1, 2, 3, 20],
+            l.iter().cloned().collect::<Vec<_>>()
+        );
+        *l.get_mut(3).unwrap() = 11;
+        assert_eq!(Some(&11), l.get(3));
+    }
+
+    #[test]
+    fn test_remove_merges_nodes() {
+        let mut l: UnrolledList<i32> = UnrolledList::with_node_capacity(4);
+        l.extend(0..8);
+        assert_eq!(2, l.get_node_count());
+        assert_eq!(Some(1), l.remove(1));
+        assert_eq!(Some(2), l.remove(1));
+        //the first node is now less than half full and merges with its successor
+        assert_eq!(Some(0), l.remove(0));
+        assert_eq!(vec![3, 4, 5, 6, 7], l.iter().cloned().collect::<Vec<_>>());
+        assert!(l.remove(5).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_out_of_bounds() {
+        let mut l = UnrolledList::new();
+        l.insert(1, 1);
+    }
+
+    #[test]
+    fn test_random_operations_against_vec() {
+        let mut rng = XorShift64::new(17);
+        let mut l = UnrolledList::with_node_capacity(8);
+        let mut reference = Vec::new();
+        for i in 0..5000 {
+            match rng.next_below(6) {
+                0 => {
+                    l.push_front(i);
+                    reference.insert(0, i);
+                }
+                1 => {
+                    l.push_back(i);
+                    reference.push(i);
+                }
+                2 => {
+                    let index = rng.next_below(reference.len() as u64 + 1) as usize;
+                    l.insert(index, i);
+                    reference.insert(index, i);
+                }
+                3 if !reference.is_empty() => {
+                    let index = rng.next_below(reference.len() as u64) as usize;
+                    assert_eq!(Some(reference.remove(index)), l.remove(index));
+                }
+                4 => assert_eq!(reference.pop(), l.pop_back()),
+                _ => {
+                    if !reference.is_empty() {
+                        assert_eq!(Some(reference.remove(0)), l.pop_front());
+                    }
+                }
+            }
+        }
+        assert_eq!(reference.len() as i32, l.get_length());
+        assert_eq!(reference, l.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(reference, l.into_iter().collect::<Vec<_>>());
+    }
+}
@@ -0,0 +1,358 @@
+//! This module implements a memory compact double linked list. Instead of a next and a previous pointer every
+//! node stores the XOR of both addresses, so a node needs only one link word. The list can be traversed in
+//! both directions as long as the address of one neighbour is known, which is why the list keeps the node
+//! before its current position next to the current position itself.
+//! It offers the same traversal and removal functions as `DlList`.
+use std::marker::PhantomData;
+use std::ptr;
+
+///A single node in the list with a value and the XOR of the addresses of its neighbours
+struct Node<T> {
+    value: T,
+    link: usize,
+}
+
+///The XOR linked list with pointers to its head, tail and current position
+pub struct XorList<T> {
+    head: *mut Node<T>,
+    tail: *mut Node<T>,
+    current_position: *mut Node<T>,
+    //the node before the current position, needed to decode the links of the current position
+    current_previous: *mut Node<T>,
+    length: i32,
+    _owns: PhantomData<Box<Node<T>>>,
+}
+
+///Iterator over the references of the values of a `XorList` from head to tail
+pub struct Iter<'a, T> {
+    previous: *mut Node<T>,
+    next: *mut Node<T>,
+    _list: PhantomData<&'a T>,
+}
+
+///Returns the address of `node` which takes part in the XOR links
+fn address<T>(node: *mut Node<T>) -> usize {
+    node.expose_provenance()
+}
+
+///Returns the neighbour of `node` on the other side of `neighbour`
+/// # Safety
+/// `node` must point to a live node and `neighbour` must be one of its neighbours or null
+unsafe fn other<T>(node: *mut Node<T>, neighbour: *mut Node<T>) -> *mut Node<T> {
+    ptr::with_exposed_provenance_mut((*node).link ^ address(neighbour))
+}
+
+///Replaces the neighbour `old` of `node` by `new`
+/// # Safety
+/// `node` must point to a live node and `old` must be one of its neighbours or null
+unsafe fn relink<T>(node: *mut Node<T>, old: *mut Node<T>, new: *mut Node<T>) {
+    (*node).link ^= address(old) ^ address(new);
+}
+
+impl<T> Default for XorList<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> XorList<T> {
+    ///Returns a new empty list
+    pub fn new() -> XorList<T> {
+        XorList {
+            head: ptr::null_mut(),
+            tail: ptr::null_mut(),
+            current_position: ptr::null_mut(),
+            current_previous: ptr::null_mut(),
+            length: 0,
+            _owns: PhantomData,
+        }
+    }
+
+    ///Returns the length of the list
+    pub fn get_length(&self) -> i32 {
+        self.length
+    }
+
+    ///Attaches a new node to the end of the list and returns its new length
+    /// # Attributes
+    /// * `value`- the value to append
+    pub fn enqueue(&mut self, value: T) -> i32 {
+        let node = Box::into_raw(Box::new(Node {
+            value,
+            link: address(self.tail),
+        }));
+        if self.tail.is_null() {
+            //first item in the list
+            self.head = node;
+            self.current_position = node;
+        } else {
+            // SAFETY: the tail is a live node whose next neighbour was null
+            unsafe { relink(self.tail, ptr::null_mut(), node) };
+        }
+        self.tail = node;
+        self.length += 1;
+        self.length
+    }
+
+    ///Unlinks `node` whose neighbours are `previous` and `next`, moves the current position away from it and
+    ///returns its value
+    /// # Safety
+    /// `node` must be a live node of this list with the neighbours `previous` and `next`
+    unsafe fn unlink(
+        &mut self,
+        node: *mut Node<T>,
+        previous: *mut Node<T>,
+        next: *mut Node<T>,
+    ) -> T {
+        if previous.is_null() {
+            self.head = next;
+        } else {
+            relink(previous, node, next);
+        }
+        if next.is_null() {
+            self.tail = previous;
+        } else {
+            relink(next, node, previous);
+        }
+        if self.current_position == node {
+            //like DlList the current position advances, or moves back if it was at the tail
+            if next.is_null() {
+                self.current_position = previous;
+                self.current_previous = if previous.is_null() {
+                    ptr::null_mut()
+                } else {
+                    other(previous, ptr::null_mut())
+                };
+            } else {
+                self.current_position = next;
+                self.current_previous = previous;
+            }
+        } else if self.current_previous == node {
+            self.current_previous = previous;
+        }
+        self.length -= 1;
+        Box::from_raw(node).value
+    }
+
+    ///Removes and returns the head item in the list
+    pub fn get_head(&mut self) -> Option<T> {
+        if self.head.is_null() {
+            return None;
+        }
+        let head = self.head;
+        // SAFETY: the head is a live node without previous neighbour
+        unsafe {
+            let next = other(head, ptr::null_mut());
+            Some(self.unlink(head, ptr::null_mut(), next))
+        }
+    }
+
+    ///Removes and returns the tail item in the list
+    pub fn get_tail(&mut self) -> Option<T> {
+        if self.tail.is_null() {
+            return None;
+        }
+        let tail = self.tail;
+        // SAFETY: the tail is a live node without next neighbour
+        unsafe {
+            let previous = other(tail, ptr::null_mut());
+            Some(self.unlink(tail, previous, ptr::null_mut()))
+        }
+    }
+
+    ///Removes and returns the item at the current position and advances the current position in the list
+    pub fn get_current_position(&mut self) -> Option<T> {
+        if self.current_position.is_null() {
+            return None;
+        }
+        let current = self.current_position;
+        let previous = self.current_previous;
+        // SAFETY: the current position is a live node and current_previous its previous neighbour
+        unsafe {
+            let next = other(current, previous);
+            Some(self.unlink(current, previous, next))
+        }
+    }
+
+    ///Moves the current position one item forward in the list and returns true if the move was successful, false otherwise.
+    pub fn move_forward(&mut self) -> bool {
+        if self.current_position.is_null() {
+            return false;
+        }
+        // SAFETY: the current position is a live node and current_previous its previous neighbour
+        let next = unsafe { other(self.current_position, self.current_previous) };
+        if next.is_null() {
+            return false;
+        }
+        self.current_previous = self.current_position;
+        self.current_position = next;
+        true
+    }
+
+    ///Moves the current position one item backwards in the list and returns true if the move was successful, false otherwise.
+    pub fn move_backward(&mut self) -> bool {
+        if self.current_previous.is_null() {
+            return false;
+        }
+        // SAFETY: current_previous is a live node and the current position its next neighbour
+        let before = unsafe { other(self.current_previous, self.current_position) };
+        self.current_position = self.current_previous;
+        self.current_previous = before;
+        true
+    }
+
+    ///Returns the reference to the value of `node`
+    fn value(&self, node: *mut Node<T>) -> Option<&T> {
+        // SAFETY: non null nodes are owned by the list and live as long as the borrow of the list
+        unsafe { node.as_ref() }.map(|node| &node.value)
+    }
+
+    ///Peeks the reference to the value of the current position without consuming it
+    pub fn peek_current_position(&self) -> Option<&T> {
+        self.value(self.current_position)
+    }
+
+    ///Peeks the reference to the value of the head position without consuming it
+    pub fn peek_head(&self) -> Option<&T> {
+        self.value(self.head)
+    }
+
+    ///Peeks the reference to the value of the tail position without consuming it
+    pub fn peek_tail(&self) -> Option<&T> {
+        self.value(self.tail)
+    }
+
+    ///Returns an iterator over the references of the values from head to tail
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            previous: ptr::null_mut(),
+            next: self.head,
+            _list: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.next.is_null() {
+            return None;
+        }
+        let current = self.next;
+        // SAFETY: the iterator borrows the list, so its nodes stay alive
+        unsafe {
+            self.next = other(current, self.previous);
+            self.previous = current;
+            Some(&(*current).value)
+        }
+    }
+}
+
+impl<T> Drop for XorList<T> {
+    fn drop(&mut self) {
+        while self.get_head().is_some() {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::memcheck::Ledger;
+
+    #[test]
+    fn test_new_xor() {
+        let mut l: XorList<i32> = XorList::new();
+        assert_eq!(0, l.get_length());
+        assert!(l.get_head().is_none());
+        assert!(l.get_tail().is_none());
+        assert!(l.get_current_position().is_none());
+        assert!(!l.move_forward());
+        assert!(!l.move_backward());
+    }
+
+    #[test]
+    fn test_multi_item_xor_mixed() {
+        let mut l = XorList::new();
+        for i in 1..6 {
+            assert_eq!(i, l.enqueue(i));
+        }
+        assert_eq!(Some(&1), l.peek_head());
+        assert_eq!(Some(&5), l.peek_tail());
+        assert_eq!(Some(5), l.get_tail());
+        assert_eq!(Some(1), l.get_head());
+        assert_eq!(4, l.enqueue(6));
+        assert_eq!(vec![2, 3, 4, 6], l.iter().cloned().collect::<Vec<_>>());
+        assert_eq!(Some(2), l.get_head());
+        assert_eq!(Some(6), l.get_tail());
+        assert_eq!(Some(4), l.get_tail());
+        assert_eq!(Some(3), l.get_head());
+        assert!(l.get_head().is_none());
+        assert_eq!(0, l.get_length());
+    }
+
+    #[test]
+    fn test_current_position() {
+        let mut l = XorList::new();
+        for i in 1..6 {
+            l.enqueue(i);
+        }
+        //same walk as the tester binary does with DlList
+        while l.move_forward() {}
+        assert_eq!(Some(5), l.get_current_position());
+        assert_eq!(Some(4), l.get_tail());
+        assert_eq!(Some(1), l.get_head());
+        while l.move_backward() {}
+        assert_eq!(Some(2), l.get_current_position());
+        assert_eq!(Some(3), l.get_current_position());
+        assert!(l.get_current_position().is_none());
+    }
+
+    #[test]
+    fn test_traversal_after_removals() {
+        let mut l = XorList::new();
+        for i in 1..8 {
+            l.enqueue(i);
+        }
+        l.move_forward();
+        l.move_forward();
+        assert_eq!(Some(3), l.get_current_position());
+        assert_eq!(Some(&4), l.peek_current_position());
+        assert!(l.move_backward());
+        assert_eq!(Some(&2), l.peek_current_position());
+        assert_eq!(Some(1), l.get_head());
+        assert!(!l.move_backward());
+        assert!(l.move_forward());
+        assert_eq!(Some(&4), l.peek_current_position());
+        assert_eq!(vec![2, 4, 5, 6, 7], l.iter().cloned().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_memory_is_released() {
+        use crate::rng::{RandomSource, XorShift64};
+        let ledger = Ledger::new();
+        let mut rng = XorShift64::new(21);
+        let mut l = XorList::new();
+        for i in 0..3000 {
+            match rng.next_below(6) {
+                0..=2 => {
+                    l.enqueue(ledger.track(i));
+                }
+                3 => drop(l.get_head()),
+                4 => drop(l.get_tail()),
+                _ => {
+                    if rng.next_bool() {
+                        l.move_forward();
+                    } else {
+                        l.move_backward();
+                    }
+                    drop(l.get_current_position());
+                }
+            }
+        }
+        assert_eq!(l.get_length() as usize, ledger.get_live_count());
+        assert_eq!(l.get_length() as usize, l.iter().count());
+        drop(l);
+        ledger.assert_all_dropped();
+    }
+}
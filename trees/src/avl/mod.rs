@@ -0,0 +1,683 @@
+//! This module implements an AVL tree map. Every node stores the height of its subtree and after every insertion
+//! or removal the nodes on the path to the root are rotated until the heights of their two subtrees differ by at
+//! most one, so all operations are O(log n). The recursion of the operations is bounded by the height of the tree.
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+///Type to simplify variable declaration
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+///A node of the tree with its entry, its two subtrees and the height of its subtree
+struct Node<K, V> {
+    key: K,
+    value: V,
+    height: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+///The AVL tree with its root and its number of entries
+pub struct AvlTree<K, V> {
+    root: Link<K, V>,
+    length: usize,
+}
+
+///In-order iterator over the entries of an `AvlTree`
+pub struct Iter<'a, K, V> {
+    //the nodes whose left subtree is being visited
+    stack: Vec<&'a Node<K, V>>,
+}
+
+///In-order iterator over the entries of an `AvlTree` within a range of keys
+pub struct Range<'a, K, V> {
+    iter: Iter<'a, K, V>,
+    //the key of the last entry in the range
+    last: Option<&'a K>,
+}
+
+///A view into a single entry of an `AvlTree`, which is either occupied or vacant
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+///A view into an entry of an `AvlTree` which has a value
+pub struct OccupiedEntry<'a, K, V> {
+    key: K,
+    value: &'a mut V,
+}
+
+///A view into an entry of an `AvlTree` which has no value
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    tree: &'a mut AvlTree<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+///Returns the height of the subtree of `link`
+fn height<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+///Sets the height of `node` from the heights of its subtrees
+fn update_height<K, V>(node: &mut Node<K, V>) {
+    node.height = 1 + height(&node.left).max(height(&node.right));
+}
+
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut right = node.right.take().unwrap();
+    node.right = right.left.take();
+    update_height(&mut node);
+    right.left = Some(node);
+    update_height(&mut right);
+    right
+}
+
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut left = node.left.take().unwrap();
+    node.left = left.right.take();
+    update_height(&mut node);
+    left.right = Some(node);
+    update_height(&mut left);
+    left
+}
+
+///Updates the height of the node of `link` and rotates it if its subtrees differ by more than one in height
+fn rebalance<K, V>(link: &mut Link<K, V>) {
+    let mut node = match link.take() {
+        Some(node) => node,
+        None => return,
+    };
+    update_height(&mut node);
+    let (left, right) = (height(&node.left), height(&node.right));
+    if left > right + 1 {
+        //a left child which leans right needs a rotation of its own first
+        let child = node.left.as_ref().unwrap();
+        if height(&child.right) > height(&child.left) {
+            node.left = Some(rotate_left(node.left.take().unwrap()));
+        }
+        node = rotate_right(node);
+    } else if right > left + 1 {
+        let child = node.right.as_ref().unwrap();
+        if height(&child.left) > height(&child.right) {
+            node.right = Some(rotate_right(node.right.take().unwrap()));
+        }
+        node = rotate_left(node);
+    }
+    *link = Some(node);
+}
+
+///Inserts `value` under `key` below `link`. Returns the old value of `key` and the pointer to the value in the tree.
+fn insert_node<K: Ord, V>(link: &mut Link<K, V>, key: K, value: V) -> (Option<V>, *mut V) {
+    let node = match link {
+        Some(node) => node,
+        None => {
+            let mut node = Box::new(Node::new(key, value));
+            //rotations move the boxes, the node itself stays where it was allocated
+            let pointer: *mut V = &mut node.value;
+            *link = Some(node);
+            return (None, pointer);
+        }
+    };
+    let result = match key.cmp(&node.key) {
+        Ordering::Less => insert_node(&mut node.left, key, value),
+        Ordering::Greater => insert_node(&mut node.right, key, value),
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut node.value, value);
+            return (Some(old), &mut node.value);
+        }
+    };
+    rebalance(link);
+    result
+}
+
+///Removes the node with the smallest key from the subtree of `node`. Returns the removed node and the rest.
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Box<Node<K, V>>, Link<K, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, rest) = remove_min(left);
+            node.left = rest;
+            let mut link = Some(node);
+            rebalance(&mut link);
+            (min, link)
+        }
+    }
+}
+
+///Removes the node with the largest key from the subtree of `node`. Returns the removed node and the rest.
+fn remove_max<K, V>(mut node: Box<Node<K, V>>) -> (Box<Node<K, V>>, Link<K, V>) {
+    match node.right.take() {
+        None => {
+            let rest = node.left.take();
+            (node, rest)
+        }
+        Some(right) => {
+            let (max, rest) = remove_max(right);
+            node.right = rest;
+            let mut link = Some(node);
+            rebalance(&mut link);
+            (max, link)
+        }
+    }
+}
+
+///Removes the entry of `key` below `link` and returns its value
+fn remove_node<K: Ord, V>(link: &mut Link<K, V>, key: &K) -> Option<V> {
+    let node = link.as_mut()?;
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => remove_node(&mut node.left, key),
+        Ordering::Greater => remove_node(&mut node.right, key),
+        Ordering::Equal => {
+            let mut node = link.take().unwrap();
+            *link = match (node.left.take(), node.right.take()) {
+                (None, rest) | (rest, None) => rest,
+                (left, Some(right)) => {
+                    //the successor takes the place of the node
+                    let (mut min, rest) = remove_min(right);
+                    min.left = left;
+                    min.right = rest;
+                    Some(min)
+                }
+            };
+            Some(node.value)
+        }
+    };
+    if removed.is_some() {
+        rebalance(link);
+    }
+    removed
+}
+
+///Checks the order, the heights and the balance of the subtree of `link` and returns its number of nodes
+fn check_node<K: Ord, V>(
+    link: &Link<K, V>,
+    lower: Option<&K>,
+    upper: Option<&K>,
+) -> Result<usize, String> {
+    let node = match link {
+        Some(node) => node,
+        None => return Ok(0),
+    };
+    if lower.is_some_and(|lower| node.key <= *lower)
+        || upper.is_some_and(|upper| node.key >= *upper)
+    {
+        return Err(String::from("keys are out of order"));
+    }
+    let count = check_node(&node.left, lower, Some(&node.key))?
+        + check_node(&node.right, Some(&node.key), upper)?
+        + 1;
+    let (left, right) = (height(&node.left), height(&node.right));
+    if node.height != 1 + left.max(right) {
+        return Err(format!("stored height {} is wrong", node.height));
+    }
+    if left > right + 1 || right > left + 1 {
+        return Err(format!(
+            "subtrees of heights {} and {} are out of balance",
+            left, right
+        ));
+    }
+    Ok(count)
+}
+
+impl<K: Ord, V> Default for AvlTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    ///Returns a new empty tree
+    pub fn new() -> AvlTree<K, V> {
+        AvlTree {
+            root: None,
+            length: 0,
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        height(&self.root)
+    }
+
+    ///Returns the node of `key`
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (old, _) = insert_node(&mut self.root, key, value);
+        if old.is_none() {
+            self.length += 1;
+        }
+        old
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|node| &node.value)
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    ///Returns true if the tree has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = remove_node(&mut self.root, key);
+        if removed.is_some() {
+            self.length -= 1;
+        }
+        removed
+    }
+
+    ///Returns the entry of `key` for in-place manipulation
+    /// # Attributes
+    /// * `key`- the key of the entry
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        if self.contains_key(&key) {
+            let value = self.get_mut(&key).unwrap();
+            Entry::Occupied(OccupiedEntry { key, value })
+        } else {
+            Entry::Vacant(VacantEntry { key, tree: self })
+        }
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Removes and returns the entry with the smallest key
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let (min, rest) = remove_min(self.root.take()?);
+        self.root = rest;
+        self.length -= 1;
+        Some((min.key, min.value))
+    }
+
+    ///Removes and returns the entry with the largest key
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let (max, rest) = remove_max(self.root.take()?);
+        self.root = rest;
+        self.length -= 1;
+        Some((max.key, max.value))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Returns an iterator over the keys in order
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    ///Returns an iterator over the values in the order of their keys
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    ///Returns an iterator over the entries whose keys are in `range`, in the order of their keys
+    /// # Attributes
+    /// * `range`- the range of the keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        //the path to the first key in the range holds the nodes whose left subtree comes next
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => node.key >= *start,
+                Bound::Excluded(start) => node.key > *start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                iter.stack.push(node);
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+        let mut last = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => node.key <= *end,
+                Bound::Excluded(end) => node.key < *end,
+                Bound::Unbounded => true,
+            };
+            if before_end {
+                last = Some(&node.key);
+                current = node.right.as_deref();
+            } else {
+                current = node.left.as_deref();
+            }
+        }
+        let first = iter.stack.last().map(|node| &node.key);
+        if first.is_none() || last.is_none() || first > last {
+            iter.stack.clear();
+        }
+        Range { iter, last }
+    }
+
+    ///Checks that the keys are in order and every node has the right height and is balanced.
+    ///Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = check_node(&self.root, None, None)?;
+        if count != self.length {
+            return Err(format!("length {} but {} nodes", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    ///Pushes `node` and its chain of left children on the stack
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        if self.last.is_some_and(|last| std::ptr::eq(last, key)) {
+            self.iter.stack.clear();
+        }
+        Some((key, value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a AvlTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    ///Returns the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => &entry.key,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    ///Inserts `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- the value to insert
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    ///Inserts the value returned by `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- returns the value to insert
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    ///Calls `f` with the value if the entry is occupied
+    /// # Attributes
+    /// * `f`- modifies the value
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(entry) => {
+                f(entry.value);
+                Entry::Occupied(entry)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    ///Inserts the default value if the entry is vacant and returns the mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    ///Returns the reference to the value
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    ///Returns the mutable reference to the value
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    ///Returns the mutable reference to the value which lives as long as the tree is borrowed
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    ///Replaces the value with `value` and returns the old one
+    /// # Attributes
+    /// * `value`- the new value
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.value, value)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    ///Inserts `value` under the key of the entry and returns the mutable reference to it
+    /// # Attributes
+    /// * `value`- the value to insert
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (_, pointer) = insert_node(&mut self.tree.root, self.key, value);
+        self.tree.length += 1;
+        //SAFETY: the pointer points into the node just allocated for the value, which stays in place until the
+        //tree is changed again, and the tree stays mutably borrowed for 'a
+        unsafe { &mut *pointer }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_avl() {
+        let t: AvlTree<i32, i32> = AvlTree::new();
+        assert!(t.is_empty());
+        assert!(t.first_key_value().is_none());
+        assert!(t.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut t = AvlTree::new();
+        for i in 0..1023 {
+            assert!(t.insert(i, i * 2).is_none());
+            assert!(t.check_invariants().is_ok());
+        }
+        assert_eq!(10, t.get_height());
+        assert_eq!(Some(&20), t.get(&10));
+        assert_eq!(Some(20), t.insert(10, 0));
+        assert_eq!(Some((&0, &0)), t.first_key_value());
+        assert_eq!(Some((&1022, &2044)), t.last_key_value());
+        assert_eq!(
+            (0..1023).collect::<Vec<_>>(),
+            t.keys().copied().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_remove_and_pop() {
+        let mut t: AvlTree<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        for i in (0..100).step_by(3) {
+            assert_eq!(Some(i), t.remove(&i));
+            assert!(t.check_invariants().is_ok());
+        }
+        assert!(t.remove(&0).is_none());
+        assert_eq!(Some((1, 1)), t.pop_first());
+        assert_eq!(Some((98, 98)), t.pop_last());
+        assert!(t.check_invariants().is_ok());
+        assert_eq!(64, t.get_length());
+    }
+
+    #[test]
+    fn test_range() {
+        let t: AvlTree<i32, ()> = (0..20).map(|i| (i * 2, ())).collect();
+        let keys = |r: Range<'_, i32, ()>| r.map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(vec![4, 6, 8], keys(t.range(3..10)));
+        assert_eq!(vec![4, 6, 8, 10], keys(t.range(4..=10)));
+        assert_eq!(vec![0, 2], keys(t.range(..4)));
+        assert_eq!(
+            vec![36, 38],
+            keys(t.range((Bound::Excluded(34), Bound::Unbounded)))
+        );
+        assert!(keys(t.range(5..6)).is_empty());
+        assert!(keys(t.range(100..)).is_empty());
+        assert_eq!(20, t.range(..).count());
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut t = AvlTree::new();
+        for word in "a b a c b a".split(' ') {
+            *t.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(Some(&3), t.get(&"a"));
+        assert_eq!(Some(&1), t.get(&"c"));
+        t.entry("c").and_modify(|count| *count += 10).or_default();
+        t.entry("d").and_modify(|count| *count += 10).or_default();
+        assert_eq!(Some(&11), t.get(&"c"));
+        assert_eq!(Some(&0), t.get(&"d"));
+        assert_eq!(&"e", t.entry("e").key());
+        if let Entry::Occupied(mut entry) = t.entry("a") {
+            assert_eq!(3, entry.insert(7));
+            assert_eq!(&7, entry.get());
+        }
+        assert_eq!(4, t.get_length());
+        assert!(t.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(40);
+        let mut t = AvlTree::new();
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(500);
+            match rng.next_below(3) {
+                0 => assert_eq!(reference.remove(&key), t.remove(&key)),
+                _ => assert_eq!(reference.insert(key, key + 1), t.insert(key, key + 1)),
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().eq(t.iter()));
+        assert!(reference.range(100..200).eq(t.range(100..200)));
+    }
+}
@@ -0,0 +1,770 @@
+//! This module implements a B+ tree map with a configurable order, the maximum number of children of an inner node.
+//! All entries live in the leaves, which hold up to `order - 1` entries and are linked to their neighbours like the
+//! nodes of `lists::dll`, so a range scan walks from leaf to leaf without going back up the tree. The inner nodes
+//! only hold copies of keys which separate their children.
+//! The nodes are kept in an arena and refer to each other by index; the slots of removed nodes are reused.
+use crate::btree::group_sizes;
+use std::ops::{Bound, RangeBounds};
+
+///A node of the tree. A leaf has entries and links to its neighbouring leaves, an inner node has separating keys
+///and one child more than keys.
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    children: Vec<usize>,
+    prev: Option<usize>,
+    next: Option<usize>,
+}
+
+///The B+ tree with its node arena, its root and the two ends of its leaf list
+pub struct BPlusTree<K, V> {
+    nodes: Vec<Node<K, V>>,
+    //the slots of removed nodes
+    free: Vec<usize>,
+    root: usize,
+    first: usize,
+    last: usize,
+    order: usize,
+    length: usize,
+}
+
+///Double-ended iterator over the entries of a `BPlusTree` within a range of keys, in the order of their keys
+pub struct Range<'a, K, V> {
+    tree: &'a BPlusTree<K, V>,
+    //the leaf and the index of the next entry from the front
+    front: (usize, usize),
+    //the leaf and the index after the next entry from the back
+    back: (usize, usize),
+}
+
+///The result of an insertion below a node
+enum Insertion<K, V> {
+    Replaced(V),
+    Added,
+    //the node was split, with the separating key and the index of the new right node
+    Split(K, usize),
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Node<K, V> {
+        Node {
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+            prev: None,
+            next: None,
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+}
+
+///Returns the key of a bound, if it is bounded
+fn bound_key<K>(bound: Bound<&K>) -> Option<&K> {
+    match bound {
+        Bound::Included(key) | Bound::Excluded(key) => Some(key),
+        Bound::Unbounded => None,
+    }
+}
+
+impl<K: Ord + Clone, V> BPlusTree<K, V> {
+    ///Returns a new empty tree whose inner nodes have up to `order` children. Panics if `order` is below 3.
+    /// # Attributes
+    /// * `order`- the maximum number of children of an inner node
+    pub fn new(order: usize) -> BPlusTree<K, V> {
+        assert!(order >= 3, "order {} is below 3", order);
+        BPlusTree {
+            nodes: vec![Node::new()],
+            free: Vec::new(),
+            root: 0,
+            first: 0,
+            last: 0,
+            order,
+            length: 0,
+        }
+    }
+
+    ///Returns a new tree with the entries of `entries`, which must come with strictly increasing keys.
+    ///The leaves are filled evenly and linked, then the inner levels are built from the first keys of their
+    ///children. Panics if the keys are not increasing.
+    /// # Attributes
+    /// * `order`- the maximum number of children of an inner node
+    /// * `entries`- the entries in the order of their keys
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(
+        order: usize,
+        entries: I,
+    ) -> BPlusTree<K, V> {
+        let mut tree = BPlusTree::new(order);
+        let entries: Vec<(K, V)> = entries.into_iter().collect();
+        assert!(
+            entries.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "keys are not strictly increasing"
+        );
+        if entries.is_empty() {
+            return tree;
+        }
+        tree.nodes.clear();
+        tree.length = entries.len();
+        let leaf_count = entries.len().div_ceil(order - 1);
+        //the nodes of the current level with the smallest key below them
+        let mut level = Vec::with_capacity(leaf_count);
+        let mut rest = entries.into_iter();
+        for (id, size) in group_sizes(tree.length, leaf_count).enumerate() {
+            let mut leaf = Node::new();
+            for (key, value) in rest.by_ref().take(size) {
+                leaf.keys.push(key);
+                leaf.values.push(value);
+            }
+            leaf.prev = id.checked_sub(1);
+            leaf.next = Some(id + 1).filter(|next| *next < leaf_count);
+            level.push((id, leaf.keys[0].clone()));
+            tree.nodes.push(leaf);
+        }
+        tree.last = leaf_count - 1;
+        while level.len() > 1 {
+            let parent_count = level.len().div_ceil(order);
+            let mut children = level.into_iter();
+            level = Vec::with_capacity(parent_count);
+            for size in group_sizes(children.len(), parent_count) {
+                let mut parent = Node::new();
+                let mut smallest = None;
+                for (child, key) in children.by_ref().take(size) {
+                    parent.children.push(child);
+                    if smallest.is_none() {
+                        smallest = Some(key);
+                    } else {
+                        parent.keys.push(key);
+                    }
+                }
+                level.push((tree.nodes.len(), smallest.unwrap()));
+                tree.nodes.push(parent);
+            }
+        }
+        tree.root = level[0].0;
+        tree
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the maximum number of children of an inner node
+    pub fn get_order(&self) -> usize {
+        self.order
+    }
+
+    ///Returns the number of levels of nodes, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.nodes[self.root];
+        while let Some(child) = node.children.first() {
+            height += 1;
+            node = &self.nodes[*child];
+        }
+        height
+    }
+
+    ///Returns the minimum number of entries of a leaf other than the root
+    fn get_min_entries(&self) -> usize {
+        self.order / 2
+    }
+
+    ///Returns the minimum number of keys of an inner node other than the root
+    fn get_min_keys(&self) -> usize {
+        self.order.div_ceil(2) - 1
+    }
+
+    ///Returns the leaf in which `key` is or would be
+    fn find_leaf(&self, key: &K) -> usize {
+        let mut id = self.root;
+        while !self.nodes[id].is_leaf() {
+            let node = &self.nodes[id];
+            id = node.children[node.keys.partition_point(|separator| separator <= key)];
+        }
+        id
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let leaf = &self.nodes[self.find_leaf(key)];
+        let index = leaf.keys.binary_search(key).ok()?;
+        Some(&leaf.values[index])
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let id = self.find_leaf(key);
+        let leaf = &mut self.nodes[id];
+        let index = leaf.keys.binary_search(key).ok()?;
+        Some(&mut leaf.values[index])
+    }
+
+    ///Returns true if the tree has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Stores `node` in a free slot of the arena and returns its index
+    fn allocate(&mut self, node: Node<K, V>) -> usize {
+        match self.free.pop() {
+            Some(id) => {
+                self.nodes[id] = node;
+                id
+            }
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    ///Empties the node `id` and marks its slot as free
+    fn release(&mut self, id: usize) {
+        self.nodes[id] = Node::new();
+        self.free.push(id);
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.insert_below(self.root, key, value) {
+            Insertion::Replaced(old) => return Some(old),
+            Insertion::Added => {}
+            Insertion::Split(key, right) => {
+                //the root was split, the tree grows by one level
+                let mut root = Node::new();
+                root.keys.push(key);
+                root.children.push(self.root);
+                root.children.push(right);
+                self.root = self.allocate(root);
+            }
+        }
+        self.length += 1;
+        None
+    }
+
+    ///Inserts `value` under `key` below the node `id` and splits the node if it overflows
+    fn insert_below(&mut self, id: usize, key: K, value: V) -> Insertion<K, V> {
+        if self.nodes[id].is_leaf() {
+            let leaf = &mut self.nodes[id];
+            match leaf.keys.binary_search(&key) {
+                Ok(index) => {
+                    return Insertion::Replaced(std::mem::replace(&mut leaf.values[index], value))
+                }
+                Err(index) => {
+                    leaf.keys.insert(index, key);
+                    leaf.values.insert(index, value);
+                }
+            }
+            if leaf.keys.len() < self.order {
+                return Insertion::Added;
+            }
+            //the right half becomes a new leaf after this one in the leaf list
+            let middle = leaf.keys.len() / 2;
+            let mut right = Node::new();
+            right.keys = leaf.keys.split_off(middle);
+            right.values = leaf.values.split_off(middle);
+            right.prev = Some(id);
+            right.next = leaf.next;
+            let separator = right.keys[0].clone();
+            let right = self.allocate(right);
+            match self.nodes[right].next {
+                Some(next) => self.nodes[next].prev = Some(right),
+                None => self.last = right,
+            }
+            self.nodes[id].next = Some(right);
+            return Insertion::Split(separator, right);
+        }
+        let index = self.nodes[id]
+            .keys
+            .partition_point(|separator| *separator <= key);
+        let child = self.nodes[id].children[index];
+        match self.insert_below(child, key, value) {
+            Insertion::Split(separator, right) => {
+                let node = &mut self.nodes[id];
+                node.keys.insert(index, separator);
+                node.children.insert(index + 1, right);
+            }
+            result => return result,
+        }
+        let node = &mut self.nodes[id];
+        if node.keys.len() < self.order {
+            return Insertion::Added;
+        }
+        //the middle key moves up to the parent
+        let middle = node.keys.len() / 2;
+        let mut right = Node::new();
+        right.keys = node.keys.split_off(middle + 1);
+        right.children = node.children.split_off(middle + 1);
+        let separator = node.keys.pop().unwrap();
+        Insertion::Split(separator, self.allocate(right))
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let removed = self.remove_below(self.root, key)?;
+        let root = &self.nodes[self.root];
+        if root.keys.is_empty() && !root.is_leaf() {
+            //the last two children of the root were merged, the tree shrinks by one level
+            let child = root.children[0];
+            self.release(self.root);
+            self.root = child;
+        }
+        self.length -= 1;
+        Some(removed)
+    }
+
+    ///Removes the entry of `key` below the node `id` and returns its value
+    fn remove_below(&mut self, id: usize, key: &K) -> Option<V> {
+        let node = &mut self.nodes[id];
+        if node.is_leaf() {
+            let index = node.keys.binary_search(key).ok()?;
+            node.keys.remove(index);
+            return Some(node.values.remove(index));
+        }
+        //separators may outlive their keys, they still separate the children correctly
+        let index = node.keys.partition_point(|separator| separator <= key);
+        let child = node.children[index];
+        let removed = self.remove_below(child, key)?;
+        self.refill(id, index);
+        Some(removed)
+    }
+
+    ///Refills the child `index` of the node `id` if it has fewer than the minimum number of keys, by borrowing from a
+    ///sibling which can spare one or else by merging it with a sibling
+    fn refill(&mut self, id: usize, index: usize) {
+        let children = &self.nodes[id].children;
+        let child = children[index];
+        let left = index.checked_sub(1).map(|left| children[left]);
+        let right = children.get(index + 1).copied();
+        let is_leaf = self.nodes[child].is_leaf();
+        let min_keys = if is_leaf {
+            self.get_min_entries()
+        } else {
+            self.get_min_keys()
+        };
+        if self.nodes[child].keys.len() >= min_keys {
+            return;
+        }
+        let spare =
+            |sibling: Option<usize>| sibling.filter(|s| self.nodes[*s].keys.len() > min_keys);
+        if let Some(left) = spare(left) {
+            let key = self.nodes[left].keys.pop().unwrap();
+            if is_leaf {
+                let value = self.nodes[left].values.pop().unwrap();
+                self.nodes[id].keys[index - 1] = key.clone();
+                self.nodes[child].keys.insert(0, key);
+                self.nodes[child].values.insert(0, value);
+            } else {
+                //the separator comes down, the last key of the sibling goes up
+                let separator = std::mem::replace(&mut self.nodes[id].keys[index - 1], key);
+                let grandchild = self.nodes[left].children.pop().unwrap();
+                self.nodes[child].keys.insert(0, separator);
+                self.nodes[child].children.insert(0, grandchild);
+            }
+        } else if let Some(right) = spare(right) {
+            let key = self.nodes[right].keys.remove(0);
+            if is_leaf {
+                let value = self.nodes[right].values.remove(0);
+                self.nodes[id].keys[index] = self.nodes[right].keys[0].clone();
+                self.nodes[child].keys.push(key);
+                self.nodes[child].values.push(value);
+            } else {
+                let separator = std::mem::replace(&mut self.nodes[id].keys[index], key);
+                let grandchild = self.nodes[right].children.remove(0);
+                self.nodes[child].keys.push(separator);
+                self.nodes[child].children.push(grandchild);
+            }
+        } else {
+            //both siblings have the minimum, merge with one of them
+            let left_index = if left.is_some() { index - 1 } else { index };
+            let (left, right) = match left {
+                Some(left) => (left, child),
+                None => (child, right.unwrap()),
+            };
+            let separator = self.nodes[id].keys.remove(left_index);
+            self.nodes[id].children.remove(left_index + 1);
+            let mut removed = std::mem::replace(&mut self.nodes[right], Node::new());
+            self.release(right);
+            let node = &mut self.nodes[left];
+            if is_leaf {
+                node.next = removed.next;
+                match removed.next {
+                    Some(next) => self.nodes[next].prev = Some(left),
+                    None => self.last = left,
+                }
+            } else {
+                node.keys.push(separator);
+            }
+            let node = &mut self.nodes[left];
+            node.keys.append(&mut removed.keys);
+            node.values.append(&mut removed.values);
+            node.children.append(&mut removed.children);
+        }
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let leaf = &self.nodes[self.first];
+        Some((leaf.keys.first()?, leaf.values.first()?))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let leaf = &self.nodes[self.last];
+        Some((leaf.keys.last()?, leaf.values.last()?))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Range<'_, K, V> {
+        self.range(..)
+    }
+
+    ///Returns an iterator over the entries whose keys are in `range`, in the order of their keys. The iterator
+    ///walks the leaf list from both ends.
+    /// # Attributes
+    /// * `range`- the range of the keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let front = match range.start_bound() {
+            Bound::Included(start) => {
+                let leaf = self.find_leaf(start);
+                (
+                    leaf,
+                    self.nodes[leaf].keys.partition_point(|key| key < start),
+                )
+            }
+            Bound::Excluded(start) => {
+                let leaf = self.find_leaf(start);
+                (
+                    leaf,
+                    self.nodes[leaf].keys.partition_point(|key| key <= start),
+                )
+            }
+            Bound::Unbounded => (self.first, 0),
+        };
+        let mut back = match range.end_bound() {
+            Bound::Included(end) => {
+                let leaf = self.find_leaf(end);
+                (
+                    leaf,
+                    self.nodes[leaf].keys.partition_point(|key| key <= end),
+                )
+            }
+            Bound::Excluded(end) => {
+                let leaf = self.find_leaf(end);
+                (leaf, self.nodes[leaf].keys.partition_point(|key| key < end))
+            }
+            Bound::Unbounded => (self.last, self.nodes[self.last].keys.len()),
+        };
+        //the ends of an empty range may cross, let them meet instead
+        let crossed = match (bound_key(range.start_bound()), bound_key(range.end_bound())) {
+            (Some(start), Some(end)) => start > end || (front.0 == back.0 && front.1 > back.1),
+            _ => false,
+        };
+        if crossed {
+            back = front;
+        }
+        Range {
+            tree: self,
+            front,
+            back,
+        }
+    }
+
+    ///Checks that the keys are in order and separated correctly, every node but the root has between the minimum
+    ///and the maximum number of keys, all leaves are on the same level and the leaf list links all leaves in order.
+    ///Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut leaves = Vec::new();
+        self.check_node(self.root, (None, None), &mut leaves)?;
+        let mut chain = Vec::new();
+        let mut current = Some(self.first);
+        let mut prev = None;
+        while let Some(id) = current {
+            if self.nodes[id].prev != prev {
+                return Err(format!("leaf {} has a wrong previous leaf", id));
+            }
+            if chain.len() > leaves.len() {
+                return Err(String::from("the leaf list does not end"));
+            }
+            chain.push(id);
+            prev = current;
+            current = self.nodes[id].next;
+        }
+        if prev != Some(self.last) {
+            return Err(String::from("the last leaf is wrong"));
+        }
+        if chain != leaves.iter().map(|(id, _)| *id).collect::<Vec<_>>() {
+            return Err(String::from(
+                "the leaf list differs from the leaves of the tree",
+            ));
+        }
+        if leaves.iter().any(|(_, depth)| *depth != leaves[0].1) {
+            return Err(String::from("leaves are on different levels"));
+        }
+        let count: usize = leaves
+            .iter()
+            .map(|(id, _)| self.nodes[*id].keys.len())
+            .sum();
+        if count != self.length {
+            return Err(format!("length {} but {} entries", self.length, count));
+        }
+        Ok(())
+    }
+
+    ///Checks the subtree of the node `id` whose keys must be within `bounds` and appends its leaves with their
+    ///depth to `leaves`
+    fn check_node(
+        &self,
+        id: usize,
+        bounds: (Option<&K>, Option<&K>),
+        leaves: &mut Vec<(usize, usize)>,
+    ) -> Result<(), String> {
+        let node = &self.nodes[id];
+        let (lower, upper) = bounds;
+        let ordered = node.keys.windows(2).all(|pair| pair[0] < pair[1])
+            && node
+                .keys
+                .first()
+                .is_none_or(|first| lower.is_none_or(|lower| first >= lower))
+            && node
+                .keys
+                .last()
+                .is_none_or(|last| upper.is_none_or(|upper| last < upper));
+        if !ordered {
+            return Err(format!("keys of node {} are out of order", id));
+        }
+        let (min_keys, max_keys) = match (id == self.root, node.is_leaf()) {
+            (true, true) => (0, self.order - 1),
+            (true, false) => (1, self.order - 1),
+            (false, true) => (self.get_min_entries(), self.order - 1),
+            (false, false) => (self.get_min_keys(), self.order - 1),
+        };
+        if node.keys.len() < min_keys || node.keys.len() > max_keys {
+            return Err(format!("node {} has {} keys", id, node.keys.len()));
+        }
+        if node.is_leaf() {
+            if node.keys.len() != node.values.len() {
+                return Err(format!("keys and values of leaf {} differ in number", id));
+            }
+            leaves.push((id, 0));
+            return Ok(());
+        }
+        if node.children.len() != node.keys.len() + 1 || !node.values.is_empty() {
+            return Err(format!("inner node {} is malformed", id));
+        }
+        let start = leaves.len();
+        for (index, child) in node.children.iter().enumerate() {
+            let lower = if index == 0 {
+                lower
+            } else {
+                Some(&node.keys[index - 1])
+            };
+            let upper = node.keys.get(index).or(upper);
+            self.check_node(*child, (lower, upper), leaves)?;
+        }
+        for leaf in &mut leaves[start..] {
+            leaf.1 += 1;
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let nodes = &self.tree.nodes;
+        if self.front.1 == nodes[self.front.0].keys.len() {
+            //the end of a leaf is the start of the next one
+            self.front = (nodes[self.front.0].next?, 0);
+            if self.front == self.back {
+                return None;
+            }
+        }
+        let (leaf, index) = self.front;
+        self.front.1 += 1;
+        Some((&nodes[leaf].keys[index], &nodes[leaf].values[index]))
+    }
+}
+
+impl<'a, K, V> DoubleEndedIterator for Range<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        let nodes = &self.tree.nodes;
+        if self.back.1 == 0 {
+            let prev = nodes[self.back.0].prev?;
+            self.back = (prev, nodes[prev].keys.len());
+            if self.front == self.back {
+                return None;
+            }
+        }
+        self.back.1 -= 1;
+        let (leaf, index) = self.back;
+        Some((&nodes[leaf].keys[index], &nodes[leaf].values[index]))
+    }
+}
+
+impl<'a, K: Ord + Clone, V> IntoIterator for &'a BPlusTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Range<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_bplustree() {
+        let t: BPlusTree<i32, i32> = BPlusTree::new(3);
+        assert!(t.is_empty());
+        assert_eq!(0, t.get_height());
+        assert!(t.last_key_value().is_none());
+        assert_eq!(0, t.iter().count());
+        assert_eq!(0, t.iter().rev().count());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_insert_links_leaves() {
+        let mut t = BPlusTree::new(4);
+        for i in (0..30).rev() {
+            assert!(t.insert(i, i * 10).is_none());
+            t.check_invariants().unwrap();
+        }
+        assert_eq!(Some(50), t.insert(5, 0));
+        *t.get_mut(&29).unwrap() += 1;
+        assert_eq!(Some((&0, &0)), t.first_key_value());
+        assert_eq!(Some((&29, &291)), t.last_key_value());
+        assert!(t.contains_key(&5) && !t.contains_key(&30));
+        assert!((0..30).eq(t.iter().map(|(k, _)| *k)));
+        assert!((0..30).rev().eq(t.iter().rev().map(|(k, _)| *k)));
+    }
+
+    #[test]
+    fn test_remove_borrows_and_merges() {
+        for order in 3..8 {
+            let mut t = BPlusTree::new(order);
+            for i in 0..200 {
+                t.insert(i, i);
+            }
+            for i in (0..200).step_by(2).chain((1..200).step_by(2).rev()) {
+                assert_eq!(Some(i), t.remove(&i), "order {}", order);
+                assert!(t.remove(&i).is_none());
+                t.check_invariants().unwrap();
+            }
+            assert!(t.is_empty());
+            assert_eq!(0, t.get_height());
+            //the slots of the removed nodes are reused
+            let slots = t.nodes.len();
+            for i in 0..200 {
+                t.insert(i, i);
+            }
+            assert_eq!(slots, t.nodes.len());
+        }
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        for order in 3..9 {
+            for length in 0..120 {
+                let t = BPlusTree::from_sorted(order, (0..length).map(|i| (i, i * 2)));
+                t.check_invariants()
+                    .unwrap_or_else(|error| panic!("order {} length {}: {}", order, length, error));
+                assert_eq!(length, t.get_length());
+                assert!((0..length)
+                    .map(|i| (i, i * 2))
+                    .eq(t.iter().map(|(k, v)| (*k, *v))));
+            }
+        }
+        let mut t = BPlusTree::from_sorted(4, (0..50).map(|i| (i, ())));
+        assert!(t.remove(&25).is_some());
+        t.insert(100, ());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted() {
+        BPlusTree::from_sorted(3, vec![(1, ()), (1, ())]);
+    }
+
+    #[test]
+    fn test_range() {
+        let t = BPlusTree::from_sorted(4, (0..50).map(|i| (i * 2, ())));
+        let keys = |r: Range<'_, i32, ()>| r.map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(vec![4, 6, 8], keys(t.range(3..10)));
+        assert_eq!(vec![4, 6, 8, 10], keys(t.range(4..=10)));
+        assert_eq!(
+            vec![96, 98],
+            keys(t.range((Bound::Excluded(94), Bound::Unbounded)))
+        );
+        assert!(keys(t.range(5..6)).is_empty());
+        assert!(keys(t.range(..0)).is_empty());
+        assert!(keys(t.range((Bound::Included(10), Bound::Excluded(4)))).is_empty());
+        assert!(keys(t.range((Bound::Excluded(10), Bound::Excluded(10)))).is_empty());
+        let mut range = t.range(10..=20);
+        assert_eq!(Some((&10, &())), range.next());
+        assert_eq!(Some((&20, &())), range.next_back());
+        assert_eq!(vec![12, 14, 16, 18], keys(range));
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(41);
+        let mut t = BPlusTree::new(5);
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(400);
+            if rng.next_bool() {
+                assert_eq!(reference.remove(&key), t.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, key), t.insert(key, key));
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().eq(t.iter()));
+        assert!(reference.iter().rev().eq(t.iter().rev()));
+        for _ in 0..100 {
+            let (start, end) = (rng.next_below(400), rng.next_below(400));
+            let (start, end) = (start.min(end), start.max(end));
+            assert!(reference.range(start..end).eq(t.range(start..end)));
+            assert!(reference
+                .range(start..=end)
+                .rev()
+                .eq(t.range(start..=end).rev()));
+        }
+    }
+}
@@ -0,0 +1,448 @@
+//! This module implements an unbalanced binary search tree map. All operations walk the tree with loops instead of
+//! recursion: the depth first traversals keep their path on a `lifo::Lifo` and the level order traversal its
+//! frontier on a `fifo::Fifo`, so even a degenerate tree, which is a list of its keys, never overflows the stack.
+//! The tree implements Drop the same way.
+use fifo::fifo::Fifo;
+use lifo::lifo::Lifo;
+use std::cmp::Ordering;
+
+///Type to simplify variable declaration
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+///A node of the tree with its entry and its two subtrees
+struct Node<K, V> {
+    key: K,
+    value: V,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+///The binary search tree with its root and its number of entries
+pub struct Bst<K, V> {
+    root: Link<K, V>,
+    length: usize,
+}
+
+///In-order iterator over the entries of a `Bst`
+pub struct Iter<'a, K, V> {
+    //the nodes whose left subtree is being visited
+    stack: Lifo<&'a Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    ///Returns a new leaf
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+impl<K: Ord, V> Default for Bst<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for Bst<K, V> {
+    fn drop(&mut self) {
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.take() {
+            stack.enqueue(root);
+        }
+        while let Some(mut node) = stack.dequeue() {
+            if let Some(left) = node.left.take() {
+                stack.enqueue(left);
+            }
+            if let Some(right) = node.right.take() {
+                stack.enqueue(right);
+            }
+        }
+    }
+}
+
+impl<K: Ord, V> Bst<K, V> {
+    ///Returns a new empty tree
+    pub fn new() -> Bst<K, V> {
+        Bst {
+            root: None,
+            length: 0,
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        let mut height = 0;
+        let mut level = Fifo::new();
+        if let Some(root) = &self.root {
+            level.enqueue(root.as_ref());
+        }
+        while level.get_length() > 0 {
+            height += 1;
+            let mut next = Fifo::new();
+            while let Some(node) = level.dequeue() {
+                for child in [&node.left, &node.right].iter().copied().flatten() {
+                    next.enqueue(child.as_ref());
+                }
+            }
+            level = next;
+        }
+        height
+    }
+
+    ///Returns the link which holds the node of `key`, or the empty link where it would be inserted
+    fn find_link(&mut self, key: &K) -> &mut Link<K, V> {
+        let mut link = &mut self.root;
+        while link.as_ref().is_some_and(|node| node.key != *key) {
+            let node = link.as_mut().unwrap();
+            link = if *key < node.key {
+                &mut node.left
+            } else {
+                &mut node.right
+            };
+        }
+        link
+    }
+
+    ///Returns the node of `key`
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let link = self.find_link(&key);
+        match link {
+            Some(node) => Some(std::mem::replace(&mut node.value, value)),
+            None => {
+                *link = Some(Box::new(Node::new(key, value)));
+                self.length += 1;
+                None
+            }
+        }
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|node| &node.value)
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find_link(key).as_mut().map(|node| &mut node.value)
+    }
+
+    ///Returns true if the tree has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    ///Removes the entry of `key` and returns its value. A node with two children takes the entry of its
+    ///successor, which is removed from the right subtree instead.
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let link = self.find_link(key);
+        let node = link.as_mut()?;
+        let value = if node.left.is_some() && node.right.is_some() {
+            let mut min_link = &mut node.right;
+            while min_link.as_ref().unwrap().left.is_some() {
+                min_link = &mut min_link.as_mut().unwrap().left;
+            }
+            let mut min = min_link.take().unwrap();
+            *min_link = min.right.take();
+            std::mem::swap(&mut node.key, &mut min.key);
+            std::mem::replace(&mut node.value, min.value)
+        } else {
+            let mut node = link.take().unwrap();
+            *link = node.left.take().or_else(|| node.right.take());
+            node.value
+        };
+        self.length -= 1;
+        Some(value)
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn min(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn max(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the smallest key larger than `key`. `key` doesn't have to be in the tree.
+    /// # Attributes
+    /// * `key`- the key to start from
+    pub fn successor(&self, key: &K) -> Option<(&K, &V)> {
+        let mut best = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if node.key > *key {
+                best = Some(node);
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+        best.map(|node| (&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key smaller than `key`. `key` doesn't have to be in the tree.
+    /// # Attributes
+    /// * `key`- the key to start from
+    pub fn predecessor(&self, key: &K) -> Option<(&K, &V)> {
+        let mut best = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            if node.key < *key {
+                best = Some(node);
+                current = node.right.as_deref();
+            } else {
+                current = node.left.as_deref();
+            }
+        }
+        best.map(|node| (&node.key, &node.value))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Lifo::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Returns the keys in order: left subtree, node, right subtree
+    pub fn in_order(&self) -> Vec<&K> {
+        self.iter().map(|(key, _)| key).collect()
+    }
+
+    ///Returns the keys in pre-order: node, left subtree, right subtree
+    pub fn pre_order(&self) -> Vec<&K> {
+        let mut keys = Vec::with_capacity(self.length);
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.enqueue(root);
+        }
+        while let Some(node) = stack.dequeue() {
+            keys.push(&node.key);
+            //the right subtree goes first on the stack so the left one comes out first
+            if let Some(right) = node.right.as_deref() {
+                stack.enqueue(right);
+            }
+            if let Some(left) = node.left.as_deref() {
+                stack.enqueue(left);
+            }
+        }
+        keys
+    }
+
+    ///Returns the keys in post-order: left subtree, right subtree, node
+    pub fn post_order(&self) -> Vec<&K> {
+        //node, right subtree, left subtree is the reverse of the post-order
+        let mut keys = Vec::with_capacity(self.length);
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.enqueue(root);
+        }
+        while let Some(node) = stack.dequeue() {
+            keys.push(&node.key);
+            if let Some(left) = node.left.as_deref() {
+                stack.enqueue(left);
+            }
+            if let Some(right) = node.right.as_deref() {
+                stack.enqueue(right);
+            }
+        }
+        keys.reverse();
+        keys
+    }
+
+    ///Returns the keys level by level from the root, every level from left to right
+    pub fn level_order(&self) -> Vec<&K> {
+        let mut keys = Vec::with_capacity(self.length);
+        let mut queue = Fifo::new();
+        if let Some(root) = self.root.as_deref() {
+            queue.enqueue(root);
+        }
+        while let Some(node) = queue.dequeue() {
+            keys.push(&node.key);
+            if let Some(left) = node.left.as_deref() {
+                queue.enqueue(left);
+            }
+            if let Some(right) = node.right.as_deref() {
+                queue.enqueue(right);
+            }
+        }
+        keys
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    ///Pushes `node` and its chain of left children on the stack
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.enqueue(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.dequeue()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///        8
+    ///      /   \
+    ///     3     10
+    ///    / \      \
+    ///   1   6      14
+    ///      / \    /
+    ///     4   7  13
+    fn sample() -> Bst<i32, String> {
+        let mut t = Bst::new();
+        for key in &[8, 3, 10, 1, 6, 14, 4, 7, 13] {
+            assert!(t.insert(*key, key.to_string()).is_none());
+        }
+        t
+    }
+
+    #[test]
+    fn test_new_bst() {
+        let t: Bst<i32, i32> = Bst::new();
+        assert!(t.is_empty());
+        assert_eq!(0, t.get_height());
+        assert!(t.min().is_none());
+        assert!(t.successor(&1).is_none());
+        assert!(t.in_order().is_empty());
+    }
+
+    #[test]
+    fn test_insert_and_get() {
+        let mut t = sample();
+        assert_eq!(9, t.get_length());
+        assert_eq!(4, t.get_height());
+        assert_eq!(Some(&String::from("6")), t.get(&6));
+        assert!(t.get(&5).is_none());
+        assert_eq!(Some(String::from("6")), t.insert(6, String::from("six")));
+        t.get_mut(&6).unwrap().push('!');
+        assert_eq!(Some(&String::from("six!")), t.get(&6));
+        assert_eq!(9, t.get_length());
+        assert!(t.contains_key(&13));
+    }
+
+    #[test]
+    fn test_traversals() {
+        let t = sample();
+        assert_eq!(vec![&1, &3, &4, &6, &7, &8, &10, &13, &14], t.in_order());
+        assert_eq!(vec![&8, &3, &1, &6, &4, &7, &10, &14, &13], t.pre_order());
+        assert_eq!(vec![&1, &4, &7, &6, &3, &13, &14, &10, &8], t.post_order());
+        assert_eq!(vec![&8, &3, &10, &1, &6, &14, &4, &7, &13], t.level_order());
+        let values: Vec<&String> = t.iter().map(|(_, value)| value).collect();
+        assert_eq!(&String::from("1"), values[0]);
+    }
+
+    #[test]
+    fn test_min_max_successor_predecessor() {
+        let t = sample();
+        assert_eq!(Some(&1), t.min().map(|(key, _)| key));
+        assert_eq!(Some(&14), t.max().map(|(key, _)| key));
+        assert_eq!(Some(&8), t.successor(&7).map(|(key, _)| key));
+        assert_eq!(Some(&10), t.successor(&9).map(|(key, _)| key));
+        assert!(t.successor(&14).is_none());
+        assert_eq!(Some(&7), t.predecessor(&8).map(|(key, _)| key));
+        assert_eq!(Some(&13), t.predecessor(&14).map(|(key, _)| key));
+        assert!(t.predecessor(&1).is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut t = sample();
+        //a leaf, a node with one child and nodes with two children
+        assert_eq!(Some(String::from("13")), t.remove(&13));
+        assert_eq!(Some(String::from("10")), t.remove(&10));
+        assert_eq!(Some(String::from("3")), t.remove(&3));
+        assert_eq!(Some(String::from("8")), t.remove(&8));
+        assert!(t.remove(&8).is_none());
+        assert_eq!(5, t.get_length());
+        assert_eq!(vec![&1, &4, &6, &7, &14], t.in_order());
+        assert_eq!(Some(&String::from("4")), t.get(&4));
+        for key in &[1, 4, 6, 7, 14] {
+            assert!(t.remove(key).is_some());
+        }
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_degenerate_tree_does_not_overflow() {
+        let n = 1_000_000;
+        //a tree built from sorted keys is a chain of right children
+        let mut t = Bst::new();
+        for key in (0..n).rev() {
+            let mut node = Box::new(Node::new(key, ()));
+            node.right = t.root.take();
+            t.root = Some(node);
+        }
+        t.length = n;
+        assert_eq!(n, t.get_height());
+        assert_eq!(n, t.in_order().len());
+        assert_eq!(Some(&0), t.pre_order().first().copied());
+        assert_eq!(Some(&0), t.post_order().last().copied());
+        assert_eq!(Some(&(n - 1)), t.level_order().last().copied());
+        assert_eq!(Some((&(n - 1), &())), t.max());
+        assert!(t.remove(&0).is_some());
+        assert!(t.insert(n, ()).is_none());
+    }
+}
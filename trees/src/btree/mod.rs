@@ -0,0 +1,653 @@
+//! This module implements a B-tree map with a configurable order, the maximum number of children of a node.
+//! Every node but the root holds between `ceil(order / 2) - 1` and `order - 1` sorted entries and all leaves are on
+//! the same level. An insertion splits a full node on the way back to the root, a removal refills a node which fell
+//! below the minimum by borrowing an entry from a sibling or by merging it with a sibling.
+//! A tree can also be bulk loaded from sorted entries level by level, without any splits.
+use std::ops::{Bound, RangeBounds};
+
+///A node of the tree with its sorted entries and, unless it is a leaf, one child more than entries
+struct Node<K, V> {
+    keys: Vec<K>,
+    values: Vec<V>,
+    children: Vec<Node<K, V>>,
+}
+
+///The B-tree with its root, its order and its number of entries
+pub struct BTree<K, V> {
+    root: Node<K, V>,
+    order: usize,
+    length: usize,
+}
+
+///In-order iterator over the entries of a `BTree`
+pub struct Iter<'a, K, V> {
+    //the path to the next entry: a node and the index of its next entry
+    stack: Vec<(&'a Node<K, V>, usize)>,
+}
+
+///In-order iterator over the entries of a `BTree` within a range of keys
+pub struct Range<'a, K, V> {
+    iter: Iter<'a, K, V>,
+    //the key of the last entry in the range
+    last: Option<&'a K>,
+}
+
+///The result of an insertion below a node
+enum Insertion<K, V> {
+    Replaced(V),
+    Added,
+    //the node was split into itself, the separating entry and the new right node
+    Split(K, V, Node<K, V>),
+}
+
+impl<K, V> Clone for Iter<'_, K, V> {
+    fn clone(&self) -> Self {
+        Iter {
+            stack: self.stack.clone(),
+        }
+    }
+}
+
+impl<K, V> Node<K, V> {
+    fn new() -> Node<K, V> {
+        Node {
+            keys: Vec::new(),
+            values: Vec::new(),
+            children: Vec::new(),
+        }
+    }
+
+    fn is_leaf(&self) -> bool {
+        self.children.is_empty()
+    }
+
+    ///Splits the node at its middle entry and returns the middle entry and the right half
+    fn split(&mut self) -> (K, V, Node<K, V>) {
+        let middle = self.keys.len() / 2;
+        let right = Node {
+            keys: self.keys.split_off(middle + 1),
+            values: self.values.split_off(middle + 1),
+            children: if self.is_leaf() {
+                Vec::new()
+            } else {
+                self.children.split_off(middle + 1)
+            },
+        };
+        let key = self.keys.pop().unwrap();
+        let value = self.values.pop().unwrap();
+        (key, value, right)
+    }
+}
+
+///Returns the sizes of `groups` groups which share `total` items as evenly as possible
+pub(crate) fn group_sizes(total: usize, groups: usize) -> impl Iterator<Item = usize> {
+    (0..groups).map(move |group| total / groups + usize::from(group < total % groups))
+}
+
+///Inserts `value` under `key` below `node` whose nodes have up to `order` children
+fn insert_below<K: Ord, V>(
+    node: &mut Node<K, V>,
+    key: K,
+    value: V,
+    order: usize,
+) -> Insertion<K, V> {
+    let index = match node.keys.binary_search(&key) {
+        Ok(index) => return Insertion::Replaced(std::mem::replace(&mut node.values[index], value)),
+        Err(index) => index,
+    };
+    if node.is_leaf() {
+        node.keys.insert(index, key);
+        node.values.insert(index, value);
+    } else {
+        match insert_below(&mut node.children[index], key, value, order) {
+            Insertion::Split(key, value, right) => {
+                node.keys.insert(index, key);
+                node.values.insert(index, value);
+                node.children.insert(index + 1, right);
+            }
+            result => return result,
+        }
+    }
+    if node.keys.len() < order {
+        return Insertion::Added;
+    }
+    let (key, value, right) = node.split();
+    Insertion::Split(key, value, right)
+}
+
+///Refills the child `index` of `node` if it has fewer than `min_keys` entries, by borrowing an entry from a
+///sibling which can spare one or else by merging it with a sibling
+fn refill<K, V>(node: &mut Node<K, V>, index: usize, min_keys: usize) {
+    if node.children[index].keys.len() >= min_keys {
+        return;
+    }
+    if index > 0 && node.children[index - 1].keys.len() > min_keys {
+        //the last entry of the left sibling goes up, the separator comes down
+        let (left, right) = node.children.split_at_mut(index);
+        let (left, child) = (left.last_mut().unwrap(), &mut right[0]);
+        let key = std::mem::replace(&mut node.keys[index - 1], left.keys.pop().unwrap());
+        let value = std::mem::replace(&mut node.values[index - 1], left.values.pop().unwrap());
+        child.keys.insert(0, key);
+        child.values.insert(0, value);
+        if let Some(grandchild) = left.children.pop() {
+            child.children.insert(0, grandchild);
+        }
+    } else if index + 1 < node.children.len() && node.children[index + 1].keys.len() > min_keys {
+        let (left, right) = node.children.split_at_mut(index + 1);
+        let (child, right) = (left.last_mut().unwrap(), &mut right[0]);
+        let key = std::mem::replace(&mut node.keys[index], right.keys.remove(0));
+        let value = std::mem::replace(&mut node.values[index], right.values.remove(0));
+        child.keys.push(key);
+        child.values.push(value);
+        if !right.is_leaf() {
+            child.children.push(right.children.remove(0));
+        }
+    } else {
+        //both siblings have the minimum, merge with one of them around their separator
+        let left_index = if index > 0 { index - 1 } else { index };
+        let mut right = node.children.remove(left_index + 1);
+        let left = &mut node.children[left_index];
+        left.keys.push(node.keys.remove(left_index));
+        left.values.push(node.values.remove(left_index));
+        left.keys.append(&mut right.keys);
+        left.values.append(&mut right.values);
+        left.children.append(&mut right.children);
+    }
+}
+
+///Removes the entry with the largest key below `node` and returns it
+fn remove_max<K, V>(node: &mut Node<K, V>, min_keys: usize) -> (K, V) {
+    if node.is_leaf() {
+        return (node.keys.pop().unwrap(), node.values.pop().unwrap());
+    }
+    let last = node.children.len() - 1;
+    let entry = remove_max(&mut node.children[last], min_keys);
+    refill(node, last, min_keys);
+    entry
+}
+
+///Removes the entry of `key` below `node` and returns its value
+fn remove_below<K: Ord, V>(node: &mut Node<K, V>, key: &K, min_keys: usize) -> Option<V> {
+    match node.keys.binary_search(key) {
+        Ok(index) if node.is_leaf() => {
+            node.keys.remove(index);
+            Some(node.values.remove(index))
+        }
+        Ok(index) => {
+            //the predecessor from the left subtree takes the place of the entry
+            let (key, value) = remove_max(&mut node.children[index], min_keys);
+            node.keys[index] = key;
+            let removed = std::mem::replace(&mut node.values[index], value);
+            refill(node, index, min_keys);
+            Some(removed)
+        }
+        Err(index) => {
+            let removed = remove_below(node.children.get_mut(index)?, key, min_keys);
+            refill(node, index, min_keys);
+            removed
+        }
+    }
+}
+
+///Checks the order, the sizes and the depth of the subtree of `node`, which itself holds at least `own_min` entries
+///and its descendants at least `min_keys`. Returns its number of entries and the depth of its leaves.
+fn check_node<K: Ord, V>(
+    node: &Node<K, V>,
+    bounds: (Option<&K>, Option<&K>),
+    own_min: usize,
+    min_keys: usize,
+    max_keys: usize,
+) -> Result<(usize, usize), String> {
+    let (lower, upper) = bounds;
+    if node.keys.len() < own_min || node.keys.len() > max_keys {
+        return Err(format!("node has {} entries", node.keys.len()));
+    }
+    if node.keys.len() != node.values.len() {
+        return Err(String::from("keys and values differ in number"));
+    }
+    let ordered = node.keys.windows(2).all(|pair| pair[0] < pair[1])
+        && node
+            .keys
+            .first()
+            .is_none_or(|first| lower.is_none_or(|lower| first > lower))
+        && node
+            .keys
+            .last()
+            .is_none_or(|last| upper.is_none_or(|upper| last < upper));
+    if !ordered {
+        return Err(String::from("keys are out of order"));
+    }
+    if node.is_leaf() {
+        return Ok((node.keys.len(), 1));
+    }
+    if node.children.len() != node.keys.len() + 1 {
+        return Err(format!(
+            "{} children for {} entries",
+            node.children.len(),
+            node.keys.len()
+        ));
+    }
+    let mut count = node.keys.len();
+    let mut depth = None;
+    for (index, child) in node.children.iter().enumerate() {
+        let lower = if index == 0 {
+            lower
+        } else {
+            Some(&node.keys[index - 1])
+        };
+        let upper = node.keys.get(index).or(upper);
+        let (child_count, child_depth) =
+            check_node(child, (lower, upper), min_keys, min_keys, max_keys)?;
+        if depth.is_some_and(|depth| depth != child_depth) {
+            return Err(String::from("leaves are on different levels"));
+        }
+        depth = Some(child_depth);
+        count += child_count;
+    }
+    Ok((count, depth.unwrap() + 1))
+}
+
+impl<K: Ord, V> BTree<K, V> {
+    ///Returns a new empty tree whose nodes have up to `order` children. Panics if `order` is below 3.
+    /// # Attributes
+    /// * `order`- the maximum number of children of a node
+    pub fn new(order: usize) -> BTree<K, V> {
+        assert!(order >= 3, "order {} is below 3", order);
+        BTree {
+            root: Node::new(),
+            order,
+            length: 0,
+        }
+    }
+
+    ///Returns a new tree with the entries of `entries`, which must come with strictly increasing keys.
+    ///The nodes are filled evenly level by level from the leaves up. Panics if the keys are not increasing.
+    /// # Attributes
+    /// * `order`- the maximum number of children of a node
+    /// * `entries`- the entries in the order of their keys
+    pub fn from_sorted<I: IntoIterator<Item = (K, V)>>(order: usize, entries: I) -> BTree<K, V> {
+        let mut tree = BTree::new(order);
+        let mut entries: Vec<(K, V)> = entries.into_iter().collect();
+        assert!(
+            entries.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "keys are not strictly increasing"
+        );
+        tree.length = entries.len();
+        //every leaf but the last is followed by the entry which separates it from the next leaf
+        let leaf_count = (entries.len() + 1).div_ceil(order);
+        let mut level = Vec::with_capacity(leaf_count);
+        let mut separators = Vec::with_capacity(leaf_count);
+        let mut rest = entries.drain(..);
+        for size in group_sizes(tree.length + 1 - leaf_count, leaf_count) {
+            let mut leaf = Node::new();
+            for (key, value) in rest.by_ref().take(size) {
+                leaf.keys.push(key);
+                leaf.values.push(value);
+            }
+            level.push(leaf);
+            separators.extend(rest.next());
+        }
+        drop(rest);
+        while level.len() > 1 {
+            let parent_count = level.len().div_ceil(order);
+            let mut children = level.into_iter();
+            let mut entries = separators.into_iter();
+            level = Vec::with_capacity(parent_count);
+            separators = Vec::with_capacity(parent_count);
+            for size in group_sizes(children.len(), parent_count) {
+                let mut parent = Node::new();
+                parent.children.extend(children.by_ref().take(size));
+                for (key, value) in entries.by_ref().take(size - 1) {
+                    parent.keys.push(key);
+                    parent.values.push(value);
+                }
+                level.push(parent);
+                separators.extend(entries.next());
+            }
+        }
+        if let Some(root) = level.pop() {
+            tree.root = root;
+        }
+        tree
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the maximum number of children of a node
+    pub fn get_order(&self) -> usize {
+        self.order
+    }
+
+    ///Returns the number of levels of nodes, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let mut height = 1;
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            height += 1;
+            node = child;
+        }
+        height
+    }
+
+    ///Returns the minimum number of entries of a node other than the root
+    fn get_min_keys(&self) -> usize {
+        self.order.div_ceil(2) - 1
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut node = &self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(index) => return Some(&node.values[index]),
+                Err(index) => node = node.children.get(index)?,
+            }
+        }
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        loop {
+            match node.keys.binary_search(key) {
+                Ok(index) => return Some(&mut node.values[index]),
+                Err(index) => node = node.children.get_mut(index)?,
+            }
+        }
+    }
+
+    ///Returns true if the tree has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match insert_below(&mut self.root, key, value, self.order) {
+            Insertion::Replaced(old) => return Some(old),
+            Insertion::Added => {}
+            Insertion::Split(key, value, right) => {
+                //the root was split, the tree grows by one level
+                let left = std::mem::replace(&mut self.root, Node::new());
+                self.root.keys.push(key);
+                self.root.values.push(value);
+                self.root.children.push(left);
+                self.root.children.push(right);
+            }
+        }
+        self.length += 1;
+        None
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let min_keys = self.get_min_keys();
+        let removed = remove_below(&mut self.root, key, min_keys)?;
+        if self.root.keys.is_empty() && !self.root.is_leaf() {
+            //the last two children of the root were merged, the tree shrinks by one level
+            self.root = self.root.children.pop().unwrap();
+        }
+        self.length -= 1;
+        Some(removed)
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.first() {
+            node = child;
+        }
+        Some((node.keys.first()?, node.values.first()?))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = &self.root;
+        while let Some(child) = node.children.last() {
+            node = child;
+        }
+        Some((node.keys.last()?, node.values.last()?))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..).iter
+    }
+
+    ///Returns an iterator over the entries whose keys are in `range`, in the order of their keys
+    /// # Attributes
+    /// * `range`- the range of the keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        //every node on the path continues with its first entry in the range
+        let mut current = Some(&self.root);
+        while let Some(node) = current {
+            let index = node.keys.partition_point(|key| match range.start_bound() {
+                Bound::Included(start) => key < start,
+                Bound::Excluded(start) => key <= start,
+                Bound::Unbounded => false,
+            });
+            iter.stack.push((node, index));
+            current = node.children.get(index);
+        }
+        let mut last = None;
+        let mut current = Some(&self.root);
+        while let Some(node) = current {
+            let index = node.keys.partition_point(|key| match range.end_bound() {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
+            });
+            if index > 0 {
+                last = Some(&node.keys[index - 1]);
+            }
+            current = node.children.get(index);
+        }
+        let first = iter.clone().next().map(|(key, _)| key);
+        let empty = match (first, last) {
+            (Some(first), Some(last)) => first > last,
+            _ => true,
+        };
+        if empty {
+            iter.stack.clear();
+        }
+        Range { iter, last }
+    }
+
+    ///Checks that the keys are in order, every node but the root has between the minimum and the maximum number of
+    ///entries, every inner node has one child more than entries and all leaves are on the same level.
+    ///Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let root_min = usize::from(!self.root.is_leaf());
+        let (count, _) = check_node(
+            &self.root,
+            (None, None),
+            root_min,
+            self.get_min_keys(),
+            self.order - 1,
+        )?;
+        if count != self.length {
+            return Err(format!("length {} but {} entries", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, index) = self.stack.pop()?;
+            if index == node.keys.len() {
+                continue;
+            }
+            self.stack.push((node, index + 1));
+            //the subtree right of the entry comes next, from its smallest key
+            let mut current = node.children.get(index + 1);
+            while let Some(child) = current {
+                self.stack.push((child, 0));
+                current = child.children.first();
+            }
+            return Some((&node.keys[index], &node.values[index]));
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        if self.last.is_some_and(|last| std::ptr::eq(last, key)) {
+            self.iter.stack.clear();
+        }
+        Some((key, value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a BTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_btree() {
+        let t: BTree<i32, i32> = BTree::new(3);
+        assert!(t.is_empty());
+        assert_eq!(0, t.get_height());
+        assert!(t.first_key_value().is_none());
+        assert_eq!(0, t.iter().count());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_order_below_three() {
+        let _: BTree<i32, i32> = BTree::new(2);
+    }
+
+    #[test]
+    fn test_insert_splits() {
+        let mut t = BTree::new(3);
+        for i in 0..7 {
+            assert!(t.insert(i, i * 10).is_none());
+            t.check_invariants().unwrap();
+        }
+        //a full binary tree of 7 entries
+        assert_eq!(3, t.get_height());
+        assert_eq!(vec![&3], t.root.keys.iter().collect::<Vec<_>>());
+        assert_eq!(Some(30), t.insert(3, 0));
+        assert_eq!(Some(&0), t.get(&3));
+        *t.get_mut(&6).unwrap() += 1;
+        assert_eq!(Some((&6, &61)), t.last_key_value());
+        assert_eq!(Some((&0, &0)), t.first_key_value());
+    }
+
+    #[test]
+    fn test_remove_borrows_and_merges() {
+        for order in 3..8 {
+            let mut t = BTree::new(order);
+            for i in 0..200 {
+                t.insert(i, i);
+            }
+            for i in (0..200).step_by(2).chain((1..200).step_by(2).rev()) {
+                assert_eq!(Some(i), t.remove(&i), "order {}", order);
+                assert!(t.remove(&i).is_none());
+                t.check_invariants().unwrap();
+            }
+            assert!(t.is_empty());
+            assert_eq!(0, t.get_height());
+        }
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        for order in 3..9 {
+            for length in 0..120 {
+                let t = BTree::from_sorted(order, (0..length).map(|i| (i, i * 2)));
+                t.check_invariants()
+                    .unwrap_or_else(|error| panic!("order {} length {}: {}", order, length, error));
+                assert_eq!(length, t.get_length());
+                assert!((0..length)
+                    .map(|i| (i, i * 2))
+                    .eq(t.iter().map(|(k, v)| (*k, *v))));
+            }
+        }
+        let mut t = BTree::from_sorted(4, (0..50).map(|i| (i, ())));
+        assert!(t.remove(&25).is_some());
+        t.insert(100, ());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted() {
+        BTree::from_sorted(3, vec![(2, ()), (1, ())]);
+    }
+
+    #[test]
+    fn test_range() {
+        let t = BTree::from_sorted(4, (0..50).map(|i| (i * 2, ())));
+        let keys = |r: Range<'_, i32, ()>| r.map(|(key, _)| *key).collect::<Vec<_>>();
+        assert_eq!(vec![4, 6, 8], keys(t.range(3..10)));
+        assert_eq!(vec![4, 6, 8, 10], keys(t.range(4..=10)));
+        assert_eq!(
+            vec![96, 98],
+            keys(t.range((Bound::Excluded(94), Bound::Unbounded)))
+        );
+        assert!(keys(t.range(5..6)).is_empty());
+        assert!(keys(t.range(..0)).is_empty());
+        assert_eq!(50, t.range(..).count());
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(41);
+        let mut t = BTree::new(5);
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(400);
+            if rng.next_bool() {
+                assert_eq!(reference.remove(&key), t.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, key), t.insert(key, key));
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().eq(t.iter()));
+        assert!(reference.range(50..=150).eq(t.range(50..=150)));
+    }
+}
@@ -0,0 +1,323 @@
+//! This module implements Fenwick trees, also called binary indexed trees, for prefix sums of integers under point
+//! updates. The entry `i` of the tree holds the sum of the `i & -i` values ending at position `i`, counting from 1,
+//! so both adding to a position and summing a prefix touch O(log n) entries.
+//! `FenwickTree` also finds the shortest prefix reaching a sum by descending its implicit tree, and `FenwickTree2D`
+//! sums rectangles of a grid.
+use crate::segment_tree::to_positions;
+use std::ops::RangeBounds;
+
+///A Fenwick tree over a sequence of integers
+pub struct FenwickTree {
+    //the entry 0 is unused
+    entries: Vec<i64>,
+}
+
+///A Fenwick tree over a grid of integers
+pub struct FenwickTree2D {
+    //the row 0 and the column 0 are unused
+    entries: Vec<Vec<i64>>,
+}
+
+///Returns the lowest set bit of `index`, the number of positions the entry `index` covers
+fn lowest_bit(index: usize) -> usize {
+    index & index.wrapping_neg()
+}
+
+impl FenwickTree {
+    ///Returns a new tree of `length` zeros
+    /// # Attributes
+    /// * `length`- the number of positions
+    pub fn new(length: usize) -> FenwickTree {
+        FenwickTree {
+            entries: vec![0; length + 1],
+        }
+    }
+
+    ///Returns a new tree holding `values`, built in O(n)
+    /// # Attributes
+    /// * `values`- the values of the positions
+    pub fn from_slice(values: &[i64]) -> FenwickTree {
+        let mut entries = vec![0];
+        entries.extend_from_slice(values);
+        for index in 1..entries.len() {
+            //every entry passes its sum on to the entry which covers it next
+            let parent = index + lowest_bit(index);
+            if parent < entries.len() {
+                entries[parent] += entries[index];
+            }
+        }
+        FenwickTree { entries }
+    }
+
+    ///Returns the number of positions
+    pub fn get_length(&self) -> usize {
+        self.entries.len() - 1
+    }
+
+    ///Returns true if the tree has no positions
+    pub fn is_empty(&self) -> bool {
+        self.get_length() == 0
+    }
+
+    ///Adds `delta` to the value at `index`. Panics if `index` is out of bounds.
+    /// # Attributes
+    /// * `index`- the position of the value
+    /// * `delta`- the amount to add
+    pub fn add(&mut self, index: usize, delta: i64) {
+        assert!(
+            index < self.get_length(),
+            "index {} is out of bounds for length {}",
+            index,
+            self.get_length()
+        );
+        let mut entry = index + 1;
+        while entry < self.entries.len() {
+            self.entries[entry] += delta;
+            entry += lowest_bit(entry);
+        }
+    }
+
+    ///Returns the sum of the first `end` values. Panics if `end` is larger than the length.
+    /// # Attributes
+    /// * `end`- the number of values to sum
+    pub fn prefix_sum(&self, end: usize) -> i64 {
+        assert!(
+            end <= self.get_length(),
+            "prefix {} is out of bounds for length {}",
+            end,
+            self.get_length()
+        );
+        let mut sum = 0;
+        let mut entry = end;
+        while entry > 0 {
+            sum += self.entries[entry];
+            entry -= lowest_bit(entry);
+        }
+        sum
+    }
+
+    ///Returns the sum of the values in `range`. Panics if the range is out of bounds.
+    /// # Attributes
+    /// * `range`- the positions to sum
+    pub fn range_sum<B: RangeBounds<usize>>(&self, range: B) -> i64 {
+        let (start, end) = to_positions(&range, self.get_length());
+        self.prefix_sum(end) - self.prefix_sum(start)
+    }
+
+    ///Returns the value at `index`
+    /// # Attributes
+    /// * `index`- the position of the value
+    pub fn get(&self, index: usize) -> Option<i64> {
+        if index < self.get_length() {
+            Some(self.range_sum(index..=index))
+        } else {
+            None
+        }
+    }
+
+    ///Returns the smallest `end` whose prefix sum of the first `end` values is at least `target`, or None if the
+    ///sum of all values is smaller. The values must not be negative, so that the prefix sums do not decrease.
+    /// # Attributes
+    /// * `target`- the sum to reach
+    pub fn lower_bound(&self, target: i64) -> Option<usize> {
+        if target <= 0 {
+            return Some(0);
+        }
+        //descend from the largest power of two, taking every entry which keeps the sum below the target
+        let mut position = 0;
+        let mut remaining = target;
+        let mut step = self.get_length().checked_next_power_of_two()?;
+        while step > 0 {
+            let next = position + step;
+            if next < self.entries.len() && self.entries[next] < remaining {
+                position = next;
+                remaining -= self.entries[next];
+            }
+            step /= 2;
+        }
+        if position < self.get_length() {
+            Some(position + 1)
+        } else {
+            None
+        }
+    }
+}
+
+impl FenwickTree2D {
+    ///Returns a new tree of `rows` by `columns` zeros
+    /// # Attributes
+    /// * `rows`- the number of rows
+    /// * `columns`- the number of columns
+    pub fn new(rows: usize, columns: usize) -> FenwickTree2D {
+        FenwickTree2D {
+            entries: vec![vec![0; columns + 1]; rows + 1],
+        }
+    }
+
+    ///Returns the number of rows
+    pub fn get_rows(&self) -> usize {
+        self.entries.len() - 1
+    }
+
+    ///Returns the number of columns
+    pub fn get_columns(&self) -> usize {
+        self.entries[0].len() - 1
+    }
+
+    ///Adds `delta` to the value at `row` and `column`. Panics if the cell is out of bounds.
+    /// # Attributes
+    /// * `row`- the row of the value
+    /// * `column`- the column of the value
+    /// * `delta`- the amount to add
+    pub fn add(&mut self, row: usize, column: usize, delta: i64) {
+        assert!(
+            row < self.get_rows() && column < self.get_columns(),
+            "cell ({}, {}) is out of bounds",
+            row,
+            column
+        );
+        let mut i = row + 1;
+        while i < self.entries.len() {
+            let mut j = column + 1;
+            while j < self.entries[i].len() {
+                self.entries[i][j] += delta;
+                j += lowest_bit(j);
+            }
+            i += lowest_bit(i);
+        }
+    }
+
+    ///Returns the sum of the values in the first `rows` rows and the first `columns` columns. Panics if the
+    ///rectangle is out of bounds.
+    /// # Attributes
+    /// * `rows`- the number of rows to sum
+    /// * `columns`- the number of columns to sum
+    pub fn prefix_sum(&self, rows: usize, columns: usize) -> i64 {
+        assert!(
+            rows <= self.get_rows() && columns <= self.get_columns(),
+            "prefix ({}, {}) is out of bounds",
+            rows,
+            columns
+        );
+        let mut sum = 0;
+        let mut i = rows;
+        while i > 0 {
+            let mut j = columns;
+            while j > 0 {
+                sum += self.entries[i][j];
+                j -= lowest_bit(j);
+            }
+            i -= lowest_bit(i);
+        }
+        sum
+    }
+
+    ///Returns the sum of the values in the rectangle of `rows` and `columns`. Panics if the rectangle is out of
+    ///bounds.
+    /// # Attributes
+    /// * `rows`- the rows to sum
+    /// * `columns`- the columns to sum
+    pub fn rectangle_sum<R: RangeBounds<usize>, C: RangeBounds<usize>>(
+        &self,
+        rows: R,
+        columns: C,
+    ) -> i64 {
+        let (top, bottom) = to_positions(&rows, self.get_rows());
+        let (left, right) = to_positions(&columns, self.get_columns());
+        self.prefix_sum(bottom, right) - self.prefix_sum(top, right) - self.prefix_sum(bottom, left)
+            + self.prefix_sum(top, left)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    #[test]
+    fn test_prefix_and_range_sums() {
+        let mut t = FenwickTree::from_slice(&[3, 1, 4, 1, 5, 9, 2, 6]);
+        assert_eq!(8, t.get_length());
+        assert_eq!(0, t.prefix_sum(0));
+        assert_eq!(8, t.prefix_sum(3));
+        assert_eq!(31, t.range_sum(..));
+        assert_eq!(15, t.range_sum(3..6));
+        t.add(4, -5);
+        assert_eq!(Some(0), t.get(4));
+        assert_eq!(10, t.range_sum(3..=5));
+        assert!(t.get(8).is_none());
+        let built = FenwickTree::new(0);
+        assert!(built.is_empty());
+        assert_eq!(0, built.range_sum(..));
+    }
+
+    #[test]
+    fn test_from_slice_equals_adds() {
+        let mut rng = XorShift64::new(44);
+        let values: Vec<i64> = (0..100).map(|_| rng.next_below(50) as i64 - 25).collect();
+        let built = FenwickTree::from_slice(&values);
+        let mut added = FenwickTree::new(values.len());
+        for (index, value) in values.iter().enumerate() {
+            added.add(index, *value);
+        }
+        assert_eq!(built.entries, added.entries);
+    }
+
+    #[test]
+    fn test_lower_bound() {
+        let t = FenwickTree::from_slice(&[2, 0, 3, 1, 0, 4]);
+        assert_eq!(Some(0), t.lower_bound(0));
+        assert_eq!(Some(1), t.lower_bound(1));
+        assert_eq!(Some(1), t.lower_bound(2));
+        assert_eq!(Some(3), t.lower_bound(3));
+        assert_eq!(Some(4), t.lower_bound(6));
+        assert_eq!(Some(6), t.lower_bound(7));
+        assert_eq!(Some(6), t.lower_bound(10));
+        assert!(t.lower_bound(11).is_none());
+        let mut rng = XorShift64::new(45);
+        let values: Vec<i64> = (0..77).map(|_| rng.next_below(5) as i64).collect();
+        let t = FenwickTree::from_slice(&values);
+        for target in 1..=t.prefix_sum(77) {
+            let expected = (0..=77).find(|end| t.prefix_sum(*end) >= target);
+            assert_eq!(expected, t.lower_bound(target));
+        }
+    }
+
+    #[test]
+    fn test_2d_against_naive() {
+        let mut rng = XorShift64::new(46);
+        let (rows, columns) = (9, 13);
+        let mut grid = vec![vec![0i64; columns]; rows];
+        let mut t = FenwickTree2D::new(rows, columns);
+        for _ in 0..300 {
+            let (row, column) = (
+                rng.next_below(rows as u64) as usize,
+                rng.next_below(columns as u64) as usize,
+            );
+            let delta = rng.next_below(20) as i64 - 10;
+            grid[row][column] += delta;
+            t.add(row, column, delta);
+            let (a, b) = (
+                rng.next_below(rows as u64 + 1) as usize,
+                rng.next_below(rows as u64 + 1) as usize,
+            );
+            let (c, d) = (
+                rng.next_below(columns as u64 + 1) as usize,
+                rng.next_below(columns as u64 + 1) as usize,
+            );
+            let (top, bottom, left, right) = (a.min(b), a.max(b), c.min(d), c.max(d));
+            let expected: i64 = grid[top..bottom]
+                .iter()
+                .map(|row| row[left..right].iter().sum::<i64>())
+                .sum();
+            assert_eq!(expected, t.rectangle_sum(top..bottom, left..right));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_add_out_of_bounds() {
+        let mut t = FenwickTree::new(3);
+        t.add(3, 1);
+    }
+}
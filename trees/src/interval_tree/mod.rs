@@ -0,0 +1,603 @@
+//! This module implements an interval tree map, an AVL tree ordered by the start and then the end of half-open
+//! intervals in which every node also stores the largest end in its subtree. A query skips every subtree whose
+//! largest end is not after the query start and stops at the first node starting at or after the query end, so
+//! finding the k intervals which overlap a point or an interval takes O(log n + k).
+//! The rotations of the AVL tree recompute the largest ends together with the heights.
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+///A half-open interval `start..end` of an ordered type
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Interval<T> {
+    pub start: T,
+    pub end: T,
+}
+
+///Type to simplify variable declaration
+type Link<T, V> = Option<Box<Node<T, V>>>;
+
+///A node of the tree with its entry, the height of its subtree, the largest end in its subtree and its two subtrees
+struct Node<T, V> {
+    interval: Interval<T>,
+    value: V,
+    height: usize,
+    max_end: T,
+    left: Link<T, V>,
+    right: Link<T, V>,
+}
+
+///The interval tree with its root and its number of entries
+pub struct IntervalTree<T, V> {
+    root: Link<T, V>,
+    length: usize,
+}
+
+///In-order iterator over the entries of an `IntervalTree`
+pub struct Iter<'a, T, V> {
+    //the nodes whose left subtree is being visited
+    stack: Vec<&'a Node<T, V>>,
+}
+
+///Iterator over the entries of an `IntervalTree` which overlap a point or an interval, in the order of the
+///intervals
+pub struct Overlaps<'a, T, V> {
+    //the nodes whose left subtree is being visited, subtrees ending too early are never pushed
+    stack: Vec<&'a Node<T, V>>,
+    start: T,
+    end: T,
+    //whether an interval starting at `end` still overlaps, as it does for a point
+    inclusive: bool,
+}
+
+impl<T: Ord> Interval<T> {
+    ///Returns the interval `start..end`. Panics if `start` is after `end`.
+    /// # Attributes
+    /// * `start`- the first point in the interval
+    /// * `end`- the first point after the interval
+    pub fn new(start: T, end: T) -> Interval<T> {
+        assert!(start <= end, "the interval starts after it ends");
+        Interval { start, end }
+    }
+
+    ///Returns true if `point` is in the interval
+    /// # Attributes
+    /// * `point`- the point to check
+    pub fn contains(&self, point: &T) -> bool {
+        self.start <= *point && *point < self.end
+    }
+
+    ///Returns true if the interval and `other` share a point
+    /// # Attributes
+    /// * `other`- the interval to check
+    pub fn overlaps(&self, other: &Interval<T>) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+}
+
+impl<T: Ord + Clone, V> Node<T, V> {
+    fn new(interval: Interval<T>, value: V) -> Node<T, V> {
+        Node {
+            max_end: interval.end.clone(),
+            interval,
+            value,
+            height: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    ///Sets the height and the largest end of the node from its subtrees
+    fn update(&mut self) {
+        self.height = 1 + height(&self.left).max(height(&self.right));
+        let mut max_end = &self.interval.end;
+        for child in [&self.left, &self.right].iter().copied().flatten() {
+            max_end = max_end.max(&child.max_end);
+        }
+        self.max_end = max_end.clone();
+    }
+}
+
+///Returns the height of the subtree of `link`
+fn height<T, V>(link: &Link<T, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.height)
+}
+
+fn rotate_left<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut right = node.right.take().unwrap();
+    node.right = right.left.take();
+    node.update();
+    right.left = Some(node);
+    right.update();
+    right
+}
+
+fn rotate_right<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> Box<Node<T, V>> {
+    let mut left = node.left.take().unwrap();
+    node.left = left.right.take();
+    node.update();
+    left.right = Some(node);
+    left.update();
+    left
+}
+
+///Updates the node of `link` and rotates it if its subtrees differ by more than one in height
+fn rebalance<T: Ord + Clone, V>(link: &mut Link<T, V>) {
+    let mut node = match link.take() {
+        Some(node) => node,
+        None => return,
+    };
+    node.update();
+    let (left, right) = (height(&node.left), height(&node.right));
+    if left > right + 1 {
+        let child = node.left.as_ref().unwrap();
+        if height(&child.right) > height(&child.left) {
+            node.left = Some(rotate_left(node.left.take().unwrap()));
+        }
+        node = rotate_right(node);
+    } else if right > left + 1 {
+        let child = node.right.as_ref().unwrap();
+        if height(&child.left) > height(&child.right) {
+            node.right = Some(rotate_right(node.right.take().unwrap()));
+        }
+        node = rotate_left(node);
+    }
+    *link = Some(node);
+}
+
+///Inserts `value` under `interval` below `link` and returns the old value of `interval`
+fn insert_node<T: Ord + Clone, V>(
+    link: &mut Link<T, V>,
+    interval: Interval<T>,
+    value: V,
+) -> Option<V> {
+    let node = match link {
+        Some(node) => node,
+        None => {
+            *link = Some(Box::new(Node::new(interval, value)));
+            return None;
+        }
+    };
+    let old = match interval.cmp(&node.interval) {
+        Ordering::Less => insert_node(&mut node.left, interval, value),
+        Ordering::Greater => insert_node(&mut node.right, interval, value),
+        Ordering::Equal => return Some(std::mem::replace(&mut node.value, value)),
+    };
+    rebalance(link);
+    old
+}
+
+///Removes the node with the smallest interval from the subtree of `node`. Returns the removed node and the rest.
+fn remove_min<T: Ord + Clone, V>(mut node: Box<Node<T, V>>) -> (Box<Node<T, V>>, Link<T, V>) {
+    match node.left.take() {
+        None => {
+            let rest = node.right.take();
+            (node, rest)
+        }
+        Some(left) => {
+            let (min, rest) = remove_min(left);
+            node.left = rest;
+            let mut link = Some(node);
+            rebalance(&mut link);
+            (min, link)
+        }
+    }
+}
+
+///Removes the entry of `interval` below `link` and returns its value
+fn remove_node<T: Ord + Clone, V>(link: &mut Link<T, V>, interval: &Interval<T>) -> Option<V> {
+    let node = link.as_mut()?;
+    let removed = match interval.cmp(&node.interval) {
+        Ordering::Less => remove_node(&mut node.left, interval),
+        Ordering::Greater => remove_node(&mut node.right, interval),
+        Ordering::Equal => {
+            let mut node = link.take().unwrap();
+            *link = match (node.left.take(), node.right.take()) {
+                (None, rest) | (rest, None) => rest,
+                (left, Some(right)) => {
+                    //the successor takes the place of the node
+                    let (mut min, rest) = remove_min(right);
+                    min.left = left;
+                    min.right = rest;
+                    Some(min)
+                }
+            };
+            Some(node.value)
+        }
+    };
+    if removed.is_some() {
+        rebalance(link);
+    }
+    removed
+}
+
+///Builds a balanced subtree of `entries`, which are sorted, by taking the middle entry as its root
+fn build<T: Ord + Clone, V>(entries: &mut [Option<(Interval<T>, V)>]) -> Link<T, V> {
+    if entries.is_empty() {
+        return None;
+    }
+    let middle = entries.len() / 2;
+    let (left, rest) = entries.split_at_mut(middle);
+    let (entry, right) = rest.split_first_mut().unwrap();
+    let (interval, value) = entry.take().unwrap();
+    let mut node = Box::new(Node::new(interval, value));
+    node.left = build(left);
+    node.right = build(right);
+    node.update();
+    Some(node)
+}
+
+///Checks the order, the heights, the balance and the largest ends of the subtree of `link` and returns its number
+///of nodes
+fn check_node<T: Ord + Clone, V>(
+    link: &Link<T, V>,
+    lower: Option<&Interval<T>>,
+    upper: Option<&Interval<T>>,
+) -> Result<usize, String> {
+    let node = match link {
+        Some(node) => node,
+        None => return Ok(0),
+    };
+    if lower.is_some_and(|lower| node.interval <= *lower)
+        || upper.is_some_and(|upper| node.interval >= *upper)
+    {
+        return Err(String::from("intervals are out of order"));
+    }
+    if node.interval.start > node.interval.end {
+        return Err(String::from("an interval starts after it ends"));
+    }
+    let count = check_node(&node.left, lower, Some(&node.interval))?
+        + check_node(&node.right, Some(&node.interval), upper)?
+        + 1;
+    let (left, right) = (height(&node.left), height(&node.right));
+    if node.height != 1 + left.max(right) {
+        return Err(format!("stored height {} is wrong", node.height));
+    }
+    if left > right + 1 || right > left + 1 {
+        return Err(format!(
+            "subtrees of heights {} and {} are out of balance",
+            left, right
+        ));
+    }
+    let max_end = [&node.left, &node.right]
+        .iter()
+        .filter_map(|child| child.as_ref().map(|child| &child.max_end))
+        .fold(&node.interval.end, |max, end| max.max(end));
+    if node.max_end != *max_end {
+        return Err(String::from("stored largest end is wrong"));
+    }
+    Ok(count)
+}
+
+impl<T: Ord + Clone, V> Default for IntervalTree<T, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone, V> IntervalTree<T, V> {
+    ///Returns a new empty tree
+    pub fn new() -> IntervalTree<T, V> {
+        IntervalTree {
+            root: None,
+            length: 0,
+        }
+    }
+
+    ///Returns a new balanced tree with the entries of `entries`, which must come with strictly increasing
+    ///intervals, built in O(n). Panics if the intervals are not increasing.
+    /// # Attributes
+    /// * `entries`- the entries in the order of their intervals
+    pub fn from_sorted<I: IntoIterator<Item = (Interval<T>, V)>>(entries: I) -> IntervalTree<T, V> {
+        let mut entries: Vec<Option<(Interval<T>, V)>> = entries.into_iter().map(Some).collect();
+        assert!(
+            entries
+                .windows(2)
+                .all(|pair| pair[0].as_ref().unwrap().0 < pair[1].as_ref().unwrap().0),
+            "intervals are not strictly increasing"
+        );
+        IntervalTree {
+            length: entries.len(),
+            root: build(&mut entries),
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        height(&self.root)
+    }
+
+    ///Inserts `value` under `interval` and returns the old value of `interval`
+    /// # Attributes
+    /// * `interval`- the interval of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, interval: Interval<T>, value: V) -> Option<V> {
+        let old = insert_node(&mut self.root, interval, value);
+        if old.is_none() {
+            self.length += 1;
+        }
+        old
+    }
+
+    ///Returns the reference to the value of `interval`
+    /// # Attributes
+    /// * `interval`- the interval to look up
+    pub fn get(&self, interval: &Interval<T>) -> Option<&V> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match interval.cmp(&node.interval) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    ///Returns true if the tree has an entry for `interval`
+    /// # Attributes
+    /// * `interval`- the interval to look up
+    pub fn contains_key(&self, interval: &Interval<T>) -> bool {
+        self.get(interval).is_some()
+    }
+
+    ///Removes the entry of `interval` and returns its value
+    /// # Attributes
+    /// * `interval`- the interval of the entry to remove
+    pub fn remove(&mut self, interval: &Interval<T>) -> Option<V> {
+        let removed = remove_node(&mut self.root, interval);
+        if removed.is_some() {
+            self.length -= 1;
+        }
+        removed
+    }
+
+    ///Returns an iterator over the entries whose intervals contain `point`
+    /// # Attributes
+    /// * `point`- the point to stab the intervals with
+    pub fn stab(&self, point: &T) -> Overlaps<'_, T, V> {
+        Overlaps::new(self.root.as_deref(), point.clone(), point.clone(), true)
+    }
+
+    ///Returns an iterator over the entries whose intervals overlap `interval`
+    /// # Attributes
+    /// * `interval`- the interval to overlap
+    pub fn overlapping(&self, interval: &Interval<T>) -> Overlaps<'_, T, V> {
+        Overlaps::new(
+            self.root.as_deref(),
+            interval.start.clone(),
+            interval.end.clone(),
+            false,
+        )
+    }
+
+    ///Returns an iterator over the entries in the order of their intervals
+    pub fn iter(&self) -> Iter<'_, T, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Checks that the intervals are in order, the stored heights and largest ends are right and the subtrees of
+    ///every node differ by at most one in height. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = check_node(&self.root, None, None)?;
+        if count != self.length {
+            return Err(format!("length {} but {} entries", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T, V> Iter<'a, T, V> {
+    ///Pushes `node` and the left spine below it
+    fn push_left(&mut self, mut node: Option<&'a Node<T, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T, V> Iterator for Iter<'a, T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.interval, &node.value))
+    }
+}
+
+impl<'a, T: Ord, V> Overlaps<'a, T, V> {
+    fn new(root: Option<&'a Node<T, V>>, start: T, end: T, inclusive: bool) -> Overlaps<'a, T, V> {
+        let mut overlaps = Overlaps {
+            stack: Vec::new(),
+            start,
+            end,
+            inclusive,
+        };
+        overlaps.push_left(root);
+        overlaps
+    }
+
+    ///Pushes `node` and the left spine below it, leaving out subtrees which end before the query starts
+    fn push_left(&mut self, mut node: Option<&'a Node<T, V>>) {
+        while let Some(current) = node {
+            if current.max_end <= self.start {
+                break;
+            }
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, T: Ord, V> Iterator for Overlaps<'a, T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.stack.pop() {
+            let starts_in_time = match node.interval.start.cmp(&self.end) {
+                Ordering::Less => true,
+                Ordering::Equal => self.inclusive,
+                Ordering::Greater => false,
+            };
+            if !starts_in_time {
+                //all following intervals start even later
+                self.stack.clear();
+                return None;
+            }
+            self.push_left(node.right.as_deref());
+            if node.interval.end > self.start {
+                return Some((&node.interval, &node.value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, T: Ord + Clone, V> IntoIterator for &'a IntervalTree<T, V> {
+    type Item = (&'a Interval<T>, &'a V);
+    type IntoIter = Iter<'a, T, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Ord + Clone, V> FromIterator<(Interval<T>, V)> for IntervalTree<T, V> {
+    fn from_iter<I: IntoIterator<Item = (Interval<T>, V)>>(iter: I) -> Self {
+        let mut tree = IntervalTree::new();
+        for (interval, value) in iter {
+            tree.insert(interval, value);
+        }
+        tree
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    ///Returns a random interval within `0..100`
+    fn random_interval(rng: &mut XorShift64) -> Interval<u64> {
+        let start = rng.next_below(100);
+        let length = rng.next_below(15);
+        Interval::new(start, start + length)
+    }
+
+    #[test]
+    fn test_new_interval_tree() {
+        let t: IntervalTree<i32, ()> = IntervalTree::new();
+        assert!(t.is_empty());
+        assert_eq!(0, t.stab(&3).count());
+        assert_eq!(0, t.overlapping(&Interval::new(0, 10)).count());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_reversed_interval() {
+        Interval::new(5, 3);
+    }
+
+    #[test]
+    fn test_half_open_intervals() {
+        let a = Interval::new(2, 5);
+        assert!(a.contains(&2) && a.contains(&4) && !a.contains(&5));
+        assert!(a.overlaps(&Interval::new(4, 9)));
+        assert!(!a.overlaps(&Interval::new(5, 9)));
+        assert!(!a.overlaps(&Interval::new(0, 2)));
+    }
+
+    #[test]
+    fn test_stab_and_overlap() {
+        let t: IntervalTree<i32, &str> = vec![
+            (Interval::new(1, 5), "a"),
+            (Interval::new(3, 8), "b"),
+            (Interval::new(6, 10), "c"),
+            (Interval::new(12, 15), "d"),
+            (Interval::new(0, 20), "e"),
+        ]
+        .into_iter()
+        .collect();
+        t.check_invariants().unwrap();
+        let values = |overlaps: Overlaps<'_, i32, &'static str>| {
+            overlaps.map(|(_, v)| *v).collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["e", "a", "b"], values(t.stab(&4)));
+        assert_eq!(vec!["e", "b", "c"], values(t.stab(&6)));
+        assert_eq!(vec!["e"], values(t.stab(&10)));
+        assert_eq!(
+            vec!["e", "c", "d"],
+            values(t.overlapping(&Interval::new(8, 13)))
+        );
+        assert_eq!(vec!["e"], values(t.overlapping(&Interval::new(15, 30))));
+        assert!(values(t.overlapping(&Interval::new(20, 30))).is_empty());
+    }
+
+    #[test]
+    fn test_from_sorted() {
+        let intervals: Vec<(Interval<u32>, u32)> =
+            (0..1000).map(|i| (Interval::new(i, i + 10), i)).collect();
+        let t = IntervalTree::from_sorted(intervals);
+        t.check_invariants().unwrap();
+        assert_eq!(1000, t.get_length());
+        assert_eq!(10, t.get_height());
+        assert!((491..=500).eq(t.stab(&500).map(|(_, v)| *v)));
+        assert!(IntervalTree::<u32, ()>::from_sorted(Vec::new()).is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_unsorted() {
+        IntervalTree::from_sorted(vec![(Interval::new(2, 3), ()), (Interval::new(1, 3), ())]);
+    }
+
+    #[test]
+    fn test_random_operations_against_naive() {
+        let mut rng = XorShift64::new(45);
+        let mut t = IntervalTree::new();
+        let mut reference: Vec<(Interval<u64>, u64)> = Vec::new();
+        for i in 0..3000 {
+            let interval = random_interval(&mut rng);
+            match reference.binary_search_by(|(other, _)| other.cmp(&interval)) {
+                Ok(index) if rng.next_bool() => {
+                    assert_eq!(Some(reference.remove(index).1), t.remove(&interval));
+                }
+                Ok(index) => {
+                    assert_eq!(Some(reference[index].1), t.insert(interval, i));
+                    reference[index].1 = i;
+                }
+                Err(index) => {
+                    assert!(t.remove(&interval).is_none());
+                    assert!(t.insert(interval, i).is_none());
+                    reference.insert(index, (interval, i));
+                }
+            }
+            t.check_invariants().unwrap();
+            let point = rng.next_below(120);
+            let expected = reference
+                .iter()
+                .filter(|(interval, _)| interval.contains(&point));
+            assert!(expected.map(|(k, v)| (k, v)).eq(t.stab(&point)));
+            let query = random_interval(&mut rng);
+            let expected = reference
+                .iter()
+                .filter(|(interval, _)| interval.overlaps(&query));
+            assert!(expected.map(|(k, v)| (k, v)).eq(t.overlapping(&query)));
+        }
+        assert!(reference.iter().map(|(k, v)| (k, v)).eq(t.iter()));
+    }
+}
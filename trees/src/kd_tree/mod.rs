@@ -0,0 +1,350 @@
+//! This module implements a static k-d tree over points with `D` coordinates for nearest neighbor and radius
+//! searches. The tree is built in O(n log n) by moving the median of the points along the splitting axis into the
+//! middle of their slice, which leaves a balanced tree implicit in the order of one vector: the middle entry of a
+//! range is its root and the halves on either side are its subtrees. The axis cycles with the depth.
+//! The k nearest neighbors are kept in a `BinaryHeap` bounded to k entries whose largest distance prunes the
+//! search, and a radius search visits the subtrees breadth first from a `fifo::Fifo`.
+use fifo::fifo::Fifo;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+///A k-d tree mapping points with `D` coordinates to values
+pub struct KdTree<V, const D: usize> {
+    //every range is split at its middle entry, which is the median of the range along the axis of its depth
+    entries: Vec<([f64; D], V)>,
+}
+
+///A candidate neighbor on the bounded heap, ordered by its distance
+struct Candidate {
+    distance: f64,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.distance
+            .total_cmp(&other.distance)
+            .then(self.index.cmp(&other.index))
+    }
+}
+
+///Returns the squared euclidean distance between `a` and `b`
+fn squared_distance<const D: usize>(a: &[f64; D], b: &[f64; D]) -> f64 {
+    a.iter().zip(b.iter()).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+///Returns the position of the root of the subtree of the entries from `start` to `end`
+fn middle(start: usize, end: usize) -> usize {
+    start + (end - start) / 2
+}
+
+///Arranges `entries` into the subtree of a node at `depth`
+fn build<V, const D: usize>(entries: &mut [([f64; D], V)], depth: usize) {
+    if entries.len() <= 1 {
+        return;
+    }
+    let axis = depth % D;
+    let middle = entries.len() / 2;
+    entries.select_nth_unstable_by(middle, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+    let (left, right) = entries.split_at_mut(middle);
+    build(left, depth + 1);
+    build(&mut right[1..], depth + 1);
+}
+
+impl<V, const D: usize> KdTree<V, D> {
+    ///Returns a new tree of `points`. Panics if a coordinate is NaN or `D` is 0.
+    /// # Attributes
+    /// * `points`- the points and their values
+    pub fn from_points<I: IntoIterator<Item = ([f64; D], V)>>(points: I) -> KdTree<V, D> {
+        assert!(D > 0, "a k-d tree needs at least one dimension");
+        let mut entries: Vec<([f64; D], V)> = points.into_iter().collect();
+        assert!(
+            entries
+                .iter()
+                .all(|(point, _)| point.iter().all(|x| !x.is_nan())),
+            "coordinates must not be NaN"
+        );
+        build(&mut entries, 0);
+        KdTree { entries }
+    }
+
+    ///Returns the number of points
+    pub fn get_length(&self) -> usize {
+        self.entries.len()
+    }
+
+    ///Returns true if the tree has no points
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    ///Returns the point nearest to `query` and its value
+    /// # Attributes
+    /// * `query`- the point to search from
+    pub fn nearest(&self, query: &[f64; D]) -> Option<(&[f64; D], &V)> {
+        self.k_nearest(query, 1).into_iter().next()
+    }
+
+    ///Returns the `k` points nearest to `query` and their values, the nearest first. Returns all points if there are
+    ///fewer than `k`.
+    /// # Attributes
+    /// * `query`- the point to search from
+    /// * `k`- the number of points to return
+    pub fn k_nearest(&self, query: &[f64; D], k: usize) -> Vec<(&[f64; D], &V)> {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(0, self.entries.len(), 0, query, k, &mut heap);
+        }
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|candidate| {
+                let (point, value) = &self.entries[candidate.index];
+                (point, value)
+            })
+            .collect()
+    }
+
+    ///Offers the points of the subtree from `start` to `end` at `depth` to the heap of the `k` nearest candidates
+    fn search(
+        &self,
+        start: usize,
+        end: usize,
+        depth: usize,
+        query: &[f64; D],
+        k: usize,
+        heap: &mut BinaryHeap<Candidate>,
+    ) {
+        if start >= end {
+            return;
+        }
+        let index = middle(start, end);
+        let point = &self.entries[index].0;
+        let candidate = Candidate {
+            distance: squared_distance(point, query),
+            index,
+        };
+        if heap.len() < k {
+            heap.push(candidate);
+        } else if heap.peek().is_some_and(|worst| candidate < *worst) {
+            heap.pop();
+            heap.push(candidate);
+        }
+        let axis = depth % D;
+        let difference = query[axis] - point[axis];
+        let (near, far) = if difference < 0.0 {
+            ((start, index), (index + 1, end))
+        } else {
+            ((index + 1, end), (start, index))
+        };
+        self.search(near.0, near.1, depth + 1, query, k, heap);
+        //the far side can only hold a nearer point if the splitting plane is nearer than the worst candidate
+        if heap.len() < k
+            || heap
+                .peek()
+                .is_some_and(|worst| difference * difference < worst.distance)
+        {
+            self.search(far.0, far.1, depth + 1, query, k, heap);
+        }
+    }
+
+    ///Returns the points within `radius` of `query` and their values, in breadth first order of the tree. Panics if
+    ///`radius` is negative or NaN.
+    /// # Attributes
+    /// * `query`- the center of the search
+    /// * `radius`- the largest distance to include
+    pub fn within_radius(&self, query: &[f64; D], radius: f64) -> Vec<(&[f64; D], &V)> {
+        assert!(
+            radius >= 0.0,
+            "the radius {} is not a non negative number",
+            radius
+        );
+        let mut found = Vec::new();
+        let mut queue = Fifo::new();
+        queue.enqueue((0, self.entries.len(), 0));
+        while let Some((start, end, depth)) = queue.dequeue() {
+            if start >= end {
+                continue;
+            }
+            let index = middle(start, end);
+            let (point, value) = &self.entries[index];
+            if squared_distance(point, query) <= radius * radius {
+                found.push((point, value));
+            }
+            let axis = depth % D;
+            let difference = query[axis] - point[axis];
+            //the left side lies at or before the splitting plane and the right side at or after it
+            if difference <= radius {
+                queue.enqueue((start, index, depth + 1));
+            }
+            if -difference <= radius {
+                queue.enqueue((index + 1, end, depth + 1));
+            }
+        }
+        found
+    }
+
+    ///Returns an iterator over the points and their values in no particular order
+    pub fn iter(&self) -> impl Iterator<Item = (&[f64; D], &V)> {
+        self.entries.iter().map(|(point, value)| (point, value))
+    }
+
+    ///Checks that every point of a left subtree is at or before the splitting plane of its root and every point of
+    ///a right subtree at or after it. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        self.check_range(0, self.entries.len(), 0)
+    }
+
+    fn check_range(&self, start: usize, end: usize, depth: usize) -> Result<(), String> {
+        if start >= end {
+            return Ok(());
+        }
+        let index = middle(start, end);
+        let axis = depth % D;
+        let split = self.entries[index].0[axis];
+        if self.entries[start..index]
+            .iter()
+            .any(|(point, _)| point[axis] > split)
+            || self.entries[index + 1..end]
+                .iter()
+                .any(|(point, _)| point[axis] < split)
+        {
+            return Err(format!(
+                "points on the wrong side of the split at {}",
+                index
+            ));
+        }
+        self.check_range(start, index, depth + 1)?;
+        self.check_range(index + 1, end, depth + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    ///Returns `count` random points in the square from 0 to 100 with their positions as values
+    fn random_points(rng: &mut XorShift64, count: usize) -> Vec<([f64; 2], usize)> {
+        (0..count)
+            .map(|i| {
+                let x = rng.next_below(10_000) as f64 / 100.0;
+                let y = rng.next_below(10_000) as f64 / 100.0;
+                ([x, y], i)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_kd_tree() {
+        let t: KdTree<(), 3> = KdTree::from_points(Vec::new());
+        assert!(t.is_empty());
+        assert!(t.nearest(&[0.0, 0.0, 0.0]).is_none());
+        assert!(t.within_radius(&[0.0, 0.0, 0.0], 10.0).is_empty());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_nan_coordinate() {
+        KdTree::from_points(vec![([1.0, f64::NAN], ())]);
+    }
+
+    #[test]
+    fn test_small_kd_tree() {
+        let t = KdTree::from_points(vec![
+            ([2.0, 3.0], "a"),
+            ([5.0, 4.0], "b"),
+            ([9.0, 6.0], "c"),
+            ([4.0, 7.0], "d"),
+            ([8.0, 1.0], "e"),
+            ([7.0, 2.0], "f"),
+        ]);
+        t.check_invariants().unwrap();
+        assert_eq!(6, t.get_length());
+        assert_eq!(Some((&[8.0, 1.0], &"e")), t.nearest(&[9.0, 2.0]));
+        let nearest: Vec<&str> = t
+            .k_nearest(&[5.0, 5.0], 3)
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        assert_eq!(vec!["b", "d", "a"], nearest);
+        assert_eq!(6, t.k_nearest(&[0.0, 0.0], 10).len());
+        assert!(t.k_nearest(&[0.0, 0.0], 0).is_empty());
+        let mut within: Vec<&str> = t
+            .within_radius(&[8.0, 2.0], 1.5)
+            .into_iter()
+            .map(|(_, v)| *v)
+            .collect();
+        within.sort_unstable();
+        assert_eq!(vec!["e", "f"], within);
+    }
+
+    #[test]
+    #[should_panic(expected = "the radius -1 is not a non negative number")]
+    fn test_negative_radius() {
+        let t = KdTree::from_points(vec![([0.0, 0.0], ())]);
+        t.within_radius(&[0.0, 0.0], -1.0);
+    }
+
+    #[test]
+    fn test_one_dimension() {
+        let t = KdTree::from_points((0..100).map(|i| ([i as f64 * 2.0], i)));
+        t.check_invariants().unwrap();
+        assert_eq!(Some(&21), t.nearest(&[42.5]).map(|(_, v)| v));
+        assert_eq!(5, t.within_radius(&[50.0], 4.0).len());
+    }
+
+    #[test]
+    fn test_random_queries_against_naive() {
+        let mut rng = XorShift64::new(46);
+        let points = random_points(&mut rng, 500);
+        let t = KdTree::from_points(points.clone());
+        t.check_invariants().unwrap();
+        for _ in 0..200 {
+            let query = [
+                rng.next_below(12_000) as f64 / 100.0 - 10.0,
+                rng.next_below(12_000) as f64 / 100.0 - 10.0,
+            ];
+            let mut distances: Vec<f64> = points
+                .iter()
+                .map(|(point, _)| squared_distance(point, &query))
+                .collect();
+            distances.sort_by(f64::total_cmp);
+            let k = rng.next_below(10) as usize + 1;
+            let found: Vec<f64> = t
+                .k_nearest(&query, k)
+                .into_iter()
+                .map(|(point, _)| squared_distance(point, &query))
+                .collect();
+            assert_eq!(distances[..k], found[..]);
+            let radius = rng.next_below(2_000) as f64 / 100.0;
+            let mut expected: Vec<usize> = points
+                .iter()
+                .filter(|(point, _)| squared_distance(point, &query) <= radius * radius)
+                .map(|(_, i)| *i)
+                .collect();
+            let mut within: Vec<usize> = t
+                .within_radius(&query, radius)
+                .into_iter()
+                .map(|(_, i)| *i)
+                .collect();
+            expected.sort_unstable();
+            within.sort_unstable();
+            assert_eq!(expected, within);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+pub mod avl;
+pub mod bplustree;
+pub mod bst;
+pub mod btree;
+pub mod fenwick;
+pub mod interval_tree;
+pub mod kd_tree;
+pub mod quadtree;
+pub mod radix;
+pub mod rbtree;
+pub mod segment_tree;
+pub mod splay;
+pub mod treap;
+pub mod trie;
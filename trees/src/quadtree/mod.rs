@@ -0,0 +1,439 @@
+//! This module implements a region quadtree mapping points of the plane to values. Every node covers a
+//! rectangle of a fixed bounding box. A leaf holds up to a fixed capacity of points and is split into four
+//! quadrants of equal size when it overflows, and an inner node is merged back into a leaf when the points below
+//! it fit into one again. Rectangle queries and the height are computed breadth first from a `fifo::Fifo`.
+//! A leaf at the largest depth is never split, so very close points can't make the tree arbitrarily deep.
+use fifo::fifo::Fifo;
+
+///The largest depth of a node, the root being at depth 0
+const MAX_DEPTH: usize = 32;
+
+///A half-open axis aligned rectangle from `min` to `max`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rectangle {
+    pub min: [f64; 2],
+    pub max: [f64; 2],
+}
+
+///A node of the tree with its region, the points of a leaf and the four quadrants of an inner node
+struct Node<V> {
+    bounds: Rectangle,
+    entries: Vec<([f64; 2], V)>,
+    children: Option<Box<[Node<V>; 4]>>,
+}
+
+///The quadtree with its root, the capacity of its leaves and its number of points
+pub struct QuadTree<V> {
+    root: Node<V>,
+    capacity: usize,
+    length: usize,
+}
+
+impl Rectangle {
+    ///Returns the rectangle from `min` to `max`. Panics if `min` is after `max` on an axis.
+    /// # Attributes
+    /// * `min`- the lower corner, inside the rectangle
+    /// * `max`- the upper corner, outside the rectangle
+    pub fn new(min: [f64; 2], max: [f64; 2]) -> Rectangle {
+        assert!(
+            min[0] <= max[0] && min[1] <= max[1],
+            "the rectangle has a negative size"
+        );
+        Rectangle { min, max }
+    }
+
+    ///Returns true if `point` is in the rectangle
+    /// # Attributes
+    /// * `point`- the point to check
+    pub fn contains(&self, point: &[f64; 2]) -> bool {
+        (0..2).all(|axis| self.min[axis] <= point[axis] && point[axis] < self.max[axis])
+    }
+
+    ///Returns true if the rectangle and `other` share a point
+    /// # Attributes
+    /// * `other`- the rectangle to check
+    pub fn intersects(&self, other: &Rectangle) -> bool {
+        (0..2).all(|axis| self.min[axis] < other.max[axis] && other.min[axis] < self.max[axis])
+    }
+
+    ///Returns the index of the quadrant of `point`: 1 is added for the right half and 2 for the upper half
+    fn quadrant_of(&self, point: &[f64; 2]) -> usize {
+        let center = self.center();
+        (point[0] >= center[0]) as usize + 2 * (point[1] >= center[1]) as usize
+    }
+
+    ///Returns the quadrant of index `index`
+    fn quadrant(&self, index: usize) -> Rectangle {
+        let center = self.center();
+        let mut quadrant = *self;
+        for (axis, half) in [index & 1, index >> 1].iter().enumerate() {
+            if *half == 0 {
+                quadrant.max[axis] = center[axis];
+            } else {
+                quadrant.min[axis] = center[axis];
+            }
+        }
+        quadrant
+    }
+
+    fn center(&self) -> [f64; 2] {
+        [
+            (self.min[0] + self.max[0]) / 2.0,
+            (self.min[1] + self.max[1]) / 2.0,
+        ]
+    }
+}
+
+impl<V> Node<V> {
+    fn new(bounds: Rectangle) -> Node<V> {
+        Node {
+            bounds,
+            entries: Vec::new(),
+            children: None,
+        }
+    }
+
+    ///Moves the points of the leaf into four new quadrants and splits the quadrants which overflow in turn
+    fn split(&mut self, capacity: usize, depth: usize) {
+        let bounds = self.bounds;
+        let mut children = Box::new([0, 1, 2, 3].map(|index| Node::new(bounds.quadrant(index))));
+        for (point, value) in self.entries.drain(..) {
+            children[bounds.quadrant_of(&point)]
+                .entries
+                .push((point, value));
+        }
+        for child in children.iter_mut() {
+            if child.entries.len() > capacity && depth + 1 < MAX_DEPTH {
+                child.split(capacity, depth + 1);
+            }
+        }
+        self.children = Some(children);
+    }
+
+    ///Removes `point` from the subtree of the node and returns its value. Merges the quadrants of the node if their
+    ///points fit into one leaf afterwards.
+    fn remove(&mut self, point: &[f64; 2], capacity: usize) -> Option<V> {
+        let children = match &mut self.children {
+            Some(children) => children,
+            None => {
+                let position = self.entries.iter().position(|(other, _)| other == point)?;
+                return Some(self.entries.swap_remove(position).1);
+            }
+        };
+        let removed = children[self.bounds.quadrant_of(point)].remove(point, capacity)?;
+        let leaves = children.iter().all(|child| child.children.is_none());
+        if leaves
+            && children
+                .iter()
+                .map(|child| child.entries.len())
+                .sum::<usize>()
+                <= capacity
+        {
+            for child in children.iter_mut() {
+                self.entries.append(&mut child.entries);
+            }
+            self.children = None;
+        }
+        Some(removed)
+    }
+
+    ///Checks the subtree of the node at `depth` and returns its number of points
+    fn check(&self, capacity: usize, depth: usize) -> Result<usize, String> {
+        match &self.children {
+            None => {
+                if self.entries.len() > capacity && depth < MAX_DEPTH {
+                    return Err(format!(
+                        "leaf at depth {} holds {} points",
+                        depth,
+                        self.entries.len()
+                    ));
+                }
+                if !self
+                    .entries
+                    .iter()
+                    .all(|(point, _)| self.bounds.contains(point))
+                {
+                    return Err(format!(
+                        "leaf at depth {} holds a point outside its bounds",
+                        depth
+                    ));
+                }
+                Ok(self.entries.len())
+            }
+            Some(children) => {
+                if !self.entries.is_empty() {
+                    return Err(format!("inner node at depth {} holds points", depth));
+                }
+                let mut count = 0;
+                for (index, child) in children.iter().enumerate() {
+                    if child.bounds != self.bounds.quadrant(index) {
+                        return Err(format!(
+                            "quadrant {} at depth {} has wrong bounds",
+                            index, depth
+                        ));
+                    }
+                    count += child.check(capacity, depth + 1)?;
+                }
+                if count <= capacity {
+                    return Err(format!(
+                        "inner node at depth {} holds only {} points",
+                        depth, count
+                    ));
+                }
+                Ok(count)
+            }
+        }
+    }
+}
+
+impl<V> QuadTree<V> {
+    ///Returns a new empty tree over `bounds` whose leaves hold up to `capacity` points. Panics if `capacity` is 0.
+    /// # Attributes
+    /// * `bounds`- the region of the points
+    /// * `capacity`- the number of points a leaf holds before it is split
+    pub fn new(bounds: Rectangle, capacity: usize) -> QuadTree<V> {
+        assert!(capacity > 0, "the capacity of a leaf must be at least 1");
+        QuadTree {
+            root: Node::new(bounds),
+            capacity,
+            length: 0,
+        }
+    }
+
+    ///Returns the number of points
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no points
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the region of the points
+    pub fn get_bounds(&self) -> Rectangle {
+        self.root.bounds
+    }
+
+    ///Returns the number of levels of nodes, 1 for a tree which is a single leaf
+    pub fn get_height(&self) -> usize {
+        let mut height = 0;
+        let mut queue = Fifo::new();
+        queue.enqueue((&self.root, 1));
+        while let Some((node, depth)) = queue.dequeue() {
+            height = depth;
+            if let Some(children) = &node.children {
+                for child in children.iter() {
+                    queue.enqueue((child, depth + 1));
+                }
+            }
+        }
+        height
+    }
+
+    ///Inserts `value` at `point` and returns the old value at `point`. Panics if `point` is outside the bounds.
+    /// # Attributes
+    /// * `point`- the position of the value
+    /// * `value`- the value to insert
+    pub fn insert(&mut self, point: [f64; 2], value: V) -> Option<V> {
+        assert!(
+            self.root.bounds.contains(&point),
+            "point ({}, {}) is outside the bounds",
+            point[0],
+            point[1]
+        );
+        let mut node = &mut self.root;
+        let mut depth = 0;
+        while node.children.is_some() {
+            let index = node.bounds.quadrant_of(&point);
+            node = &mut node.children.as_mut().unwrap()[index];
+            depth += 1;
+        }
+        if let Some((_, old)) = node.entries.iter_mut().find(|(other, _)| *other == point) {
+            return Some(std::mem::replace(old, value));
+        }
+        node.entries.push((point, value));
+        self.length += 1;
+        if node.entries.len() > self.capacity && depth < MAX_DEPTH {
+            node.split(self.capacity, depth);
+        }
+        None
+    }
+
+    ///Returns the reference to the value at `point`
+    /// # Attributes
+    /// * `point`- the position to look up
+    pub fn get(&self, point: &[f64; 2]) -> Option<&V> {
+        let mut node = &self.root;
+        while let Some(children) = &node.children {
+            node = &children[node.bounds.quadrant_of(point)];
+        }
+        node.entries
+            .iter()
+            .find(|(other, _)| other == point)
+            .map(|(_, value)| value)
+    }
+
+    ///Removes the value at `point` and returns it
+    /// # Attributes
+    /// * `point`- the position of the value to remove
+    pub fn remove(&mut self, point: &[f64; 2]) -> Option<V> {
+        if !self.root.bounds.contains(point) {
+            return None;
+        }
+        let removed = self.root.remove(point, self.capacity);
+        if removed.is_some() {
+            self.length -= 1;
+        }
+        removed
+    }
+
+    ///Returns the points in `rectangle` and their values, in breadth first order of the tree
+    /// # Attributes
+    /// * `rectangle`- the region to search
+    pub fn query(&self, rectangle: &Rectangle) -> Vec<(&[f64; 2], &V)> {
+        let mut found = Vec::new();
+        let mut queue = Fifo::new();
+        queue.enqueue(&self.root);
+        while let Some(node) = queue.dequeue() {
+            if !node.bounds.intersects(rectangle) {
+                continue;
+            }
+            match &node.children {
+                Some(children) => {
+                    for child in children.iter() {
+                        queue.enqueue(child);
+                    }
+                }
+                None => found.extend(
+                    node.entries
+                        .iter()
+                        .filter(|(point, _)| rectangle.contains(point))
+                        .map(|(point, value)| (point, value)),
+                ),
+            }
+        }
+        found
+    }
+
+    ///Checks that every leaf holds at most the capacity in points inside its bounds, every inner node holds more
+    ///points below it and the quadrants split their parent evenly. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let count = self.root.check(self.capacity, 0)?;
+        if count != self.length {
+            return Err(format!("length {} but {} points", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    fn square() -> Rectangle {
+        Rectangle::new([0.0, 0.0], [100.0, 100.0])
+    }
+
+    #[test]
+    fn test_rectangle() {
+        let r = Rectangle::new([0.0, 0.0], [4.0, 2.0]);
+        assert!(r.contains(&[0.0, 1.0]) && !r.contains(&[4.0, 1.0]));
+        assert!(r.intersects(&Rectangle::new([3.0, 1.0], [5.0, 5.0])));
+        assert!(!r.intersects(&Rectangle::new([4.0, 0.0], [5.0, 5.0])));
+        assert_eq!(Rectangle::new([2.0, 1.0], [4.0, 2.0]), r.quadrant(3));
+        assert_eq!(1, r.quadrant_of(&[3.0, 0.5]));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_insert_outside() {
+        let mut t = QuadTree::new(square(), 4);
+        t.insert([100.0, 5.0], ());
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let mut t = QuadTree::new(square(), 2);
+        assert_eq!(1, t.get_height());
+        assert!(t.insert([10.0, 10.0], 'a').is_none());
+        assert!(t.insert([60.0, 10.0], 'b').is_none());
+        assert_eq!(Some('b'), t.insert([60.0, 10.0], 'c'));
+        assert_eq!(1, t.get_height());
+        t.insert([10.0, 60.0], 'd');
+        assert_eq!(2, t.get_height());
+        t.insert([12.0, 12.0], 'e');
+        t.insert([11.0, 11.0], 'f');
+        t.check_invariants().unwrap();
+        assert_eq!(5, t.get_length());
+        assert!(t.get_height() > 2);
+        assert_eq!(Some(&'e'), t.get(&[12.0, 12.0]));
+        assert_eq!(Some('f'), t.remove(&[11.0, 11.0]));
+        assert!(t.remove(&[11.0, 11.0]).is_none());
+        assert_eq!(2, t.get_height());
+        t.remove(&[10.0, 60.0]);
+        t.remove(&[60.0, 10.0]);
+        t.check_invariants().unwrap();
+        assert_eq!(1, t.get_height());
+        assert!(t.remove(&[-1.0, 0.0]).is_none());
+    }
+
+    #[test]
+    fn test_close_points_stop_at_max_depth() {
+        let mut t = QuadTree::new(square(), 1);
+        for i in 0..10 {
+            t.insert([50.0, 50.0 + i as f64 * 1e-12], i);
+        }
+        t.check_invariants().unwrap();
+        assert!(t.get_height() <= MAX_DEPTH + 1);
+        assert_eq!(
+            10,
+            t.query(&Rectangle::new([49.0, 49.0], [51.0, 51.0])).len()
+        );
+    }
+
+    #[test]
+    fn test_random_operations_against_naive() {
+        let mut rng = XorShift64::new(46);
+        let mut t = QuadTree::new(square(), 4);
+        let mut reference: Vec<([f64; 2], u64)> = Vec::new();
+        for i in 0..2000 {
+            let point = [
+                rng.next_below(50) as f64 * 2.0,
+                rng.next_below(50) as f64 * 2.0,
+            ];
+            match reference.iter().position(|(other, _)| *other == point) {
+                Some(position) if rng.next_bool() => {
+                    assert_eq!(Some(reference.swap_remove(position).1), t.remove(&point));
+                }
+                Some(position) => {
+                    assert_eq!(Some(reference[position].1), t.insert(point, i));
+                    reference[position].1 = i;
+                }
+                None => {
+                    assert!(t.insert(point, i).is_none());
+                    reference.push((point, i));
+                }
+            }
+            let (a, b) = (rng.next_below(101) as f64, rng.next_below(101) as f64);
+            let (c, d) = (rng.next_below(101) as f64, rng.next_below(101) as f64);
+            let rectangle = Rectangle::new([a.min(b), c.min(d)], [a.max(b), c.max(d)]);
+            let mut expected: Vec<u64> = reference
+                .iter()
+                .filter(|(point, _)| rectangle.contains(point))
+                .map(|(_, value)| *value)
+                .collect();
+            let mut found: Vec<u64> = t
+                .query(&rectangle)
+                .into_iter()
+                .map(|(_, value)| *value)
+                .collect();
+            expected.sort_unstable();
+            found.sort_unstable();
+            assert_eq!(expected, found);
+        }
+        t.check_invariants().unwrap();
+        assert_eq!(reference.len(), t.get_length());
+    }
+}
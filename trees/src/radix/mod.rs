@@ -0,0 +1,472 @@
+//! This module implements a radix tree map, a trie keyed by byte strings whose chains of nodes with a single child
+//! and no value are compressed into one edge labelled with their bytes. An insertion splits an edge where the new
+//! key branches off, a removal merges a node which is left with a single child and no value into that child.
+//! The children of every node are sorted by the first byte of their labels, so walking the tree in depth first
+//! order visits the keys in lexicographic order. The walks keep their path on a `lifo::Lifo` instead of recursing.
+use crate::trie::MemoryUsage;
+use lifo::lifo::Lifo;
+use std::mem::size_of;
+
+///A node of the tree with the label of the edge leading to it, its value, if the path to it spells a key, and its
+///children sorted by the first byte of their labels
+struct Node<V> {
+    label: Vec<u8>,
+    value: Option<V>,
+    children: Vec<Node<V>>,
+}
+
+///The radix tree with its root, the node of the empty key, and its number of keys
+pub struct RadixTree<V> {
+    root: Node<V>,
+    length: usize,
+}
+
+///Iterator over the entries of a `RadixTree` whose keys start with a prefix, in lexicographic order
+pub struct Prefix<'a, V> {
+    //the nodes still to visit, the next one on top, with the length of the key of their parent and the bytes
+    //their key adds to it
+    stack: Lifo<(&'a Node<V>, usize, &'a [u8])>,
+    //the key of the node visited last, shared by all nodes on the stack
+    key: Vec<u8>,
+}
+
+impl<V> Node<V> {
+    fn new(label: Vec<u8>) -> Node<V> {
+        Node {
+            label,
+            value: None,
+            children: Vec::new(),
+        }
+    }
+
+    ///Returns the index of the child whose label starts with `byte`
+    fn find_child(&self, byte: u8) -> Result<usize, usize> {
+        self.children
+            .binary_search_by_key(&byte, |child| child.label[0])
+    }
+
+    ///Merges the node with its only child if it has no value of its own
+    fn compress(&mut self) {
+        if self.value.is_none() && self.children.len() == 1 {
+            let mut child = self.children.pop().unwrap();
+            self.label.append(&mut child.label);
+            self.value = child.value.take();
+            self.children = std::mem::take(&mut child.children);
+        }
+    }
+}
+
+///Returns the length of the common prefix of `a` and `b`
+fn common_prefix(a: &[u8], b: &[u8]) -> usize {
+    a.iter().zip(b).take_while(|(x, y)| x == y).count()
+}
+
+impl<V> Default for RadixTree<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for RadixTree<V> {
+    fn drop(&mut self) {
+        let mut stack = Lifo::new();
+        stack.enqueue(std::mem::replace(&mut self.root, Node::new(Vec::new())));
+        while let Some(mut node) = stack.dequeue() {
+            for child in node.children.drain(..) {
+                stack.enqueue(child);
+            }
+        }
+    }
+}
+
+impl<V> RadixTree<V> {
+    ///Returns a new empty radix tree
+    pub fn new() -> RadixTree<V> {
+        RadixTree {
+            root: Node::new(Vec::new()),
+            length: 0,
+        }
+    }
+
+    ///Returns the number of keys
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no keys
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        let mut node = &self.root;
+        let mut rest = key;
+        while !rest.is_empty() {
+            let child = &node.children[node.find_child(rest[0]).ok()?];
+            rest = rest.strip_prefix(&child.label[..])?;
+            node = child;
+        }
+        node.value.as_ref()
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        let mut rest = key;
+        while !rest.is_empty() {
+            let index = node.find_child(rest[0]).ok()?;
+            let child = &mut node.children[index];
+            rest = rest.strip_prefix(&child.label[..])?;
+            node = child;
+        }
+        node.value.as_mut()
+    }
+
+    ///Returns true if the tree has the key `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        let mut rest = key;
+        while !rest.is_empty() {
+            let index = match node.find_child(rest[0]) {
+                Ok(index) => index,
+                Err(index) => {
+                    let mut leaf = Node::new(rest.to_vec());
+                    leaf.value = Some(value);
+                    node.children.insert(index, leaf);
+                    self.length += 1;
+                    return None;
+                }
+            };
+            let length = common_prefix(&node.children[index].label, rest);
+            if length < node.children[index].label.len() {
+                //the key branches off inside the label, split the edge there
+                let mut child = std::mem::replace(&mut node.children[index], Node::new(Vec::new()));
+                let suffix = child.label.split_off(length);
+                let middle = &mut node.children[index];
+                middle.label = std::mem::replace(&mut child.label, suffix);
+                middle.children.push(child);
+            }
+            node = &mut node.children[index];
+            rest = &rest[length..];
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.length += 1;
+        }
+        old
+    }
+
+    ///Removes `key` and returns its value. A node which is left without a value and with a single child is merged
+    ///into the child, a node without a value and without children is removed.
+    /// # Attributes
+    /// * `key`- the key to remove
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        //the indices of the children on the path to the node of `key`
+        let mut path = Vec::new();
+        let mut node = &self.root;
+        let mut rest = key;
+        while !rest.is_empty() {
+            let index = node.find_child(rest[0]).ok()?;
+            node = &node.children[index];
+            rest = rest.strip_prefix(&node.label[..])?;
+            path.push(index);
+        }
+        node.value.as_ref()?;
+        let removed = match path.split_last() {
+            None => self.root.value.take(),
+            Some((last, above)) => {
+                let parent = self.descend(above);
+                let removed = parent.children[*last].value.take();
+                if parent.children[*last].children.is_empty() {
+                    parent.children.remove(*last);
+                } else {
+                    parent.children[*last].compress();
+                }
+                //the parent may be left with a single child
+                if let Some((_, above)) = above.split_last() {
+                    let grandparent = self.descend(above);
+                    let parent = path[above.len()];
+                    grandparent.children[parent].compress();
+                }
+                removed
+            }
+        };
+        self.length -= 1;
+        removed
+    }
+
+    ///Returns the node at the end of the child indices `path`
+    fn descend(&mut self, path: &[usize]) -> &mut Node<V> {
+        let mut node = &mut self.root;
+        for index in path {
+            node = &mut node.children[*index];
+        }
+        node
+    }
+
+    ///Returns the longest key which is a prefix of `key`, together with its value
+    /// # Attributes
+    /// * `key`- the key whose prefixes are looked up
+    pub fn longest_prefix<'a, 'k>(&'a self, key: &'k [u8]) -> Option<(&'k [u8], &'a V)> {
+        let mut node = &self.root;
+        let mut depth = 0;
+        let mut longest = node.value.as_ref().map(|value| (0, value));
+        while depth < key.len() {
+            node = match node.find_child(key[depth]) {
+                Ok(index) => &node.children[index],
+                Err(_) => break,
+            };
+            if !key[depth..].starts_with(&node.label) {
+                break;
+            }
+            depth += node.label.len();
+            if let Some(value) = node.value.as_ref() {
+                longest = Some((depth, value));
+            }
+        }
+        longest.map(|(length, value)| (&key[..length], value))
+    }
+
+    ///Returns an iterator over the entries whose keys start with `prefix`, in lexicographic order
+    /// # Attributes
+    /// * `prefix`- the common prefix of the keys
+    pub fn starts_with(&self, prefix: &[u8]) -> Prefix<'_, V> {
+        let mut stack = Lifo::new();
+        let mut node = &self.root;
+        let mut key = Vec::new();
+        let mut rest = prefix;
+        loop {
+            if rest.is_empty() {
+                stack.enqueue((node, key.len(), &[][..]));
+                break;
+            }
+            node = match node.find_child(rest[0]) {
+                Ok(index) => &node.children[index],
+                Err(_) => break,
+            };
+            key.extend_from_slice(&node.label);
+            //the prefix may end inside the label
+            let length = common_prefix(&node.label, rest);
+            if length == rest.len() {
+                stack.enqueue((node, key.len(), &[][..]));
+                break;
+            }
+            if length < node.label.len() {
+                break;
+            }
+            rest = &rest[length..];
+        }
+        Prefix { stack, key }
+    }
+
+    ///Returns an iterator over all entries in lexicographic order of their keys
+    pub fn iter(&self) -> Prefix<'_, V> {
+        self.starts_with(&[])
+    }
+
+    ///Returns the number of nodes and the bytes allocated for them and their labels
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            node_count: 0,
+            bytes: size_of::<Node<V>>(),
+        };
+        let mut stack = Lifo::new();
+        stack.enqueue(&self.root);
+        while let Some(node) = stack.dequeue() {
+            usage.node_count += 1;
+            usage.bytes += node.label.capacity() + node.children.capacity() * size_of::<Node<V>>();
+            for child in &node.children {
+                stack.enqueue(child);
+            }
+        }
+        usage
+    }
+
+    ///Checks that every node but the root has a label, the children of every node start with distinct bytes in
+    ///order, every node but the root without a value has at least two children and the length matches the number
+    ///of values. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut count = usize::from(self.root.value.is_some());
+        let mut stack = Lifo::new();
+        stack.enqueue(&self.root);
+        while let Some(node) = stack.dequeue() {
+            for child in &node.children {
+                if child.label.is_empty() {
+                    return Err(String::from("a child without a label"));
+                }
+                if child.value.is_none() && child.children.len() < 2 {
+                    return Err(String::from("a node without a value is not compressed"));
+                }
+                count += usize::from(child.value.is_some());
+                stack.enqueue(child);
+            }
+            if node
+                .children
+                .windows(2)
+                .any(|pair| pair[0].label[0] >= pair[1].label[0])
+            {
+                return Err(String::from("children are out of order"));
+            }
+        }
+        if count != self.length {
+            return Err(format!("length {} but {} keys", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, V> Iterator for Prefix<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth, label)) = self.stack.dequeue() {
+            //going back up drops the labels of the nodes visited since the parent
+            self.key.truncate(depth);
+            self.key.extend_from_slice(label);
+            //the smallest child has to come out first, so it goes on the stack last
+            for child in node.children.iter().rev() {
+                self.stack
+                    .enqueue((child, self.key.len(), child.label.as_slice()));
+            }
+            if let Some(value) = node.value.as_ref() {
+                return Some((self.key.clone(), value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, V> IntoIterator for &'a RadixTree<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Prefix<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trie::Trie;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_radix_tree() {
+        let t: RadixTree<i32> = RadixTree::new();
+        assert!(t.is_empty());
+        assert!(t.get(b"").is_none());
+        assert_eq!(0, t.iter().count());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_split_and_compress_edges() {
+        let mut t = RadixTree::new();
+        assert!(t.insert(b"romane", 1).is_none());
+        assert_eq!(2, t.memory_usage().node_count);
+        assert!(t.insert(b"romanus", 2).is_none());
+        assert!(t.insert(b"rubens", 3).is_none());
+        assert!(t.insert(b"rom", 4).is_none());
+        t.check_invariants().unwrap();
+        //root, r, om, an, e, us, ubens
+        assert_eq!(7, t.memory_usage().node_count);
+        assert_eq!(Some(&4), t.get(b"rom"));
+        assert!(t.get(b"roma").is_none());
+        assert!(t.get(b"r").is_none());
+        *t.get_mut(b"rubens").unwrap() = 30;
+        assert_eq!(Some(30), t.remove(b"rubens"));
+        t.check_invariants().unwrap();
+        assert_eq!(Some(1), t.remove(b"romane"));
+        t.check_invariants().unwrap();
+        //root, rom, anus
+        assert_eq!(3, t.memory_usage().node_count);
+        assert!(t.remove(b"roman").is_none());
+        assert_eq!(Some(4), t.remove(b"rom"));
+        assert_eq!(Some(2), t.remove(b"romanus"));
+        assert_eq!(1, t.memory_usage().node_count);
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_starts_with_and_longest_prefix() {
+        let mut t = RadixTree::new();
+        for (index, word) in ["banana", "band", "ban", "apple", "bandana", "can"]
+            .iter()
+            .enumerate()
+        {
+            t.insert(word.as_bytes(), index);
+        }
+        let keys = |prefix: &[u8]| {
+            t.starts_with(prefix)
+                .map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["ban", "banana", "band", "bandana"], keys(b"ba"));
+        assert_eq!(vec!["band", "bandana"], keys(b"band"));
+        assert_eq!(vec!["bandana"], keys(b"banda"));
+        assert!(keys(b"bat").is_empty());
+        assert_eq!(6, keys(b"").len());
+        assert_eq!(Some((&b"band"[..], &1)), t.longest_prefix(b"bandit"));
+        assert_eq!(Some((&b"ban"[..], &2)), t.longest_prefix(b"bang"));
+        assert!(t.longest_prefix(b"ba").is_none());
+    }
+
+    #[test]
+    fn test_uses_fewer_nodes_than_trie() {
+        let mut radix = RadixTree::new();
+        let mut trie = Trie::new();
+        for i in 0..100 {
+            let key = format!("https://example.com/some/long/path/{}", i);
+            radix.insert(key.as_bytes(), i);
+            trie.insert(key.as_bytes(), i);
+        }
+        let radix_usage = radix.memory_usage();
+        let trie_usage = trie.memory_usage();
+        assert!(radix_usage.node_count < trie_usage.node_count);
+        assert!(radix_usage.bytes < trie_usage.bytes);
+        assert!(trie.iter().eq(radix.iter()));
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(43);
+        let mut t = RadixTree::new();
+        let mut reference = BTreeMap::new();
+        for i in 0..3000 {
+            let length = rng.next_below(6) as usize;
+            let key: Vec<u8> = (0..length)
+                .map(|_| b'a' + rng.next_below(3) as u8)
+                .collect();
+            if rng.next_bool() {
+                assert_eq!(reference.remove(&key), t.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key.clone(), i), t.insert(&key, i));
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().map(|(k, v)| (k.clone(), v)).eq(t.iter()));
+        for prefix in [&b"a"[..], b"ab", b"cab"].iter() {
+            let expected = reference.iter().filter(|(k, _)| k.starts_with(prefix));
+            assert!(expected
+                .map(|(k, v)| (k.clone(), v))
+                .eq(t.starts_with(prefix)));
+        }
+    }
+}
@@ -0,0 +1,755 @@
+//! This module implements a red-black tree map in the left-leaning variant of Sedgewick. Every node is red or
+//! black, a red node is the left child of its parent and never has a red child, and every path from the root to an
+//! empty subtree passes the same number of black nodes. Insertion and removal restore these rules with rotations
+//! and color flips on the way back to the root, so the tree is at most twice as high as a perfectly balanced one.
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+
+///Type to simplify variable declaration
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+///A node of the tree with its entry, its color and its two subtrees
+struct Node<K, V> {
+    key: K,
+    value: V,
+    red: bool,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+///The red-black tree with its root and its number of entries
+pub struct RbTree<K, V> {
+    root: Link<K, V>,
+    length: usize,
+}
+
+///In-order iterator over the entries of a `RbTree`
+pub struct Iter<'a, K, V> {
+    //the nodes whose left subtree is being visited
+    stack: Vec<&'a Node<K, V>>,
+}
+
+///In-order iterator over the entries of a `RbTree` within a range of keys
+pub struct Range<'a, K, V> {
+    iter: Iter<'a, K, V>,
+    //the key of the last entry in the range
+    last: Option<&'a K>,
+}
+
+///A view into a single entry of a `RbTree`, which is either occupied or vacant
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+///A view into an entry of a `RbTree` which has a value
+pub struct OccupiedEntry<'a, K, V> {
+    key: K,
+    value: &'a mut V,
+}
+
+///A view into an entry of a `RbTree` which has no value
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    tree: &'a mut RbTree<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    ///Returns a new red leaf
+    fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            red: true,
+            left: None,
+            right: None,
+        }
+    }
+}
+
+///Returns true if the node of `link` is red. Empty subtrees are black.
+fn is_red<K, V>(link: &Link<K, V>) -> bool {
+    link.as_ref().is_some_and(|node| node.red)
+}
+
+///Returns true if the left child of the node of `link` is red
+fn is_left_red<K, V>(link: &Link<K, V>) -> bool {
+    link.as_ref().is_some_and(|node| is_red(&node.left))
+}
+
+fn rotate_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut right = node.right.take().unwrap();
+    node.right = right.left.take();
+    right.red = node.red;
+    node.red = true;
+    right.left = Some(node);
+    right
+}
+
+fn rotate_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    let mut left = node.left.take().unwrap();
+    node.left = left.right.take();
+    left.red = node.red;
+    node.red = true;
+    left.right = Some(node);
+    left
+}
+
+///Flips the colors of `node` and its two children
+fn flip_colors<K, V>(node: &mut Node<K, V>) {
+    node.red = !node.red;
+    for child in [&mut node.left, &mut node.right].iter_mut() {
+        if let Some(child) = child.as_mut() {
+            child.red = !child.red;
+        }
+    }
+}
+
+///Restores the rules of the left-leaning tree at `node` on the way back to the root
+fn fix_up<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    if is_red(&node.right) && !is_red(&node.left) {
+        node = rotate_left(node);
+    }
+    if is_red(&node.left) && is_left_red(&node.left) {
+        node = rotate_right(node);
+    }
+    if is_red(&node.left) && is_red(&node.right) {
+        flip_colors(&mut node);
+    }
+    node
+}
+
+///Makes the left child of `node` or one of its children red, before descending left for a removal
+fn move_red_left<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut node);
+    if is_left_red(&node.right) {
+        node.right = Some(rotate_right(node.right.take().unwrap()));
+        node = rotate_left(node);
+        flip_colors(&mut node);
+    }
+    node
+}
+
+///Makes the right child of `node` or one of its children red, before descending right for a removal
+fn move_red_right<K, V>(mut node: Box<Node<K, V>>) -> Box<Node<K, V>> {
+    flip_colors(&mut node);
+    if is_left_red(&node.left) {
+        node = rotate_right(node);
+        flip_colors(&mut node);
+    }
+    node
+}
+
+///Inserts `value` under `key` below `node`. Returns the new subtree, the old value of `key` and the pointer to
+///the value in the tree.
+fn insert_node<K: Ord, V>(
+    node: Link<K, V>,
+    key: K,
+    value: V,
+) -> (Box<Node<K, V>>, Option<V>, *mut V) {
+    let mut node = match node {
+        Some(node) => node,
+        None => {
+            let mut node = Box::new(Node::new(key, value));
+            //rotations move the boxes, the node itself stays where it was allocated
+            let pointer: *mut V = &mut node.value;
+            return (node, None, pointer);
+        }
+    };
+    let (old, pointer) = match key.cmp(&node.key) {
+        Ordering::Less => {
+            let (left, old, pointer) = insert_node(node.left.take(), key, value);
+            node.left = Some(left);
+            (old, pointer)
+        }
+        Ordering::Greater => {
+            let (right, old, pointer) = insert_node(node.right.take(), key, value);
+            node.right = Some(right);
+            (old, pointer)
+        }
+        Ordering::Equal => {
+            let old = std::mem::replace(&mut node.value, value);
+            let pointer: *mut V = &mut node.value;
+            (Some(old), pointer)
+        }
+    };
+    (fix_up(node), old, pointer)
+}
+
+///Removes the node with the smallest key from the subtree of `node`. Returns the rest and the removed node.
+fn remove_min<K, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    if node.left.is_none() {
+        //a left-leaning node without left child has no right child either
+        return (None, node);
+    }
+    if !is_red(&node.left) && !is_left_red(&node.left) {
+        node = move_red_left(node);
+    }
+    let (rest, min) = remove_min(node.left.take().unwrap());
+    node.left = rest;
+    (Some(fix_up(node)), min)
+}
+
+///Removes the node with the largest key from the subtree of `node`. Returns the rest and the removed node.
+fn remove_max<K, V>(mut node: Box<Node<K, V>>) -> (Link<K, V>, Box<Node<K, V>>) {
+    if is_red(&node.left) {
+        node = rotate_right(node);
+    }
+    if node.right.is_none() {
+        return (None, node);
+    }
+    if !is_red(&node.right) && !is_left_red(&node.right) {
+        node = move_red_right(node);
+    }
+    let (rest, max) = remove_max(node.right.take().unwrap());
+    node.right = rest;
+    (Some(fix_up(node)), max)
+}
+
+///Removes the entry of `key`, which must be in the subtree of `node`. Returns the rest and the removed value.
+fn remove_node<K: Ord, V>(mut node: Box<Node<K, V>>, key: &K) -> (Link<K, V>, V) {
+    let removed;
+    if *key < node.key {
+        if !is_red(&node.left) && !is_left_red(&node.left) {
+            node = move_red_left(node);
+        }
+        let (rest, value) = remove_node(node.left.take().unwrap(), key);
+        node.left = rest;
+        removed = value;
+    } else {
+        if is_red(&node.left) {
+            node = rotate_right(node);
+        }
+        if *key == node.key && node.right.is_none() {
+            return (None, node.value);
+        }
+        if !is_red(&node.right) && !is_left_red(&node.right) {
+            node = move_red_right(node);
+        }
+        if *key == node.key {
+            //the successor takes the place of the node
+            let (rest, mut min) = remove_min(node.right.take().unwrap());
+            std::mem::swap(&mut node.key, &mut min.key);
+            std::mem::swap(&mut node.value, &mut min.value);
+            node.right = rest;
+            removed = min.value;
+        } else {
+            let (rest, value) = remove_node(node.right.take().unwrap(), key);
+            node.right = rest;
+            removed = value;
+        }
+    }
+    (Some(fix_up(node)), removed)
+}
+
+///Checks the order and the colors of the subtree of `link`. Returns its number of nodes and its black height.
+fn check_node<K: Ord, V>(
+    link: &Link<K, V>,
+    lower: Option<&K>,
+    upper: Option<&K>,
+) -> Result<(usize, usize), String> {
+    let node = match link {
+        Some(node) => node,
+        None => return Ok((0, 1)),
+    };
+    if lower.is_some_and(|lower| node.key <= *lower)
+        || upper.is_some_and(|upper| node.key >= *upper)
+    {
+        return Err(String::from("keys are out of order"));
+    }
+    if is_red(&node.right) {
+        return Err(String::from("red right child"));
+    }
+    if node.red && is_red(&node.left) {
+        return Err(String::from("red node with a red child"));
+    }
+    let (left_count, left_black) = check_node(&node.left, lower, Some(&node.key))?;
+    let (right_count, right_black) = check_node(&node.right, Some(&node.key), upper)?;
+    if left_black != right_black {
+        return Err(format!(
+            "black heights {} and {} differ",
+            left_black, right_black
+        ));
+    }
+    Ok((
+        left_count + right_count + 1,
+        left_black + usize::from(!node.red),
+    ))
+}
+
+impl<K: Ord, V> Default for RbTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> RbTree<K, V> {
+    ///Returns a new empty tree
+    pub fn new() -> RbTree<K, V> {
+        RbTree {
+            root: None,
+            length: 0,
+        }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf, 0 for an empty tree
+    pub fn get_height(&self) -> usize {
+        let mut height = 0;
+        let mut level: Vec<&Node<K, V>> = self.root.as_deref().into_iter().collect();
+        while !level.is_empty() {
+            height += 1;
+            level = level
+                .into_iter()
+                .flat_map(|node| {
+                    node.left
+                        .as_deref()
+                        .into_iter()
+                        .chain(node.right.as_deref())
+                })
+                .collect();
+        }
+        height
+    }
+
+    ///Returns the node of `key`
+    fn find(&self, key: &K) -> Option<&Node<K, V>> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(node),
+            };
+        }
+        None
+    }
+
+    ///Inserts `value` under `key` below the root and returns the old value and the pointer to the value
+    fn insert_entry(&mut self, key: K, value: V) -> (Option<V>, *mut V) {
+        let (mut root, old, pointer) = insert_node(self.root.take(), key, value);
+        root.red = false;
+        self.root = Some(root);
+        if old.is_none() {
+            self.length += 1;
+        }
+        (old, pointer)
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        self.insert_entry(key, value).0
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|node| &node.value)
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    ///Returns true if the tree has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    ///Colors the root red if both its children are black, which the removal needs to descend
+    fn prepare_removal(&mut self) -> Option<Box<Node<K, V>>> {
+        let mut root = self.root.take()?;
+        if !is_red(&root.left) && !is_red(&root.right) {
+            root.red = true;
+        }
+        Some(root)
+    }
+
+    ///Sets the root after a removal
+    fn finish_removal(&mut self, root: Link<K, V>) {
+        self.root = root;
+        if let Some(root) = self.root.as_mut() {
+            root.red = false;
+        }
+        self.length -= 1;
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        //the transformations on the way down assume the key is in the tree
+        if !self.contains_key(key) {
+            return None;
+        }
+        let root = self.prepare_removal()?;
+        let (rest, value) = remove_node(root, key);
+        self.finish_removal(rest);
+        Some(value)
+    }
+
+    ///Returns the entry of `key` for in-place manipulation
+    /// # Attributes
+    /// * `key`- the key of the entry
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        if self.contains_key(&key) {
+            let value = self.get_mut(&key).unwrap();
+            Entry::Occupied(OccupiedEntry { key, value })
+        } else {
+            Entry::Vacant(VacantEntry { key, tree: self })
+        }
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Removes and returns the entry with the smallest key
+    pub fn pop_first(&mut self) -> Option<(K, V)> {
+        let root = self.prepare_removal()?;
+        let (rest, min) = remove_min(root);
+        self.finish_removal(rest);
+        Some((min.key, min.value))
+    }
+
+    ///Removes and returns the entry with the largest key
+    pub fn pop_last(&mut self) -> Option<(K, V)> {
+        let root = self.prepare_removal()?;
+        let (rest, max) = remove_max(root);
+        self.finish_removal(rest);
+        Some((max.key, max.value))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Returns an iterator over the keys in order
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    ///Returns an iterator over the values in the order of their keys
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    ///Returns an iterator over the entries whose keys are in `range`, in the order of their keys
+    /// # Attributes
+    /// * `range`- the range of the keys
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Range<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        //the path to the first key in the range holds the nodes whose left subtree comes next
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => node.key >= *start,
+                Bound::Excluded(start) => node.key > *start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                iter.stack.push(node);
+                current = node.left.as_deref();
+            } else {
+                current = node.right.as_deref();
+            }
+        }
+        let mut last = None;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => node.key <= *end,
+                Bound::Excluded(end) => node.key < *end,
+                Bound::Unbounded => true,
+            };
+            if before_end {
+                last = Some(&node.key);
+                current = node.right.as_deref();
+            } else {
+                current = node.left.as_deref();
+            }
+        }
+        let first = iter.stack.last().map(|node| &node.key);
+        if first.is_none() || last.is_none() || first > last {
+            iter.stack.clear();
+        }
+        Range { iter, last }
+    }
+
+    ///Checks that the keys are in order, the root is black, no red node is a right child or has a red child and
+    ///all paths have the same number of black nodes. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        if is_red(&self.root) {
+            return Err(String::from("red root"));
+        }
+        let (count, _) = check_node(&self.root, None, None)?;
+        if count != self.length {
+            return Err(format!("length {} but {} nodes", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    ///Pushes `node` and its chain of left children on the stack
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (key, value) = self.iter.next()?;
+        if self.last.is_some_and(|last| std::ptr::eq(last, key)) {
+            self.iter.stack.clear();
+        }
+        Some((key, value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a RbTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for RbTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = RbTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    ///Returns the key of the entry
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => &entry.key,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    ///Inserts `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- the value to insert
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    ///Inserts the value returned by `default` if the entry is vacant and returns the mutable reference to the value
+    /// # Attributes
+    /// * `default`- returns the value to insert
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    ///Calls `f` with the value if the entry is occupied
+    /// # Attributes
+    /// * `f`- modifies the value
+    pub fn and_modify<F: FnOnce(&mut V)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(entry) => {
+                f(entry.value);
+                Entry::Occupied(entry)
+            }
+            vacant => vacant,
+        }
+    }
+}
+
+impl<'a, K: Ord, V: Default> Entry<'a, K, V> {
+    ///Inserts the default value if the entry is vacant and returns the mutable reference to the value
+    pub fn or_default(self) -> &'a mut V {
+        self.or_insert_with(V::default)
+    }
+}
+
+impl<'a, K: Ord, V> OccupiedEntry<'a, K, V> {
+    ///Returns the reference to the value
+    pub fn get(&self) -> &V {
+        self.value
+    }
+
+    ///Returns the mutable reference to the value
+    pub fn get_mut(&mut self) -> &mut V {
+        self.value
+    }
+
+    ///Returns the mutable reference to the value which lives as long as the tree is borrowed
+    pub fn into_mut(self) -> &'a mut V {
+        self.value
+    }
+
+    ///Replaces the value with `value` and returns the old one
+    /// # Attributes
+    /// * `value`- the new value
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.value, value)
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    ///Inserts `value` under the key of the entry and returns the mutable reference to it
+    /// # Attributes
+    /// * `value`- the value to insert
+    pub fn insert(self, value: V) -> &'a mut V {
+        let (_, pointer) = self.tree.insert_entry(self.key, value);
+        //SAFETY: the pointer points into the node just allocated for the value, which stays in place until the
+        //tree is changed again, and the tree stays mutably borrowed for 'a
+        unsafe { &mut *pointer }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_rbtree() {
+        let mut t: RbTree<i32, i32> = RbTree::new();
+        assert!(t.is_empty());
+        assert!(t.pop_first().is_none());
+        assert!(t.remove(&1).is_none());
+        assert!(t.check_invariants().is_ok());
+    }
+
+    #[test]
+    fn test_sorted_inserts_stay_balanced() {
+        let mut t = RbTree::new();
+        for i in 0..1024 {
+            assert!(t.insert(i, i * 2).is_none());
+            t.check_invariants().unwrap();
+        }
+        //at most twice the height of a perfectly balanced tree
+        assert!(t.get_height() <= 20);
+        assert_eq!(Some(&20), t.get(&10));
+        assert_eq!(Some(20), t.insert(10, 0));
+        assert_eq!(Some((&0, &0)), t.first_key_value());
+        assert_eq!(Some((&1023, &2046)), t.last_key_value());
+    }
+
+    #[test]
+    fn test_remove_and_pop() {
+        let mut t: RbTree<i32, i32> = (0..100).map(|i| (i, i)).collect();
+        for i in (0..100).step_by(3) {
+            assert_eq!(Some(i), t.remove(&i));
+            t.check_invariants().unwrap();
+        }
+        assert!(t.remove(&0).is_none());
+        assert_eq!(Some((1, 1)), t.pop_first());
+        t.check_invariants().unwrap();
+        assert_eq!(Some((98, 98)), t.pop_last());
+        t.check_invariants().unwrap();
+        assert_eq!(64, t.get_length());
+        while t.pop_last().is_some() {
+            t.check_invariants().unwrap();
+        }
+        assert!(t.is_empty());
+    }
+
+    #[test]
+    fn test_range_and_entry() {
+        let mut t: RbTree<i32, i32> = (0..20).map(|i| (i * 2, 0)).collect();
+        let keys: Vec<i32> = t.range(3..=10).map(|(key, _)| *key).collect();
+        assert_eq!(vec![4, 6, 8, 10], keys);
+        assert_eq!(0, t.range(7..8).count());
+        *t.entry(4).or_insert(5) += 1;
+        *t.entry(5).or_insert(5) += 1;
+        assert_eq!(Some(&1), t.get(&4));
+        assert_eq!(Some(&6), t.get(&5));
+        t.entry(5).and_modify(|value| *value = 0);
+        assert_eq!(
+            vec![&1, &0, &0],
+            t.range(4..7).map(|(_, value)| value).collect::<Vec<_>>()
+        );
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(41);
+        let mut t = RbTree::new();
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(500);
+            match rng.next_below(5) {
+                0 | 1 => assert_eq!(reference.remove(&key), t.remove(&key)),
+                2 => assert_eq!(reference.pop_first(), t.pop_first()),
+                _ => assert_eq!(reference.insert(key, key + 1), t.insert(key, key + 1)),
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().eq(t.iter()));
+        assert!(reference.range(100..200).eq(t.range(100..200)));
+    }
+}
@@ -0,0 +1,600 @@
+//! This module implements segment trees over a monoid, an associative operation with an identity, for range
+//! queries such as sums, minima or maxima. `SegmentTree` stores the leaves behind the inner nodes in one vector
+//! and updates single positions bottom up. `LazySegmentTree` also applies an update to a whole range in O(log n)
+//! by keeping pending updates at the inner nodes and pushing them down only when a query or update passes by.
+//! The operation does not need to be commutative: queries combine the values strictly from left to right.
+use std::ops::{Bound, RangeBounds};
+
+///An associative operation on values with an identity
+pub trait Monoid {
+    ///The type of the values
+    type Value: Clone;
+
+    ///Returns the identity, the value which leaves every value unchanged when combined with it
+    fn identity() -> Self::Value;
+
+    ///Returns the combination of `left` and `right`, in this order
+    /// # Attributes
+    /// * `left`- the value of the left range
+    /// * `right`- the value of the right range
+    fn combine(left: &Self::Value, right: &Self::Value) -> Self::Value;
+}
+
+///A monoid with updates which can be applied to the combined value of a whole range
+pub trait LazyMonoid: Monoid {
+    ///The type of the updates
+    type Update: Clone;
+
+    ///Returns the value of a range of `length` positions after applying `update` to each of its positions
+    /// # Attributes
+    /// * `update`- the update to apply
+    /// * `value`- the combined value of the range
+    /// * `length`- the number of positions in the range
+    fn apply(update: &Self::Update, value: &Self::Value, length: usize) -> Self::Value;
+
+    ///Returns the update which has the effect of applying `older` and then `newer`
+    /// # Attributes
+    /// * `newer`- the update applied last
+    /// * `older`- the update applied first
+    fn compose(newer: &Self::Update, older: &Self::Update) -> Self::Update;
+}
+
+///The sum of integers, updated by adding to every position. The sums saturate at the bounds of `i64`, so they are
+///exact as long as the sums of the ranges fit into an `i64`.
+pub struct Sum;
+
+///The minimum of integers, updated by adding to every position. The additions saturate at the bounds of `i64`.
+pub struct Min;
+
+///The maximum of integers, updated by adding to every position. The additions saturate at the bounds of `i64`.
+pub struct Max;
+
+impl Monoid for Sum {
+    type Value = i64;
+
+    fn identity() -> i64 {
+        0
+    }
+
+    fn combine(left: &i64, right: &i64) -> i64 {
+        left.saturating_add(*right)
+    }
+}
+
+impl LazyMonoid for Sum {
+    type Update = i64;
+
+    fn apply(update: &i64, value: &i64, length: usize) -> i64 {
+        value.saturating_add(update.saturating_mul(length as i64))
+    }
+
+    fn compose(newer: &i64, older: &i64) -> i64 {
+        newer.saturating_add(*older)
+    }
+}
+
+impl Monoid for Min {
+    type Value = i64;
+
+    fn identity() -> i64 {
+        i64::MAX
+    }
+
+    fn combine(left: &i64, right: &i64) -> i64 {
+        *left.min(right)
+    }
+}
+
+impl LazyMonoid for Min {
+    type Update = i64;
+
+    fn apply(update: &i64, value: &i64, length: usize) -> i64 {
+        //the identity of an empty range stays the identity
+        if length == 0 {
+            *value
+        } else {
+            value.saturating_add(*update)
+        }
+    }
+
+    fn compose(newer: &i64, older: &i64) -> i64 {
+        newer.saturating_add(*older)
+    }
+}
+
+impl Monoid for Max {
+    type Value = i64;
+
+    fn identity() -> i64 {
+        i64::MIN
+    }
+
+    fn combine(left: &i64, right: &i64) -> i64 {
+        *left.max(right)
+    }
+}
+
+impl LazyMonoid for Max {
+    type Update = i64;
+
+    fn apply(update: &i64, value: &i64, length: usize) -> i64 {
+        if length == 0 {
+            *value
+        } else {
+            value.saturating_add(*update)
+        }
+    }
+
+    fn compose(newer: &i64, older: &i64) -> i64 {
+        newer.saturating_add(*older)
+    }
+}
+
+///Returns the positions `start..end` of `range` within `length` positions. Panics if the range is out of bounds.
+pub(crate) fn to_positions<B: RangeBounds<usize>>(range: &B, length: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(start) => *start,
+        Bound::Excluded(start) => start + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(end) => end + 1,
+        Bound::Excluded(end) => *end,
+        Bound::Unbounded => length,
+    };
+    assert!(
+        start <= end && end <= length,
+        "range {}..{} is out of bounds for length {}",
+        start,
+        end,
+        length
+    );
+    (start, end)
+}
+
+///A segment tree with point updates. The node `i` combines the nodes `2i` and `2i + 1`, the leaves are the nodes
+///`length..2 * length`.
+pub struct SegmentTree<M: Monoid> {
+    nodes: Vec<M::Value>,
+    length: usize,
+}
+
+impl<M: Monoid> SegmentTree<M> {
+    ///Returns a new tree of `length` positions holding the identity
+    /// # Attributes
+    /// * `length`- the number of positions
+    pub fn new(length: usize) -> SegmentTree<M> {
+        SegmentTree {
+            nodes: vec![M::identity(); 2 * length],
+            length,
+        }
+    }
+
+    ///Returns a new tree holding `values`
+    /// # Attributes
+    /// * `values`- the values of the positions
+    pub fn from_slice(values: &[M::Value]) -> SegmentTree<M> {
+        let length = values.len();
+        let mut nodes = vec![M::identity(); length];
+        nodes.extend_from_slice(values);
+        for node in (1..length).rev() {
+            nodes[node] = M::combine(&nodes[2 * node], &nodes[2 * node + 1]);
+        }
+        SegmentTree { nodes, length }
+    }
+
+    ///Returns the number of positions
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no positions
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the reference to the value at `index`
+    /// # Attributes
+    /// * `index`- the position of the value
+    pub fn get(&self, index: usize) -> Option<&M::Value> {
+        if index < self.length {
+            Some(&self.nodes[self.length + index])
+        } else {
+            None
+        }
+    }
+
+    ///Sets the value at `index` to `value`. Panics if `index` is out of bounds.
+    /// # Attributes
+    /// * `index`- the position of the value
+    /// * `value`- the new value
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(
+            index < self.length,
+            "index {} is out of bounds for length {}",
+            index,
+            self.length
+        );
+        let mut node = self.length + index;
+        self.nodes[node] = value;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = M::combine(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+        }
+    }
+
+    ///Returns the combination of the values in `range`, the identity for an empty range. Panics if the range is
+    ///out of bounds.
+    /// # Attributes
+    /// * `range`- the positions to combine
+    pub fn query<B: RangeBounds<usize>>(&self, range: B) -> M::Value {
+        let (start, end) = to_positions(&range, self.length);
+        //the two halves grow inwards from both ends and meet in the middle
+        let mut left = M::identity();
+        let mut right = M::identity();
+        let (mut start, mut end) = (start + self.length, end + self.length);
+        while start < end {
+            if start % 2 == 1 {
+                left = M::combine(&left, &self.nodes[start]);
+                start += 1;
+            }
+            if end % 2 == 1 {
+                end -= 1;
+                right = M::combine(&self.nodes[end], &right);
+            }
+            start /= 2;
+            end /= 2;
+        }
+        M::combine(&left, &right)
+    }
+}
+
+///A segment tree with range updates. The node `i` covers a range of positions and combines the nodes `2i` and
+///`2i + 1`, which cover its two halves; `pending` holds the updates not yet pushed down to the children.
+pub struct LazySegmentTree<M: LazyMonoid> {
+    nodes: Vec<M::Value>,
+    pending: Vec<Option<M::Update>>,
+    length: usize,
+}
+
+impl<M: LazyMonoid> LazySegmentTree<M> {
+    ///Returns a new tree of `length` positions holding the identity
+    /// # Attributes
+    /// * `length`- the number of positions
+    pub fn new(length: usize) -> LazySegmentTree<M> {
+        LazySegmentTree::from_slice(&vec![M::identity(); length])
+    }
+
+    ///Returns a new tree holding `values`
+    /// # Attributes
+    /// * `values`- the values of the positions
+    pub fn from_slice(values: &[M::Value]) -> LazySegmentTree<M> {
+        let length = values.len();
+        let size = 2 * length.next_power_of_two();
+        let mut tree = LazySegmentTree {
+            nodes: vec![M::identity(); size],
+            pending: vec![None; size],
+            length,
+        };
+        if length > 0 {
+            tree.build(1, 0, length, values);
+        }
+        tree
+    }
+
+    ///Builds the node `node` covering the positions `start..end` from `values`
+    fn build(&mut self, node: usize, start: usize, end: usize, values: &[M::Value]) {
+        if end - start == 1 {
+            self.nodes[node] = values[start].clone();
+            return;
+        }
+        let middle = (start + end) / 2;
+        self.build(2 * node, start, middle, values);
+        self.build(2 * node + 1, middle, end, values);
+        self.nodes[node] = M::combine(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+    }
+
+    ///Returns the number of positions
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the tree has no positions
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Applies `update` to the node `node` covering `length` positions and keeps it pending for its children
+    fn apply(&mut self, node: usize, update: &M::Update, length: usize) {
+        self.nodes[node] = M::apply(update, &self.nodes[node], length);
+        if node < self.pending.len() / 2 {
+            self.pending[node] = Some(match self.pending[node].take() {
+                Some(older) => M::compose(update, &older),
+                None => update.clone(),
+            });
+        }
+    }
+
+    ///Pushes the pending update of the node `node` covering the positions `start..end` down to its children
+    fn push_down(&mut self, node: usize, start: usize, end: usize) {
+        if let Some(update) = self.pending[node].take() {
+            let middle = (start + end) / 2;
+            self.apply(2 * node, &update, middle - start);
+            self.apply(2 * node + 1, &update, end - middle);
+        }
+    }
+
+    ///Returns the combination of the values in `range`, the identity for an empty range. Panics if the range is
+    ///out of bounds.
+    /// # Attributes
+    /// * `range`- the positions to combine
+    pub fn query<B: RangeBounds<usize>>(&mut self, range: B) -> M::Value {
+        let (start, end) = to_positions(&range, self.length);
+        if start == end {
+            return M::identity();
+        }
+        self.query_node(1, (0, self.length), (start, end))
+    }
+
+    fn query_node(
+        &mut self,
+        node: usize,
+        covered: (usize, usize),
+        range: (usize, usize),
+    ) -> M::Value {
+        let ((start, end), (from, to)) = (covered, range);
+        if from <= start && end <= to {
+            return self.nodes[node].clone();
+        }
+        self.push_down(node, start, end);
+        let middle = (start + end) / 2;
+        let mut value = M::identity();
+        if from < middle {
+            value = self.query_node(2 * node, (start, middle), range);
+        }
+        if to > middle {
+            let right = self.query_node(2 * node + 1, (middle, end), range);
+            value = M::combine(&value, &right);
+        }
+        value
+    }
+
+    ///Applies `update` to every position in `range`. Panics if the range is out of bounds.
+    /// # Attributes
+    /// * `range`- the positions to update
+    /// * `update`- the update to apply
+    pub fn update<B: RangeBounds<usize>>(&mut self, range: B, update: M::Update) {
+        let (start, end) = to_positions(&range, self.length);
+        if start < end {
+            self.update_node(1, (0, self.length), (start, end), &update);
+        }
+    }
+
+    fn update_node(
+        &mut self,
+        node: usize,
+        covered: (usize, usize),
+        range: (usize, usize),
+        update: &M::Update,
+    ) {
+        let ((start, end), (from, to)) = (covered, range);
+        if from <= start && end <= to {
+            self.apply(node, update, end - start);
+            return;
+        }
+        self.push_down(node, start, end);
+        let middle = (start + end) / 2;
+        if from < middle {
+            self.update_node(2 * node, (start, middle), range, update);
+        }
+        if to > middle {
+            self.update_node(2 * node + 1, (middle, end), range, update);
+        }
+        self.nodes[node] = M::combine(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+    }
+
+    ///Returns the value at `index`
+    /// # Attributes
+    /// * `index`- the position of the value
+    pub fn get(&mut self, index: usize) -> Option<M::Value> {
+        if index < self.length {
+            Some(self.query(index..=index))
+        } else {
+            None
+        }
+    }
+
+    ///Sets the value at `index` to `value`. Panics if `index` is out of bounds.
+    /// # Attributes
+    /// * `index`- the position of the value
+    /// * `value`- the new value
+    pub fn set(&mut self, index: usize, value: M::Value) {
+        assert!(
+            index < self.length,
+            "index {} is out of bounds for length {}",
+            index,
+            self.length
+        );
+        let (mut node, mut start, mut end) = (1, 0, self.length);
+        //walk down pushing the pending updates, then recombine on the way back up
+        while end - start > 1 {
+            self.push_down(node, start, end);
+            let middle = (start + end) / 2;
+            if index < middle {
+                node *= 2;
+                end = middle;
+            } else {
+                node = 2 * node + 1;
+                start = middle;
+            }
+        }
+        self.nodes[node] = value;
+        while node > 1 {
+            node /= 2;
+            self.nodes[node] = M::combine(&self.nodes[2 * node], &self.nodes[2 * node + 1]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+
+    ///Concatenation of strings, a monoid which is not commutative
+    struct Concat;
+
+    impl Monoid for Concat {
+        type Value = String;
+
+        fn identity() -> String {
+            String::new()
+        }
+
+        fn combine(left: &String, right: &String) -> String {
+            format!("{}{}", left, right)
+        }
+    }
+
+    #[test]
+    fn test_empty_tree() {
+        let t: SegmentTree<Sum> = SegmentTree::new(0);
+        assert!(t.is_empty());
+        assert_eq!(0, t.query(..));
+        let mut t: LazySegmentTree<Min> = LazySegmentTree::new(0);
+        t.update(.., 5);
+        assert_eq!(i64::MAX, t.query(..));
+    }
+
+    #[test]
+    fn test_sum_min_max() {
+        let values = [5, -2, 7, 1, 0, 3, 9];
+        let sum: SegmentTree<Sum> = SegmentTree::from_slice(&values);
+        let min: SegmentTree<Min> = SegmentTree::from_slice(&values);
+        let mut max: SegmentTree<Max> = SegmentTree::from_slice(&values);
+        assert_eq!(23, sum.query(..));
+        assert_eq!(6, sum.query(1..4));
+        assert_eq!(-2, min.query(..=3));
+        assert_eq!(0, min.query(3..6));
+        assert_eq!(9, max.query(..));
+        max.set(6, -1);
+        assert_eq!(7, max.query(..));
+        assert_eq!(Some(&-1), max.get(6));
+        assert!(max.get(7).is_none());
+        assert_eq!(i64::MIN, max.query(4..4));
+    }
+
+    #[test]
+    fn test_not_commutative() {
+        let words: Vec<String> = ["a", "b", "c", "d", "e"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut t: SegmentTree<Concat> = SegmentTree::from_slice(&words);
+        assert_eq!("abcde", t.query(..));
+        assert_eq!("bcd", t.query(1..4));
+        t.set(2, String::from("x"));
+        assert_eq!("bxde", t.query(1..));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_query_out_of_bounds() {
+        let t: SegmentTree<Sum> = SegmentTree::new(3);
+        t.query(1..4);
+    }
+
+    #[test]
+    fn test_lazy_update_at_the_bounds() {
+        let mut min: LazySegmentTree<Min> = LazySegmentTree::from_slice(&[i64::MAX, 3, i64::MAX]);
+        min.update(..1, -1);
+        assert_eq!(i64::MAX - 1, min.query(..1));
+        assert_eq!(Some(i64::MAX - 1), min.get(0));
+        min.update(1.., -10);
+        assert_eq!(i64::MAX - 10, min.query(2..));
+        assert_eq!(-7, min.query(..));
+        let mut max: LazySegmentTree<Max> = LazySegmentTree::from_slice(&[i64::MIN, -3]);
+        max.update(.., 2);
+        assert_eq!(i64::MIN + 2, max.query(..1));
+        assert_eq!(-1, max.query(..));
+        //the additions saturate instead of overflowing
+        max.update(.., i64::MIN);
+        assert_eq!(i64::MIN, max.query(..));
+        assert_eq!(Min::apply(&5, &i64::MAX, 0), i64::MAX);
+    }
+
+    #[test]
+    fn test_repeated_extreme_updates() {
+        let mut max: LazySegmentTree<Max> = LazySegmentTree::from_slice(&[0, 0, 0]);
+        let mut min: LazySegmentTree<Min> = LazySegmentTree::from_slice(&[0, 0, 0]);
+        let mut sum: LazySegmentTree<Sum> = LazySegmentTree::from_slice(&[0, 0, 0]);
+        for _ in 0..3 {
+            max.update(0..2, i64::MIN);
+            min.update(1.., i64::MAX);
+            sum.update(.., i64::MAX);
+        }
+        assert_eq!(i64::MIN, max.query(..2));
+        assert_eq!(0, max.query(..));
+        assert_eq!(i64::MAX, min.query(1..));
+        assert_eq!(0, min.query(..));
+        assert_eq!(i64::MAX, sum.query(..));
+        assert_eq!(i64::MAX, sum.query(1..2));
+        for _ in 0..3 {
+            max.update(.., i64::MAX);
+            sum.update(..1, i64::MIN);
+        }
+        assert_eq!(i64::MAX, max.query(2..));
+        assert_eq!(i64::MIN, sum.query(..1));
+    }
+
+    #[test]
+    fn test_lazy_against_naive() {
+        let mut rng = XorShift64::new(44);
+        for length in [1usize, 2, 7, 64, 100].iter().copied() {
+            let mut values: Vec<i64> = (0..length)
+                .map(|_| rng.next_below(100) as i64 - 50)
+                .collect();
+            let mut sum: LazySegmentTree<Sum> = LazySegmentTree::from_slice(&values);
+            let mut min: LazySegmentTree<Min> = LazySegmentTree::from_slice(&values);
+            let mut max: LazySegmentTree<Max> = LazySegmentTree::from_slice(&values);
+            for _ in 0..500 {
+                let a = rng.next_below(length as u64 + 1) as usize;
+                let b = rng.next_below(length as u64 + 1) as usize;
+                let (start, end) = (a.min(b), a.max(b));
+                match rng.next_below(3) {
+                    0 => {
+                        let delta = rng.next_below(20) as i64 - 10;
+                        values[start..end]
+                            .iter_mut()
+                            .for_each(|value| *value += delta);
+                        sum.update(start..end, delta);
+                        min.update(start..end, delta);
+                        max.update(start..end, delta);
+                    }
+                    1 if start < length => {
+                        let value = rng.next_below(100) as i64;
+                        values[start] = value;
+                        sum.set(start, value);
+                        min.set(start, value);
+                        max.set(start, value);
+                    }
+                    _ => {
+                        let range = &values[start..end];
+                        assert_eq!(range.iter().sum::<i64>(), sum.query(start..end));
+                        assert_eq!(
+                            range.iter().copied().min().unwrap_or(i64::MAX),
+                            min.query(start..end)
+                        );
+                        assert_eq!(
+                            range.iter().copied().max().unwrap_or(i64::MIN),
+                            max.query(start..end)
+                        );
+                    }
+                }
+            }
+            for (index, value) in values.iter().enumerate() {
+                assert_eq!(Some(*value), sum.get(index));
+            }
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! This module implements a splay tree map. Every access splays the accessed node to the root with top-down
+//! rotations, so recently used keys are cheap to reach again and any sequence of m operations takes
+//! O(m log n) time, although a single operation may walk a path of length n. As the tree can degenerate into a
+//! list, splaying, checking and dropping the tree run in loops instead of recursion.
+//! Every node keeps the size of its subtree, so the length of the tree, and of both parts after a split, is the size
+//! of the root.
+use lifo::lifo::Lifo;
+use std::cmp::Ordering;
+
+///Type to simplify variable declaration
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+///A node of the tree with its entry, its two subtrees and the number of nodes in its subtree
+struct Node<K, V> {
+    key: K,
+    value: V,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+///The splay tree with its root
+pub struct SplayTree<K, V> {
+    root: Link<K, V>,
+}
+
+///In-order iterator over the entries of a `SplayTree`
+pub struct Iter<'a, K, V> {
+    //the nodes whose left subtree is being visited
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    ///Recomputes the size of the subtree from the sizes of the children
+    fn update_size(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+///Returns the number of nodes in the subtree of `link`
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+///Splays the node below `root` which `direction` leads to up to the top and returns the new root. `direction` compares
+///the searched key with the key of a node; if no node matches, the last node on the search path becomes the root.
+fn splay<K, V, F: Fn(&K) -> Ordering>(mut root: Box<Node<K, V>>, direction: F) -> Box<Node<K, V>> {
+    //the nodes smaller than the searched key, each to become the right child of the one before it, and the
+    //larger nodes, each to become the left child of the one before it
+    let mut smaller: Vec<Box<Node<K, V>>> = Vec::new();
+    let mut larger: Vec<Box<Node<K, V>>> = Vec::new();
+    loop {
+        match direction(&root.key) {
+            Ordering::Less => {
+                let mut left = match root.left.take() {
+                    Some(left) => left,
+                    None => break,
+                };
+                if direction(&left.key) == Ordering::Less {
+                    //zig-zig: rotate right before linking
+                    root.left = left.right.take();
+                    root.update_size();
+                    left.right = Some(root);
+                    root = left;
+                    match root.left.take() {
+                        Some(next) => {
+                            larger.push(root);
+                            root = next;
+                        }
+                        None => break,
+                    }
+                } else {
+                    larger.push(root);
+                    root = left;
+                }
+            }
+            Ordering::Greater => {
+                let mut right = match root.right.take() {
+                    Some(right) => right,
+                    None => break,
+                };
+                if direction(&right.key) == Ordering::Greater {
+                    root.right = right.left.take();
+                    root.update_size();
+                    right.left = Some(root);
+                    root = right;
+                    match root.right.take() {
+                        Some(next) => {
+                            smaller.push(root);
+                            root = next;
+                        }
+                        None => break,
+                    }
+                } else {
+                    smaller.push(root);
+                    root = right;
+                }
+            }
+            Ordering::Equal => break,
+        }
+    }
+    //reassemble: the subtrees of the new root hang below the innermost nodes of both sides, the sizes are
+    //recomputed from the inside out
+    let mut left = root.left.take();
+    while let Some(mut node) = smaller.pop() {
+        node.right = left;
+        node.update_size();
+        left = Some(node);
+    }
+    let mut right = root.right.take();
+    while let Some(mut node) = larger.pop() {
+        node.left = right;
+        node.update_size();
+        right = Some(node);
+    }
+    root.left = left;
+    root.right = right;
+    root.update_size();
+    root
+}
+
+impl<K, V> Default for SplayTree<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, V> Drop for SplayTree<K, V> {
+    fn drop(&mut self) {
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.take() {
+            stack.enqueue(root);
+        }
+        while let Some(mut node) = stack.dequeue() {
+            if let Some(left) = node.left.take() {
+                stack.enqueue(left);
+            }
+            if let Some(right) = node.right.take() {
+                stack.enqueue(right);
+            }
+        }
+    }
+}
+
+impl<K, V> SplayTree<K, V> {
+    ///Returns a new empty tree
+    pub fn new() -> SplayTree<K, V> {
+        SplayTree { root: None }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        size(&self.root)
+    }
+
+    ///Returns true if the tree has no entries
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    ///Returns the key at the root, the most recently accessed key
+    pub fn root_key(&self) -> Option<&K> {
+        self.root.as_ref().map(|root| &root.key)
+    }
+}
+
+impl<K: Ord, V> SplayTree<K, V> {
+    ///Splays `key` to the root and returns true if the root has `key`
+    fn splay(&mut self, key: &K) -> bool {
+        match self.root.take() {
+            Some(root) => {
+                let root = splay(root, |other| key.cmp(other));
+                let found = root.key == *key;
+                self.root = Some(root);
+                found
+            }
+            None => false,
+        }
+    }
+
+    ///Returns the reference to the value of `key` and splays it to the root
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&mut self, key: &K) -> Option<&V> {
+        if self.splay(key) {
+            self.root.as_ref().map(|root| &root.value)
+        } else {
+            None
+        }
+    }
+
+    ///Returns the mutable reference to the value of `key` and splays it to the root
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        if self.splay(key) {
+            self.root.as_mut().map(|root| &mut root.value)
+        } else {
+            None
+        }
+    }
+
+    ///Returns true if the tree has an entry for `key` and splays it to the root
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&mut self, key: &K) -> bool {
+        self.splay(key)
+    }
+
+    ///Inserts `value` under `key`, which becomes the root, and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if self.splay(&key) {
+            let root = self.root.as_mut().unwrap();
+            return Some(std::mem::replace(&mut root.value, value));
+        }
+        let mut node = Box::new(Node::new(key, value));
+        //the old root is the neighbour of the new key, its other side goes below the new root
+        if let Some(mut root) = self.root.take() {
+            if root.key < node.key {
+                node.right = root.right.take();
+                root.update_size();
+                node.left = Some(root);
+            } else {
+                node.left = root.left.take();
+                root.update_size();
+                node.right = Some(root);
+            }
+            node.update_size();
+        }
+        self.root = Some(node);
+        None
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        if !self.splay(key) {
+            return None;
+        }
+        let mut root = self.root.take().unwrap();
+        self.root = match root.left.take() {
+            None => root.right.take(),
+            Some(left) => {
+                //the largest key of the left subtree has no right child once splayed
+                let mut left = splay(left, |other| key.cmp(other));
+                left.right = root.right.take();
+                left.update_size();
+                Some(left)
+            }
+        };
+        Some(root.value)
+    }
+
+    ///Moves the entries whose keys are not smaller than `key` into a new tree and returns it
+    /// # Attributes
+    /// * `key`- the smallest key of the new tree
+    pub fn split(&mut self, key: &K) -> SplayTree<K, V> {
+        let mut other = SplayTree::new();
+        self.splay(key);
+        let mut root = match self.root.take() {
+            Some(root) => root,
+            None => return other,
+        };
+        if root.key < *key {
+            other.root = root.right.take();
+            root.update_size();
+            self.root = Some(root);
+        } else {
+            self.root = root.left.take();
+            root.update_size();
+            other.root = Some(root);
+        }
+        other
+    }
+
+    ///Moves all entries of `other` into this tree. Panics if a key of `other` is not larger than all keys of this
+    ///tree.
+    /// # Attributes
+    /// * `other`- the tree with the larger keys
+    pub fn merge(&mut self, mut other: SplayTree<K, V>) {
+        //compare before splaying, so a failed check leaves both trees untouched
+        if let (Some((last, _)), Some((first, _))) =
+            (self.last_key_value(), other.first_key_value())
+        {
+            assert!(last < first, "the keys of the trees interleave");
+        }
+        let right = match other.root.take() {
+            Some(right) => right,
+            None => return,
+        };
+        self.root = match self.root.take() {
+            None => Some(right),
+            Some(left) => {
+                //splaying the largest key leaves the root without a right child
+                let mut left = splay(left, |_| Ordering::Greater);
+                left.right = Some(right);
+                left.update_size();
+                Some(left)
+            }
+        };
+    }
+
+    ///Returns the entry with the smallest key without splaying
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key without splaying
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf
+    pub fn get_height(&self) -> usize {
+        let mut height = 0;
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.enqueue((root, 1));
+        }
+        while let Some((node, depth)) = stack.dequeue() {
+            height = height.max(depth);
+            for child in [&node.left, &node.right].iter() {
+                if let Some(child) = child.as_deref() {
+                    stack.enqueue((child, depth + 1));
+                }
+            }
+        }
+        height
+    }
+
+    ///Returns an iterator over the entries in the order of their keys. Iterating does not splay.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Checks that the keys are in order and every node has the size of its subtree. Returns the first violation
+    ///found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut previous: Option<&K> = None;
+        for (key, _) in self.iter() {
+            if previous.is_some_and(|previous| previous >= key) {
+                return Err(String::from("keys are out of order"));
+            }
+            previous = Some(key);
+        }
+        let mut stack = Lifo::new();
+        if let Some(root) = self.root.as_deref() {
+            stack.enqueue(root);
+        }
+        while let Some(node) = stack.dequeue() {
+            if node.size != 1 + size(&node.left) + size(&node.right) {
+                return Err(String::from("a node has a wrong subtree size"));
+            }
+            for child in [&node.left, &node.right].iter() {
+                if let Some(child) = child.as_deref() {
+                    stack.enqueue(child);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    ///Pushes `node` and the left spine below it
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V> IntoIterator for &'a SplayTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_splay_tree() {
+        let mut t: SplayTree<i32, i32> = SplayTree::new();
+        assert!(t.is_empty());
+        assert!(t.get(&1).is_none());
+        assert!(t.remove(&1).is_none());
+        assert_eq!(0, t.get_height());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_access_moves_to_root() {
+        let mut t = SplayTree::new();
+        for i in 0..100 {
+            t.insert(i, i * 3);
+            assert_eq!(Some(&i), t.root_key());
+        }
+        assert_eq!(Some(&150), t.get(&50));
+        assert_eq!(Some(&50), t.root_key());
+        *t.get_mut(&7).unwrap() = 0;
+        assert_eq!(Some(&7), t.root_key());
+        assert_eq!(Some(0), t.insert(7, 1));
+        assert!(!t.contains_key(&1000));
+        assert_eq!(Some(&99), t.root_key());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_degenerate_tree() {
+        let mut t = SplayTree::new();
+        for i in 0..100_000 {
+            t.insert(i, ());
+        }
+        //sorted inserts leave a path, the first access walks it and roughly halves its depth
+        assert_eq!(100_000, t.get_height());
+        assert!(t.contains_key(&0));
+        assert!(t.get_height() < 60_000);
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(42);
+        let mut t = SplayTree::new();
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(300);
+            match rng.next_below(3) {
+                0 => assert_eq!(reference.remove(&key), t.remove(&key)),
+                1 => assert_eq!(reference.get(&key), t.get(&key)),
+                _ => assert_eq!(reference.insert(key, key), t.insert(key, key)),
+            }
+        }
+        t.check_invariants().unwrap();
+        assert!(reference.iter().eq(t.iter()));
+        assert_eq!(reference.iter().next(), t.first_key_value());
+        assert_eq!(reference.iter().next_back(), t.last_key_value());
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let mut t = SplayTree::new();
+        for i in (0..100).rev() {
+            t.insert(i, i);
+        }
+        let mut right = t.split(&40);
+        assert_eq!(40, t.get_length());
+        assert_eq!(60, right.get_length());
+        assert!(t.iter().map(|(k, _)| *k).eq(0..40));
+        assert!(right.iter().map(|(k, _)| *k).eq(40..100));
+        let empty = right.split(&1000);
+        assert!(empty.is_empty());
+        right.remove(&40);
+        t.merge(right);
+        t.check_invariants().unwrap();
+        assert_eq!(99, t.get_length());
+        assert!(!t.contains_key(&40));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_interleaving() {
+        let mut a = SplayTree::new();
+        let mut b = SplayTree::new();
+        a.insert(1, ());
+        a.insert(5, ());
+        b.insert(3, ());
+        a.merge(b);
+    }
+
+    #[test]
+    fn test_failed_merge_keeps_entries() {
+        let mut a = SplayTree::new();
+        let mut b = SplayTree::new();
+        for i in 0..10 {
+            a.insert(i * 2, ());
+        }
+        b.insert(5, ());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| a.merge(b)));
+        assert!(result.is_err());
+        assert_eq!(10, a.get_length());
+        assert!(a.iter().map(|(k, _)| *k).eq((0..10).map(|i| i * 2)));
+        a.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_split_keeps_sizes() {
+        let mut rng = XorShift64::new(17);
+        let mut t = SplayTree::new();
+        for _ in 0..2000 {
+            let key = rng.next_below(10_000);
+            t.insert(key, ());
+        }
+        let length = t.get_length();
+        for key in [0, 2500, 5000, 9999, 20_000].iter() {
+            let right = t.split(key);
+            assert_eq!(length, t.get_length() + right.get_length());
+            assert_eq!(right.iter().count(), right.get_length());
+            assert!(right.iter().all(|(k, _)| k >= key));
+            t.check_invariants().unwrap();
+            right.check_invariants().unwrap();
+            t.merge(right);
+        }
+        assert_eq!(length, t.get_length());
+        t.check_invariants().unwrap();
+    }
+}
@@ -0,0 +1,895 @@
+//! This module implements treaps, binary trees which are ordered by their keys like a search tree and by random
+//! priorities like a max-heap, so their expected height is O(log n). All operations are built on two primitives:
+//! `split`, which cuts a treap into two, and `merge`, which joins two treaps whose entries do not interleave.
+//! `Treap` is a map ordered by its keys with order statistics. `ImplicitTreap` is a sequence whose keys are the
+//! positions of its elements, which supports inserting and removing at a position and reversing a range in
+//! O(log n) by marking the range reversed and pushing the mark down lazily.
+//! The priorities are drawn from a `lists::rng::RandomSource`, so a seeded source gives reproducible shapes.
+use lists::rng::{RandomSource, SeedableSource, XorShift64};
+use std::cmp::Ordering;
+use std::ops::{Bound, RangeBounds};
+
+///Type to simplify variable declaration
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+///Type to simplify variable declaration
+type ImplicitLink<T> = Option<Box<ImplicitNode<T>>>;
+
+///A node of a `Treap` with its entry, its priority, the size of its subtree and its two subtrees
+struct Node<K, V> {
+    key: K,
+    value: V,
+    priority: u64,
+    size: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+///A node of an `ImplicitTreap` with its element, its priority, the size of its subtree, its two subtrees and
+///whether the order of its subtree has to be reversed
+struct ImplicitNode<T> {
+    value: T,
+    priority: u64,
+    size: usize,
+    reversed: bool,
+    left: ImplicitLink<T>,
+    right: ImplicitLink<T>,
+}
+
+///A treap map with its root and its source of priorities
+pub struct Treap<K, V, R = XorShift64> {
+    root: Link<K, V>,
+    rng: R,
+}
+
+///A sequence stored in a treap with its root and its source of priorities
+pub struct ImplicitTreap<T, R = XorShift64> {
+    root: ImplicitLink<T>,
+    rng: R,
+}
+
+///In-order iterator over the entries of a `Treap`
+pub struct Iter<'a, K, V> {
+    //the nodes whose left subtree is being visited
+    stack: Vec<&'a Node<K, V>>,
+}
+
+///Iterator over the elements of an `ImplicitTreap` in the order of their positions
+pub struct ImplicitIter<'a, T> {
+    //the nodes whose first subtree is being visited, with whether their subtree is reversed
+    stack: Vec<(&'a ImplicitNode<T>, bool)>,
+}
+
+impl<K, V> Node<K, V> {
+    fn new(key: K, value: V, priority: u64) -> Node<K, V> {
+        Node {
+            key,
+            value,
+            priority,
+            size: 1,
+            left: None,
+            right: None,
+        }
+    }
+
+    ///Recomputes the size of the subtree from the sizes of the two subtrees
+    fn update(&mut self) {
+        self.size = 1 + size(&self.left) + size(&self.right);
+    }
+}
+
+impl<T> ImplicitNode<T> {
+    fn new(value: T, priority: u64) -> ImplicitNode<T> {
+        ImplicitNode {
+            value,
+            priority,
+            size: 1,
+            reversed: false,
+            left: None,
+            right: None,
+        }
+    }
+
+    fn update(&mut self) {
+        self.size = 1 + implicit_size(&self.left) + implicit_size(&self.right);
+    }
+
+    ///Applies a pending reversal to the two subtrees and passes it on to them
+    fn push_down(&mut self) {
+        if self.reversed {
+            std::mem::swap(&mut self.left, &mut self.right);
+            for child in [&mut self.left, &mut self.right].iter_mut() {
+                if let Some(child) = child.as_mut() {
+                    child.reversed = !child.reversed;
+                }
+            }
+            self.reversed = false;
+        }
+    }
+}
+
+///Returns the number of nodes below `link`
+fn size<K, V>(link: &Link<K, V>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+///Returns the number of nodes below `link`
+fn implicit_size<T>(link: &ImplicitLink<T>) -> usize {
+    link.as_ref().map_or(0, |node| node.size)
+}
+
+///Splits the treap below `link` into the entries with keys smaller than `key` and the rest
+fn split<K: Ord, V>(link: Link<K, V>, key: &K) -> (Link<K, V>, Link<K, V>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            if node.key < *key {
+                let (left, right) = split(node.right.take(), key);
+                node.right = left;
+                node.update();
+                (Some(node), right)
+            } else {
+                let (left, right) = split(node.left.take(), key);
+                node.left = right;
+                node.update();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+///Merges two treaps whose keys of `left` are all smaller than the keys of `right`
+fn merge<K, V>(left: Link<K, V>, right: Link<K, V>) -> Link<K, V> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            //the node with the higher priority stays on top
+            if left.priority > right.priority {
+                left.right = merge(left.right.take(), Some(right));
+                left.update();
+                Some(left)
+            } else {
+                right.left = merge(Some(left), right.left.take());
+                right.update();
+                Some(right)
+            }
+        }
+    }
+}
+
+///Splits the sequence below `link` into its first `index` elements and the rest
+fn split_at<T>(link: ImplicitLink<T>, index: usize) -> (ImplicitLink<T>, ImplicitLink<T>) {
+    match link {
+        None => (None, None),
+        Some(mut node) => {
+            node.push_down();
+            let left_size = implicit_size(&node.left);
+            if left_size < index {
+                let (left, right) = split_at(node.right.take(), index - left_size - 1);
+                node.right = left;
+                node.update();
+                (Some(node), right)
+            } else {
+                let (left, right) = split_at(node.left.take(), index);
+                node.left = right;
+                node.update();
+                (left, Some(node))
+            }
+        }
+    }
+}
+
+///Concatenates the sequences below `left` and `right`
+fn concat<T>(left: ImplicitLink<T>, right: ImplicitLink<T>) -> ImplicitLink<T> {
+    match (left, right) {
+        (None, right) => right,
+        (left, None) => left,
+        (Some(mut left), Some(mut right)) => {
+            if left.priority > right.priority {
+                left.push_down();
+                left.right = concat(left.right.take(), Some(right));
+                left.update();
+                Some(left)
+            } else {
+                right.push_down();
+                right.left = concat(Some(left), right.left.take());
+                right.update();
+                Some(right)
+            }
+        }
+    }
+}
+
+///Removes the entry of `key` below `link` and returns its value
+fn remove<K: Ord, V>(link: &mut Link<K, V>, key: &K) -> Option<V> {
+    let node = link.as_mut()?;
+    let removed = match key.cmp(&node.key) {
+        Ordering::Less => remove(&mut node.left, key)?,
+        Ordering::Greater => remove(&mut node.right, key)?,
+        Ordering::Equal => {
+            //the two subtrees take the place of the node
+            let mut node = link.take().unwrap();
+            *link = merge(node.left.take(), node.right.take());
+            return Some(node.value);
+        }
+    };
+    node.size -= 1;
+    Some(removed)
+}
+
+///Checks the order, the priorities and the sizes of the subtree below `link` and returns its height
+fn check_node<K: Ord, V>(
+    link: &Link<K, V>,
+    lower: Option<&K>,
+    upper: Option<&K>,
+) -> Result<usize, String> {
+    let node = match link {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    if lower.is_some_and(|lower| node.key <= *lower)
+        || upper.is_some_and(|upper| node.key >= *upper)
+    {
+        return Err(String::from("keys are out of order"));
+    }
+    for child in [&node.left, &node.right].iter() {
+        if child
+            .as_ref()
+            .is_some_and(|child| child.priority > node.priority)
+        {
+            return Err(String::from(
+                "a child has a higher priority than its parent",
+            ));
+        }
+    }
+    if node.size != 1 + size(&node.left) + size(&node.right) {
+        return Err(String::from("wrong subtree size"));
+    }
+    let left = check_node(&node.left, lower, Some(&node.key))?;
+    let right = check_node(&node.right, Some(&node.key), upper)?;
+    Ok(1 + left.max(right))
+}
+
+///Checks the priorities and the sizes of the subtree below `link` and returns its height
+fn check_implicit_node<T>(link: &ImplicitLink<T>) -> Result<usize, String> {
+    let node = match link {
+        None => return Ok(0),
+        Some(node) => node,
+    };
+    for child in [&node.left, &node.right].iter() {
+        if child
+            .as_ref()
+            .is_some_and(|child| child.priority > node.priority)
+        {
+            return Err(String::from(
+                "a child has a higher priority than its parent",
+            ));
+        }
+    }
+    if node.size != 1 + implicit_size(&node.left) + implicit_size(&node.right) {
+        return Err(String::from("wrong subtree size"));
+    }
+    let left = check_implicit_node(&node.left)?;
+    let right = check_implicit_node(&node.right)?;
+    Ok(1 + left.max(right))
+}
+
+impl<K: Ord, V> Default for Treap<K, V, XorShift64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Ord, V> Treap<K, V, XorShift64> {
+    ///Returns a new empty treap whose priorities are drawn from a time seeded `XorShift64`
+    pub fn new() -> Treap<K, V, XorShift64> {
+        Treap::with_rng(XorShift64::from_time())
+    }
+}
+
+impl<K: Ord, V, R: RandomSource> Treap<K, V, R> {
+    ///Returns a new empty treap whose priorities are drawn from `rng`
+    /// # Attributes
+    /// * `rng`- the random source used to draw the priority of new nodes
+    pub fn with_rng(rng: R) -> Treap<K, V, R> {
+        Treap { root: None, rng }
+    }
+
+    ///Returns the number of entries
+    pub fn get_length(&self) -> usize {
+        size(&self.root)
+    }
+
+    ///Returns true if the treap has no entries
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    ///Returns the number of nodes on the longest path from the root to a leaf
+    pub fn get_height(&self) -> usize {
+        let mut height = 0;
+        let mut level = Vec::new();
+        level.extend(self.root.as_deref());
+        while !level.is_empty() {
+            height += 1;
+            level = level
+                .into_iter()
+                .flat_map(|node| {
+                    node.left
+                        .as_deref()
+                        .into_iter()
+                        .chain(node.right.as_deref())
+                })
+                .collect();
+        }
+        height
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &K) -> Option<&V> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => node.right.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+            };
+        }
+        None
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Greater => node.right.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+            };
+        }
+        None
+    }
+
+    ///Returns true if the treap has an entry for `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        if let Some(old) = self.get_mut(&key) {
+            return Some(std::mem::replace(old, value));
+        }
+        let (left, right) = split(self.root.take(), &key);
+        let node = Box::new(Node::new(key, value, self.rng.next_u64()));
+        self.root = merge(merge(left, Some(node)), right);
+        None
+    }
+
+    ///Removes the entry of `key` and returns its value
+    /// # Attributes
+    /// * `key`- the key of the entry to remove
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        remove(&mut self.root, key)
+    }
+
+    ///Returns the number of keys smaller than `key`
+    /// # Attributes
+    /// * `key`- the key to rank
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            current = match key.cmp(&node.key) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Greater => {
+                    rank += size(&node.left) + 1;
+                    node.right.as_deref()
+                }
+                Ordering::Equal => return rank + size(&node.left),
+            };
+        }
+        rank
+    }
+
+    ///Returns the entry with the `index`th smallest key, counting from 0
+    /// # Attributes
+    /// * `index`- the number of smaller keys of the entry
+    pub fn select(&self, mut index: usize) -> Option<(&K, &V)> {
+        let mut current = self.root.as_deref();
+        while let Some(node) = current {
+            let left = size(&node.left);
+            current = match index.cmp(&left) {
+                Ordering::Less => node.left.as_deref(),
+                Ordering::Equal => return Some((&node.key, &node.value)),
+                Ordering::Greater => {
+                    index -= left + 1;
+                    node.right.as_deref()
+                }
+            };
+        }
+        None
+    }
+
+    ///Moves the entries whose keys are not smaller than `key` into a new treap and returns it. The new treap draws
+    ///its priorities from a random source reseeded from this one.
+    /// # Attributes
+    /// * `key`- the smallest key of the new treap
+    pub fn split(&mut self, key: &K) -> Treap<K, V, R>
+    where
+        R: SeedableSource,
+    {
+        let (left, right) = split(self.root.take(), key);
+        self.root = left;
+        Treap {
+            root: right,
+            rng: self.rng.reseed(),
+        }
+    }
+
+    ///Moves all entries of `other` into this treap. Panics if a key of `other` is not larger than all keys of this
+    ///treap.
+    /// # Attributes
+    /// * `other`- the treap with the larger keys
+    pub fn merge(&mut self, mut other: Treap<K, V, R>) {
+        if let (Some((last, _)), Some((first, _))) =
+            (self.last_key_value(), other.first_key_value())
+        {
+            assert!(last < first, "the keys of the treaps interleave");
+        }
+        self.root = merge(self.root.take(), other.root.take());
+    }
+
+    ///Returns the entry with the smallest key
+    pub fn first_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(left) = node.left.as_deref() {
+            node = left;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns the entry with the largest key
+    pub fn last_key_value(&self) -> Option<(&K, &V)> {
+        let mut node = self.root.as_deref()?;
+        while let Some(right) = node.right.as_deref() {
+            node = right;
+        }
+        Some((&node.key, &node.value))
+    }
+
+    ///Returns an iterator over the entries in the order of their keys
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left(self.root.as_deref());
+        iter
+    }
+
+    ///Checks that the keys are in order, no node has a higher priority than its parent and every node knows the
+    ///size of its subtree. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        check_node(&self.root, None, None).map(|_| ())
+    }
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    ///Pushes `node` and the left spine below it
+    fn push_left(&mut self, mut node: Option<&'a Node<K, V>>) {
+        while let Some(current) = node {
+            self.stack.push(current);
+            node = current.left.as_deref();
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.push_left(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+impl<'a, K: Ord, V, R: RandomSource> IntoIterator for &'a Treap<K, V, R> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T> Default for ImplicitTreap<T, XorShift64> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> ImplicitTreap<T, XorShift64> {
+    ///Returns a new empty sequence whose priorities are drawn from a time seeded `XorShift64`
+    pub fn new() -> ImplicitTreap<T, XorShift64> {
+        ImplicitTreap::with_rng(XorShift64::from_time())
+    }
+}
+
+impl<T, R: RandomSource> ImplicitTreap<T, R> {
+    ///Returns a new empty sequence whose priorities are drawn from `rng`
+    /// # Attributes
+    /// * `rng`- the random source used to draw the priority of new nodes
+    pub fn with_rng(rng: R) -> ImplicitTreap<T, R> {
+        ImplicitTreap { root: None, rng }
+    }
+
+    ///Returns the number of elements
+    pub fn get_length(&self) -> usize {
+        implicit_size(&self.root)
+    }
+
+    ///Returns true if the sequence has no elements
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    ///Returns the reference to the element at `index`
+    /// # Attributes
+    /// * `index`- the position of the element
+    pub fn get(&self, mut index: usize) -> Option<&T> {
+        let mut current = self.root.as_deref();
+        //pending reversals are not pushed down here, only tracked on the way
+        let mut reversed = false;
+        while let Some(node) = current {
+            reversed ^= node.reversed;
+            let (first, second) = if reversed {
+                (&node.right, &node.left)
+            } else {
+                (&node.left, &node.right)
+            };
+            let first_size = implicit_size(first);
+            current = match index.cmp(&first_size) {
+                Ordering::Less => first.as_deref(),
+                Ordering::Equal => return Some(&node.value),
+                Ordering::Greater => {
+                    index -= first_size + 1;
+                    second.as_deref()
+                }
+            };
+        }
+        None
+    }
+
+    ///Returns the mutable reference to the element at `index`
+    /// # Attributes
+    /// * `index`- the position of the element
+    pub fn get_mut(&mut self, mut index: usize) -> Option<&mut T> {
+        let mut current = self.root.as_deref_mut();
+        while let Some(node) = current {
+            node.push_down();
+            let left_size = implicit_size(&node.left);
+            current = match index.cmp(&left_size) {
+                Ordering::Less => node.left.as_deref_mut(),
+                Ordering::Equal => return Some(&mut node.value),
+                Ordering::Greater => {
+                    index -= left_size + 1;
+                    node.right.as_deref_mut()
+                }
+            };
+        }
+        None
+    }
+
+    ///Inserts `value` at `index`, shifting the elements after it. Panics if `index` is larger than the length.
+    /// # Attributes
+    /// * `index`- the position of the new element
+    /// * `value`- the new element
+    pub fn insert(&mut self, index: usize, value: T) {
+        let length = self.get_length();
+        assert!(
+            index <= length,
+            "index {} is out of bounds for length {}",
+            index,
+            length
+        );
+        let (left, right) = split_at(self.root.take(), index);
+        let node = Box::new(ImplicitNode::new(value, self.rng.next_u64()));
+        self.root = concat(concat(left, Some(node)), right);
+    }
+
+    ///Appends `value` to the end of the sequence
+    /// # Attributes
+    /// * `value`- the new element
+    pub fn push_back(&mut self, value: T) {
+        self.insert(self.get_length(), value);
+    }
+
+    ///Removes the element at `index` and returns it
+    /// # Attributes
+    /// * `index`- the position of the element to remove
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        if index >= self.get_length() {
+            return None;
+        }
+        let (left, rest) = split_at(self.root.take(), index);
+        let (middle, right) = split_at(rest, 1);
+        self.root = concat(left, right);
+        middle.map(|node| node.value)
+    }
+
+    ///Reverses the order of the elements whose positions are in `range`. Panics if the range is out of bounds.
+    /// # Attributes
+    /// * `range`- the positions of the elements to reverse
+    pub fn reverse<B: RangeBounds<usize>>(&mut self, range: B) {
+        let start = match range.start_bound() {
+            Bound::Included(start) => *start,
+            Bound::Excluded(start) => start + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => end + 1,
+            Bound::Excluded(end) => *end,
+            Bound::Unbounded => self.get_length(),
+        };
+        assert!(
+            start <= end && end <= self.get_length(),
+            "range {}..{} is out of bounds for length {}",
+            start,
+            end,
+            self.get_length()
+        );
+        let (left, rest) = split_at(self.root.take(), start);
+        let (mut middle, right) = split_at(rest, end - start);
+        if let Some(middle) = middle.as_mut() {
+            middle.reversed = !middle.reversed;
+        }
+        self.root = concat(concat(left, middle), right);
+    }
+
+    ///Moves the elements from `index` on into a new sequence and returns it. The new sequence draws its
+    ///priorities from a random source reseeded from this one. Panics if `index` is larger than the length.
+    /// # Attributes
+    /// * `index`- the position of the first element of the new sequence
+    pub fn split(&mut self, index: usize) -> ImplicitTreap<T, R>
+    where
+        R: SeedableSource,
+    {
+        let length = self.get_length();
+        assert!(
+            index <= length,
+            "index {} is out of bounds for length {}",
+            index,
+            length
+        );
+        let (left, right) = split_at(self.root.take(), index);
+        self.root = left;
+        ImplicitTreap {
+            root: right,
+            rng: self.rng.reseed(),
+        }
+    }
+
+    ///Appends all elements of `other` to the end of the sequence
+    /// # Attributes
+    /// * `other`- the sequence to append
+    pub fn merge(&mut self, mut other: ImplicitTreap<T, R>) {
+        self.root = concat(self.root.take(), other.root.take());
+    }
+
+    ///Returns an iterator over the elements in the order of their positions
+    pub fn iter(&self) -> ImplicitIter<'_, T> {
+        let mut iter = ImplicitIter { stack: Vec::new() };
+        iter.push_first(self.root.as_deref(), false);
+        iter
+    }
+
+    ///Checks that no node has a higher priority than its parent and every node knows the size of its subtree.
+    ///Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        check_implicit_node(&self.root).map(|_| ())
+    }
+}
+
+impl<'a, T> ImplicitIter<'a, T> {
+    ///Pushes `node` and the spine of first subtrees below it, where `reversed` tells whether the subtree of `node`
+    ///is inside reversed ranges
+    fn push_first(&mut self, mut node: Option<&'a ImplicitNode<T>>, mut reversed: bool) {
+        while let Some(current) = node {
+            reversed ^= current.reversed;
+            self.stack.push((current, reversed));
+            node = if reversed {
+                current.right.as_deref()
+            } else {
+                current.left.as_deref()
+            };
+        }
+    }
+}
+
+impl<'a, T> Iterator for ImplicitIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, reversed) = self.stack.pop()?;
+        let second = if reversed {
+            node.left.as_deref()
+        } else {
+            node.right.as_deref()
+        };
+        self.push_first(second, reversed);
+        Some(&node.value)
+    }
+}
+
+impl<'a, T, R: RandomSource> IntoIterator for &'a ImplicitTreap<T, R> {
+    type Item = &'a T;
+    type IntoIter = ImplicitIter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_treap() {
+        let t: Treap<i32, i32> = Treap::new();
+        assert!(t.is_empty());
+        assert_eq!(0, t.get_height());
+        assert!(t.select(0).is_none());
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_same_seed_same_shape() {
+        let mut a = Treap::with_rng(XorShift64::new(7));
+        let mut b = Treap::with_rng(XorShift64::new(7));
+        for i in 0..1000 {
+            a.insert(i, ());
+            b.insert(i, ());
+        }
+        assert_eq!(a.get_height(), b.get_height());
+        assert_eq!(a.root.as_ref().unwrap().key, b.root.as_ref().unwrap().key);
+        //sorted inserts do not degenerate the treap
+        assert!(a.get_height() < 40);
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(42);
+        let mut t = Treap::with_rng(XorShift64::new(1));
+        let mut reference = BTreeMap::new();
+        for _ in 0..5000 {
+            let key = rng.next_below(300);
+            if rng.next_bool() {
+                assert_eq!(reference.remove(&key), t.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key, key), t.insert(key, key));
+            }
+        }
+        t.check_invariants().unwrap();
+        assert_eq!(reference.len(), t.get_length());
+        assert!(reference.iter().eq(t.iter()));
+        for (index, (key, _)) in reference.iter().enumerate() {
+            assert_eq!(index, t.rank(key));
+            assert_eq!(Some(key), t.select(index).map(|(key, _)| key));
+        }
+        assert_eq!(reference.range(..150).count(), t.rank(&150));
+    }
+
+    #[test]
+    fn test_split_and_merge() {
+        let mut t = Treap::with_rng(XorShift64::new(3));
+        for i in 0..100 {
+            t.insert(i, i * 2);
+        }
+        let mut right = t.split(&40);
+        t.check_invariants().unwrap();
+        right.check_invariants().unwrap();
+        assert_eq!(40, t.get_length());
+        assert_eq!(60, right.get_length());
+        assert_eq!(Some((&39, &78)), t.last_key_value());
+        assert_eq!(Some((&40, &80)), right.first_key_value());
+        //the treaps draw different priorities
+        assert_ne!(t.rng.next_u64(), right.rng.next_u64());
+        right.insert(200, 0);
+        t.merge(right);
+        t.check_invariants().unwrap();
+        assert_eq!(101, t.get_length());
+        assert_eq!(Some(&80), t.get(&40));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_merge_interleaving() {
+        let mut a = Treap::with_rng(XorShift64::new(1));
+        let mut b = Treap::with_rng(XorShift64::new(2));
+        a.insert(5, ());
+        b.insert(3, ());
+        a.merge(b);
+    }
+
+    #[test]
+    fn test_implicit_insert_and_remove() {
+        let mut t = ImplicitTreap::with_rng(XorShift64::new(5));
+        let mut reference = Vec::new();
+        let mut rng = XorShift64::new(6);
+        for i in 0..2000 {
+            if reference.is_empty() || rng.next_below(3) > 0 {
+                let index = rng.next_below(reference.len() as u64 + 1) as usize;
+                t.insert(index, i);
+                reference.insert(index, i);
+            } else {
+                let index = rng.next_below(reference.len() as u64) as usize;
+                assert_eq!(Some(reference.remove(index)), t.remove(index));
+            }
+        }
+        t.check_invariants().unwrap();
+        assert!(reference.iter().eq(t.iter()));
+        assert!(t.remove(reference.len()).is_none());
+    }
+
+    #[test]
+    fn test_implicit_reverse() {
+        let mut t = ImplicitTreap::with_rng(XorShift64::new(8));
+        let mut reference: Vec<u64> = (0..200).collect();
+        for i in 0..200 {
+            t.push_back(i);
+        }
+        let mut rng = XorShift64::new(9);
+        for _ in 0..500 {
+            let a = rng.next_below(201) as usize;
+            let b = rng.next_below(201) as usize;
+            let (start, end) = (a.min(b), a.max(b));
+            t.reverse(start..end);
+            reference[start..end].reverse();
+            let index = rng.next_below(200) as usize;
+            assert_eq!(Some(&reference[index]), t.get(index));
+        }
+        assert!(reference.iter().eq(t.iter()));
+        *t.get_mut(10).unwrap() = 1000;
+        reference[10] = 1000;
+        t.reverse(..);
+        reference.reverse();
+        assert!(reference.iter().eq(t.iter()));
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_implicit_split_and_merge() {
+        let mut t = ImplicitTreap::with_rng(XorShift64::new(10));
+        for i in 0..50 {
+            t.push_back(i);
+        }
+        t.reverse(0..20);
+        let mut right = t.split(30);
+        assert_eq!(30, t.get_length());
+        assert_eq!(Some(&30), right.get(0));
+        right.reverse(..);
+        t.merge(right);
+        let expected: Vec<i32> = (0..20).rev().chain(20..30).chain((30..50).rev()).collect();
+        assert!(expected.iter().eq(t.iter()));
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_implicit_insert_out_of_bounds() {
+        let mut t = ImplicitTreap::with_rng(XorShift64::new(1));
+        t.insert(1, 0);
+    }
+}
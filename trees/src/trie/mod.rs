@@ -0,0 +1,395 @@
+//! This module implements a trie map keyed by byte strings. Every node stands for the prefix spelled by the bytes
+//! on its path from the root and keeps its children sorted by their byte, so walking the trie in depth first
+//! order visits the keys in lexicographic order. The walks keep their path on a `lifo::Lifo` instead of recursing,
+//! so keys may be arbitrarily long; the trie implements Drop the same way.
+use lifo::lifo::Lifo;
+use std::mem::size_of;
+
+///A node of the trie with the value of its prefix, if the prefix is a key, and its children sorted by their byte
+struct Node<V> {
+    value: Option<V>,
+    children: Vec<(u8, Node<V>)>,
+}
+
+///The trie with its root, the node of the empty key, and its number of keys
+pub struct Trie<V> {
+    root: Node<V>,
+    length: usize,
+}
+
+///The memory used by the nodes of a tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryUsage {
+    ///the number of nodes
+    pub node_count: usize,
+    ///the bytes allocated for the nodes, their child lists and their labels
+    pub bytes: usize,
+}
+
+///Iterator over the entries of a `Trie` whose keys start with a prefix, in lexicographic order
+pub struct Prefix<'a, V> {
+    //the nodes still to visit, the next one on top, with the length of the key of their parent and the bytes
+    //their key adds to it
+    stack: Lifo<(&'a Node<V>, usize, &'a [u8])>,
+    //the key of the node visited last, shared by all nodes on the stack
+    key: Vec<u8>,
+}
+
+impl<V> Node<V> {
+    fn new() -> Node<V> {
+        Node {
+            value: None,
+            children: Vec::new(),
+        }
+    }
+
+    ///Returns the child for `byte`
+    fn get_child(&self, byte: u8) -> Option<&Node<V>> {
+        let index = self
+            .children
+            .binary_search_by_key(&byte, |(b, _)| *b)
+            .ok()?;
+        Some(&self.children[index].1)
+    }
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Drop for Trie<V> {
+    fn drop(&mut self) {
+        let mut stack = Lifo::new();
+        stack.enqueue(std::mem::replace(&mut self.root, Node::new()));
+        while let Some(mut node) = stack.dequeue() {
+            for (_, child) in node.children.drain(..) {
+                stack.enqueue(child);
+            }
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    ///Returns a new empty trie
+    pub fn new() -> Trie<V> {
+        Trie {
+            root: Node::new(),
+            length: 0,
+        }
+    }
+
+    ///Returns the number of keys
+    pub fn get_length(&self) -> usize {
+        self.length
+    }
+
+    ///Returns true if the trie has no keys
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+
+    ///Returns the node of `key`
+    fn find(&self, key: &[u8]) -> Option<&Node<V>> {
+        let mut node = &self.root;
+        for byte in key {
+            node = node.get_child(*byte)?;
+        }
+        Some(node)
+    }
+
+    ///Returns the reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get(&self, key: &[u8]) -> Option<&V> {
+        self.find(key)?.value.as_ref()
+    }
+
+    ///Returns the mutable reference to the value of `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn get_mut(&mut self, key: &[u8]) -> Option<&mut V> {
+        let mut node = &mut self.root;
+        for byte in key {
+            let index = node.children.binary_search_by_key(byte, |(b, _)| *b).ok()?;
+            node = &mut node.children[index].1;
+        }
+        node.value.as_mut()
+    }
+
+    ///Returns true if the trie has the key `key`
+    /// # Attributes
+    /// * `key`- the key to look up
+    pub fn contains_key(&self, key: &[u8]) -> bool {
+        self.get(key).is_some()
+    }
+
+    ///Inserts `value` under `key` and returns the old value of `key`
+    /// # Attributes
+    /// * `key`- the key of the entry
+    /// * `value`- the value of the entry
+    pub fn insert(&mut self, key: &[u8], value: V) -> Option<V> {
+        let mut node = &mut self.root;
+        for byte in key {
+            let index = match node.children.binary_search_by_key(byte, |(b, _)| *b) {
+                Ok(index) => index,
+                Err(index) => {
+                    node.children.insert(index, (*byte, Node::new()));
+                    index
+                }
+            };
+            node = &mut node.children[index].1;
+        }
+        let old = node.value.replace(value);
+        if old.is_none() {
+            self.length += 1;
+        }
+        old
+    }
+
+    ///Removes `key` and returns its value. The nodes which no longer lead to a key are removed as well.
+    /// # Attributes
+    /// * `key`- the key to remove
+    pub fn remove(&mut self, key: &[u8]) -> Option<V> {
+        //the deepest node on the path which has to stay and the child below it which can go with its subtree
+        let mut cut = None;
+        let mut node = &self.root;
+        for (depth, byte) in key.iter().enumerate() {
+            if depth == 0 || node.value.is_some() || node.children.len() > 1 {
+                cut = Some(depth);
+            }
+            node = node.get_child(*byte)?;
+        }
+        node.value.as_ref()?;
+        let prune = node.children.is_empty();
+        let mut node = &mut self.root;
+        let mut removed = None;
+        for (depth, byte) in key.iter().enumerate() {
+            let index = node
+                .children
+                .binary_search_by_key(byte, |(b, _)| *b)
+                .unwrap();
+            if prune && cut == Some(depth) {
+                let (_, mut child) = node.children.remove(index);
+                //the cut off branch is a single path, take the value from its end
+                while let Some((_, next)) = child.children.pop() {
+                    child = next;
+                }
+                removed = child.value.take();
+                break;
+            }
+            node = &mut node.children[index].1;
+        }
+        if !prune || key.is_empty() {
+            removed = node.value.take();
+        }
+        self.length -= 1;
+        removed
+    }
+
+    ///Returns the longest key which is a prefix of `key`, together with its value
+    /// # Attributes
+    /// * `key`- the key whose prefixes are looked up
+    pub fn longest_prefix<'a, 'k>(&'a self, key: &'k [u8]) -> Option<(&'k [u8], &'a V)> {
+        let mut node = &self.root;
+        let mut longest = node.value.as_ref().map(|value| (0, value));
+        for (depth, byte) in key.iter().enumerate() {
+            node = match node.get_child(*byte) {
+                Some(child) => child,
+                None => break,
+            };
+            if let Some(value) = node.value.as_ref() {
+                longest = Some((depth + 1, value));
+            }
+        }
+        longest.map(|(length, value)| (&key[..length], value))
+    }
+
+    ///Returns an iterator over the entries whose keys start with `prefix`, in lexicographic order
+    /// # Attributes
+    /// * `prefix`- the common prefix of the keys
+    pub fn starts_with(&self, prefix: &[u8]) -> Prefix<'_, V> {
+        let mut stack = Lifo::new();
+        if let Some(node) = self.find(prefix) {
+            stack.enqueue((node, prefix.len(), &[][..]));
+        }
+        Prefix {
+            stack,
+            key: prefix.to_vec(),
+        }
+    }
+
+    ///Returns an iterator over all entries in lexicographic order of their keys
+    pub fn iter(&self) -> Prefix<'_, V> {
+        self.starts_with(&[])
+    }
+
+    ///Returns the number of nodes and the bytes allocated for them
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let mut usage = MemoryUsage {
+            node_count: 0,
+            bytes: size_of::<Node<V>>(),
+        };
+        let mut stack = Lifo::new();
+        stack.enqueue(&self.root);
+        while let Some(node) = stack.dequeue() {
+            usage.node_count += 1;
+            usage.bytes += node.children.capacity() * size_of::<(u8, Node<V>)>();
+            for (_, child) in &node.children {
+                stack.enqueue(child);
+            }
+        }
+        usage
+    }
+
+    ///Checks that the children of every node are sorted, every leaf but the root holds a value and the length
+    ///matches the number of values. Returns the first violation found.
+    pub fn check_invariants(&self) -> Result<(), String> {
+        let mut count = 0;
+        let mut stack = Lifo::new();
+        stack.enqueue(&self.root);
+        while let Some(node) = stack.dequeue() {
+            if node.children.windows(2).any(|pair| pair[0].0 >= pair[1].0) {
+                return Err(String::from("children are out of order"));
+            }
+            for (_, child) in &node.children {
+                if child.value.is_none() && child.children.is_empty() {
+                    return Err(String::from("a leaf without a value"));
+                }
+                stack.enqueue(child);
+            }
+            count += usize::from(node.value.is_some());
+        }
+        if count != self.length {
+            return Err(format!("length {} but {} keys", self.length, count));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, V> Iterator for Prefix<'a, V> {
+    type Item = (Vec<u8>, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth, bytes)) = self.stack.dequeue() {
+            //going back up drops the bytes of the nodes visited since the parent
+            self.key.truncate(depth);
+            self.key.extend_from_slice(bytes);
+            //the smallest child has to come out first, so it goes on the stack last
+            for (byte, child) in node.children.iter().rev() {
+                self.stack
+                    .enqueue((child, self.key.len(), std::slice::from_ref(byte)));
+            }
+            if let Some(value) = node.value.as_ref() {
+                return Some((self.key.clone(), value));
+            }
+        }
+        None
+    }
+}
+
+impl<'a, V> IntoIterator for &'a Trie<V> {
+    type Item = (Vec<u8>, &'a V);
+    type IntoIter = Prefix<'a, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lists::rng::{RandomSource, XorShift64};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_new_trie() {
+        let t: Trie<i32> = Trie::new();
+        assert!(t.is_empty());
+        assert!(t.get(b"").is_none());
+        assert_eq!(0, t.iter().count());
+        assert_eq!(1, t.memory_usage().node_count);
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_insert_get_remove() {
+        let mut t = Trie::new();
+        assert!(t.insert(b"tea", 1).is_none());
+        assert!(t.insert(b"ten", 2).is_none());
+        assert!(t.insert(b"te", 3).is_none());
+        assert!(t.insert(b"", 4).is_none());
+        assert_eq!(Some(1), t.insert(b"tea", 5));
+        assert_eq!(4, t.get_length());
+        assert_eq!(Some(&5), t.get(b"tea"));
+        assert!(t.get(b"t").is_none());
+        *t.get_mut(b"te").unwrap() += 10;
+        assert_eq!(Some(&13), t.get(b"te"));
+        assert_eq!(5, t.memory_usage().node_count);
+        assert_eq!(Some(2), t.remove(b"ten"));
+        assert!(t.remove(b"ten").is_none());
+        assert!(t.remove(b"t").is_none());
+        assert_eq!(4, t.memory_usage().node_count);
+        assert_eq!(Some(13), t.remove(b"te"));
+        assert_eq!(Some(5), t.remove(b"tea"));
+        assert_eq!(Some(4), t.remove(b""));
+        assert_eq!(1, t.memory_usage().node_count);
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_starts_with_in_lexicographic_order() {
+        let mut t = Trie::new();
+        for (index, word) in ["banana", "band", "ban", "apple", "bandana", "can"]
+            .iter()
+            .enumerate()
+        {
+            t.insert(word.as_bytes(), index);
+        }
+        let keys = |prefix: &[u8]| {
+            t.starts_with(prefix)
+                .map(|(key, _)| String::from_utf8(key).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["ban", "banana", "band", "bandana"], keys(b"ba"));
+        assert_eq!(vec!["band", "bandana"], keys(b"band"));
+        assert!(keys(b"bat").is_empty());
+        assert_eq!(6, keys(b"").len());
+        assert_eq!(Some((&b"band"[..], &1)), t.longest_prefix(b"bandit"));
+        assert_eq!(Some((&b"ban"[..], &2)), t.longest_prefix(b"bang"));
+        assert!(t.longest_prefix(b"ba").is_none());
+    }
+
+    #[test]
+    fn test_long_key() {
+        let mut t = Trie::new();
+        let key = vec![7; 200_000];
+        t.insert(&key, ());
+        assert!(t.contains_key(&key));
+        assert_eq!(1, t.iter().count());
+        assert_eq!(Some(key), t.iter().next().map(|(key, _)| key));
+        t.check_invariants().unwrap();
+    }
+
+    #[test]
+    fn test_random_operations_against_btreemap() {
+        let mut rng = XorShift64::new(43);
+        let mut t = Trie::new();
+        let mut reference = BTreeMap::new();
+        for i in 0..3000 {
+            let length = rng.next_below(5) as usize;
+            let key: Vec<u8> = (0..length)
+                .map(|_| b'a' + rng.next_below(3) as u8)
+                .collect();
+            if rng.next_bool() {
+                assert_eq!(reference.remove(&key), t.remove(&key));
+            } else {
+                assert_eq!(reference.insert(key.clone(), i), t.insert(&key, i));
+            }
+            t.check_invariants().unwrap();
+        }
+        assert!(reference.iter().map(|(k, v)| (k.clone(), v)).eq(t.iter()));
+    }
+}